@@ -76,7 +76,7 @@ mod tests {
     use rand::{thread_rng, Rng};
     use rsa::{traits::PublicKeyParts, RsaPrivateKey, RsaPublicKey};
     use sha2::{Digest, Sha256};
-    use halo2_base::halo2_proofs::{dev::MockProver, halo2curves::{pasta::Fp, bn256::Fr}};
+    use halo2_base::halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
     use crate::TestRSASignatureWithHashCircuit1;
 
     #[test]
@@ -104,8 +104,8 @@ mod tests {
             // Conditional Secrets Subcircuit
             let cond_secrets_circuit = IdentityCircuit::new(
                 Some(true),
-                Some(1),
-                Some(1),
+                Some(true),
+                Some(true),
                 Some(true),
                 Some(1),
                 Some(1),
@@ -153,7 +153,7 @@ mod tests {
             prover.verify().unwrap();
 
             // Verifying the conditional secrets subcircuit
-            let prover: MockProver<Fp> = MockProver::run(k, &cond_secrets_circuit.clone(), vec![]).unwrap();
+            let prover: MockProver<F> = MockProver::run(k, &cond_secrets_circuit.clone(), vec![]).unwrap();
             assert!(prover.verify().is_ok());
 
             // Verifying the timestamp subcircuit
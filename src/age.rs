@@ -0,0 +1,784 @@
+use halo2_base::halo2_proofs::{
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector,
+        TableColumn,
+    },
+    poly::Rotation,
+};
+use halo2_base::utils::PrimeField;
+use std::marker::PhantomData;
+
+use crate::timestamp::{calendar_lookup_input, load_calendar_table};
+
+/// The byte length of the DOB field `DD-MM-YYYY` in the QR data.
+pub const DOB_LEN: usize = 10;
+
+/// The positions of the `-` separators in the DOB field.
+const DOB_SEPARATORS: [usize; 2] = [2, 5];
+
+/// The position of the first digit of the year in the DOB field.
+const DOB_YEAR_START: usize = 6;
+
+/// The position of the first digit of the month in the DOB field.
+const DOB_MONTH_START: usize = 3;
+
+/// The age in years checked by the `age_above_18` output of [`AgeCircuit`].
+pub const ADULT_AGE: u64 = 18;
+
+/// The number of decimal digits of a date key `YYYYMMDD`, see [`date_key`].
+const DATE_KEY_DIGITS: usize = 8;
+
+/// The number of decimal digits of the key `MMDD` of a date within its year, i.e. the last digits of [`date_key`].
+const BIRTHDAY_KEY_DIGITS: usize = 4;
+
+/// Returns true if `age` is at least [`ADULT_AGE`] years, i.e. the value of `age_above_18` for a person of that age.
+pub fn is_adult(age: u64) -> bool {
+    age >= ADULT_AGE
+}
+
+/// Returns the decimal number `YYYYMMDD` of a date, which orders the dates of 4-digit years chronologically.
+pub fn date_key(year: u64, month: u64, day: u64) -> u64 {
+    year * 10000 + month * 100 + day
+}
+
+/// A circuit computing the age in years from the date of birth and the current date.
+///
+/// The birth year is constrained to be the decimal number in the DOB field, whose digits are range-checked to be 0-9.
+/// The birth month and day in the DOB field are constrained to be a valid date, where February 29th is accepted whatever the birth year.
+/// The age is revealed as an instance when `reveal_age` is true, otherwise zero is revealed instead.
+///
+/// Independently of the revealed age, the circuit computes `age_above_18`, which is one if and only if the DOB is at least [`ADULT_AGE`] years before the current date.
+/// It is assigned by [`AgeCircuit::assign_age_cells`] for a parent circuit, which must also constrain the current date equal to the public one.
+#[derive(Debug, Clone, Default)]
+pub struct AgeCircuit<F: PrimeField> {
+    /// The DOB field as in the QR data.
+    dob: [u8; DOB_LEN],
+    birth_year: u64,
+    birth_month: u64,
+    birth_day: u64,
+    current_year: u64,
+    current_month: u64,
+    current_day: u64,
+    reveal_age: bool,
+    _f: PhantomData<F>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AgeConfig {
+    sel: Selector,
+    /// Enabled at the rows of `dob` holding a digit.
+    q_digit: Selector,
+    /// The DOB bytes over [`DOB_LEN`] rows from the row of `sel`.
+    dob: Column<Advice>,
+    digit_table: TableColumn,
+    /// Enabled at the row of `sel`, where the month and the day of the DOB field are looked up in `calendar_table`.
+    q_date: Selector,
+    calendar_table: TableColumn,
+    birth_year: Column<Advice>,
+    current_year: Column<Advice>,
+    before_birthday: Column<Advice>,
+    /// The decimal digits of the difference of the `MMDD` keys of the current date and the DOB shifted by `10^BIRTHDAY_KEY_DIGITS`, without its leading digit `1 - before_birthday`, the least significant first.
+    before_birthday_digits: Vec<Column<Advice>>,
+    age: Column<Advice>,
+    reveal_age: Column<Advice>,
+    revealed_age: Column<Advice>,
+    current_month: Column<Advice>,
+    current_day: Column<Advice>,
+    /// One if the DOB is at least [`ADULT_AGE`] years before the current date, and zero otherwise.
+    age_above_18: Column<Advice>,
+    /// The decimal digits of the difference of the date keys shifted by `10^DATE_KEY_DIGITS`, without its leading digit `age_above_18`, the least significant first.
+    age_above_18_digits: Vec<Column<Advice>>,
+    instance: Column<Instance>,
+}
+
+/// The cells assigned by [`AgeCircuit::assign_age_cells`], which a parent circuit can constrain equal to cells in other regions.
+#[derive(Debug, Clone)]
+pub struct AssignedAge<F: PrimeField> {
+    pub revealed_age: AssignedCell<F, F>,
+    /// One if the DOB is at least [`ADULT_AGE`] years before the current date, and zero otherwise.
+    pub age_above_18: AssignedCell<F, F>,
+    /// The current year, month and day, which are witnesses until constrained equal to the public date.
+    pub current_date: [AssignedCell<F, F>; 3],
+}
+
+impl AgeConfig {
+    /// Loads the table of the decimal digits, which the digits of the DOB field are looked up in.
+    pub fn load_digit_table<F: PrimeField>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_table(
+            || "decimal digits",
+            |mut table| {
+                for digit in 0..10 {
+                    table.assign_cell(
+                        || format!("digit {}", digit),
+                        self.digit_table,
+                        digit,
+                        || Value::known(F::from(digit as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Loads the table of [`load_calendar_table`], which the month and the day of the DOB field are looked up in.
+    pub fn load_calendar_table<F: PrimeField>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        load_calendar_table(layouter, self.calendar_table)
+    }
+}
+
+impl<F: PrimeField> AgeCircuit<F> {
+    /// Creates new [`AgeCircuit`] from the date of birth `(year, month, day)` and the current date.
+    pub fn new(birth_date: (u64, u64, u64), current_date: (u64, u64, u64), reveal_age: bool) -> Self {
+        let dob = format!("{:02}-{:02}-{:04}", birth_date.2, birth_date.1, birth_date.0);
+        let mut dob_bytes = [0; DOB_LEN];
+        dob_bytes.copy_from_slice(&dob.as_bytes()[..DOB_LEN]);
+        Self {
+            dob: dob_bytes,
+            birth_year: birth_date.0,
+            birth_month: birth_date.1,
+            birth_day: birth_date.2,
+            current_year: current_date.0,
+            current_month: current_date.1,
+            current_day: current_date.2,
+            reveal_age,
+            _f: PhantomData,
+        }
+    }
+
+    /// Creates new [`AgeCircuit`] from the DOB field `DD-MM-YYYY` of the QR data and the current date `(year, month, day)`.
+    ///
+    /// Non-digit bytes are parsed as zeros out of the circuit, and rejected by the range check of the digits in the circuit.
+    pub fn from_dob(dob: [u8; DOB_LEN], current_date: (u64, u64, u64), reveal_age: bool) -> Self {
+        let number = |bytes: &[u8]| {
+            bytes.iter().fold(0, |acc, byte| {
+                let digit = if byte.is_ascii_digit() { byte - b'0' } else { 0 };
+                acc * 10 + digit as u64
+            })
+        };
+        Self {
+            dob,
+            birth_year: number(&dob[DOB_YEAR_START..]),
+            birth_month: number(&dob[DOB_MONTH_START..DOB_MONTH_START + 2]),
+            birth_day: number(&dob[0..2]),
+            current_year: current_date.0,
+            current_month: current_date.1,
+            current_day: current_date.2,
+            reveal_age,
+            _f: PhantomData,
+        }
+    }
+
+    /// Returns true if the birthday has not yet come in the current year.
+    fn is_before_birthday(&self) -> bool {
+        (self.current_month, self.current_day) < (self.birth_month, self.birth_day)
+    }
+
+    /// Returns the age in years on the current date.
+    pub fn age(&self) -> u64 {
+        self.current_year
+            .saturating_sub(self.birth_year)
+            .saturating_sub(self.is_before_birthday() as u64)
+    }
+
+    /// Returns true if the DOB is at least [`ADULT_AGE`] years before the current date.
+    ///
+    /// A person born on February 29th comes of age on March 1st of a non-leap year.
+    pub fn is_age_above_18(&self) -> bool {
+        date_key(self.birth_year, self.birth_month, self.birth_day) + ADULT_AGE * 10000
+            <= date_key(self.current_year, self.current_month, self.current_day)
+    }
+
+    /// Returns the digits assigned to `age_above_18_digits`, i.e. those of the difference of the date keys shifted by `10^DATE_KEY_DIGITS`.
+    fn age_above_18_digits(&self) -> Vec<u64> {
+        let shifted = (date_key(self.current_year, self.current_month, self.current_day)
+            + 10u64.pow(DATE_KEY_DIGITS as u32))
+        .saturating_sub(date_key(self.birth_year, self.birth_month, self.birth_day))
+        .saturating_sub(ADULT_AGE * 10000);
+        (0..DATE_KEY_DIGITS)
+            .map(|i| shifted / 10u64.pow(i as u32) % 10)
+            .collect()
+    }
+
+    /// Returns the digits assigned to `before_birthday_digits`, i.e. those of the difference of the `MMDD` keys shifted by `10^BIRTHDAY_KEY_DIGITS`.
+    fn before_birthday_digits(&self) -> Vec<u64> {
+        let shifted = (date_key(0, self.current_month, self.current_day)
+            + 10u64.pow(BIRTHDAY_KEY_DIGITS as u32))
+        .saturating_sub(date_key(0, self.birth_month, self.birth_day));
+        (0..BIRTHDAY_KEY_DIGITS)
+            .map(|i| shifted / 10u64.pow(i as u32) % 10)
+            .collect()
+    }
+
+    /// Returns the value exposed as an instance, i.e. the age if `reveal_age` is true and zero otherwise.
+    pub fn revealed_age(&self) -> u64 {
+        if self.reveal_age {
+            self.age()
+        } else {
+            0
+        }
+    }
+
+    /// Assigns the DOB field, the years, the computed age and its revealed value.
+    ///
+    /// The tables of [`AgeConfig::load_digit_table`] and [`AgeConfig::load_calendar_table`] must be loaded once in the circuit.
+    ///
+    /// # Return values
+    /// Returns the assigned revealed age cell, so that a parent circuit can constrain it equal to cells in other regions.
+    pub fn assign_age(
+        &self,
+        config: &AgeConfig,
+        layouter: impl Layouter<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.assign_age_cells(config, layouter)
+            .map(|assigned| assigned.revealed_age)
+    }
+
+    /// Assigns the cells of [`AgeCircuit::assign_age`] and the comparison of the DOB with the current date.
+    ///
+    /// # Return values
+    /// Returns the assigned revealed age, `age_above_18` and current date.
+    pub fn assign_age_cells(
+        &self,
+        config: &AgeConfig,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<AssignedAge<F>, Error> {
+        layouter.assign_region(
+            || "age calculation",
+            |mut region| {
+                config.sel.enable(&mut region, 0)?;
+                config.q_date.enable(&mut region, 0)?;
+
+                for (i, byte) in self.dob.iter().enumerate() {
+                    if !DOB_SEPARATORS.contains(&i) {
+                        config.q_digit.enable(&mut region, i)?;
+                    }
+                    region.assign_advice(
+                        || format!("dob {}", i),
+                        config.dob,
+                        i,
+                        || Value::known(F::from(*byte as u64)),
+                    )?;
+                }
+
+                region.assign_advice(
+                    || "birth_year",
+                    config.birth_year,
+                    0,
+                    || Value::known(F::from(self.birth_year)),
+                )?;
+                let current_year = region.assign_advice(
+                    || "current_year",
+                    config.current_year,
+                    0,
+                    || Value::known(F::from(self.current_year)),
+                )?;
+                let current_month = region.assign_advice(
+                    || "current_month",
+                    config.current_month,
+                    0,
+                    || Value::known(F::from(self.current_month)),
+                )?;
+                let current_day = region.assign_advice(
+                    || "current_day",
+                    config.current_day,
+                    0,
+                    || Value::known(F::from(self.current_day)),
+                )?;
+                region.assign_advice(
+                    || "before_birthday",
+                    config.before_birthday,
+                    0,
+                    || Value::known(F::from(self.is_before_birthday() as u64)),
+                )?;
+                for (i, digit) in self.before_birthday_digits().into_iter().enumerate() {
+                    region.assign_advice(
+                        || format!("before_birthday digit {}", i),
+                        config.before_birthday_digits[i],
+                        0,
+                        || Value::known(F::from(digit)),
+                    )?;
+                }
+                region.assign_advice(
+                    || "age",
+                    config.age,
+                    0,
+                    || Value::known(F::from(self.age())),
+                )?;
+                region.assign_advice(
+                    || "reveal_age",
+                    config.reveal_age,
+                    0,
+                    || Value::known(F::from(self.reveal_age as u64)),
+                )?;
+                let revealed_age = region.assign_advice(
+                    || "revealed_age",
+                    config.revealed_age,
+                    0,
+                    || Value::known(F::from(self.revealed_age())),
+                )?;
+                let age_above_18 = region.assign_advice(
+                    || "age_above_18",
+                    config.age_above_18,
+                    0,
+                    || Value::known(F::from(self.is_age_above_18() as u64)),
+                )?;
+                for (i, digit) in self.age_above_18_digits().into_iter().enumerate() {
+                    region.assign_advice(
+                        || format!("age_above_18 digit {}", i),
+                        config.age_above_18_digits[i],
+                        0,
+                        || Value::known(F::from(digit)),
+                    )?;
+                }
+                Ok(AssignedAge {
+                    revealed_age,
+                    age_above_18,
+                    current_date: [current_year, current_month, current_day],
+                })
+            },
+        )
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for AgeCircuit<F> {
+    type Config = AgeConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let sel = meta.selector();
+        let q_digit = meta.complex_selector();
+        let dob = meta.advice_column();
+        let digit_table = meta.lookup_table_column();
+        let q_date = meta.complex_selector();
+        let calendar_table = meta.lookup_table_column();
+        let birth_year = meta.advice_column();
+        let current_year = meta.advice_column();
+        let before_birthday = meta.advice_column();
+        let before_birthday_digits = (0..BIRTHDAY_KEY_DIGITS)
+            .map(|_| meta.advice_column())
+            .collect::<Vec<Column<Advice>>>();
+        let age = meta.advice_column();
+        let reveal_age = meta.advice_column();
+        let revealed_age = meta.advice_column();
+        let current_month = meta.advice_column();
+        let current_day = meta.advice_column();
+        let age_above_18 = meta.advice_column();
+        let age_above_18_digits = (0..DATE_KEY_DIGITS)
+            .map(|_| meta.advice_column())
+            .collect::<Vec<Column<Advice>>>();
+        let instance = meta.instance_column();
+        meta.enable_equality(revealed_age);
+        for column in [current_year, current_month, current_day, age_above_18] {
+            meta.enable_equality(column);
+        }
+        meta.enable_equality(instance);
+
+        meta.lookup("dob digit", |meta| {
+            let q_digit = meta.query_selector(q_digit);
+            let dob = meta.query_advice(dob, Rotation::cur());
+            vec![(q_digit * (dob - Expression::Constant(F::from(b'0' as u64))), digit_table)]
+        });
+
+        // The birth year is not known to be a leap one, so the month and the day are looked up as those of a leap year.
+        meta.lookup("dob date", |meta| {
+            let q_date = meta.query_selector(q_date);
+            let mut number = |start: usize| {
+                let mut digit = |i: usize| {
+                    meta.query_advice(dob, Rotation(i as i32))
+                        - Expression::Constant(F::from(b'0' as u64))
+                };
+                digit(start) * Expression::Constant(F::from(10)) + digit(start + 1)
+            };
+            let day = number(0);
+            let month = number(DOB_MONTH_START);
+            vec![(
+                calendar_lookup_input(q_date, Expression::Constant(F::one()), month, day),
+                calendar_table,
+            )]
+        });
+
+        // Bounds the current month and day, so that the date key of the current date is not wrapped around.
+        meta.lookup("current date", |meta| {
+            let q_date = meta.query_selector(q_date);
+            let month = meta.query_advice(current_month, Rotation::cur());
+            let day = meta.query_advice(current_day, Rotation::cur());
+            vec![(
+                calendar_lookup_input(q_date, Expression::Constant(F::one()), month, day),
+                calendar_table,
+            )]
+        });
+
+        for column in before_birthday_digits.iter() {
+            meta.lookup("before_birthday digit", |meta| {
+                let q_date = meta.query_selector(q_date);
+                let digit = meta.query_advice(*column, Rotation::cur());
+                vec![(q_date * digit, digit_table)]
+            });
+        }
+
+        for column in age_above_18_digits.iter() {
+            meta.lookup("age_above_18 digit", |meta| {
+                let q_date = meta.query_selector(q_date);
+                let digit = meta.query_advice(*column, Rotation::cur());
+                vec![(q_date * digit, digit_table)]
+            });
+        }
+
+        // `current - (dob + 18 years) + 10^8` is in `[0, 2 * 10^8)` for 4-digit years, so its leading digit is one if and only if the difference is not negative.
+        meta.create_gate("age_above_18 comparison", |meta| {
+            let sel = meta.query_selector(sel);
+            let number = |digits: Vec<Expression<F>>| {
+                digits
+                    .into_iter()
+                    .fold(Expression::Constant(F::zero()), |acc, digit| {
+                        acc * Expression::Constant(F::from(10)) + digit
+                    })
+            };
+            let key = |year: Expression<F>, month: Expression<F>, day: Expression<F>| {
+                year * Expression::Constant(F::from(10000))
+                    + month * Expression::Constant(F::from(100))
+                    + day
+            };
+            let mut dob_digit = |i: usize| {
+                meta.query_advice(dob, Rotation(i as i32))
+                    - Expression::Constant(F::from(b'0' as u64))
+            };
+            let birth_day = number(vec![dob_digit(0), dob_digit(1)]);
+            let birth_month = number(vec![
+                dob_digit(DOB_MONTH_START),
+                dob_digit(DOB_MONTH_START + 1),
+            ]);
+            let birth_year = meta.query_advice(birth_year, Rotation::cur());
+            let current_year = meta.query_advice(current_year, Rotation::cur());
+            let current_month = meta.query_advice(current_month, Rotation::cur());
+            let current_day = meta.query_advice(current_day, Rotation::cur());
+            let age_above_18 = meta.query_advice(age_above_18, Rotation::cur());
+            let mut digits = vec![age_above_18.clone()];
+            for column in age_above_18_digits.iter().rev() {
+                digits.push(meta.query_advice(*column, Rotation::cur()));
+            }
+            let shifted = key(current_year, current_month, current_day)
+                - key(birth_year, birth_month, birth_day)
+                - Expression::Constant(F::from(ADULT_AGE * 10000))
+                + Expression::Constant(F::from(10u64.pow(DATE_KEY_DIGITS as u32)));
+            vec![
+                sel.clone()
+                    * age_above_18.clone()
+                    * (age_above_18 - Expression::Constant(F::one())),
+                sel * (shifted - number(digits)),
+            ]
+        });
+
+        meta.create_gate("dob format", |meta| {
+            let sel = meta.query_selector(sel);
+            let mut constraints = DOB_SEPARATORS
+                .iter()
+                .map(|i| {
+                    let separator = meta.query_advice(dob, Rotation(*i as i32));
+                    sel.clone() * (separator - Expression::Constant(F::from(b'-' as u64)))
+                })
+                .collect::<Vec<_>>();
+            let year = (DOB_YEAR_START..DOB_LEN).fold(Expression::Constant(F::zero()), |acc, i| {
+                let digit = meta.query_advice(dob, Rotation(i as i32))
+                    - Expression::Constant(F::from(b'0' as u64));
+                acc * Expression::Constant(F::from(10)) + digit
+            });
+            let birth_year = meta.query_advice(birth_year, Rotation::cur());
+            constraints.push(sel * (birth_year - year));
+            constraints
+        });
+
+        meta.create_gate("reveal_age constraint", |meta| {
+            let sel = meta.query_selector(sel);
+            let reveal_age = meta.query_advice(reveal_age, Rotation::cur());
+            vec![sel * reveal_age.clone() * (reveal_age - Expression::Constant(F::one()))]
+        });
+
+        // `current - birth + 10^4` of the `MMDD` keys is in `[0, 2 * 10^4)` for the dates of the calendar table, so its leading digit is zero if and only if the birthday has not yet come.
+        meta.create_gate("age calculation", |meta| {
+            let sel = meta.query_selector(sel);
+            let birth_year = meta.query_advice(birth_year, Rotation::cur());
+            let current_year = meta.query_advice(current_year, Rotation::cur());
+            let before_birthday = meta.query_advice(before_birthday, Rotation::cur());
+            let age = meta.query_advice(age, Rotation::cur());
+            let current_month = meta.query_advice(current_month, Rotation::cur());
+            let current_day = meta.query_advice(current_day, Rotation::cur());
+            let ten = Expression::Constant(F::from(10));
+            let mut dob_digit = |i: usize| {
+                meta.query_advice(dob, Rotation(i as i32))
+                    - Expression::Constant(F::from(b'0' as u64))
+            };
+            let hundred = Expression::Constant(F::from(100));
+            let birth_month =
+                dob_digit(DOB_MONTH_START) * ten.clone() + dob_digit(DOB_MONTH_START + 1);
+            let birth_day = dob_digit(0) * ten.clone() + dob_digit(1);
+            let birth_key = birth_month * hundred.clone() + birth_day;
+            let current_key = current_month * hundred + current_day;
+            let digits = before_birthday_digits.iter().rev().fold(
+                Expression::Constant(F::one()) - before_birthday.clone(),
+                |acc, column| acc * ten.clone() + meta.query_advice(*column, Rotation::cur()),
+            );
+            let shift = Expression::Constant(F::from(10u64.pow(BIRTHDAY_KEY_DIGITS as u32)));
+            vec![
+                sel.clone()
+                    * before_birthday.clone()
+                    * (before_birthday.clone() - Expression::Constant(F::one())),
+                sel.clone() * (current_key - birth_key + shift - digits),
+                sel * (age - (current_year - birth_year - before_birthday)),
+            ]
+        });
+
+        meta.create_gate("revealed_age assignment", |meta| {
+            let sel = meta.query_selector(sel);
+            let reveal_age = meta.query_advice(reveal_age, Rotation::cur());
+            let age = meta.query_advice(age, Rotation::cur());
+            let revealed_age = meta.query_advice(revealed_age, Rotation::cur());
+            vec![sel * (revealed_age - reveal_age * age)]
+        });
+
+        AgeConfig {
+            sel,
+            q_digit,
+            dob,
+            digit_table,
+            q_date,
+            calendar_table,
+            birth_year,
+            current_year,
+            before_birthday,
+            before_birthday_digits,
+            age,
+            reveal_age,
+            revealed_age,
+            current_month,
+            current_day,
+            age_above_18,
+            age_above_18_digits,
+            instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.load_digit_table(&mut layouter)?;
+        config.load_calendar_table(&mut layouter)?;
+        let revealed_age = self.assign_age(&config, layouter.namespace(|| "age"))?;
+        layouter.constrain_instance(revealed_age.cell(), config.instance, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_base::halo2_proofs::{
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::Fp},
+    };
+
+    #[test]
+    fn test_age_circuit() {
+        let k = 10;
+
+        // Born on 1984-08-15, the birthday has not yet come on 2024-07-08.
+        let circuit = AgeCircuit::<Fp>::new((1984, 8, 15), (2024, 7, 8), true);
+        assert_eq!(circuit.age(), 39);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(39)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(40)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The age is hidden when the flag is false.
+        let circuit = AgeCircuit::<Fp>::new((1984, 8, 15), (2024, 7, 8), false);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::zero()]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(39)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The birthday is compared with the month and the day of the DOB field in the circuit, so a witness of another birth month cannot skip the borrow of one year.
+        let forged = AgeCircuit::<Fp> {
+            birth_month: 1,
+            ..AgeCircuit::new((1984, 8, 15), (2024, 7, 8), true)
+        };
+        assert_eq!(forged.age(), 40);
+        let prover = MockProver::run(k, &forged, vec![vec![Fp::from(40)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The birthday itself is not before the birthday.
+        for (current_date, age) in [
+            ((2024, 8, 15), 40),
+            ((2024, 8, 14), 39),
+            ((2024, 12, 31), 40),
+        ] {
+            let circuit = AgeCircuit::<Fp>::new((1984, 8, 15), current_date, true);
+            assert_eq!(circuit.age(), age);
+            let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(age)]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+
+        // The circuit is proven over `bn256::Fr` as well, the field of the aggregated SNARK.
+        let circuit = AgeCircuit::<Fr>::new((1984, 8, 15), (2024, 7, 8), true);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fr::from(39)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_age_from_dob() {
+        let k = 10;
+
+        let circuit = AgeCircuit::<Fp>::from_dob(*b"15-08-1984", (2024, 7, 8), true);
+        assert_eq!(circuit.age(), 39);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(39)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A non-digit byte in the year is rejected.
+        let circuit = AgeCircuit::<Fp>::from_dob(*b"15-08-19:4", (2024, 7, 8), true);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(circuit.age())]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // So is a non-digit byte in the day, which does not affect the year.
+        let circuit = AgeCircuit::<Fp>::from_dob(*b"1/-08-1984", (2024, 7, 8), true);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(39)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The separators are fixed.
+        let circuit = AgeCircuit::<Fp>::from_dob(*b"15/08/1984", (2024, 7, 8), true);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(39)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_age_dob_calendar() {
+        let k = 10;
+        let is_satisfied = |dob: &[u8; DOB_LEN]| {
+            let circuit = AgeCircuit::<Fp>::from_dob(*dob, (2024, 7, 8), true);
+            let instance = vec![vec![Fp::from(circuit.age())]];
+            let prover = MockProver::run(k, &circuit, instance).unwrap();
+            prover.verify().is_ok()
+        };
+
+        assert!(is_satisfied(b"31-12-1984"));
+        assert!(is_satisfied(b"29-02-1984"));
+        // Month 13 and February 30th are made of digits, but are not dates.
+        assert!(!is_satisfied(b"15-13-1984"));
+        assert!(!is_satisfied(b"30-02-1984"));
+        assert!(!is_satisfied(b"31-04-1984"));
+        assert!(!is_satisfied(b"00-08-1984"));
+        assert!(!is_satisfied(b"15-00-1984"));
+    }
+
+    /// [`AgeCircuit`] exposing `age_above_18` and the current date in an additional instance column.
+    #[derive(Clone)]
+    struct AgeAbove18Circuit(AgeCircuit<Fp>);
+
+    impl Circuit<Fp> for AgeAbove18Circuit {
+        type Config = (AgeConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let config = AgeCircuit::configure(meta);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.load_digit_table(&mut layouter)?;
+            config.load_calendar_table(&mut layouter)?;
+            let assigned = self
+                .0
+                .assign_age_cells(&config, layouter.namespace(|| "age"))?;
+            layouter.constrain_instance(assigned.revealed_age.cell(), config.instance, 0)?;
+            layouter.constrain_instance(assigned.age_above_18.cell(), instance, 0)?;
+            for (i, cell) in assigned.current_date.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), instance, i + 1)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_age_above_18() {
+        let k = 10;
+        let current_date = (2024, 7, 8);
+        let verify = |dob: &[u8; DOB_LEN], age_above_18: bool| {
+            let circuit = AgeCircuit::<Fp>::from_dob(*dob, current_date, false);
+            let instance = vec![
+                vec![Fp::zero()],
+                vec![
+                    Fp::from(age_above_18 as u64),
+                    Fp::from(current_date.0),
+                    Fp::from(current_date.1),
+                    Fp::from(current_date.2),
+                ],
+            ];
+            let prover = MockProver::run(k, &AgeAbove18Circuit(circuit), instance).unwrap();
+            prover.verify()
+        };
+
+        // Exactly 18 years ago.
+        let circuit = AgeCircuit::<Fp>::from_dob(*b"08-07-2006", current_date, false);
+        assert!(circuit.is_age_above_18());
+        assert_eq!(verify(b"08-07-2006", true), Ok(()));
+        assert!(verify(b"08-07-2006", false).is_err());
+
+        // One day short of 18 years.
+        let circuit = AgeCircuit::<Fp>::from_dob(*b"09-07-2006", current_date, false);
+        assert!(!circuit.is_age_above_18());
+        assert_eq!(verify(b"09-07-2006", false), Ok(()));
+        assert!(verify(b"09-07-2006", true).is_err());
+
+        // 19 years ago.
+        let circuit = AgeCircuit::<Fp>::from_dob(*b"08-07-2005", current_date, false);
+        assert!(circuit.is_age_above_18());
+        assert_eq!(verify(b"08-07-2005", true), Ok(()));
+        assert!(verify(b"08-07-2005", false).is_err());
+
+        // The month outweighs the day.
+        assert_eq!(verify(b"31-01-2006", true), Ok(()));
+        assert_eq!(verify(b"01-12-2006", false), Ok(()));
+    }
+
+    #[test]
+    fn test_age_above_18_leap_day() {
+        let birth_date = (2004, 2, 29);
+        let circuit = AgeCircuit::<Fp>::new(birth_date, (2022, 2, 28), false);
+        assert!(!circuit.is_age_above_18());
+        let circuit = AgeCircuit::<Fp>::new(birth_date, (2022, 3, 1), false);
+        assert!(circuit.is_age_above_18());
+
+        let prover = MockProver::run(
+            10,
+            &AgeAbove18Circuit(circuit),
+            vec![
+                vec![Fp::zero()],
+                vec![Fp::one(), Fp::from(2022), Fp::from(3), Fp::from(1)],
+            ],
+        )
+        .unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
@@ -10,7 +10,7 @@ use halo2_base::{
     AssignedValue, Context,
 };
 use halo2_ecc::bigint::{
-    big_is_equal, big_is_zero, mul_no_carry, select, sub, FixedOverflowInteger, OverflowInteger,
+    big_is_equal, mul_no_carry, select, sub, FixedOverflowInteger, OverflowInteger,
 };
 use num_bigint::{BigInt, BigUint, Sign};
 use num_traits::{One, Zero};
@@ -44,6 +44,12 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         bit_len: usize,
     ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
         assert_eq!(bit_len % self.limb_bits, 0);
+        // The limbs only hold `bit_len` bits, so any higher bits of `value` would be silently dropped.
+        let mut is_overflowed = false;
+        value.as_ref().map(|v| is_overflowed = v.bits() as usize > bit_len);
+        if is_overflowed {
+            return Err(Error::Synthesis);
+        }
         let num_limbs = bit_len / self.limb_bits;
         let gate = self.gate();
         let range = self.range();
@@ -301,6 +307,17 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
     }
 
     /// Given two inputs `a,b` and a modulus `n`, performs the modular addition `a + b mod n`.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `a` - input of addition.
+    /// * `b` - input of addition.
+    /// * `n` - a modulus.
+    ///
+    /// # Return values
+    /// Returns the modular addition result `a + b mod n` in `[0, n)` as [`AssignedInteger<F, Fresh>`], with as many limbs as the longer of `a` and `b`.
+    /// # Requirements
+    /// Before calling this function, you must assert that `a<n` and `b<n`.
     fn add_mod<'v>(
         &self,
         ctx: &mut Context<'v, F>,
@@ -319,6 +336,17 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
     }
 
     /// Given two inputs `a,b` and a modulus `n`, performs the modular subtraction `a - b mod n`.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `a` - input of subtraction.
+    /// * `b` - input of subtraction.
+    /// * `n` - a modulus.
+    ///
+    /// # Return values
+    /// Returns the modular subtraction result `a - b mod n` in `[0, n)` as [`AssignedInteger<F, Fresh>`], with as many limbs as the longer of `a` and `b`.
+    /// # Requirements
+    /// Before calling this function, you must assert that `a<n` and `b<n`.
     fn sub_mod<'v>(
         &self,
         ctx: &mut Context<'v, F>,
@@ -331,11 +359,14 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         // 3. If the subtraction in 1 is overflowed, i.e., `a - b < 0`, returns `a - b + n`. Otherwise, returns `a - b`.
         // The number of limbs of `subed1` is `max(a.num_limbs(), b.num_limbs())`.
         let (subed1, is_overflowed1) = self.sub_unsafe(ctx, a, b)?;
-        // If `is_overflowed1=1`, `subed2` is equal to `a - b + n` because `subed1` is `b - a` in that case.
-        // The number of limbs of `subed2` is `max(n.num_limbs(), subed1.num_limbs()) >= subed1.num_limbs()`.
+        // If `is_overflowed1=1`, `subed2` is equal to `a - b + n`, which is less than `n` under the requirements.
+        // The number of limbs of `subed2` is `max(a.num_limbs(), n.num_limbs()) + 1`, as `added` carries one more limb.
         let added = self.add(ctx, a, n)?;
         let (subed2, is_overflowed2) = self.sub_unsafe(ctx, &added, b)?;
         self.gate().assert_is_const(ctx, &is_overflowed2, F::zero());
+        // `subed1` is extended with zero limbs so that both candidates have the same number of limbs.
+        let zero_value = self.gate().load_zero(ctx);
+        let subed1 = subed1.extend_limbs(subed2.num_limbs() - subed1.num_limbs(), zero_value);
         let result = self.select(ctx, &subed2, &subed1, &is_overflowed1)?;
         Ok(result.slice_limbs(0, result.num_limbs() - 2))
     }
@@ -489,14 +520,173 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         Ok(acc)
     }
 
+    /// Given two inputs `a,b` in the Montgomery form and a modulus `n`, performs the Montgomery multiplication `a * b * R^-1 mod n`, where `R = 2^(limb_bits * n.num_limbs())`.
+    ///
+    /// # Arguments
+    /// * `ctx` - a region context.
+    /// * `a` - input of multiplication.
+    /// * `b` - input of multiplication.
+    /// * `n` - a modulus.
+    ///
+    /// # Return values
+    /// Returns the Montgomery multiplication result in `[0, n)` as [`AssignedBigUint<F, Fresh>`], or [`Error::Synthesis`] if `n` is even, which has no Montgomery form.
+    /// # Requirements
+    /// Before calling this function, you must assert that `a<n` and `b<n`.
+    fn mul_mont<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        b: &AssignedBigUint<'v, F, Fresh>,
+        n: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        // Instead of dividing by `n`, we assert `a * b + m * n = t * R`, where the multiplication by `R` is a shift of limbs.
+        // Since `m = (t * R - a * b) / n` may be negative, its sign `s` is assigned and `a * b + (1 - s) * |m| * n = t * R + s * |m| * n` is asserted.
+        let limb_bits = self.limb_bits;
+        let num_limbs = n.num_limbs();
+        assert_eq!(a.num_limbs(), num_limbs);
+        assert_eq!(b.num_limbs(), num_limbs);
+        let r_big = BigUint::one() << (limb_bits * num_limbs);
+        // 1. Compute `t`, `|m|` and the sign of `m` as `BigUint`.
+        let mut is_invertible = true;
+        let witness = a
+            .value()
+            .zip(b.value())
+            .zip(n.value())
+            .map(|((a, b), n)| {
+                let r_inv = match r_big.modinv(&n) {
+                    Some(r_inv) => r_inv,
+                    None => {
+                        is_invertible = false;
+                        return (BigUint::zero(), BigUint::zero(), false);
+                    }
+                };
+                let ab = &a * &b;
+                let t = (&ab % &n) * r_inv % &n;
+                let tr = &t * &r_big;
+                if tr >= ab {
+                    (t, (tr - ab) / &n, false)
+                } else {
+                    (t, (ab - tr) / &n, true)
+                }
+            });
+        if !is_invertible {
+            return Err(Error::Synthesis);
+        }
+        let t_big = witness.as_ref().map(|(t, _, _)| t.clone());
+        let m_big = witness.as_ref().map(|(_, m, _)| m.clone());
+        let sign = witness.as_ref().map(|(_, _, sign)| F::from(*sign as u64));
+
+        // 2. Assign `t`, `|m|` and the sign after checking the range of each of them.
+        // The limbs only bound `t` by `R`, so `t < n` is asserted as well, otherwise `t + n` would satisfy the same constraints.
+        let assign_t = self.assign_integer(ctx, t_big, num_limbs * limb_bits)?;
+        self.assert_in_field(ctx, &assign_t, n)?;
+        let assign_m = self.assign_integer(ctx, m_big, num_limbs * limb_bits)?;
+        let gate = self.gate();
+        let sign = gate.load_witness(ctx, sign);
+        self.range().range_check(ctx, &sign, 1);
+
+        // 3. Assert `a * b + (1 - s) * |m| * n = t * R + s * |m| * n`.
+        let ab = self.mul(ctx, a, b)?;
+        let mn = self.mul(ctx, &assign_m, n)?;
+        let zero = gate.load_zero(ctx);
+        let (ab_limbs, mn_limbs, t_limbs) = (ab.limbs(), mn.limbs(), assign_t.limbs());
+        let mut lhs = Vec::with_capacity(2 * num_limbs);
+        let mut rhs = Vec::with_capacity(2 * num_limbs);
+        for i in 0..(2 * num_limbs - 1) {
+            let mn_r = gate.mul(
+                ctx,
+                QuantumCell::Existing(&sign),
+                QuantumCell::Existing(&mn_limbs[i]),
+            );
+            let mn_l = gate.sub(
+                ctx,
+                QuantumCell::Existing(&mn_limbs[i]),
+                QuantumCell::Existing(&mn_r),
+            );
+            lhs.push(gate.add(
+                ctx,
+                QuantumCell::Existing(&ab_limbs[i]),
+                QuantumCell::Existing(&mn_l),
+            ));
+            if i < num_limbs {
+                rhs.push(mn_r);
+            } else {
+                rhs.push(gate.add(
+                    ctx,
+                    QuantumCell::Existing(&mn_r),
+                    QuantumCell::Existing(&t_limbs[i - num_limbs]),
+                ));
+            }
+        }
+        lhs.push(zero);
+        rhs.push(t_limbs[num_limbs - 1].clone());
+        // Each limb of `lhs` is the sum of limbs of two products of `num_limbs` limbs integers.
+        let is_eq = self.is_equal_limbs(ctx, &lhs, &rhs, 2 * num_limbs, 2 * num_limbs);
+        gate.assert_is_const(ctx, &is_eq, F::one());
+        Ok(assign_t)
+    }
+
+    /// Given a base `a`, a fixed exponent `e`, and a modulus `n`, performs the modular power `a^e mod n` in the Montgomery form.
+    ///
+    /// `a` is converted into the Montgomery form once, the square-and-multiply loop runs with [`BigUintInstructions::mul_mont`], and the result is converted out once.
+    /// The accumulator starts from the power of the lowest set bit of `e` and the square after its highest set bit is skipped, so `e = 65537` takes 16 squares and a single multiplication.
+    ///
+    /// # Return values
+    /// Returns `a^e mod n` in `[0, n)`, or [`Error::Synthesis`] if `n` is even.
+    /// # Requirements
+    /// Before calling this function, you must assert that `a<n`.
+    fn pow_mont<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        e: &BigUint,
+        n: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        let num_limbs = a.num_limbs();
+        assert_eq!(num_limbs, n.num_limbs());
+        let num_e_bits = Self::bits_size(&BigInt::from_biguint(Sign::Plus, e.clone()));
+        if num_e_bits == 0 {
+            let zero = self.gate().load_zero(ctx);
+            let one = self.assign_constant(ctx, BigUint::one())?;
+            return Ok(one.extend_limbs(num_limbs - one.num_limbs(), zero));
+        }
+        let mut acc: Option<AssignedBigUint<'v, F, Fresh>> = None;
+        let mut squared = self.to_mont(ctx, a, n)?;
+        for i in 0..num_e_bits {
+            // If the `i`-th bit of `e` is one, update `acc` to `acc * squared`.
+            if e.bit(i as u64) {
+                acc = Some(match acc {
+                    Some(acc) => self.mul_mont(ctx, &acc, &squared, n)?,
+                    None => squared.clone(),
+                });
+            }
+            // Square `squared`, unless no higher bit uses it.
+            if i + 1 < num_e_bits {
+                squared = self.mul_mont(ctx, &squared, &squared, n)?;
+            }
+        }
+        // `e` is nonzero, so its highest bit set `acc`.
+        self.from_mont(ctx, &acc.unwrap(), n)
+    }
+
     /// Returns an assigned bit representing whether `a` is zero or not.
     fn is_zero<'v>(
         &self,
         ctx: &mut Context<'v, F>,
-        a: &'v AssignedBigUint<'v, F, Fresh>,
+        a: &AssignedBigUint<'v, F, Fresh>,
     ) -> Result<AssignedValue<'v, F>, Error> {
-        let out = big_is_zero::assign(self.gate(), ctx, a.int_ref());
-        Ok(out)
+        // The limbs of a fresh integer are range-checked, so `a` is zero if and only if all of its limbs are zero.
+        let gate = self.gate();
+        let mut is_zero = gate.load_constant(ctx, F::one());
+        for limb in a.limbs() {
+            let is_limb_zero = gate.is_zero(ctx, limb);
+            is_zero = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_zero),
+                QuantumCell::Existing(&is_limb_zero),
+            );
+        }
+        Ok(is_zero)
     }
 
     /// Returns an assigned bit representing whether `a` and `b` are equivalent, whose [`RangeType`] is [`Fresh`].
@@ -518,95 +708,13 @@ impl<F: PrimeField> BigUintInstructions<F> for BigUintConfig<F> {
         num_limbs_l: usize,
         num_limbs_r: usize,
     ) -> Result<AssignedValue<'v, F>, Error> {
-        // The following constraints are designed with reference to EqualWhenCarried template in https://github.com/jacksoom/circom-bigint/blob/master/circuits/mult.circom.
-        // We use lookup tables to optimize range checks.
         let min_n = if num_limbs_r >= num_limbs_l {
             num_limbs_l
         } else {
             num_limbs_r
         };
-        // Each limb of `a` and `b` is less than `min_n * (1^(limb_bits) - 1)^2  + (1^(limb_bits) - 1)`.
-        let muled_limb_max = Self::compute_muled_limb_max(self.limb_bits, min_n);
-        let muled_limb_max_fe = bigint_to_fe::<F>(&muled_limb_max);
         let num_limbs = num_limbs_l + num_limbs_r - 1;
-        let muled_limb_max_bits = Self::bits_size(&(&muled_limb_max * 2u32));
-        let carry_bits = muled_limb_max_bits - self.limb_bits;
-        let gate = self.gate();
-        let range = self.range();
-
-        // The naive approach is to subtract the two integers limb by limb and:
-        //  a. Verify that they sum to zero along the way while
-        //  b. Propagating carries
-        // but this doesn't work because early sums might be negative.
-        // So instead we verify that `a - b + word_max = word_max`.
-        let limb_max = BigInt::from(1) << self.limb_bits;
-        let zero = gate.load_constant(ctx, F::zero());
-        let mut accumulated_extra = zero.clone();
-        let mut carry = Vec::with_capacity(num_limbs);
-        let mut cs = Vec::with_capacity(num_limbs);
-        carry.push(zero.clone());
-        let mut eq_bit = gate.load_constant(ctx, F::one());
-        let a_limbs = a.limbs();
-        let b_limbs = b.limbs();
-        for i in 0..num_limbs {
-            // `sum = a - b + word_max`
-            let a_b_sub = gate.sub(
-                ctx,
-                QuantumCell::Existing(&a_limbs[i]),
-                QuantumCell::Existing(&b_limbs[i]),
-            );
-            let sum = gate.sum(
-                ctx,
-                vec![
-                    QuantumCell::Existing(&a_b_sub),
-                    QuantumCell::Existing(&carry[i]),
-                    QuantumCell::Constant(muled_limb_max_fe),
-                ],
-            );
-            // `c` is lower `self.limb_width` bits of `sum`.
-            // `new_carry` is any other upper bits.
-            let (new_carry, c) = self.div_mod_unsafe(ctx, &sum, &limb_max);
-            carry.push(new_carry);
-            cs.push(c);
-
-            // `accumulated_extra` is the sum of `word_max`.
-            accumulated_extra = gate.add(
-                ctx,
-                QuantumCell::Existing(&accumulated_extra),
-                QuantumCell::Constant(muled_limb_max_fe),
-            );
-            let (q_acc, mod_acc) = self.div_mod_unsafe(ctx, &accumulated_extra, &limb_max);
-            // If and only if `a` is equal to `b`, lower `self.limb_width` bits of `sum` and `accumulated_extra` are the same.
-            let cs_acc_eq = gate.is_equal(
-                ctx,
-                QuantumCell::Existing(&cs[i]),
-                QuantumCell::Existing(&mod_acc),
-            );
-            eq_bit = gate.and(
-                ctx,
-                QuantumCell::Existing(&eq_bit),
-                QuantumCell::Existing(&cs_acc_eq),
-            );
-            accumulated_extra = q_acc;
-
-            if i < num_limbs - 1 {
-                // Assert that each carry fits in `carry_bits` bits.
-                range.range_check(ctx, &carry[i + 1], carry_bits);
-            } else {
-                // The final carry should match the `accumulated_extra`.
-                let final_carry_eq = gate.is_equal(
-                    ctx,
-                    QuantumCell::Existing(&carry[i + 1]),
-                    QuantumCell::Existing(&accumulated_extra),
-                );
-                eq_bit = gate.and(
-                    ctx,
-                    QuantumCell::Existing(&eq_bit),
-                    QuantumCell::Existing(&final_carry_eq),
-                );
-            }
-        }
-        Ok(eq_bit)
+        Ok(self.is_equal_limbs(ctx, a.limbs(), b.limbs(), num_limbs, min_n))
     }
 
     /// Returns an assigned bit representing whether `a` is less than `b` (`a<b`).
@@ -733,6 +841,191 @@ impl<F: PrimeField> BigUintConfig<F> {
         )
     }
 
+    /// Returns the maximum number of bytes that [`BigUintConfig::pack_bytes`] packs into one field element without overflow.
+    pub fn max_packed_bytes() -> usize {
+        F::CAPACITY as usize / 8
+    }
+
+    /// Returns `[1, 2^8, ..., 2^(8*(n-1))]`, the bases to pack `n` little-endian bytes into a field element.
+    pub fn byte_shift_bases(n: usize) -> Vec<F> {
+        let shift = F::from(1u64 << 8);
+        let mut bases = Vec::with_capacity(n);
+        let mut base = F::one();
+        for _ in 0..n {
+            bases.push(base);
+            base *= shift;
+        }
+        bases
+    }
+
+    /// Packs little-endian bytes into a single field element, i.e. returns `sum_i bytes[i] * 2^(8*i)`.
+    ///
+    /// Each of `bytes` must already be constrained to 8 bits.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * bytes - at most [`BigUintConfig::max_packed_bytes`] assigned bytes, the least significant first.
+    ///
+    /// # Return values
+    /// Returns the packed value, or [`Error::Synthesis`] if the bytes do not fit in a field element.
+    pub fn pack_bytes<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        bytes: &[AssignedValue<'v, F>],
+    ) -> Result<AssignedValue<'v, F>, Error> {
+        if bytes.len() > Self::max_packed_bytes() {
+            return Err(Error::Synthesis);
+        }
+        let bases = Self::byte_shift_bases(bytes.len())
+            .into_iter()
+            .map(QuantumCell::Constant)
+            .collect::<Vec<QuantumCell<F>>>();
+        let bytes = bytes
+            .iter()
+            .map(QuantumCell::Existing)
+            .collect::<Vec<QuantumCell<F>>>();
+        Ok(self.gate().inner_product(ctx, bytes, bases))
+    }
+
+    /// Packs big-endian hash bytes into the little-endian limbs of an integer, e.g. the message hash consumed by [`crate::RSAInstructions::verify_pkcs1v15_signature`].
+    ///
+    /// Unlike [`BigUintConfig::pack_bytes`], each byte is range-checked to 8 bits and each packed limb to `limb_bits` bits, so the limbs are well-formed whichever chip assigned the bytes.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * hashed_bytes - the assigned hash bytes, the most significant first, whose number is a multiple of `limb_bits / 8`.
+    ///
+    /// # Return values
+    /// Returns the range-checked limbs, the least significant first, or [`Error::Synthesis`] if the bytes do not fill whole limbs.
+    pub fn pack_hash_limbs<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        hashed_bytes: &[AssignedValue<'v, F>],
+    ) -> Result<Vec<AssignedValue<'v, F>>, Error> {
+        let limb_bytes = self.limb_bits / 8;
+        if self.limb_bits % 8 != 0 || hashed_bytes.len() % limb_bytes != 0 {
+            return Err(Error::Synthesis);
+        }
+        let range = self.range();
+        for byte in hashed_bytes {
+            range.range_check(ctx, byte, 8);
+        }
+        let mut bytes = hashed_bytes.to_vec();
+        bytes.reverse();
+        bytes
+            .chunks(limb_bytes)
+            .map(|chunk| {
+                let limb = self.pack_bytes(ctx, chunk)?;
+                range.range_check(ctx, &limb, self.limb_bits);
+                Ok(limb)
+            })
+            .collect()
+    }
+
+    /// Decomposes `a` into its bytes, the inverse of [`BigUintConfig::pack_bytes`] for each limb.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * a - an assigned integer whose limb bits are a multiple of 8.
+    ///
+    /// # Return values
+    /// Returns the range-checked bytes of all limbs, the least significant first.
+    pub fn decompose_bytes<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<Vec<AssignedValue<'v, F>>, Error> {
+        assert_eq!(self.limb_bits % 8, 0);
+        let gate = self.gate();
+        let range = self.range();
+        let limb_bytes = self.limb_bits / 8;
+        let mut bytes = Vec::with_capacity(a.num_limbs() * limb_bytes);
+        for limb in a.limbs() {
+            let limb_big = limb.value().map(|v| fe_to_biguint(v));
+            let mut limb_assigned = Vec::with_capacity(limb_bytes);
+            for i in 0..limb_bytes {
+                let byte = limb_big
+                    .as_ref()
+                    .map(|v| biguint_to_fe::<F>(&((v >> (8 * i)) % 256u64)));
+                let byte = gate.load_witness(ctx, byte);
+                range.range_check(ctx, &byte, 8);
+                limb_assigned.push(byte);
+            }
+            let packed = self.pack_bytes(ctx, &limb_assigned)?;
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(limb),
+                QuantumCell::Existing(&packed),
+            );
+            bytes.append(&mut limb_assigned);
+        }
+        Ok(bytes)
+    }
+
+    /// Converts `a` into the Montgomery form `a * R mod n`, where `R = 2^(limb_bits * n.num_limbs())`.
+    ///
+    /// The product with `R` is a shift of limbs, so the conversion asserts `q * n + a * R mod n = a * R` directly instead of multiplying by `R^2 mod n`.
+    /// The result is asserted to be in `[0, n)`, as its limbs only bound it by `R`.
+    /// # Requirements
+    /// Before calling this function, you must assert that `a<n`.
+    pub fn to_mont<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        n: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        let limb_bits = self.limb_bits;
+        let num_limbs = n.num_limbs();
+        assert_eq!(a.num_limbs(), num_limbs);
+        let (q_big, mont_big) = a
+            .value()
+            .zip(n.value())
+            .map(|(a, n)| {
+                let ar = a << (limb_bits * num_limbs);
+                (&ar / &n, &ar % &n)
+            })
+            .unzip();
+        let assign_q = self.assign_integer(ctx, q_big, num_limbs * limb_bits)?;
+        let assign_mont = self.assign_integer(ctx, mont_big, num_limbs * limb_bits)?;
+        self.assert_in_field(ctx, &assign_mont, n)?;
+        let qn = self.mul(ctx, &assign_q, n)?;
+        let gate = self.gate();
+        let zero = gate.load_zero(ctx);
+        let (qn_limbs, mont_limbs) = (qn.limbs(), assign_mont.limbs());
+        let mut lhs = Vec::with_capacity(2 * num_limbs);
+        for i in 0..(2 * num_limbs - 1) {
+            if i < num_limbs {
+                lhs.push(gate.add(
+                    ctx,
+                    QuantumCell::Existing(&qn_limbs[i]),
+                    QuantumCell::Existing(&mont_limbs[i]),
+                ));
+            } else {
+                lhs.push(qn_limbs[i].clone());
+            }
+        }
+        lhs.push(zero.clone());
+        let mut rhs = vec![zero; num_limbs];
+        rhs.extend_from_slice(a.limbs());
+        let is_eq = self.is_equal_limbs(ctx, &lhs, &rhs, 2 * num_limbs, num_limbs);
+        gate.assert_is_const(ctx, &is_eq, F::one());
+        Ok(assign_mont)
+    }
+
+    /// Converts `a` out of the Montgomery form, i.e. returns `a * R^-1 mod n`.
+    pub fn from_mont<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        n: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        let num_limbs = n.num_limbs();
+        let zero = self.gate().load_zero(ctx);
+        let one = self.assign_constant(ctx, BigUint::one())?;
+        let one = one.extend_limbs(num_limbs - one.num_limbs(), zero);
+        self.mul_mont(ctx, a, &one, n)
+    }
+
     /// Returns the fewest bits necessary to express the [`BigUint`].
     fn bits_size(val: &BigInt) -> usize {
         val.bits() as usize
@@ -748,6 +1041,100 @@ impl<F: PrimeField> BigUintConfig<F> {
         num_limbs
     }
 
+    /// Returns an assigned bit representing whether the integers of the limbs `a_limbs` and `b_limbs` are equivalent.
+    ///
+    /// Each of the first `num_limbs` limbs must be less than the maximum limb of the product of two integers with `min_n` limbs.
+    fn is_equal_limbs<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a_limbs: &[AssignedValue<'v, F>],
+        b_limbs: &[AssignedValue<'v, F>],
+        num_limbs: usize,
+        min_n: usize,
+    ) -> AssignedValue<'v, F> {
+        // The following constraints are designed with reference to EqualWhenCarried template in https://github.com/jacksoom/circom-bigint/blob/master/circuits/mult.circom.
+        // We use lookup tables to optimize range checks.
+        // Each limb of `a` and `b` is less than `min_n * (1^(limb_bits) - 1)^2  + (1^(limb_bits) - 1)`.
+        let muled_limb_max = Self::compute_muled_limb_max(self.limb_bits, min_n);
+        let muled_limb_max_fe = bigint_to_fe::<F>(&muled_limb_max);
+        let muled_limb_max_bits = Self::bits_size(&(&muled_limb_max * 2u32));
+        let carry_bits = muled_limb_max_bits - self.limb_bits;
+        let gate = self.gate();
+        let range = self.range();
+
+        // The naive approach is to subtract the two integers limb by limb and:
+        //  a. Verify that they sum to zero along the way while
+        //  b. Propagating carries
+        // but this doesn't work because early sums might be negative.
+        // So instead we verify that `a - b + word_max = word_max`.
+        let limb_max = BigInt::from(1) << self.limb_bits;
+        let zero = gate.load_constant(ctx, F::zero());
+        let mut accumulated_extra = zero.clone();
+        let mut carry = Vec::with_capacity(num_limbs);
+        let mut cs = Vec::with_capacity(num_limbs);
+        carry.push(zero.clone());
+        let mut eq_bit = gate.load_constant(ctx, F::one());
+        for i in 0..num_limbs {
+            // `sum = a - b + word_max`
+            let a_b_sub = gate.sub(
+                ctx,
+                QuantumCell::Existing(&a_limbs[i]),
+                QuantumCell::Existing(&b_limbs[i]),
+            );
+            let sum = gate.sum(
+                ctx,
+                vec![
+                    QuantumCell::Existing(&a_b_sub),
+                    QuantumCell::Existing(&carry[i]),
+                    QuantumCell::Constant(muled_limb_max_fe),
+                ],
+            );
+            // `c` is lower `self.limb_width` bits of `sum`.
+            // `new_carry` is any other upper bits.
+            let (new_carry, c) = self.div_mod_unsafe(ctx, &sum, &limb_max);
+            carry.push(new_carry);
+            cs.push(c);
+
+            // `accumulated_extra` is the sum of `word_max`.
+            accumulated_extra = gate.add(
+                ctx,
+                QuantumCell::Existing(&accumulated_extra),
+                QuantumCell::Constant(muled_limb_max_fe),
+            );
+            let (q_acc, mod_acc) = self.div_mod_unsafe(ctx, &accumulated_extra, &limb_max);
+            // If and only if `a` is equal to `b`, lower `self.limb_width` bits of `sum` and `accumulated_extra` are the same.
+            let cs_acc_eq = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(&cs[i]),
+                QuantumCell::Existing(&mod_acc),
+            );
+            eq_bit = gate.and(
+                ctx,
+                QuantumCell::Existing(&eq_bit),
+                QuantumCell::Existing(&cs_acc_eq),
+            );
+            accumulated_extra = q_acc;
+
+            if i < num_limbs - 1 {
+                // Assert that each carry fits in `carry_bits` bits.
+                range.range_check(ctx, &carry[i + 1], carry_bits);
+            } else {
+                // The final carry should match the `accumulated_extra`.
+                let final_carry_eq = gate.is_equal(
+                    ctx,
+                    QuantumCell::Existing(&carry[i + 1]),
+                    QuantumCell::Existing(&accumulated_extra),
+                );
+                eq_bit = gate.and(
+                    ctx,
+                    QuantumCell::Existing(&eq_bit),
+                    QuantumCell::Existing(&final_carry_eq),
+                );
+            }
+        }
+        eq_bit
+    }
+
     /// Returns the maximum limb size of [`Muled`] type integers.
     fn compute_muled_limb_max(limb_width: usize, min_n: usize) -> BigInt {
         let one = BigInt::from(1usize);
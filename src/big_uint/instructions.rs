@@ -140,11 +140,29 @@ pub trait BigUintInstructions<F: PrimeField> {
         n: &AssignedBigUint<'v, F, Fresh>,
     ) -> Result<AssignedBigUint<'v, F, Fresh>, Error>;
 
+    /// Given two inputs `a,b` in the Montgomery form and a modulus `n`, performs the Montgomery multiplication `a * b * R^-1 mod n`, where `R = 2^(limb_bits * n.num_limbs())`.
+    fn mul_mont<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        b: &AssignedBigUint<'v, F, Fresh>,
+        n: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error>;
+
+    /// Given a base `a`, a fixed exponent `e`, and a modulus `n`, performs the modular power `a^e mod n` in the Montgomery form.
+    fn pow_mont<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedBigUint<'v, F, Fresh>,
+        e: &BigUint,
+        n: &AssignedBigUint<'v, F, Fresh>,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error>;
+
     /// Returns an assigned bit representing whether `a` is zero or not.
     fn is_zero<'v>(
         &self,
         ctx: &mut Context<'v, F>,
-        a: &'v AssignedBigUint<'v, F, Fresh>,
+        a: &AssignedBigUint<'v, F, Fresh>,
     ) -> Result<AssignedValue<'v, F>, Error>;
 
     /// Returns an assigned bit representing whether `a` and `b` are equivalent, whose [`RangeType`] is [`Fresh`].
@@ -68,6 +68,11 @@ impl<'v, F: PrimeField, T: RangeType> AssignedBigUint<'v, F, T> {
 }
 
 impl<'v, F: PrimeField> AssignedBigUint<'v, F, Fresh> {
+    /// Returns the bit width of the integer, i.e. the number of limbs times the bit length of each limb.
+    pub fn bits(&self) -> usize {
+        self.num_limbs() * self.int.max_limb_bits
+    }
+
     pub fn to_muled(self) -> AssignedBigUint<'v, F, Muled> {
         AssignedBigUint::new(self.int, self.value)
     }
@@ -17,6 +17,18 @@ pub fn decompose_bigint<F: PrimeField>(
     }
 }
 
+/// The order of the limbs returned by [`decompose_biguint_with_endianness`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    /// The least significant limb first, as the limbs assigned by [`crate::BigUintInstructions::assign_integer`] and the instances of the modulus.
+    Little,
+    /// The most significant limb first.
+    Big,
+}
+
+/// Decomposes `e` into `number_of_limbs` limbs of `limb_bits_len` bits in little-endian order.
+///
+/// This is the order of the limbs of [`crate::AssignedBigUint`], so the limbs of a modulus assigned with [`crate::RSAConfig::assign_public_key`] are constrained equal to these instances.
 pub fn decompose_biguint<F: PrimeField>(
     e: &BigUint,
     number_of_limbs: usize,
@@ -33,6 +45,20 @@ pub fn decompose_biguint<F: PrimeField>(
     }
 }
 
+/// Decomposes `e` as [`decompose_biguint`] into limbs in the order of `endianness`.
+pub fn decompose_biguint_with_endianness<F: PrimeField>(
+    e: &BigUint,
+    number_of_limbs: usize,
+    limb_bits_len: usize,
+    endianness: Endianness,
+) -> Vec<F> {
+    let mut limbs = decompose_biguint(e, number_of_limbs, limb_bits_len);
+    if endianness == Endianness::Big {
+        limbs.reverse();
+    }
+    limbs
+}
+
 pub(crate) fn decompose_u64_digits_to_limbs(
     e: impl IntoIterator<Item = u64>,
     number_of_limbs: usize,
@@ -1,4 +1,5 @@
 use crate::big_uint::BigUintInstructions;
+use crate::params::ParamsError;
 use crate::{
     AssignedBigUint, AssignedRSAPubE, AssignedRSAPublicKey, AssignedRSASignature, BigUintConfig,
     Fresh, RSAInstructions, RSAPubE, RSAPublicKey, RSASignature,
@@ -13,6 +14,57 @@ use halo2_base::{
 
 use num_bigint::BigUint;
 
+#[cfg(feature = "legacy-sha1")]
+use crate::sha1::SHA1_HASH_LEN;
+
+/// The DER encoding of the DigestInfo of SHA-1 up to the hash, as in [RFC 8017](https://www.rfc-editor.org/rfc/rfc8017#section-9.2).
+#[cfg(feature = "legacy-sha1")]
+const SHA1_DIGEST_INFO_PREFIX: [u8; 15] = [
+    0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04, 0x14,
+];
+
+/// The DER encoding of the DigestInfo of SHA256 up to the hash, as in [RFC 8017](https://www.rfc-editor.org/rfc/rfc8017#section-9.2).
+///
+/// The pkcs1v15 padding check expects these bytes right before the SHA256 hash, so they can be used to build test vectors of [`crate::RSASignatureVerifier`].
+/// It is also returned by [`HashAlg::digest_info_prefix`] of [`HashAlg::Sha256`].
+pub const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// The byte length of SHA256 digests.
+const SHA256_HASH_LEN: usize = 32;
+
+/// The hash function of a pkcs1v15 signature verified by [`crate::RSASignatureVerifier::verify_pkcs1v15_signature`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlg {
+    /// SHA256, hashed by the [`halo2_dynamic_sha256::Sha256DynamicConfig`] of the verifier.
+    Sha256,
+    /// SHA-1, hashed by [`crate::sha1::Sha1Config`], only for legacy Aadhaar QR codes.
+    #[cfg(feature = "legacy-sha1")]
+    Sha1,
+}
+
+impl HashAlg {
+    /// Returns the byte length of the digests.
+    pub const fn digest_len(&self) -> usize {
+        match self {
+            Self::Sha256 => SHA256_HASH_LEN,
+            #[cfg(feature = "legacy-sha1")]
+            Self::Sha1 => SHA1_HASH_LEN,
+        }
+    }
+
+    /// Returns the DER encoding of the DigestInfo up to the hash, which precedes the digest in the encoded message.
+    pub const fn digest_info_prefix(&self) -> &'static [u8] {
+        match self {
+            Self::Sha256 => &SHA256_DIGEST_INFO,
+            #[cfg(feature = "legacy-sha1")]
+            Self::Sha1 => &SHA1_DIGEST_INFO_PREFIX,
+        }
+    }
+}
+
 /// Configuration for [`RSAConfig`].
 #[derive(Clone, Debug)]
 pub struct RSAConfig<F: PrimeField> {
@@ -27,6 +79,12 @@ pub struct RSAConfig<F: PrimeField> {
 impl<F: PrimeField> RSAInstructions<F> for RSAConfig<F> {
     /// Assigns a [`AssignedRSAPublicKey`].
     ///
+    /// The modulus must be exactly `default_bits` bits long: a modulus with more bits is rejected with [`Error::Synthesis`], and the top bit of the top limb is constrained to be one.
+    ///
+    /// A variable exponent must fit in the `exp_bits` bits of [`RSAConfig::construct`]: a longer exponent is rejected with [`Error::Synthesis`] instead of making the modular power wrong.
+    ///
+    /// The returned key can be passed by reference to any number of verifications in the same context, e.g. by [`crate::RSASignatureVerifier::verify_pkcs1v15_signatures`], so its limbs are range-checked only once.
+    ///
     /// # Arguments
     /// * `ctx` - a region context.
     /// * `public_key` - a RSA public key to assign.
@@ -40,8 +98,23 @@ impl<F: PrimeField> RSAInstructions<F> for RSAConfig<F> {
     ) -> Result<AssignedRSAPublicKey<'v, F>, Error> {
         let biguint_config = self.biguint_config();
         let n = biguint_config.assign_integer(ctx, public_key.n, self.default_bits)?;
+        // The top limb is range-checked to `limb_bits` bits, so its top bit is one if and only if the limb minus that bit fits in `limb_bits - 1` bits.
+        let limb_bits = biguint_config.limb_bits();
+        let top_bit = biguint_to_fe::<F>(&(BigUint::from(1u64) << (limb_bits - 1)));
+        let top_limb_rest = self.gate().sub(
+            ctx,
+            QuantumCell::Existing(&n.limbs()[n.num_limbs() - 1]),
+            QuantumCell::Constant(top_bit),
+        );
+        self.range().range_check(ctx, &top_limb_rest, limb_bits - 1);
         let e = match public_key.e {
             RSAPubE::Var(e) => {
+                let mut is_overflowed = false;
+                e.as_ref()
+                    .map(|v| is_overflowed = v.bits() as usize > self.exp_bits);
+                if is_overflowed {
+                    return Err(Error::Synthesis);
+                }
                 let assigned = self.gate().load_witness(ctx, e.map(|v| biguint_to_fe(&v)));
                 self.range().range_check(ctx, &assigned, self.exp_bits);
                 AssignedRSAPubE::Var(assigned)
@@ -242,20 +315,31 @@ impl<F: PrimeField> RSAConfig<F> {
     /// # Arguments
     /// * biguint_config - a configuration for [`BigUintConfig`].
     /// * default_bits - the default bit length of [`Fresh`] type integers in this chip.
-    /// * exp_bits - the bit length of exponents.
+    /// * exp_bits - the bit length of variable exponents, which must be positive and less than the capacity of `F`, e.g. 17 for 65537.
     ///
     /// # Return values
-    /// Returns new [`RSAConfig`].
+    /// Returns new [`RSAConfig`], or [`ParamsError::InvalidExpBits`] if `exp_bits` is zero or not less than the capacity of `F`.
     pub fn construct(
         biguint_config: BigUintConfig<F>,
         default_bits: usize,
         exp_bits: usize,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, ParamsError> {
+        if exp_bits == 0 || exp_bits >= F::CAPACITY as usize {
+            return Err(ParamsError::InvalidExpBits {
+                exp_bits,
+                capacity: F::CAPACITY,
+            });
+        }
+        Ok(Self {
             biguint_config,
             default_bits,
             exp_bits,
-        }
+        })
+    }
+
+    /// Getter for the bit length of variable exponents, which bounds the exponents accepted by [`RSAInstructions::assign_public_key`].
+    pub fn exp_bits(&self) -> usize {
+        self.exp_bits
     }
 
     /// Return [`Context<F>`]
@@ -264,10 +348,161 @@ impl<F: PrimeField> RSAConfig<F> {
     }
 
     /// Getter for [`BigUintConfig`].
+    ///
+    /// Through the returned config, a gadget reaches the same chips as this chip:
+    /// [`BigUintInstructions::gate`] and [`BigUintInstructions::range`] to add constraints in a [`Context`], [`BigUintInstructions::limb_bits`] for the limb width, and the big-integer operations of [`BigUintInstructions`].
+    /// Since the chips add constraints through `&self`, this reference is enough for most gadgets.
     pub fn biguint_config(&self) -> &BigUintConfig<F> {
         &self.biguint_config
     }
 
+    /// Mutable getter for [`BigUintConfig`].
+    ///
+    /// Reaches the same chips as [`RSAConfig::biguint_config`], and additionally allows a gadget to reconfigure them through the public `range` and `limb_bits` fields of [`BigUintConfig`].
+    pub fn biguint_config_mut(&mut self) -> &mut BigUintConfig<F> {
+        &mut self.biguint_config
+    }
+
+    /// Returns a cell holding the exponent of `public_key`, so that a caller can expose it as a public input.
+    ///
+    /// A variable exponent is returned as its assigned cell.
+    /// A fixed exponent is assigned as a constant, so that verifiers reading the instance can confirm the exponent used in [`RSAInstructions::modpow_public_key`], e.g. that it is 65537 and not a small one.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key.
+    ///
+    /// # Return values
+    /// Returns the assigned exponent.
+    pub fn assign_exponent<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        match &public_key.e {
+            AssignedRSAPubE::Var(e) => e.clone(),
+            AssignedRSAPubE::Fix(e) => self.gate().load_constant(ctx, biguint_to_fe(e)),
+        }
+    }
+
+    /// Decrypts a pkcs1v15 signature with a RSA public key, i.e. computes the encoded message `signature^e mod n`.
+    ///
+    /// The encoded message is `0x00 || 0x01 || 0xff..0xff || 0x00 || T` for a valid signature, where `T` is the DER encoded DigestInfo followed by the hash.
+    /// Its limbs are returned so that the padding can be checked apart, e.g. by [`RSAConfig::check_sha256_digest_info`].
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * public_key - an assigned public key used for the decryption.
+    /// * signature - an assigned pkcs1v15 signature.
+    ///
+    /// # Return values
+    /// Returns the encoded message as [`AssignedBigUint<F, Fresh>`], whose limbs are in little-endian order.
+    pub fn decrypt_signature<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        signature: &AssignedRSASignature<'v, F>,
+    ) -> Result<AssignedBigUint<'v, F, Fresh>, Error> {
+        self.modpow_public_key(ctx, &signature.c, public_key)
+    }
+
+    /// Returns the offset of the DigestInfo of SHA256 in the big-endian bytes of the encoded message, i.e. 205 for a 2048-bit modulus.
+    ///
+    /// `T` takes the last bytes of the encoded message, so the offset only depends on the byte length of the modulus.
+    pub fn sha256_digest_info_offset(&self) -> usize {
+        self.default_bits / 8 - SHA256_DIGEST_INFO.len() - SHA256_HASH_LEN
+    }
+
+    /// Checks that an encoded message ends with `0x00 || T`, where `T` is the DER encoded DigestInfo of SHA256 followed by `hashed_msg`.
+    ///
+    /// The DigestInfo must start exactly at [`RSAConfig::sha256_digest_info_offset`], so a hash shifted by any number of bytes is rejected.
+    /// The bytes before the `0x00` separator, i.e. `0x00 || 0x01 || PS`, are not checked here.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * em - the assigned encoded message, e.g. returned by [`RSAConfig::decrypt_signature`].
+    /// * hashed_msg - the assigned bytes of the SHA256 message hash in big-endian order.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>`.
+    /// If the DigestInfo and `hashed_msg` are at the offset, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    pub fn check_sha256_digest_info<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        em: &AssignedBigUint<'v, F, Fresh>,
+        hashed_msg: &[AssignedValue<'v, F>],
+    ) -> Result<AssignedValue<'v, F>, Error> {
+        assert_eq!(hashed_msg.len(), SHA256_HASH_LEN);
+        let gate = self.gate();
+        let mut em = self.biguint_config.decompose_bytes(ctx, em)?;
+        em.reverse();
+        let offset = self.sha256_digest_info_offset();
+        let expected = [0x00]
+            .into_iter()
+            .chain(SHA256_DIGEST_INFO)
+            .map(|byte| QuantumCell::Constant(F::from(byte as u64)))
+            .chain(hashed_msg.iter().map(QuantumCell::Existing));
+        let mut is_eq = gate.load_constant(ctx, F::one());
+        for (byte, expected) in em[offset - 1..].iter().zip(expected) {
+            let is_byte_eq = gate.is_equal(ctx, QuantumCell::Existing(byte), expected);
+            is_eq = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_eq),
+                QuantumCell::Existing(&is_byte_eq),
+            );
+        }
+        Ok(is_eq)
+    }
+
+    /// Given a RSA public key, a SHA-1 message hash, and a pkcs1v15 signature, verifies the signature.
+    ///
+    /// Only for legacy Aadhaar QR codes signed with RSA-SHA1: SHA-1 is broken against collisions, so this is insecure for new use.
+    /// The encoded message is checked byte by byte as `0x00 || 0x01 || 0xff..0xff || 0x00 || T`, where `T` is the DER encoded DigestInfo of SHA-1 followed by the hash.
+    ///
+    /// # Arguments
+    /// * public_key - an assigned public key used for the verification.
+    /// * hashed_msg - the assigned bytes of the SHA-1 message hash in big-endian order.
+    /// * signature - a pkcs1v15 signature to be verified.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>`.
+    /// If `signature` is valid for `public_key` and `hashed_msg`, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    #[cfg(feature = "legacy-sha1")]
+    pub fn verify_pkcs1v15_signature_sha1<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        hashed_msg: &[AssignedValue<'v, F>],
+        signature: &AssignedRSASignature<'v, F>,
+    ) -> Result<AssignedValue<'v, F>, Error> {
+        assert_eq!(hashed_msg.len(), SHA1_HASH_LEN);
+        let gate = self.gate();
+        let powed = self.modpow_public_key(ctx, &signature.c, public_key)?;
+        let mut em = self.biguint_config.decompose_bytes(ctx, &powed)?;
+        em.reverse();
+        let t_len = SHA1_DIGEST_INFO_PREFIX.len() + SHA1_HASH_LEN;
+        let ps_len = em.len() - t_len - 3;
+        let expected_prefix = [0x00, 0x01]
+            .into_iter()
+            .chain(vec![0xff; ps_len])
+            .chain([0x00])
+            .chain(SHA1_DIGEST_INFO_PREFIX)
+            .map(|byte| QuantumCell::Constant(F::from(byte as u64)));
+        let expected = expected_prefix.chain(hashed_msg.iter().map(QuantumCell::Existing));
+        let mut is_eq = gate.load_constant(ctx, F::one());
+        for (byte, expected) in em.iter().zip(expected) {
+            let is_byte_eq = gate.is_equal(ctx, QuantumCell::Existing(byte), expected);
+            is_eq = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_eq),
+                QuantumCell::Existing(&is_byte_eq),
+            );
+        }
+        Ok(is_eq)
+    }
+
     /// Getter for [`FlexGateConfig`].
     pub fn gate(&self) -> &FlexGateConfig<F> {
         &self.biguint_config.gate()
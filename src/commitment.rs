@@ -0,0 +1,201 @@
+//! Committing to the full signed QR data by the Poseidon hash of its packed bytes, for selective openings after the proof.
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use halo2_base::{gates::flex_gate::FlexGateConfig, AssignedValue, Context};
+
+use crate::nullifier::{PHOTO_PACK_BYTES, RATE, T};
+use crate::photo::{assign_packed_bytes, pack_bytes};
+use crate::poseidon_chip::{poseidon_hash, PoseidonChip};
+
+/// Returns the number of elements the signed data of `len` bytes is packed into, i.e. `len / PHOTO_PACK_BYTES` rounded up.
+pub fn commitment_pack_size(len: usize) -> usize {
+    (len + PHOTO_PACK_BYTES - 1) / PHOTO_PACK_BYTES
+}
+
+/// Computes the commitment to `signed_data` out of the circuit, as [`CommitmentChip::assign_commitment`] does in it.
+///
+/// The bytes are packed by [`pack_bytes`] into [`commitment_pack_size`] elements, which are hashed by Poseidon.
+/// Unlike the SHA256 digest of the signature, the commitment is cheap to open in another circuit, e.g. to reveal a field of the signed data later.
+pub fn poseidon_commitment(signed_data: &[u8]) -> Fr {
+    let packed = pack_bytes::<Fr>(signed_data, commitment_pack_size(signed_data.len()))
+        .expect("the pack size fits the data");
+    poseidon_hash::<T, RATE>(&packed)
+}
+
+/// A chip computing the commitment of [`poseidon_commitment`] to the assigned signed data.
+///
+/// The length of the signed data is fixed by the circuit, so the zero padding of the last element does not make two data of different lengths collide.
+#[derive(Clone, Debug)]
+pub struct CommitmentChip {
+    poseidon: PoseidonChip<T, RATE>,
+}
+
+impl CommitmentChip {
+    /// Creates new [`CommitmentChip`] from [`FlexGateConfig`].
+    pub fn construct(gate: FlexGateConfig<Fr>) -> Self {
+        Self {
+            poseidon: PoseidonChip::construct(gate),
+        }
+    }
+
+    /// Getter for [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<Fr> {
+        self.poseidon.gate()
+    }
+
+    /// Packs the assigned signed data and hashes the packed elements.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * signed_data - the assigned bytes of the signed data, e.g. [`crate::Pkcs1v15Result::msg`] so that the committed data is the one whose signature is verified. They must be 8-bit as required by [`assign_packed_bytes`].
+    ///
+    /// # Return values
+    /// Returns the assigned commitment, which the caller exposes as an instance, e.g. by `constrain_instance` after the region.
+    pub fn assign_commitment<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        signed_data: &[AssignedValue<'v, Fr>],
+    ) -> AssignedValue<'v, Fr> {
+        let packed = assign_packed_bytes(
+            self.gate(),
+            ctx,
+            signed_data,
+            commitment_pack_size(signed_data.len()),
+        );
+        self.poseidon.hash(ctx, &packed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nullifier::{R_F, R_P};
+    use crate::poseidon_chip::{to_circuit_fr, to_native_fr};
+    use crate::qr::test_utils::{generate_test_qr, TestQrFields};
+    use crate::qr::{decompress_qr, signed_region};
+    use halo2_base::gates::{
+        range::{RangeConfig, RangeStrategy::Vertical},
+        GateInstructions, RangeInstructions,
+    };
+    use halo2_base::halo2_proofs::{
+        circuit::{Cell, Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use halo2_base::{ContextParams, SKIP_FIRST_PASS};
+    use halo2curves::bn256::Fr as FR;
+    use poseidon::Poseidon;
+
+    /// A circuit exposing the commitment to `signed_data`, range-checked to bytes, in the first row of the instance column.
+    #[derive(Clone)]
+    struct CommitmentCircuit {
+        signed_data: Vec<u8>,
+    }
+
+    impl CommitmentCircuit {
+        const K: u32 = 14;
+    }
+
+    impl Circuit<Fr> for CommitmentCircuit {
+        type Config = (RangeConfig<Fr>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let range =
+                RangeConfig::configure(meta, Vertical, &[4], &[1], 1, 8, 0, Self::K as usize);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (range, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (range, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            range.load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let mut commitment = None::<Cell>;
+            layouter.assign_region(
+                || "commitment",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = Context::new(
+                        region,
+                        ContextParams {
+                            max_rows: range.gate.max_rows,
+                            num_context_ids: 1,
+                            fixed_columns: range.gate.constants.clone(),
+                        },
+                    );
+                    let ctx = &mut aux;
+                    let signed_data = self
+                        .signed_data
+                        .iter()
+                        .map(|byte| {
+                            let byte = range
+                                .gate
+                                .load_witness(ctx, Value::known(Fr::from(*byte as u64)));
+                            range.range_check(ctx, &byte, 8);
+                            byte
+                        })
+                        .collect::<Vec<_>>();
+                    let chip = CommitmentChip::construct(range.gate.clone());
+                    commitment = Some(chip.assign_commitment(ctx, &signed_data).cell());
+                    range.finalize(ctx);
+                    Ok(())
+                },
+            )?;
+            layouter.constrain_instance(commitment.unwrap(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn test_commitment_pack_size() {
+        assert_eq!(commitment_pack_size(0), 0);
+        assert_eq!(commitment_pack_size(1), 1);
+        assert_eq!(commitment_pack_size(31), 1);
+        assert_eq!(commitment_pack_size(32), 2);
+    }
+
+    #[test]
+    fn test_assign_commitment() {
+        let k = CommitmentCircuit::K;
+        let (qr, _, _) = generate_test_qr(TestQrFields::default());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = signed_region(&decompressed, 2048).unwrap().to_vec();
+
+        // The exposed commitment matches the Poseidon hash of the packed QR computed out of the circuit.
+        let packed =
+            pack_bytes::<Fr>(&signed_data, commitment_pack_size(signed_data.len())).unwrap();
+        let mut poseidon = Poseidon::<FR, T, RATE>::new(R_F, R_P);
+        poseidon.update(&packed.iter().map(to_native_fr).collect::<Vec<FR>>());
+        let commitment = to_circuit_fr(&poseidon.squeeze());
+        assert_eq!(poseidon_commitment(&signed_data), commitment);
+        let circuit = CommitmentCircuit {
+            signed_data: signed_data.clone(),
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![commitment]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A flipped byte changes the commitment, so the proof does not hold for other data.
+        let mut tampered = signed_data.clone();
+        tampered[signed_data.len() - 1] ^= 1;
+        assert_ne!(poseidon_commitment(&tampered), commitment);
+        let prover = MockProver::run(
+            k,
+            &CommitmentCircuit {
+                signed_data: tampered,
+            },
+            vec![vec![commitment]],
+        )
+        .unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! A chip comparing and masking small integers encoded in field elements, such as the dates in the QR data.
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::{biguint_to_fe, fe_to_biguint, PrimeField},
+    AssignedValue, Context, QuantumCell,
+};
+use num_bigint::BigUint;
+
+/// The largest number of digits kept by [`ComparisonConfig::last_n_digits`], as `10^19` is the largest power of ten in `u64`.
+pub const MAX_MASKED_DIGITS: usize = 19;
+
+/// Configuration for comparisons of assigned integers bounded by a declared bit width.
+#[derive(Clone, Debug)]
+pub struct ComparisonConfig<F: PrimeField> {
+    range: RangeConfig<F>,
+}
+
+impl<F: PrimeField> ComparisonConfig<F> {
+    /// Creates new [`ComparisonConfig`] from [`RangeConfig`].
+    ///
+    /// # Arguments
+    /// * range - a configuration for [`RangeConfig`], e.g. the one of [`crate::BigUintConfig`].
+    ///
+    /// # Return values
+    /// Returns new [`ComparisonConfig`].
+    pub fn construct(range: RangeConfig<F>) -> Self {
+        Self { range }
+    }
+
+    /// Getter for [`RangeConfig`].
+    pub fn range(&self) -> &RangeConfig<F> {
+        &self.range
+    }
+
+    /// Returns an assigned bit representing whether `a` is less than `b` (`a<b`).
+    ///
+    /// Both `a` and `b` are range-checked to `num_bits` bits, so the comparison is sound for any witnesses: a value wider than `num_bits` bits makes the circuit unsatisfied instead of wrapping around.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * a - an assigned integer of at most `num_bits` bits.
+    /// * b - an assigned integer of at most `num_bits` bits.
+    /// * num_bits - the declared bit width of `a` and `b`, which must be less than the capacity of `F`.
+    pub fn less_than<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedValue<'v, F>,
+        b: &AssignedValue<'v, F>,
+        num_bits: usize,
+    ) -> AssignedValue<'v, F> {
+        assert!(num_bits < F::CAPACITY as usize);
+        self.range.range_check(ctx, a, num_bits);
+        self.range.range_check(ctx, b, num_bits);
+        self.range.is_less_than(
+            ctx,
+            QuantumCell::Existing(a),
+            QuantumCell::Existing(b),
+            num_bits,
+        )
+    }
+
+    /// Returns an assigned bit representing whether `value` is within the window `[not_before, not_after]` (`not_before<=value<=not_after`).
+    ///
+    /// The three integers are range-checked to `num_bits` bits as in [`ComparisonConfig::less_than`].
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * value - an assigned integer of at most `num_bits` bits, e.g. the timestamp of the QR data.
+    /// * not_before - the assigned inclusive lower bound of the window.
+    /// * not_after - the assigned inclusive upper bound of the window.
+    /// * num_bits - the declared bit width of the integers, which must be less than the capacity of `F`.
+    pub fn is_within_window<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        value: &AssignedValue<'v, F>,
+        not_before: &AssignedValue<'v, F>,
+        not_after: &AssignedValue<'v, F>,
+        num_bits: usize,
+    ) -> AssignedValue<'v, F> {
+        let gate = self.range.gate();
+        let is_early = self.less_than(ctx, value, not_before, num_bits);
+        let is_late = self.less_than(ctx, not_after, value, num_bits);
+        let is_not_early = gate.not(ctx, QuantumCell::Existing(&is_early));
+        let is_not_late = gate.not(ctx, QuantumCell::Existing(&is_late));
+        gate.and(
+            ctx,
+            QuantumCell::Existing(&is_not_early),
+            QuantumCell::Existing(&is_not_late),
+        )
+    }
+
+    /// Returns an assigned integer of the last `n` decimal digits of `packed_int`, i.e. `packed_int mod 10^n`.
+    ///
+    /// `packed_int` is decomposed into a quotient and a remainder less than `10^n`, both range-checked, so the remainder is unique for any witnesses.
+    /// The quotient is bounded so that the decomposition does not wrap around the modulus of `F`, hence `packed_int` must be less than `2^(F::CAPACITY - 1)`, e.g. any integer of at most 75 decimal digits.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * packed_int - an assigned integer, e.g. the Aadhaar reference ID or the pincode.
+    /// * n - the number of digits to keep, at most [`MAX_MASKED_DIGITS`].
+    pub fn last_n_digits<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        packed_int: &AssignedValue<'v, F>,
+        n: usize,
+    ) -> AssignedValue<'v, F> {
+        assert!(n <= MAX_MASKED_DIGITS);
+        let gate = self.range.gate();
+        let modulus = 10u64.pow(n as u32);
+        // `10^n < 2^remainder_bits`, so that any remainder fits in `remainder_bits` bits.
+        let remainder_bits = (u64::BITS - modulus.leading_zeros()) as usize;
+        let quotient_bits = F::CAPACITY as usize - remainder_bits;
+        let (quotient, remainder) = packed_int
+            .value()
+            .map(|v| {
+                let big_v = fe_to_biguint(v);
+                let modulus = BigUint::from(modulus);
+                let quotient = biguint_to_fe::<F>(&(&big_v / &modulus));
+                let remainder = biguint_to_fe::<F>(&(&big_v % &modulus));
+                (quotient, remainder)
+            })
+            .unzip();
+        let quotient = gate.load_witness(ctx, quotient);
+        self.range.range_check(ctx, &quotient, quotient_bits);
+        let remainder = gate.load_witness(ctx, remainder);
+        self.range.range_check(ctx, &remainder, remainder_bits);
+        let is_less = self.range.is_less_than(
+            ctx,
+            QuantumCell::Existing(&remainder),
+            QuantumCell::Constant(F::from(modulus)),
+            remainder_bits,
+        );
+        gate.assert_is_const(ctx, &is_less, F::one());
+        let recomposed = gate.mul_add(
+            ctx,
+            QuantumCell::Existing(&quotient),
+            QuantumCell::Constant(F::from(modulus)),
+            QuantumCell::Existing(&remainder),
+        );
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(packed_int),
+            QuantumCell::Existing(&recomposed),
+        );
+        remainder
+    }
+}
+
+/// Returns an assigned bit representing whether the integer of the bits `a` is less than that of the bits `b`, both the least significant first.
+///
+/// Unlike [`ComparisonConfig::less_than`], the integers are not bounded by the capacity of `F`, so full field elements can be compared by their [`GateInstructions::num_to_bits`], e.g. the leaves of [`crate::revocation::RevocationList`].
+pub fn bits_less_than<'v, F: PrimeField>(
+    gate: &FlexGateConfig<F>,
+    ctx: &mut Context<'v, F>,
+    a: &[AssignedValue<'v, F>],
+    b: &[AssignedValue<'v, F>],
+) -> AssignedValue<'v, F> {
+    assert_eq!(a.len(), b.len());
+    let mut is_less = gate.load_constant(ctx, F::zero());
+    let mut is_eq = gate.load_constant(ctx, F::one());
+    // From the most significant bit, `a` becomes less at the first bit where `a` has zero and `b` has one.
+    for (a_bit, b_bit) in a.iter().zip(b.iter()).rev() {
+        let is_a_zero = gate.not(ctx, QuantumCell::Existing(a_bit));
+        let is_bit_less = gate.and(
+            ctx,
+            QuantumCell::Existing(&is_a_zero),
+            QuantumCell::Existing(b_bit),
+        );
+        is_less = gate.mul_add(
+            ctx,
+            QuantumCell::Existing(&is_eq),
+            QuantumCell::Existing(&is_bit_less),
+            QuantumCell::Existing(&is_less),
+        );
+        let is_bit_eq = gate.is_equal(
+            ctx,
+            QuantumCell::Existing(a_bit),
+            QuantumCell::Existing(b_bit),
+        );
+        is_eq = gate.and(
+            ctx,
+            QuantumCell::Existing(&is_eq),
+            QuantumCell::Existing(&is_bit_eq),
+        );
+    }
+    is_less
+}
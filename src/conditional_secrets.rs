@@ -1,15 +1,138 @@
-use halo2_base::halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+use halo2_base::halo2_proofs::circuit::{AssignedCell, Cell, Layouter, SimpleFloorPlanner, Value};
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
 use halo2_base::halo2_proofs::plonk::{
-    Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector,
+    Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector,
 };
 use halo2_base::halo2_proofs::poly::Rotation;
 use halo2_base::utils::PrimeField;
 
+use crate::delimiter::{AssignedFields, NUM_DELIMITERS};
+use crate::field_extractor::DOB_POSITION;
+use crate::nullifier::{PoseidonCircuit, PoseidonConfig};
+use crate::photo::PackError;
+use crate::qr::QrFieldLayout;
+
+/// The positions of the revealable fields in the QR data, i.e. the numbers of their start delimiters.
+pub const GENDER_POSITION: usize = 5;
+pub const PINCODE_POSITION: usize = 11;
+pub const STATE_POSITION: usize = 13;
+
+/// The number of decimal digits of the pincode.
+pub const PINCODE_LEN: usize = 6;
+
+/// The number of bytes of the state bound to the QR data.
+pub const STATE_LEN: usize = 5;
+
+/// The number of values exposed as instances by [`IdentityCircuit`], see [`IdentityCircuit::revealed_values`].
+pub const NUM_REVEALED_VALUES: usize = 3 + STATE_LEN;
+
+/// How the bytes of a revealed attribute are exposed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeEncoding {
+    /// Each byte is exposed as a value.
+    Bytes,
+    /// The ASCII digits are exposed as a single number, the most significant first.
+    Decimal,
+}
+
+/// A revealable attribute of the QR data: the first `len` bytes of the field following the delimiter numbered `position`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeSpec {
+    pub name: &'static str,
+    pub position: usize,
+    pub len: usize,
+    pub encoding: AttributeEncoding,
+}
+
+impl AttributeSpec {
+    /// Creates the spec of an attribute whose bytes are exposed one by one.
+    pub const fn new(name: &'static str, position: usize, len: usize) -> Self {
+        Self {
+            name,
+            position,
+            len,
+            encoding: AttributeEncoding::Bytes,
+        }
+    }
+
+    /// Creates the spec of an attribute of `len` ASCII digits exposed as a number.
+    pub const fn decimal(name: &'static str, position: usize, len: usize) -> Self {
+        Self {
+            name,
+            position,
+            len,
+            encoding: AttributeEncoding::Decimal,
+        }
+    }
+
+    /// Returns the number of values exposed for the attribute.
+    pub fn num_values(&self) -> usize {
+        match self.encoding {
+            AttributeEncoding::Bytes => self.len,
+            AttributeEncoding::Decimal => 1,
+        }
+    }
+}
+
+/// The attributes revealed by [`IdentityCircuit`] after the age flag: the gender, the pincode and the leading bytes of the state.
+pub const IDENTITY_ATTRIBUTES: [AttributeSpec; 3] = [
+    AttributeSpec::new("gender", GENDER_POSITION, 1),
+    AttributeSpec::decimal("pincode", PINCODE_POSITION, PINCODE_LEN),
+    AttributeSpec::new("state", STATE_POSITION, STATE_LEN),
+];
+
+/// The witnesses of an attribute: its reveal flag, its exposed values and the bytes of its field, the last two zero-padded to the spec.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AttributeSlot {
+    pub reveal: Option<bool>,
+    pub value: Option<Vec<u64>>,
+    pub qr_data: Option<Vec<u8>>,
+}
+
+impl AttributeSlot {
+    /// Creates the slot of the `qr_data` bytes of `spec`, whose values are exposed if `reveal` and zero otherwise.
+    pub fn new(spec: &AttributeSpec, reveal: bool, qr_data: &[u8]) -> Self {
+        let slot = Self {
+            reveal: Some(reveal),
+            value: None,
+            qr_data: Some(qr_data.to_vec()),
+        };
+        Self {
+            value: Some(slot.revealed_values(spec)),
+            ..slot
+        }
+    }
+
+    /// Returns the [`AttributeSpec::num_values`] values exposed for `spec`: those of the `qr_data` bytes if revealed, zero otherwise.
+    pub fn revealed_values(&self, spec: &AttributeSpec) -> Vec<u64> {
+        let qr_data = self.qr_data.clone().unwrap_or_default();
+        let bytes = (0..spec.len).map(|i| *qr_data.get(i).unwrap_or(&0) as u64);
+        let values = match spec.encoding {
+            AttributeEncoding::Bytes => bytes.collect(),
+            AttributeEncoding::Decimal => vec![bytes.fold(0u64, |number, digit| {
+                number
+                    .wrapping_mul(10)
+                    .wrapping_add(digit.wrapping_sub(b'0' as u64))
+            })],
+        };
+        if self.reveal.unwrap_or(false) {
+            values
+        } else {
+            vec![0; values.len()]
+        }
+    }
+}
+
+/// A circuit revealing the age flag, the gender, the pincode and the state of the QR data.
+///
+/// Each value is exposed as an instance when its reveal flag is true, otherwise zero is exposed instead.
+/// The attributes other than the age flag are the slots of [`IDENTITY_ATTRIBUTES`], while a parent circuit reveals any other attributes with [`IdentityConfig::configure`] and [`IdentityConfig::assign_attributes`].
 #[derive(Default, Clone)]
 pub struct IdentityCircuit {
     reveal_age_above_18: Option<bool>,
-    age_above_18: Option<u64>,
-    qr_data_age_above_18: Option<u64>,
+    /// Whether the age is at least [`crate::age::ADULT_AGE`] years, see [`crate::age::is_adult`], rather than the age itself.
+    age_above_18: Option<bool>,
+    qr_data_age_above_18: Option<bool>,
     reveal_gender: Option<bool>,
     gender: Option<u8>,
     qr_data_gender: Option<u8>,
@@ -21,83 +144,88 @@ pub struct IdentityCircuit {
     qr_data_state: Option<Vec<u8>>,
 }
 
+/// The columns of an attribute configured by [`IdentityConfig::configure`].
+#[derive(Clone, Debug)]
+pub struct AttributeConfig {
+    pub spec: AttributeSpec,
+    reveal: Column<Advice>,
+    /// The exposed values, i.e. `reveal` times the values of `qr_data`.
+    value: Vec<Column<Advice>>,
+    /// The bytes of the field.
+    qr_data: Vec<Column<Advice>>,
+    /// The number of the digits of `qr_data`, only for [`AttributeEncoding::Decimal`].
+    qr_data_number: Option<Column<Advice>>,
+}
+
 #[derive(Clone)]
 pub struct IdentityConfig {
     reveal_age_above_18: Column<Advice>,
     age_above_18: Column<Advice>,
     qr_data_age_above_18: Column<Advice>,
-    reveal_gender: Column<Advice>,
-    gender: Column<Advice>,
-    qr_data_gender: Column<Advice>,
-    reveal_pincode: Column<Advice>,
-    pincode: Column<Advice>,
-    qr_data_pincode: Column<Advice>,
-    reveal_state: Column<Advice>,
-    state: Vec<Column<Advice>>,
-    qr_data_state: Vec<Column<Advice>>,
+    pub attributes: Vec<AttributeConfig>,
     s: Selector,
+    instance: Column<Instance>,
 }
 
-impl IdentityCircuit {
-    pub fn new(
-        reveal_age_above_18: Option<bool>,
-        age_above_18: Option<u64>,
-        qr_data_age_above_18: Option<u64>,
-        reveal_gender: Option<bool>,
-        gender: Option<u8>,
-        qr_data_gender: Option<u8>,
-        reveal_pincode: Option<bool>,
-        pincode: Option<u32>,
-        qr_data_pincode: Option<u32>,
-        reveal_state: Option<bool>,
-        state: Option<Vec<u8>>,
-        qr_data_state: Option<Vec<u8>>,
-    ) -> Self {
-        Self {
-            reveal_age_above_18,
-            age_above_18,
-            qr_data_age_above_18,
-            reveal_gender,
-            gender,
-            qr_data_gender,
-            reveal_pincode,
-            pincode,
-            qr_data_pincode,
-            reveal_state,
-            state,
-            qr_data_state,
-        }
-    }
+/// The assigned `qr_data` values of [`IdentityConfig::assign_attributes`], to be bound to the signed QR data with [`AssignedQrData::constrain_fields`].
+#[derive(Debug, Clone)]
+pub struct AssignedQrData<F: PrimeField> {
+    /// The specs of the attributes, in the order of `qr_data`.
+    pub specs: Vec<AttributeSpec>,
+    /// The bytes of each attribute.
+    pub qr_data: Vec<Vec<AssignedCell<F, F>>>,
+    /// The age flag followed by the values of each attribute, in the order of [`IdentityCircuit::revealed_values`].
+    pub revealed: Vec<AssignedCell<F, F>>,
 }
 
-impl<F: PrimeField> Circuit<F> for IdentityCircuit {
-    type Config = IdentityConfig;
-    type FloorPlanner = SimpleFloorPlanner;
-
-    fn without_witnesses(&self) -> Self {
-        unimplemented!();
+impl<F: PrimeField> AssignedQrData<F> {
+    /// Constrains the `qr_data` bytes of each attribute equal to the leading bytes of its field in the scanned QR data.
+    ///
+    /// The bytes of [`IDENTITY_ATTRIBUTES`] fit in the default field prefixes, while longer attributes need a scan configured by [`crate::delimiter::DelimiterCircuit::configure_with_prefix_len`].
+    ///
+    /// # Return values
+    /// Returns [`Error::Synthesis`] if an attribute is longer than the field prefixes of `fields`.
+    pub fn constrain_fields(
+        &self,
+        fields: &AssignedFields<F>,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "bind qr data",
+            |mut region| {
+                for (spec, qr_data) in self.specs.iter().zip(self.qr_data.iter()) {
+                    let field = &fields.field_prefixes[spec.position - 1];
+                    if qr_data.len() > field.len() {
+                        return Err(Error::Synthesis);
+                    }
+                    for (qr_byte, byte) in qr_data.iter().zip(field.iter()) {
+                        region.constrain_equal(qr_byte.cell(), byte.cell())?;
+                    }
+                }
+                Ok(())
+            },
+        )
     }
+}
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+impl IdentityConfig {
+    /// Configures the age flag and one attribute per spec in a single row.
+    ///
+    /// The gates of the attributes are generated per spec: the reveal flag is boolean, the values are `reveal` times those of the `qr_data` bytes, and the number of [`AttributeEncoding::Decimal`] digits is packed from them.
+    ///
+    /// # Panics
+    /// Panics if the position of a spec is not in `1..=NUM_DELIMITERS`.
+    pub fn configure<F: PrimeField>(
+        meta: &mut ConstraintSystem<F>,
+        specs: &[AttributeSpec],
+    ) -> Self {
         let reveal_age_above_18 = meta.advice_column();
         let age_above_18 = meta.advice_column();
         let qr_data_age_above_18 = meta.advice_column();
-        let reveal_gender = meta.advice_column();
-        let gender = meta.advice_column();
-        let qr_data_gender = meta.advice_column();
-        let reveal_pincode = meta.advice_column();
-        let pincode = meta.advice_column();
-        let qr_data_pincode = meta.advice_column();
-        let reveal_state = meta.advice_column();
-        let mut state = vec![];
-        for _i in 0..5 {
-            state.push(meta.advice_column());
-        }
-        let mut qr_data_state = vec![];
-        for _i in 0..5 {
-            qr_data_state.push(meta.advice_column());
-        }
         let s = meta.selector();
+        let instance = meta.instance_column();
+        meta.enable_equality(age_above_18);
+        meta.enable_equality(instance);
 
         meta.create_gate("revealAgeAbove18 constraint", |meta| {
             let s = meta.query_selector(s);
@@ -108,6 +236,15 @@ impl<F: PrimeField> Circuit<F> for IdentityCircuit {
             ]
         });
 
+        meta.create_gate("qrDataAgeAbove18 constraint", |meta| {
+            let s = meta.query_selector(s);
+            let qr_data_age_above_18 = meta.query_advice(qr_data_age_above_18, Rotation::cur());
+            vec![
+                s * qr_data_age_above_18.clone()
+                    * (qr_data_age_above_18 - Expression::Constant(F::one())),
+            ]
+        });
+
         meta.create_gate("ageAbove18 assignment", |meta| {
             let s = meta.query_selector(s);
             let reveal_age_above_18 = meta.query_advice(reveal_age_above_18, Rotation::cur());
@@ -116,60 +253,186 @@ impl<F: PrimeField> Circuit<F> for IdentityCircuit {
             vec![s * (age_above_18 - reveal_age_above_18 * qr_data_age_above_18)]
         });
 
-        meta.create_gate("gender constraint", |meta| {
-            let s = meta.query_selector(s);
-            let reveal_gender = meta.query_advice(reveal_gender, Rotation::cur());
-            vec![s * reveal_gender.clone() * (reveal_gender - Expression::Constant(F::one()))]
-        });
+        let mut attributes = Vec::with_capacity(specs.len());
+        for spec in specs {
+            assert!(
+                (1..=NUM_DELIMITERS).contains(&spec.position),
+                "attribute {} has no field position",
+                spec.name
+            );
+            let reveal = meta.advice_column();
+            let value = (0..spec.num_values())
+                .map(|_| meta.advice_column())
+                .collect::<Vec<Column<Advice>>>();
+            let qr_data = (0..spec.len)
+                .map(|_| meta.advice_column())
+                .collect::<Vec<Column<Advice>>>();
+            let qr_data_number = match spec.encoding {
+                AttributeEncoding::Bytes => None,
+                AttributeEncoding::Decimal => Some(meta.advice_column()),
+            };
+            for column in value.iter().chain(qr_data.iter()) {
+                meta.enable_equality(*column);
+            }
 
-        meta.create_gate("gender assignment", |meta| {
-            let s = meta.query_selector(s);
-            let gender = meta.query_advice(gender, Rotation::cur());
-            let qr_data_gender = meta.query_advice(qr_data_gender, Rotation::cur());
-            vec![s * (gender - qr_data_gender)]
-        });
+            meta.create_gate(spec.name, |meta| {
+                let s = meta.query_selector(s);
+                let reveal = meta.query_advice(reveal, Rotation::cur());
+                let mut constraints = vec![
+                    s.clone() * reveal.clone() * (reveal.clone() - Expression::Constant(F::one())),
+                ];
+                let qr_values = match qr_data_number {
+                    Some(qr_data_number) => {
+                        let qr_data_number = meta.query_advice(qr_data_number, Rotation::cur());
+                        let mut number = Expression::Constant(F::zero());
+                        for column in qr_data.iter() {
+                            let digit = meta.query_advice(*column, Rotation::cur());
+                            number = number * Expression::Constant(F::from(10)) + digit
+                                - Expression::Constant(F::from(b'0' as u64));
+                        }
+                        constraints.push(s.clone() * (qr_data_number.clone() - number));
+                        vec![qr_data_number]
+                    }
+                    None => qr_data
+                        .iter()
+                        .map(|column| meta.query_advice(*column, Rotation::cur()))
+                        .collect(),
+                };
+                for (value, qr_value) in value.iter().zip(qr_values) {
+                    let value = meta.query_advice(*value, Rotation::cur());
+                    constraints.push(s.clone() * (value - reveal.clone() * qr_value));
+                }
+                constraints
+            });
 
-        meta.create_gate("pincode constraint", |meta| {
-            let s = meta.query_selector(s);
-            let reveal_pincode = meta.query_advice(reveal_pincode, Rotation::cur());
-            vec![s * reveal_pincode.clone() * (reveal_pincode - Expression::Constant(F::one()))]
-        });
+            attributes.push(AttributeConfig {
+                spec: spec.clone(),
+                reveal,
+                value,
+                qr_data,
+                qr_data_number,
+            });
+        }
 
-        meta.create_gate("pincode assignment", |meta| {
-            let s = meta.query_selector(s);
-            let pincode = meta.query_advice(pincode, Rotation::cur());
-            let qr_data_pincode = meta.query_advice(qr_data_pincode, Rotation::cur());
-            vec![s * (pincode - qr_data_pincode)]
-        });
+        Self {
+            reveal_age_above_18,
+            age_above_18,
+            qr_data_age_above_18,
+            attributes,
+            s,
+            instance,
+        }
+    }
 
-        meta.create_gate("state constraint", |meta| {
-            let s = meta.query_selector(s);
-            let reveal_state = meta.query_advice(reveal_state, Rotation::cur());
-            vec![s * reveal_state.clone() * (reveal_state - Expression::Constant(F::one()))]
-        });
+    /// Assigns the age flags and one slot per configured attribute in a single row.
+    ///
+    /// All the bytes and values of each attribute are assigned, as the values are exposed as instances even if not provided.
+    ///
+    /// # Return values
+    /// Returns the assigned `qr_data` values, which are only tied to the signed data once constrained with [`AssignedQrData::constrain_fields`], and the revealed values.
+    /// Returns [`Error::Synthesis`] if the number of slots differs from the number of attributes.
+    pub fn assign_attributes<F: PrimeField>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        reveal_age_above_18: Option<bool>,
+        age_above_18: Option<bool>,
+        qr_data_age_above_18: Option<bool>,
+        slots: &[AttributeSlot],
+    ) -> Result<AssignedQrData<F>, Error> {
+        if slots.len() != self.attributes.len() {
+            return Err(Error::Synthesis);
+        }
+        layouter.assign_region(
+            || "identity constraints",
+            |mut region| {
+                self.s.enable(&mut region, 0)?;
+                let mut assign = |name: String, column: Column<Advice>, value: F| {
+                    region.assign_advice(|| name.clone(), column, 0, || Value::known(value))
+                };
 
-        meta.create_gate("state assignment", |meta| {
-            let s = meta.query_selector(s);
-            let mut constraints = Vec::with_capacity(5);
-            let mut states = Vec::with_capacity(5);
-            let mut qr_states = Vec::with_capacity(5);
-            for i in 0..5 {
-                states.push(meta.query_advice(state.clone()[i], Rotation::cur()));
-            }
-            for i in 0..5 {
-                qr_states.push(meta.query_advice(qr_data_state.clone()[i], Rotation::cur()));
-            }
-            let st = states.clone();
-            let qrs = qr_states.clone();
-            for i in 0..5 {
-                let a = st.get(i).unwrap().clone();
-                let b = qrs.get(i).unwrap().clone();
-                constraints.push(s.clone() * (a - b));
-            }
-            constraints
-        });
+                assign(
+                    "reveal_age_above_18".to_string(),
+                    self.reveal_age_above_18,
+                    F::from(reveal_age_above_18.unwrap_or(false) as u64),
+                )?;
+                assign(
+                    "qr_data_age_above_18".to_string(),
+                    self.qr_data_age_above_18,
+                    F::from(qr_data_age_above_18.unwrap_or(false) as u64),
+                )?;
+                let age_above_18 = assign(
+                    "age_above_18".to_string(),
+                    self.age_above_18,
+                    F::from(age_above_18.unwrap_or(false) as u64),
+                )?;
+
+                let mut revealed = vec![age_above_18];
+                let mut qr_data = Vec::with_capacity(slots.len());
+                for (config, slot) in self.attributes.iter().zip(slots.iter()) {
+                    let name = config.spec.name;
+                    assign(
+                        format!("reveal_{}", name),
+                        config.reveal,
+                        F::from(slot.reveal.unwrap_or(false) as u64),
+                    )?;
+
+                    let bytes = slot.qr_data.clone().unwrap_or_default();
+                    let mut number = F::zero();
+                    let mut qr_bytes = Vec::with_capacity(config.qr_data.len());
+                    for (i, column) in config.qr_data.iter().enumerate() {
+                        let byte = *bytes.get(i).unwrap_or(&0) as u64;
+                        number = number * F::from(10) + F::from(byte) - F::from(b'0' as u64);
+                        qr_bytes.push(assign(
+                            format!("qr_data_{}_{}", name, i),
+                            *column,
+                            F::from(byte),
+                        )?);
+                    }
+                    if let Some(column) = config.qr_data_number {
+                        assign(format!("qr_data_{}", name), column, number)?;
+                    }
+
+                    let values = slot.value.clone().unwrap_or_default();
+                    for (i, column) in config.value.iter().enumerate() {
+                        revealed.push(assign(
+                            format!("{}_{}", name, i),
+                            *column,
+                            F::from(*values.get(i).unwrap_or(&0)),
+                        )?);
+                    }
+                    qr_data.push(qr_bytes);
+                }
 
-        IdentityConfig {
+                Ok(AssignedQrData {
+                    specs: self
+                        .attributes
+                        .iter()
+                        .map(|config| config.spec.clone())
+                        .collect(),
+                    qr_data,
+                    revealed,
+                })
+            },
+        )
+    }
+}
+
+impl IdentityCircuit {
+    pub fn new(
+        reveal_age_above_18: Option<bool>,
+        age_above_18: Option<bool>,
+        qr_data_age_above_18: Option<bool>,
+        reveal_gender: Option<bool>,
+        gender: Option<u8>,
+        qr_data_gender: Option<u8>,
+        reveal_pincode: Option<bool>,
+        pincode: Option<u32>,
+        qr_data_pincode: Option<u32>,
+        reveal_state: Option<bool>,
+        state: Option<Vec<u8>>,
+        qr_data_state: Option<Vec<u8>>,
+    ) -> Self {
+        Self {
             reveal_age_above_18,
             age_above_18,
             qr_data_age_above_18,
@@ -182,123 +445,599 @@ impl<F: PrimeField> Circuit<F> for IdentityCircuit {
             reveal_state,
             state,
             qr_data_state,
-            s,
         }
     }
 
+    /// Creates a circuit revealing the attributes selected by `reveal` of the given `qr_data` values, and zero in place of the others.
+    pub fn disclose(
+        reveal: RevealFlags,
+        age_above_18: bool,
+        gender: u8,
+        pincode: u32,
+        state: &[u8],
+    ) -> Self {
+        let state = state[..state.len().min(STATE_LEN)].to_vec();
+        Self {
+            reveal_age_above_18: Some(reveal.age_above_18),
+            age_above_18: Some(reveal.age_above_18 && age_above_18),
+            qr_data_age_above_18: Some(age_above_18),
+            reveal_gender: Some(reveal.gender),
+            gender: Some(if reveal.gender { gender } else { 0 }),
+            qr_data_gender: Some(gender),
+            reveal_pincode: Some(reveal.pincode),
+            pincode: Some(if reveal.pincode { pincode } else { 0 }),
+            qr_data_pincode: Some(pincode),
+            reveal_state: Some(reveal.state),
+            state: Some(if reveal.state {
+                state.clone()
+            } else {
+                vec![0; state.len()]
+            }),
+            qr_data_state: Some(state),
+        }
+    }
+
+    /// Creates a circuit as [`IdentityCircuit::disclose`] for a QR code laid out by `layout`, e.g. a masked QR code omitting some fields.
+    ///
+    /// The reveal flags of the absent attributes are forced off by [`RevealFlags::masked`] and their `qr_data` values are zero, so zero is exposed in their place whatever `reveal` requests.
+    /// The age flag is absent with the DOB it is derived from.
+    pub fn disclose_with_layout(
+        reveal: RevealFlags,
+        layout: &QrFieldLayout,
+        age_above_18: bool,
+        gender: u8,
+        pincode: u32,
+        state: &[u8],
+    ) -> Self {
+        let present = RevealFlags::present(layout);
+        Self::disclose(
+            reveal.masked(layout),
+            present.age_above_18 && age_above_18,
+            if present.gender { gender } else { 0 },
+            if present.pincode { pincode } else { 0 },
+            if present.state { state } else { &[] },
+        )
+    }
+
+    /// Returns the slots of [`IDENTITY_ATTRIBUTES`], i.e. of the gender, the pincode and the state.
+    ///
+    /// The pincode is laid out as its [`PINCODE_LEN`] ASCII digits, as in the QR data.
+    pub fn attribute_slots(&self) -> Vec<AttributeSlot> {
+        vec![
+            AttributeSlot {
+                reveal: self.reveal_gender,
+                value: self.gender.map(|gender| vec![gender as u64]),
+                qr_data: self.qr_data_gender.map(|gender| vec![gender]),
+            },
+            AttributeSlot {
+                reveal: self.reveal_pincode,
+                value: self.pincode.map(|pincode| vec![pincode as u64]),
+                qr_data: Some(format!("{:06}", self.qr_data_pincode.unwrap_or(0)).into_bytes()),
+            },
+            AttributeSlot {
+                reveal: self.reveal_state,
+                value: self
+                    .state
+                    .as_ref()
+                    .map(|state| state.iter().map(|byte| *byte as u64).collect()),
+                qr_data: self.qr_data_state.clone(),
+            },
+        ]
+    }
+
+    /// Returns the values exposed as instances: `age_above_18`, the gender, the pincode and the [`STATE_LEN`] bytes of the state, each zero if it is not revealed.
+    pub fn revealed_values(&self) -> Vec<u64> {
+        let age_above_18 = if self.reveal_age_above_18.unwrap_or(false) {
+            self.qr_data_age_above_18.unwrap_or(false) as u64
+        } else {
+            0
+        };
+        let mut values = vec![age_above_18];
+        for (spec, slot) in IDENTITY_ATTRIBUTES.iter().zip(self.attribute_slots()) {
+            values.extend(slot.revealed_values(spec));
+        }
+        values
+    }
+
+    /// Assigns the reveal flags, the revealed values and the `qr_data` values as [`IdentityConfig::assign_attributes`] over the slots of [`IdentityCircuit::attribute_slots`].
+    ///
+    /// # Return values
+    /// Returns the assigned `qr_data` values, which are only tied to the signed data once constrained with [`AssignedQrData::constrain_fields`], and the revealed values.
+    pub fn assign_identity<F: PrimeField>(
+        &self,
+        config: &IdentityConfig,
+        layouter: impl Layouter<F>,
+    ) -> Result<AssignedQrData<F>, Error> {
+        config.assign_attributes(
+            layouter,
+            self.reveal_age_above_18,
+            self.age_above_18,
+            self.qr_data_age_above_18,
+            &self.attribute_slots(),
+        )
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for IdentityCircuit {
+    type Config = IdentityConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        IdentityConfig::configure(meta, &IDENTITY_ATTRIBUTES)
+    }
+
     fn synthesize(
         &self,
         config: IdentityConfig,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
-        layouter.assign_region(
-            || "identity constraints",
-            |mut region| {
-                config.s.enable(&mut region, 0)?;
+        let assigned = self.assign_identity(&config, layouter.namespace(|| "identity"))?;
+        for (i, cell) in assigned.revealed.iter().enumerate() {
+            layouter.constrain_instance(cell.cell(), config.instance, i)?;
+        }
+        Ok(())
+    }
+}
 
-                region.assign_advice(
-                    || "reveal_age_above_18",
-                    config.reveal_age_above_18,
-                    0,
-                    || Value::known(F::from(self.reveal_age_above_18.unwrap_or(false) as u64)),
-                )?;
+/// The reveal flags of [`IdentityCircuit::disclose`], one per revealable attribute.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RevealFlags {
+    pub age_above_18: bool,
+    pub gender: bool,
+    pub pincode: bool,
+    pub state: bool,
+}
 
-                region.assign_advice(
-                    || "qr_data_age_above_18",
-                    config.qr_data_age_above_18,
-                    0,
-                    || Value::known(F::from(self.qr_data_age_above_18.unwrap_or(0) as u64)),
-                )?;
+impl RevealFlags {
+    /// Returns the flags of the attributes present in `layout`, the age flag being present with the DOB.
+    pub fn present(layout: &QrFieldLayout) -> Self {
+        Self {
+            age_above_18: layout.is_present(DOB_POSITION),
+            gender: layout.is_present(GENDER_POSITION),
+            pincode: layout.is_present(PINCODE_POSITION),
+            state: layout.is_present(STATE_POSITION),
+        }
+    }
 
-                region.assign_advice(
-                    || "age_above_18",
-                    config.age_above_18,
-                    0,
-                    || Value::known(F::from(self.age_above_18.unwrap_or(0) as u64)),
-                )?;
+    /// Returns these flags with those of the attributes absent from `layout` forced off.
+    pub fn masked(self, layout: &QrFieldLayout) -> Self {
+        let present = Self::present(layout);
+        Self {
+            age_above_18: self.age_above_18 && present.age_above_18,
+            gender: self.gender && present.gender,
+            pincode: self.pincode && present.pincode,
+            state: self.state && present.state,
+        }
+    }
+}
 
-                region.assign_advice(
-                    || "reveal_gender",
-                    config.reveal_gender,
-                    0,
-                    || Value::known(F::from(self.reveal_gender.unwrap_or(false) as u64)),
-                )?;
+/// A circuit disclosing any subset of the age flag, the gender, the pincode and the state of the QR data together with one nullifier.
+///
+/// The attributes are revealed by [`IdentityCircuit`] with independent reveal flags, so a single proof covers e.g. the age flag and the state while hiding the gender and the pincode.
+/// The single instance column holds the [`NUM_REVEALED_VALUES`] values of [`IdentityCircuit::revealed_values`] followed by the nullifier in the row [`DisclosureCircuit::NULLIFIER_ROW`].
+/// The nullifier is hashed in the circuit by [`PoseidonCircuit::assign`] over the assigned seed and photo, so it equals [`PoseidonCircuit::nullifier`] only for the photo of the witness.
+/// A parent circuit binds the `qr_data` values to the signed data with [`AssignedQrData::constrain_fields`] on the cells returned by [`DisclosureCircuit::assign_disclosure`].
+#[derive(Default, Clone)]
+pub struct DisclosureCircuit {
+    identity: IdentityCircuit,
+    nullifier: PoseidonCircuit,
+}
 
-                region.assign_advice(
-                    || "gender",
-                    config.gender,
-                    0,
-                    || Value::known(F::from(self.gender.unwrap_or(0) as u64)),
-                )?;
+#[derive(Clone)]
+pub struct DisclosureConfig {
+    identity: IdentityConfig,
+    nullifier: PoseidonConfig,
+}
 
-                region.assign_advice(
-                    || "qr_data_gender",
-                    config.qr_data_gender,
-                    0,
-                    || Value::known(F::from(self.qr_data_gender.unwrap_or(0) as u64)),
-                )?;
+impl DisclosureCircuit {
+    /// The row of the nullifier in the instance column.
+    pub const NULLIFIER_ROW: usize = NUM_REVEALED_VALUES;
 
-                region.assign_advice(
-                    || "reveal_pincode",
-                    config.reveal_pincode,
-                    0,
-                    || Value::known(F::from(self.reveal_pincode.unwrap_or(false) as u64)),
-                )?;
+    /// Creates new [`DisclosureCircuit`] revealing the attributes of `identity` with the nullifier of `nullifier`.
+    pub fn new(identity: IdentityCircuit, nullifier: PoseidonCircuit) -> Self {
+        Self {
+            identity,
+            nullifier,
+        }
+    }
 
-                region.assign_advice(
-                    || "pincode",
-                    config.pincode,
-                    0,
-                    || Value::known(F::from(self.pincode.unwrap_or(0) as u64)),
-                )?;
+    /// Returns the public inputs of the instance column, i.e. the revealed values followed by the nullifier.
+    ///
+    /// # Return values
+    /// Returns the instances, or [`PackError::TooLong`] if the photo of the nullifier is too long.
+    pub fn instances(&self) -> Result<Vec<Fr>, PackError> {
+        let mut instances = self
+            .identity
+            .revealed_values()
+            .into_iter()
+            .map(Fr::from)
+            .collect::<Vec<Fr>>();
+        instances.push(self.nullifier.nullifier()?);
+        Ok(instances)
+    }
 
-                region.assign_advice(
-                    || "qr_data_pincode",
-                    config.qr_data_pincode,
-                    0,
-                    || Value::known(F::from(self.qr_data_pincode.unwrap_or(0) as u64)),
-                )?;
+    /// Assigns the attributes as [`IdentityCircuit::assign_identity`] and the nullifier as [`PoseidonCircuit::assign`].
+    ///
+    /// # Return values
+    /// Returns the assigned `qr_data` values with the revealed values, and the cell of the assigned nullifier.
+    pub fn assign_disclosure(
+        &self,
+        config: &DisclosureConfig,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(AssignedQrData<Fr>, Cell), Error> {
+        let qr_data = self
+            .identity
+            .assign_identity(&config.identity, layouter.namespace(|| "identity"))?;
+        let nullifier = self
+            .nullifier
+            .assign(&config.nullifier, layouter.namespace(|| "nullifier"))?;
+        Ok((qr_data, nullifier.nullifier))
+    }
+}
 
-                region.assign_advice(
-                    || "reveal_state",
-                    config.reveal_state,
-                    0,
-                    || Value::known(F::from(self.reveal_state.unwrap_or(false) as u64)),
-                )?;
+impl Circuit<Fr> for DisclosureCircuit {
+    type Config = DisclosureConfig;
+    type FloorPlanner = SimpleFloorPlanner;
 
-                if let Some(state) = &self.state {
-                    for (i, &byte) in state.iter().enumerate() {
-                        region.assign_advice(
-                            || format!("state_{}", i),
-                            config.state[i],
-                            0,
-                            || Value::known(F::from(byte as u64)),
-                        )?;
-                    }
-                }
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
 
-                if let Some(qr_data_state) = &self.qr_data_state {
-                    for (i, &byte) in qr_data_state.iter().enumerate() {
-                        region.assign_advice(
-                            || format!("qr_data_state_{}", i),
-                            config.qr_data_state[i],
-                            0,
-                            || Value::known(F::from(byte as u64)),
-                        )?;
-                    }
-                }
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let identity = <IdentityCircuit as Circuit<Fr>>::configure(meta);
+        let nullifier = PoseidonConfig::configure(meta);
+        DisclosureConfig {
+            identity,
+            nullifier,
+        }
+    }
 
-                Ok(())
-            },
-        )
+    fn synthesize(
+        &self,
+        config: DisclosureConfig,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let (qr_data, nullifier) =
+            self.assign_disclosure(&config, layouter.namespace(|| "disclosure"))?;
+        for (i, cell) in qr_data.revealed.iter().enumerate() {
+            layouter.constrain_instance(cell.cell(), config.identity.instance, i)?;
+        }
+        layouter.constrain_instance(nullifier, config.identity.instance, Self::NULLIFIER_ROW)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::age::{is_adult, AgeCircuit};
+    use crate::delimiter::{DelimiterCircuit, DelimiterConfig, DELIMITER};
+    use crate::nullifier::compute_nullifier;
+    use crate::qr::test_utils::{generate_test_qr, mask_fields, TestQrFields};
+    use crate::qr::{decompress_qr, qr_fields_with_layout, signed_region};
+    use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
     use halo2_base::halo2_proofs::{dev::MockProver, halo2curves::pasta::Fp};
 
+    /// [`IdentityCircuit`] whose `qr_data` values are bound to the fields scanned by [`DelimiterCircuit`].
+    #[derive(Clone)]
+    struct BoundIdentityCircuit {
+        identity: IdentityCircuit,
+        delimiter: DelimiterCircuit<Fr>,
+    }
+
+    impl Circuit<Fr> for BoundIdentityCircuit {
+        type Config = (IdentityConfig, DelimiterConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let identity = <IdentityCircuit as Circuit<Fr>>::configure(meta);
+            let delimiter = DelimiterCircuit::configure(meta);
+            (identity, delimiter)
+        }
+
+        fn synthesize(
+            &self,
+            (identity, delimiter): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let qr_data = self
+                .identity
+                .assign_identity(&identity, layouter.namespace(|| "identity"))?;
+            let fields = self
+                .delimiter
+                .assign_fields(&delimiter, layouter.namespace(|| "delimiter"))?;
+            qr_data.constrain_fields(&fields, layouter.namespace(|| "bind"))
+        }
+    }
+
+    /// The instances of [`IdentityCircuit`], preceding any instance column of a parent circuit.
+    fn identity_instances<F: PrimeField>(circuit: &IdentityCircuit) -> Vec<F> {
+        circuit.revealed_values().into_iter().map(F::from).collect()
+    }
+
+    fn signed_data() -> Vec<u8> {
+        let fields = [
+            "V2",
+            "3",
+            "269720240718124557387",
+            "Sumit Kumar",
+            "01-01-1984",
+            "M",
+            "C/O Ishwar Chand",
+            "East Delhi",
+            "",
+            "B-31, 3rd Floor",
+            "",
+            "110051",
+            "Krishna Nagar",
+            "Delhi",
+            "Radhey Shyam Park Extension",
+            "Gandhi Nagar",
+            "Krishna Nagar",
+            "1234",
+        ];
+        let mut data = vec![];
+        for field in fields {
+            data.extend_from_slice(field.as_bytes());
+            data.push(DELIMITER);
+        }
+        data.extend([255, 79, 255, 81, 0, 47, 0, 0]);
+        data
+    }
+
+    fn bound_identity_circuit(qr_data_gender: u8, qr_data_pincode: u32) -> BoundIdentityCircuit {
+        BoundIdentityCircuit {
+            identity: IdentityCircuit::new(
+                Some(true),
+                Some(true),
+                Some(true),
+                Some(true),
+                Some(qr_data_gender),
+                Some(qr_data_gender),
+                Some(true),
+                Some(qr_data_pincode),
+                Some(qr_data_pincode),
+                Some(true),
+                Some(b"Delhi".to_vec()),
+                Some(b"Delhi".to_vec()),
+            ),
+            delimiter: DelimiterCircuit::new(signed_data()),
+        }
+    }
+
+    /// Six attributes of the QR data, some longer than [`crate::delimiter::FIELD_PREFIX_LEN`] bytes.
+    const SIX_ATTRIBUTES: [AttributeSpec; 6] = [
+        AttributeSpec::new("gender", GENDER_POSITION, 1),
+        AttributeSpec::new("care_of", 6, 16),
+        AttributeSpec::new("district", 7, 10),
+        AttributeSpec::decimal("pincode", PINCODE_POSITION, PINCODE_LEN),
+        AttributeSpec::new("post_office", 12, 13),
+        AttributeSpec::new("state", STATE_POSITION, STATE_LEN),
+    ];
+
+    /// The length of the longest of [`SIX_ATTRIBUTES`].
+    const SIX_ATTRIBUTES_PREFIX_LEN: usize = 16;
+
+    /// The attributes of [`SIX_ATTRIBUTES`], whose `qr_data` bytes are bound to the fields scanned by [`DelimiterCircuit`].
+    #[derive(Clone)]
+    struct SixAttributesCircuit {
+        slots: Vec<AttributeSlot>,
+        delimiter: DelimiterCircuit<Fr>,
+    }
+
+    impl Circuit<Fr> for SixAttributesCircuit {
+        type Config = (IdentityConfig, DelimiterConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let identity = IdentityConfig::configure(meta, &SIX_ATTRIBUTES);
+            let delimiter =
+                DelimiterCircuit::configure_with_prefix_len(meta, SIX_ATTRIBUTES_PREFIX_LEN);
+            (identity, delimiter)
+        }
+
+        fn synthesize(
+            &self,
+            (identity, delimiter): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let qr_data = identity.assign_attributes(
+                layouter.namespace(|| "attributes"),
+                Some(false),
+                Some(false),
+                Some(false),
+                &self.slots,
+            )?;
+            for (i, cell) in qr_data.revealed.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), identity.instance, i)?;
+            }
+            let fields = self
+                .delimiter
+                .assign_fields(&delimiter, layouter.namespace(|| "delimiter"))?;
+            qr_data.constrain_fields(&fields, layouter.namespace(|| "bind"))
+        }
+    }
+
+    fn six_attribute_slots(reveals: [bool; 6]) -> Vec<AttributeSlot> {
+        let qr_data: [&[u8]; 6] = [
+            b"M",
+            b"C/O Ishwar Chand",
+            b"East Delhi",
+            b"110051",
+            b"Krishna Nagar",
+            b"Delhi",
+        ];
+        SIX_ATTRIBUTES
+            .iter()
+            .zip(reveals)
+            .zip(qr_data)
+            .map(|((spec, reveal), qr_data)| AttributeSlot::new(spec, reveal, qr_data))
+            .collect()
+    }
+
+    /// The instances of [`SixAttributesCircuit`]: the hidden age flag followed by the values of each slot.
+    fn six_attribute_instances(slots: &[AttributeSlot]) -> Vec<Fr> {
+        let mut values = vec![0];
+        for (spec, slot) in SIX_ATTRIBUTES.iter().zip(slots.iter()) {
+            values.extend(slot.revealed_values(spec));
+        }
+        values.into_iter().map(Fr::from).collect()
+    }
+
+    #[test]
+    fn test_six_attribute_slots() {
+        let k = 11;
+        let run = |slots: Vec<AttributeSlot>, instances: Vec<Fr>| {
+            let circuit = SixAttributesCircuit {
+                slots,
+                delimiter: DelimiterCircuit::new(signed_data()),
+            };
+            MockProver::run(k, &circuit, vec![instances, vec![]])
+                .unwrap()
+                .verify()
+        };
+
+        for reveals in [
+            [true; 6],
+            [true, false, false, true, false, true],
+            [false; 6],
+        ] {
+            let slots = six_attribute_slots(reveals);
+            let instances = six_attribute_instances(&slots);
+            assert_eq!(run(slots, instances), Ok(()));
+        }
+
+        // The pincode is revealed as a number, and the other attributes byte by byte.
+        let slots = six_attribute_slots([true; 6]);
+        assert_eq!(slots[3].revealed_values(&SIX_ATTRIBUTES[3]), vec![110051]);
+        assert_eq!(
+            six_attribute_instances(&slots).len(),
+            1 + 1 + 16 + 10 + 1 + 13 + STATE_LEN
+        );
+
+        // A hidden attribute must not leak its value.
+        let slots = six_attribute_slots([true, false, true, true, true, true]);
+        let instances = six_attribute_instances(&slots);
+        let mut leaked = slots;
+        leaked[1].value = Some(b"C/O Ishwar Chand".iter().map(|b| *b as u64).collect());
+        assert!(run(leaked, instances).is_err());
+
+        // A revealed value must be the one in the QR data.
+        let slots = six_attribute_slots([true; 6]);
+        let instances = six_attribute_instances(&slots);
+        let mut forged = slots;
+        forged[2].value = Some(b"West Delhi".iter().map(|b| *b as u64).collect());
+        assert!(run(forged, instances).is_err());
+
+        // So must the qr_data bytes, including those beyond the default field prefix.
+        for (i, qr_data) in [(4, &b"Gandhi Nagar!"[..]), (1, &b"C/O Ishwar Chant"[..])] {
+            let mut slots = six_attribute_slots([true; 6]);
+            slots[i] = AttributeSlot::new(&SIX_ATTRIBUTES[i], true, qr_data);
+            let instances = six_attribute_instances(&slots);
+            assert!(run(slots, instances).is_err());
+        }
+    }
+
+    /// [`SixAttributesCircuit`] over the default field prefixes cannot bind the attributes longer than them.
+    #[test]
+    fn test_attribute_longer_than_field_prefix() {
+        #[derive(Clone)]
+        struct DefaultPrefixCircuit(SixAttributesCircuit);
+
+        impl Circuit<Fr> for DefaultPrefixCircuit {
+            type Config = (IdentityConfig, DelimiterConfig);
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                unimplemented!();
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+                let identity = IdentityConfig::configure(meta, &SIX_ATTRIBUTES);
+                let delimiter = DelimiterCircuit::configure(meta);
+                (identity, delimiter)
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                layouter: impl Layouter<Fr>,
+            ) -> Result<(), Error> {
+                self.0.synthesize(config, layouter)
+            }
+        }
+
+        let slots = six_attribute_slots([true; 6]);
+        let instances = six_attribute_instances(&slots);
+        let circuit = DefaultPrefixCircuit(SixAttributesCircuit {
+            slots,
+            delimiter: DelimiterCircuit::new(signed_data()),
+        });
+        assert!(MockProver::run(11, &circuit, vec![instances, vec![]]).is_err());
+    }
+
+    #[test]
+    fn test_identity_bound_to_qr_data() {
+        let k = 11;
+
+        let run = |circuit: BoundIdentityCircuit| {
+            let instances = vec![identity_instances(&circuit.identity), vec![]];
+            MockProver::run(k, &circuit, instances).unwrap().verify()
+        };
+
+        assert_eq!(run(bound_identity_circuit(b'M', 110051)), Ok(()));
+
+        // A gender other than the extracted one is rejected.
+        assert!(run(bound_identity_circuit(b'F', 110051)).is_err());
+
+        // So is a pincode other than the extracted one.
+        assert!(run(bound_identity_circuit(b'M', 110052)).is_err());
+    }
+
+    #[test]
+    fn test_identity_age_above_18_from_age() {
+        let k = 4;
+        let current_date = (2024, 7, 8);
+        for (birth_date, expected_age) in [
+            ((2007, 7, 9), 16),
+            ((2006, 7, 9), 17),
+            ((2006, 7, 8), 18),
+            ((2005, 7, 8), 19),
+            ((1984, 8, 15), 39),
+        ] {
+            let age = AgeCircuit::<Fp>::new(birth_date, current_date, true);
+            assert_eq!(age.age(), expected_age);
+            // The flag derived from the computed age is the one the age gadget constrains.
+            let age_above_18 = is_adult(age.age());
+            assert_eq!(age_above_18, age.is_age_above_18());
+
+            let identity = |value: bool| IdentityCircuit {
+                reveal_age_above_18: Some(true),
+                age_above_18: Some(value),
+                qr_data_age_above_18: Some(age_above_18),
+                ..IdentityCircuit::default()
+            };
+            let instances = vec![identity_instances(&identity(age_above_18))];
+            let prover: MockProver<Fp> =
+                MockProver::run(k, &identity(age_above_18), instances.clone()).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+            let prover: MockProver<Fp> =
+                MockProver::run(k, &identity(!age_above_18), instances).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
     #[test]
     fn test_identity_circuit() {
         let k = 4; // The size of the circuit (log_2 of the number of rows)
@@ -306,8 +1045,8 @@ mod tests {
         // Test case where reveal_age_above_18 is true
         let circuit = IdentityCircuit {
             reveal_age_above_18: Some(true),
-            age_above_18: Some(1),
-            qr_data_age_above_18: Some(1),
+            age_above_18: Some(true),
+            qr_data_age_above_18: Some(true),
             reveal_gender: Some(true),
             gender: Some(1),
             qr_data_gender: Some(1),
@@ -319,9 +1058,43 @@ mod tests {
             qr_data_state: Some(vec![10, 11, 12, 13, 14]),
         };
 
-        let prover: MockProver<Fp> = MockProver::run(k, &circuit, vec![]).unwrap();
+        let instances = vec![identity_instances(&circuit)];
+        let prover: MockProver<Fp> = MockProver::run(k, &circuit, instances).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The circuit is proven over `bn256::Fr` as well, the field of the aggregated SNARK.
+        let instances = vec![identity_instances::<Fr>(&circuit)];
+        let prover = MockProver::run(k, &circuit, instances).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Hidden values are exposed as zero.
+        let hidden = IdentityCircuit {
+            reveal_gender: Some(false),
+            gender: Some(0),
+            reveal_state: Some(false),
+            state: Some(vec![0; STATE_LEN]),
+            ..circuit.clone()
+        };
+        let instances = identity_instances::<Fp>(&hidden);
+        assert_eq!(instances.len(), NUM_REVEALED_VALUES);
+        assert_eq!(instances[1], Fp::zero());
+        assert_eq!(instances[3..], [Fp::zero(); STATE_LEN]);
+        let prover: MockProver<Fp> = MockProver::run(k, &hidden, vec![instances]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
 
+        // A hidden value must not leak, neither in the witness nor in the instances.
+        let leaked = IdentityCircuit {
+            gender: Some(1),
+            ..hidden.clone()
+        };
+        let instances = vec![identity_instances(&leaked)];
+        let prover: MockProver<Fp> = MockProver::run(k, &leaked, instances).unwrap();
+        assert!(prover.verify().is_err());
+        let mut instances = identity_instances::<Fp>(&hidden);
+        instances[1] = Fp::one();
+        let prover: MockProver<Fp> = MockProver::run(k, &hidden, vec![instances]).unwrap();
+        assert!(prover.verify().is_err());
+
         // Test case where reveal_age_above_18 is false
         /*let circuit = IdentityCircuit {
             reveal_age_above_18: Some(false),
@@ -474,4 +1247,142 @@ mod tests {
         let prover: MockProver<Fp> = MockProver::run(k, &circuit, vec![]).unwrap();
         assert!(prover.verify().is_ok());*/
     }
+
+    #[test]
+    fn test_disclosure_circuit() {
+        let k = PoseidonCircuit::K;
+        let disclosure = |reveal: RevealFlags| {
+            DisclosureCircuit::new(
+                IdentityCircuit::disclose(reveal, true, b'M', 110051, b"Delhi"),
+                PoseidonCircuit::new(12345678, vec![1, 2, 3, 4]),
+            )
+        };
+
+        // Only the age flag and the state are revealed, in a single proof with the nullifier.
+        let circuit = disclosure(RevealFlags {
+            age_above_18: true,
+            state: true,
+            ..RevealFlags::default()
+        });
+        let nullifier = PoseidonCircuit::new(12345678, vec![1, 2, 3, 4])
+            .nullifier()
+            .unwrap();
+        let mut expected = [1, 0, 0, b'D', b'e', b'l', b'h', b'i']
+            .map(|value| Fr::from(value as u64))
+            .to_vec();
+        expected.push(nullifier);
+        assert_eq!(circuit.instances().unwrap(), expected);
+        let prover = MockProver::run(k, &circuit, vec![circuit.instances().unwrap()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The hidden gender and pincode cannot be claimed, nor can another nullifier.
+        for (row, value) in [
+            (1, Fr::from(b'M' as u64)),
+            (2, Fr::from(110051)),
+            (DisclosureCircuit::NULLIFIER_ROW, nullifier + Fr::one()),
+        ] {
+            let mut instances = expected.clone();
+            instances[row] = value;
+            let prover = MockProver::run(k, &circuit, vec![instances]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // The claimed nullifier is that of the photo of the witness, not of any other photo or seed.
+        for nullifier in [
+            PoseidonCircuit::new(12345678, vec![1, 2, 3, 5]),
+            PoseidonCircuit::new(12345679, vec![1, 2, 3, 4]),
+        ] {
+            let other = DisclosureCircuit::new(
+                IdentityCircuit::disclose(
+                    RevealFlags {
+                        age_above_18: true,
+                        state: true,
+                        ..RevealFlags::default()
+                    },
+                    true,
+                    b'M',
+                    110051,
+                    b"Delhi",
+                ),
+                nullifier,
+            );
+            let prover = MockProver::run(k, &other, vec![expected.clone()]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // Any subset of the attributes can be revealed.
+        for subset in 0..16 {
+            let circuit = disclosure(RevealFlags {
+                age_above_18: subset & 1 != 0,
+                gender: subset & 2 != 0,
+                pincode: subset & 4 != 0,
+                state: subset & 8 != 0,
+            });
+            let prover = MockProver::run(k, &circuit, vec![circuit.instances().unwrap()]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_disclosure_masked_qr() {
+        let k = PoseidonCircuit::K;
+        let (qr, _, _) = generate_test_qr(TestQrFields::default());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = signed_region(&decompressed, 2048).unwrap();
+
+        // A masked QR code omitting the gender and the state, but keeping the photo of the nullifier.
+        let layout = QrFieldLayout::default()
+            .without(GENDER_POSITION)
+            .without(STATE_POSITION);
+        let masked = mask_fields(signed_data, &layout);
+        let fields = qr_fields_with_layout(&masked, &layout).unwrap();
+        assert_eq!(fields[GENDER_POSITION], None);
+        assert_eq!(fields[STATE_POSITION], None);
+        let pincode = std::str::from_utf8(fields[PINCODE_POSITION].unwrap())
+            .unwrap()
+            .parse::<u32>()
+            .unwrap();
+        let nullifier_seed = 12345678;
+        let nullifier = compute_nullifier(nullifier_seed, &masked, &layout).unwrap();
+        let photo = fields[NUM_DELIMITERS]
+            .unwrap()
+            .iter()
+            .map(|byte| *byte as u64)
+            .collect::<Vec<u64>>();
+
+        // Every attribute is requested, but only the present ones are revealed.
+        let reveal = RevealFlags {
+            age_above_18: true,
+            gender: true,
+            pincode: true,
+            state: true,
+        };
+        assert_eq!(
+            reveal.masked(&layout),
+            RevealFlags {
+                gender: false,
+                state: false,
+                ..reveal
+            }
+        );
+        let circuit = DisclosureCircuit::new(
+            IdentityCircuit::disclose_with_layout(reveal, &layout, true, b'M', pincode, b"Delhi"),
+            PoseidonCircuit::new(nullifier_seed, photo),
+        );
+        let mut expected = [1, 0, 110051, 0, 0, 0, 0, 0]
+            .map(|value| Fr::from(value as u64))
+            .to_vec();
+        expected.push(nullifier);
+        assert_eq!(circuit.instances().unwrap(), expected);
+        let prover = MockProver::run(k, &circuit, vec![circuit.instances().unwrap()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The absent gender and state cannot be claimed.
+        for (row, value) in [(1, Fr::from(b'M' as u64)), (3, Fr::from(b'D' as u64))] {
+            let mut instances = expected.clone();
+            instances[row] = value;
+            let prover = MockProver::run(k, &circuit, vec![instances]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
 }
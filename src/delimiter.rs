@@ -0,0 +1,826 @@
+use halo2_base::halo2_proofs::{
+    circuit::{AssignedCell, Cell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector,
+    },
+    poly::Rotation,
+};
+use halo2_base::utils::PrimeField;
+use std::marker::PhantomData;
+use zeroize::Zeroizing;
+
+use crate::qr_data_extractor::DEFAULT_MAX_DATA_LEN;
+
+/// The byte separating the fields of the QR data.
+pub const DELIMITER: u8 = 255;
+
+/// The number of delimiters before the photo, which may itself contain the delimiter byte.
+pub const NUM_DELIMITERS: usize = 18;
+
+/// The default number of leading bytes of each field assigned by [`DelimiterCircuit::assign_fields`], see [`DelimiterCircuit::configure_with_prefix_len`].
+pub const FIELD_PREFIX_LEN: usize = 6;
+
+/// Returns the index of the first byte of the field at `position`, i.e. the byte following the delimiter of the same number.
+///
+/// # Panics
+/// Panics if `position` is zero or greater than the number of `delimiter_indices`.
+pub fn field_start(delimiter_indices: &[usize], position: usize) -> usize {
+    delimiter_indices[position - 1] + 1
+}
+
+/// Returns the byte range `start..end` of the field at `position`, which runs up to the next delimiter.
+///
+/// The photo, at position [`NUM_DELIMITERS`], has no delimiter after it, so only its [`field_start`] is defined.
+///
+/// # Panics
+/// Panics if `position` is zero or not less than the number of `delimiter_indices`.
+pub fn field_range(delimiter_indices: &[usize], position: usize) -> (usize, usize) {
+    (
+        field_start(delimiter_indices, position),
+        delimiter_indices[position],
+    )
+}
+
+/// A circuit deriving the positions of the first [`NUM_DELIMITERS`] delimiters of the QR data.
+///
+/// The data is scanned row by row, so the positions are not a witness input and cannot be forged.
+/// The scan also derives `nDelimitedData`, where the `n`-th delimiter is remapped to `n * 255` up to the photo delimiter, the last one of them.
+/// They are exposed as instances in [`Circuit::synthesize`], while a parent circuit can use [`DelimiterCircuit::assign_delimiter_indices`] instead.
+#[derive(Debug, Clone)]
+pub struct DelimiterCircuit<F: PrimeField> {
+    /// The signed QR data, zeroized on drop.
+    n_delimited_data: Zeroizing<Vec<u8>>,
+    /// The number of scanned rows, [`DEFAULT_MAX_DATA_LEN`] by default.
+    max_data_len: usize,
+    _f: PhantomData<F>,
+}
+
+impl<F: PrimeField> Default for DelimiterCircuit<F> {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DelimiterConfig {
+    q_scan: Selector,
+    q_first: Selector,
+    q_step: Selector,
+    q_index: Selector,
+    data: Column<Advice>,
+    /// The inverse of `data - DELIMITER`, or zero if it is not invertible.
+    diff_inv: Column<Advice>,
+    /// One if the byte is the delimiter, and zero otherwise.
+    is_delimiter: Column<Advice>,
+    /// The number of delimiters up to and including the row.
+    count: Column<Advice>,
+    /// The inverse of `count - NUM_DELIMITERS`, or zero if it is not invertible.
+    count_diff_inv: Column<Advice>,
+    /// One if the byte is the photo delimiter, i.e. the [`NUM_DELIMITERS`]-th delimiter, and zero otherwise.
+    is_photo_delimiter: Column<Advice>,
+    /// One if the row is not after the photo delimiter, and zero otherwise.
+    before_photo: Column<Advice>,
+    /// The byte of `nDelimitedData`.
+    n_delimited: Column<Advice>,
+    row_index: Column<Fixed>,
+    /// The 1-indexed number of the delimiter whose position is assigned in the same row of `delimiter_index`.
+    delimiter_number: Column<Fixed>,
+    delimiter_index: Column<Advice>,
+    /// The byte at the position assigned in the same row of `delimiter_index`, which must be [`DELIMITER`].
+    delimiter_byte: Column<Advice>,
+    /// The bytes following the delimiter whose position is assigned in the same row of `delimiter_index`, [`FIELD_PREFIX_LEN`] of them unless configured by [`DelimiterCircuit::configure_with_prefix_len`].
+    field_prefix: Vec<Column<Advice>>,
+    instance: Column<Instance>,
+}
+
+/// The cells assigned by [`DelimiterCircuit::assign_fields`], which a parent circuit can constrain equal to cells in other regions.
+#[derive(Debug, Clone)]
+pub struct AssignedFields<F: PrimeField> {
+    /// The scanned bytes over the rows of [`DelimiterCircuit::with_max_data_len`], zero-padded after the data.
+    pub data: Vec<AssignedCell<F, F>>,
+    /// The positions of the first [`NUM_DELIMITERS`] delimiters.
+    pub delimiter_indices: Vec<AssignedCell<F, F>>,
+    /// The bytes of `nDelimitedData` over the same rows.
+    pub n_delimited_data: Vec<AssignedCell<F, F>>,
+    /// The leading bytes of the field at each position from 1 to [`NUM_DELIMITERS`], i.e. the bytes following the delimiter of the same number, as many as the field prefix of the config.
+    pub field_prefixes: Vec<Vec<AssignedCell<F, F>>>,
+}
+
+impl<F: PrimeField> AssignedFields<F> {
+    /// Constrains the leading scanned bytes equal to `signed_data`, e.g. the bytes of [`crate::Pkcs1v15Result::msg`].
+    ///
+    /// Then the positions and the fields derived by the scan are those of the data whose signature is verified, so tampering with a byte of either side is not satisfiable.
+    ///
+    /// # Return values
+    /// Returns [`Error::Synthesis`] if `signed_data` is longer than the scanned bytes.
+    pub fn constrain_signed_data(
+        &self,
+        signed_data: &[Cell],
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        if signed_data.len() > self.data.len() {
+            return Err(Error::Synthesis);
+        }
+        layouter.assign_region(
+            || "bind signed data",
+            |mut region| {
+                for (byte, signed_byte) in self.data.iter().zip(signed_data.iter()) {
+                    region.constrain_equal(byte.cell(), *signed_byte)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: PrimeField> DelimiterCircuit<F> {
+    /// Creates new [`DelimiterCircuit`] from the signed QR data, which is zero-padded to [`DEFAULT_MAX_DATA_LEN`] bytes.
+    pub fn new(n_delimited_data: Vec<u8>) -> Self {
+        Self {
+            n_delimited_data: Zeroizing::new(n_delimited_data),
+            max_data_len: DEFAULT_MAX_DATA_LEN,
+            _f: PhantomData,
+        }
+    }
+
+    /// Sets the number of scanned rows, e.g. the `MAX_DATA_LEN` of [`crate::qr_data_extractor::AadhaarQRVerifierCircuit`] so that every scanned byte is bound to its padded data.
+    pub fn with_max_data_len(mut self, max_data_len: usize) -> Self {
+        self.max_data_len = max_data_len;
+        self
+    }
+
+    /// Returns the positions of the first [`NUM_DELIMITERS`] delimiters computed out of the circuit.
+    pub fn delimiter_indices(&self) -> Vec<usize> {
+        self.n_delimited_data
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| **byte == DELIMITER)
+            .map(|(i, _)| i)
+            .take(NUM_DELIMITERS)
+            .collect()
+    }
+
+    /// Returns `nDelimitedData` computed out of the circuit.
+    ///
+    /// Bytes equal to the delimiter after the photo delimiter belong to the photo and are kept as they are.
+    pub fn n_delimited_data(&self) -> Vec<u64> {
+        let mut count = 0;
+        self.n_delimited_data
+            .iter()
+            .map(|byte| {
+                if *byte != DELIMITER || count == NUM_DELIMITERS {
+                    return *byte as u64;
+                }
+                count += 1;
+                (count * DELIMITER as usize) as u64
+            })
+            .collect()
+    }
+
+    /// Assigns the scanned data and the derived positions of the delimiters.
+    ///
+    /// # Return values
+    /// Returns the assigned positions of the first [`NUM_DELIMITERS`] delimiters, so that a parent circuit can constrain them equal to cells in other regions.
+    /// If the data contains fewer delimiters, the lookup of the missing positions is not satisfied.
+    pub fn assign_delimiter_indices(
+        &self,
+        config: &DelimiterConfig,
+        layouter: impl Layouter<F>,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        self.assign_n_delimited_data(config, layouter)
+            .map(|(delimiter_indices, _)| delimiter_indices)
+    }
+
+    /// Assigns the scanned data, the derived positions of the delimiters and `nDelimitedData`.
+    ///
+    /// # Return values
+    /// Returns the assigned positions as [`DelimiterCircuit::assign_delimiter_indices`], and the assigned bytes of `nDelimitedData` over the scanned rows.
+    #[allow(clippy::type_complexity)]
+    pub fn assign_n_delimited_data(
+        &self,
+        config: &DelimiterConfig,
+        layouter: impl Layouter<F>,
+    ) -> Result<(Vec<AssignedCell<F, F>>, Vec<AssignedCell<F, F>>), Error> {
+        self.assign_fields(config, layouter)
+            .map(|fields| (fields.delimiter_indices, fields.n_delimited_data))
+    }
+
+    /// Assigns the scanned data, the derived positions of the delimiters, `nDelimitedData` and the leading bytes of each field.
+    ///
+    /// The leading bytes are looked up together with the position of the delimiter before them, so they are the bytes of the signed data.
+    /// They may run into the following fields when the field is shorter than the field prefix of the config.
+    pub fn assign_fields(
+        &self,
+        config: &DelimiterConfig,
+        layouter: impl Layouter<F>,
+    ) -> Result<AssignedFields<F>, Error> {
+        self.assign_fields_at(config, layouter, &self.delimiter_indices(), DELIMITER)
+    }
+
+    /// Assigns the same cells as [`DelimiterCircuit::assign_fields`] with the positions of the delimiters taken from `indices`, which the constraints check against the scanned data.
+    ///
+    /// The scan counts the bytes equal to `delimiter`, which the constraints check to be [`DELIMITER`].
+    fn assign_fields_at(
+        &self,
+        config: &DelimiterConfig,
+        mut layouter: impl Layouter<F>,
+        indices: &[usize],
+        delimiter: u8,
+    ) -> Result<AssignedFields<F>, Error> {
+        if self.n_delimited_data.len() > self.max_data_len {
+            return Err(Error::Synthesis);
+        }
+        let mut data = self.n_delimited_data.clone();
+        data.resize(self.max_data_len, 0);
+
+        layouter.assign_region(
+            || "delimiter scan",
+            |mut region| {
+                let mut count = 0u64;
+                let mut before_photo = true;
+                let mut data_cells = Vec::with_capacity(self.max_data_len);
+                let mut n_delimited_data = Vec::with_capacity(self.max_data_len);
+                for (i, byte) in data.iter().enumerate() {
+                    config.q_scan.enable(&mut region, i)?;
+                    if i == 0 {
+                        config.q_first.enable(&mut region, i)?;
+                    } else {
+                        config.q_step.enable(&mut region, i)?;
+                    }
+                    let diff = F::from(*byte as u64) - F::from(DELIMITER as u64);
+                    let is_delimiter = *byte == delimiter;
+                    count += is_delimiter as u64;
+                    data_cells.push(region.assign_advice(
+                        || format!("data {}", i),
+                        config.data,
+                        i,
+                        || Value::known(F::from(*byte as u64)),
+                    )?);
+                    region.assign_advice(
+                        || format!("diff_inv {}", i),
+                        config.diff_inv,
+                        i,
+                        || Value::known(diff.invert().unwrap_or(F::zero())),
+                    )?;
+                    region.assign_advice(
+                        || format!("is_delimiter {}", i),
+                        config.is_delimiter,
+                        i,
+                        || Value::known(F::from(is_delimiter as u64)),
+                    )?;
+                    region.assign_advice(
+                        || format!("count {}", i),
+                        config.count,
+                        i,
+                        || Value::known(F::from(count)),
+                    )?;
+                    region.assign_fixed(
+                        || format!("row_index {}", i),
+                        config.row_index,
+                        i,
+                        || Value::known(F::from(i as u64)),
+                    )?;
+
+                    let count_diff = F::from(count) - F::from(NUM_DELIMITERS as u64);
+                    let is_photo_delimiter = is_delimiter && count == NUM_DELIMITERS as u64;
+                    region.assign_advice(
+                        || format!("count_diff_inv {}", i),
+                        config.count_diff_inv,
+                        i,
+                        || Value::known(count_diff.invert().unwrap_or(F::zero())),
+                    )?;
+                    region.assign_advice(
+                        || format!("is_photo_delimiter {}", i),
+                        config.is_photo_delimiter,
+                        i,
+                        || Value::known(F::from(is_photo_delimiter as u64)),
+                    )?;
+                    region.assign_advice(
+                        || format!("before_photo {}", i),
+                        config.before_photo,
+                        i,
+                        || Value::known(F::from(before_photo as u64)),
+                    )?;
+                    let n_delimited = if before_photo && is_delimiter {
+                        F::from(count * DELIMITER as u64)
+                    } else {
+                        F::from(*byte as u64)
+                    };
+                    n_delimited_data.push(region.assign_advice(
+                        || format!("n_delimited {}", i),
+                        config.n_delimited,
+                        i,
+                        || Value::known(n_delimited),
+                    )?);
+                    before_photo &= !is_photo_delimiter;
+                }
+
+                let mut delimiter_indices = Vec::with_capacity(NUM_DELIMITERS);
+                let mut field_prefixes = Vec::with_capacity(NUM_DELIMITERS);
+                for j in 0..NUM_DELIMITERS {
+                    config.q_index.enable(&mut region, j)?;
+                    region.assign_fixed(
+                        || format!("delimiter_number {}", j),
+                        config.delimiter_number,
+                        j,
+                        || Value::known(F::from(j as u64 + 1)),
+                    )?;
+                    // A missing delimiter is assigned zero, which is not found in the lookup.
+                    let index = indices.get(j).copied().unwrap_or_default();
+                    delimiter_indices.push(region.assign_advice(
+                        || format!("delimiter_index {}", j),
+                        config.delimiter_index,
+                        j,
+                        || Value::known(F::from(index as u64)),
+                    )?);
+                    let byte = data.get(index).copied().unwrap_or_default();
+                    region.assign_advice(
+                        || format!("delimiter_byte {}", j),
+                        config.delimiter_byte,
+                        j,
+                        || Value::known(F::from(byte as u64)),
+                    )?;
+                    let mut field_prefix = Vec::with_capacity(config.field_prefix.len());
+                    for (k, column) in config.field_prefix.iter().enumerate() {
+                        let byte = data.get(index + 1 + k).copied().unwrap_or_default();
+                        field_prefix.push(region.assign_advice(
+                            || format!("field_prefix {} {}", j, k),
+                            *column,
+                            j,
+                            || Value::known(F::from(byte as u64)),
+                        )?);
+                    }
+                    field_prefixes.push(field_prefix);
+                }
+                Ok(AssignedFields {
+                    data: data_cells,
+                    delimiter_indices,
+                    n_delimited_data,
+                    field_prefixes,
+                })
+            },
+        )
+    }
+}
+
+impl<F: PrimeField> Circuit<F> for DelimiterCircuit<F> {
+    type Config = DelimiterConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        Self::configure_with_prefix_len(meta, FIELD_PREFIX_LEN)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let delimiter_indices =
+            self.assign_delimiter_indices(&config, layouter.namespace(|| "delimiter"))?;
+        for (j, index) in delimiter_indices.iter().enumerate() {
+            layouter.constrain_instance(index.cell(), config.instance, j)?;
+        }
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> DelimiterCircuit<F> {
+    /// Configures the scan as [`Circuit::configure`], with `field_prefix_len` leading bytes of each field rather than [`FIELD_PREFIX_LEN`].
+    ///
+    /// A parent circuit binding longer attributes to the fields, e.g. with [`crate::conditional_secrets::AssignedQrData::constrain_fields`], configures the scan with their length.
+    pub fn configure_with_prefix_len(
+        meta: &mut ConstraintSystem<F>,
+        field_prefix_len: usize,
+    ) -> DelimiterConfig {
+        let q_scan = meta.complex_selector();
+        let q_first = meta.selector();
+        let q_step = meta.selector();
+        let q_index = meta.complex_selector();
+        let data = meta.advice_column();
+        let diff_inv = meta.advice_column();
+        let is_delimiter = meta.advice_column();
+        let count = meta.advice_column();
+        let count_diff_inv = meta.advice_column();
+        let is_photo_delimiter = meta.advice_column();
+        let before_photo = meta.advice_column();
+        let n_delimited = meta.advice_column();
+        let row_index = meta.fixed_column();
+        let delimiter_number = meta.fixed_column();
+        let delimiter_index = meta.advice_column();
+        let delimiter_byte = meta.advice_column();
+        let field_prefix = (0..field_prefix_len)
+            .map(|_| meta.advice_column())
+            .collect::<Vec<Column<Advice>>>();
+        let instance = meta.instance_column();
+        meta.enable_equality(data);
+        meta.enable_equality(delimiter_index);
+        for column in field_prefix.iter() {
+            meta.enable_equality(*column);
+        }
+        meta.enable_equality(n_delimited);
+        meta.enable_equality(instance);
+
+        meta.create_gate("is delimiter", |meta| {
+            let q_scan = meta.query_selector(q_scan);
+            let data = meta.query_advice(data, Rotation::cur());
+            let diff_inv = meta.query_advice(diff_inv, Rotation::cur());
+            let is_delimiter = meta.query_advice(is_delimiter, Rotation::cur());
+            let diff = data - Expression::Constant(F::from(DELIMITER as u64));
+            vec![
+                q_scan.clone()
+                    * (is_delimiter.clone()
+                        - (Expression::Constant(F::one()) - diff.clone() * diff_inv)),
+                q_scan * diff * is_delimiter,
+            ]
+        });
+
+        meta.create_gate("delimiter count first row", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let is_delimiter = meta.query_advice(is_delimiter, Rotation::cur());
+            let count = meta.query_advice(count, Rotation::cur());
+            vec![q_first * (count - is_delimiter)]
+        });
+
+        meta.create_gate("delimiter count step", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let is_delimiter = meta.query_advice(is_delimiter, Rotation::cur());
+            let count_prev = meta.query_advice(count, Rotation::prev());
+            let count = meta.query_advice(count, Rotation::cur());
+            vec![q_step * (count - count_prev - is_delimiter)]
+        });
+
+        meta.create_gate("is photo delimiter", |meta| {
+            let q_scan = meta.query_selector(q_scan);
+            let is_delimiter = meta.query_advice(is_delimiter, Rotation::cur());
+            let count = meta.query_advice(count, Rotation::cur());
+            let count_diff_inv = meta.query_advice(count_diff_inv, Rotation::cur());
+            let is_photo_delimiter = meta.query_advice(is_photo_delimiter, Rotation::cur());
+            let count_diff = count - Expression::Constant(F::from(NUM_DELIMITERS as u64));
+            let is_last_count =
+                Expression::Constant(F::one()) - count_diff.clone() * count_diff_inv;
+            vec![
+                q_scan.clone() * count_diff * is_last_count.clone(),
+                q_scan * (is_photo_delimiter - is_delimiter * is_last_count),
+            ]
+        });
+
+        // Only the first row with the count reaching `NUM_DELIMITERS` is the photo delimiter, so `before_photo` drops to zero right after it.
+        meta.create_gate("before photo first row", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let before_photo = meta.query_advice(before_photo, Rotation::cur());
+            vec![q_first * (before_photo - Expression::Constant(F::one()))]
+        });
+
+        meta.create_gate("before photo step", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let before_photo_prev = meta.query_advice(before_photo, Rotation::prev());
+            let is_photo_delimiter_prev = meta.query_advice(is_photo_delimiter, Rotation::prev());
+            let before_photo = meta.query_advice(before_photo, Rotation::cur());
+            vec![
+                q_step
+                    * (before_photo
+                        - before_photo_prev
+                            * (Expression::Constant(F::one()) - is_photo_delimiter_prev)),
+            ]
+        });
+
+        meta.create_gate("n delimited data", |meta| {
+            let q_scan = meta.query_selector(q_scan);
+            let data = meta.query_advice(data, Rotation::cur());
+            let is_delimiter = meta.query_advice(is_delimiter, Rotation::cur());
+            let count = meta.query_advice(count, Rotation::cur());
+            let before_photo = meta.query_advice(before_photo, Rotation::cur());
+            let n_delimited = meta.query_advice(n_delimited, Rotation::cur());
+            let delimiter = Expression::Constant(F::from(DELIMITER as u64));
+            let remapped =
+                before_photo * is_delimiter * (count - Expression::Constant(F::one())) * delimiter;
+            vec![q_scan * (n_delimited - data - remapped)]
+        });
+
+        // The byte at each derived position is the delimiter itself, not merely a byte that the scan counted.
+        meta.create_gate("delimiter byte", |meta| {
+            let q_index = meta.query_selector(q_index);
+            let delimiter_byte = meta.query_advice(delimiter_byte, Rotation::cur());
+            vec![q_index * (delimiter_byte - Expression::Constant(F::from(DELIMITER as u64)))]
+        });
+
+        // Since `count` increases exactly at the delimiters, `(j, i)` is in the table only if the `j`-th delimiter is at row `i`, holding the delimiter byte and followed by the bytes of the field prefix.
+        meta.lookup_any("delimiter index", |meta| {
+            let q_index = meta.query_selector(q_index);
+            let delimiter_number = meta.query_fixed(delimiter_number, Rotation::cur());
+            let delimiter_index = meta.query_advice(delimiter_index, Rotation::cur());
+            let delimiter_byte = meta.query_advice(delimiter_byte, Rotation::cur());
+            let q_scan = meta.query_selector(q_scan);
+            let is_delimiter = meta.query_advice(is_delimiter, Rotation::cur());
+            let count = meta.query_advice(count, Rotation::cur());
+            let row_index = meta.query_fixed(row_index, Rotation::cur());
+            let data_byte = meta.query_advice(data, Rotation::cur());
+            let found = q_scan * is_delimiter;
+            let mut lookups = vec![
+                (q_index.clone() * delimiter_number, found.clone() * count),
+                (q_index.clone() * delimiter_index, found.clone() * row_index),
+                (q_index.clone() * delimiter_byte, found.clone() * data_byte),
+            ];
+            for (k, column) in field_prefix.iter().enumerate() {
+                let prefix_byte = meta.query_advice(*column, Rotation::cur());
+                let byte = meta.query_advice(data, Rotation(k as i32 + 1));
+                lookups.push((q_index.clone() * prefix_byte, found.clone() * byte));
+            }
+            lookups
+        });
+
+        DelimiterConfig {
+            q_scan,
+            q_first,
+            q_step,
+            q_index,
+            data,
+            diff_inv,
+            is_delimiter,
+            count,
+            count_diff_inv,
+            is_photo_delimiter,
+            before_photo,
+            n_delimited,
+            row_index,
+            delimiter_number,
+            delimiter_index,
+            delimiter_byte,
+            field_prefix,
+            instance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_base::halo2_proofs::{
+        dev::{MockProver, VerifyFailure},
+        halo2curves::bn256::Fr as Fp,
+    };
+
+    #[test]
+    fn test_delimiter_indices() {
+        let k = 11;
+
+        // The head of the sample payload, up to the first byte of the photo.
+        let n_delimited_data = vec![
+            86, 50, 255, 51, 255, 50, 54, 57, 55, 50, 48, 50, 52, 48, 55, 49, 56, 49, 50, 52, 53,
+            53, 55, 51, 56, 55, 255, 83, 117, 109, 105, 116, 32, 75, 117, 109, 97, 114, 255, 48,
+            49, 45, 48, 49, 45, 49, 57, 56, 52, 255, 77, 255, 67, 47, 79, 32, 73, 115, 104, 119,
+            97, 114, 32, 67, 104, 97, 110, 100, 255, 69, 97, 115, 116, 32, 68, 101, 108, 104,
+            105, 255, 255, 66, 45, 51, 49, 44, 32, 51, 114, 100, 32, 70, 108, 111, 111, 114, 255,
+            255, 49, 49, 48, 48, 53, 49, 255, 75, 114, 105, 115, 104, 110, 97, 32, 78, 97, 103,
+            97, 114, 255, 68, 101, 108, 104, 105, 255, 82, 97, 100, 104, 101, 121, 32, 83, 104,
+            121, 97, 109, 32, 80, 97, 114, 107, 32, 69, 120, 116, 101, 110, 115, 105, 111, 110,
+            255, 71, 97, 110, 100, 104, 105, 32, 78, 97, 103, 97, 114, 255, 75, 114, 105, 115,
+            104, 110, 97, 32, 78, 97, 103, 97, 114, 255, 49, 50, 51, 52, 255, 255, 79, 255, 81,
+        ];
+        let expected = [
+            2, 4, 26, 38, 49, 51, 68, 79, 80, 96, 97, 104, 118, 124, 152, 165, 179, 184,
+        ];
+        let circuit = DelimiterCircuit::<Fp>::new(n_delimited_data);
+        assert_eq!(circuit.delimiter_indices(), expected);
+
+        let instance = expected
+            .iter()
+            .map(|index| Fp::from(*index as u64))
+            .collect::<Vec<Fp>>();
+        let prover = MockProver::run(k, &circuit, vec![instance.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A position other than the derived one is rejected.
+        let mut forged = instance.clone();
+        forged[2] = Fp::from(27);
+        let prover = MockProver::run(k, &circuit, vec![forged]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Data with fewer delimiters has no position to derive.
+        let circuit = DelimiterCircuit::<Fp>::new(vec![86, 50, 255, 51]);
+        let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_field_range() {
+        let fields = [
+            "V2",
+            "3",
+            "269720240718124557387",
+            "Sumit Kumar",
+            "01-01-1984",
+            "M",
+            "C/O Ishwar Chand",
+            "East Delhi",
+            "",
+            "B-31, 3rd Floor",
+            "",
+            "110051",
+            "Krishna Nagar",
+            "Delhi",
+            "Radhey Shyam Park Extension",
+            "Gandhi Nagar",
+            "Krishna Nagar",
+            "1234",
+        ];
+        let mut data = vec![];
+        for field in fields {
+            data.extend_from_slice(field.as_bytes());
+            data.push(DELIMITER);
+        }
+        data.extend([255, 79, 255, 81]);
+        let delimiter_indices = DelimiterCircuit::<Fp>::new(data.clone()).delimiter_indices();
+
+        let field = |position: usize| {
+            let (start, end) = field_range(&delimiter_indices, position);
+            &data[start..end]
+        };
+        assert_eq!(field_range(&delimiter_indices, 5), (50, 51));
+        assert_eq!(field(5), b"M");
+        assert_eq!(field_range(&delimiter_indices, 11), (98, 104));
+        assert_eq!(field(11), b"110051");
+        assert_eq!(field_range(&delimiter_indices, 13), (119, 124));
+        assert_eq!(field(13), b"Delhi");
+        // Empty fields have empty ranges.
+        assert_eq!(field(8), b"");
+
+        assert_eq!(field_start(&delimiter_indices, NUM_DELIMITERS), 185);
+        assert_eq!(data[185..], [255, 79, 255, 81]);
+    }
+
+    /// [`DelimiterCircuit`] assigning the positions of the delimiters from `indices` rather than deriving them.
+    #[derive(Clone, Default)]
+    struct ForgedIndicesCircuit {
+        circuit: DelimiterCircuit<Fp>,
+        indices: Vec<usize>,
+        /// The byte counted by the scan, [`DELIMITER`] unless the scan itself is forged.
+        delimiter: u8,
+    }
+
+    impl Circuit<Fp> for ForgedIndicesCircuit {
+        type Config = DelimiterConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            DelimiterCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let fields = self.circuit.assign_fields_at(
+                &config,
+                layouter.namespace(|| "delimiter"),
+                &self.indices,
+                self.delimiter,
+            )?;
+            for (j, index) in fields.delimiter_indices.iter().enumerate() {
+                layouter.constrain_instance(index.cell(), config.instance, j)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_delimiter_byte() {
+        let k = 11;
+
+        let mut data = vec![];
+        for field in 0..NUM_DELIMITERS {
+            data.push(b'a' + field as u8);
+            data.push(DELIMITER);
+        }
+        let circuit = DelimiterCircuit::<Fp>::new(data.clone());
+        let indices = circuit.delimiter_indices();
+        let instance = |indices: &[usize]| {
+            indices
+                .iter()
+                .map(|index| Fp::from(*index as u64))
+                .collect::<Vec<Fp>>()
+        };
+
+        let honest = ForgedIndicesCircuit {
+            circuit: circuit.clone(),
+            indices: indices.clone(),
+            delimiter: DELIMITER,
+        };
+        let prover = MockProver::run(k, &honest, vec![instance(&indices)]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A position pointing at a field byte instead of the delimiter is rejected.
+        let mut forged = indices;
+        forged[3] -= 1;
+        assert_ne!(data[forged[3]], DELIMITER);
+        let circuit = ForgedIndicesCircuit {
+            circuit,
+            indices: forged.clone(),
+            delimiter: DELIMITER,
+        };
+        let prover = MockProver::run(k, &circuit, vec![instance(&forged)]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // A scan counting another byte as the delimiter finds its positions in the lookup table, so only the byte checks reject them.
+        let mut data = vec![];
+        for field in 0..NUM_DELIMITERS {
+            data.push(b'a' + field as u8);
+            data.push(b'|');
+        }
+        let indices = (0..NUM_DELIMITERS)
+            .map(|field| 2 * field + 1)
+            .collect::<Vec<usize>>();
+        let circuit = ForgedIndicesCircuit {
+            circuit: DelimiterCircuit::<Fp>::new(data),
+            indices: indices.clone(),
+            delimiter: b'|',
+        };
+        let prover = MockProver::run(k, &circuit, vec![instance(&indices)]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert!(!failures
+            .iter()
+            .any(|failure| matches!(failure, VerifyFailure::Lookup { .. })));
+        assert!(failures
+            .iter()
+            .any(|failure| failure.to_string().contains("'delimiter byte'")));
+    }
+
+    /// [`DelimiterCircuit`] exposing `nDelimitedData` in an additional instance column.
+    #[derive(Clone, Default)]
+    struct NDelimitedCircuit(DelimiterCircuit<Fp>);
+
+    impl Circuit<Fp> for NDelimitedCircuit {
+        type Config = (DelimiterConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let config = DelimiterCircuit::configure(meta);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let (_, n_delimited_data) = self
+                .0
+                .assign_n_delimited_data(&config, layouter.namespace(|| "delimiter"))?;
+            for (i, byte) in n_delimited_data.iter().enumerate() {
+                layouter.constrain_instance(byte.cell(), instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_n_delimited_data_stops_at_photo() {
+        let k = 11;
+
+        // 18 fields followed by a photo starting with two delimiter bytes.
+        let mut data = vec![];
+        for field in 0..NUM_DELIMITERS {
+            data.push(b'a' + field as u8);
+            data.push(DELIMITER);
+        }
+        let photo_start = data.len();
+        data.extend([255, 79, 255, 81, 0, 47]);
+        let circuit = DelimiterCircuit::<Fp>::new(data.clone());
+        let n_delimited_data = circuit.n_delimited_data();
+        for (j, index) in circuit.delimiter_indices().into_iter().enumerate() {
+            assert_eq!(n_delimited_data[index], (j as u64 + 1) * 255);
+        }
+        assert_eq!(n_delimited_data[photo_start..], [255, 79, 255, 81, 0, 47]);
+
+        let mut instance = n_delimited_data
+            .iter()
+            .map(|byte| Fp::from(*byte))
+            .collect::<Vec<Fp>>();
+        instance.resize(DEFAULT_MAX_DATA_LEN, Fp::zero());
+        let circuit = NDelimitedCircuit(circuit);
+        let prover = MockProver::run(k, &circuit, vec![vec![], instance.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Remapping a delimiter byte of the photo is rejected.
+        let mut remapped = instance;
+        remapped[photo_start] = Fp::from((NUM_DELIMITERS as u64 + 1) * 255);
+        let prover = MockProver::run(k, &circuit, vec![vec![], remapped]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,1138 @@
+//! Extracting the revealable fields of the QR data from `nDelimitedData` in a single context, as `QRDataExtractor` of the circom circuits does.
+use halo2_base::halo2_proofs::plonk::Error;
+use halo2_base::{
+    gates::{range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+use crate::age::DOB_LEN;
+use crate::comparison::ComparisonConfig;
+use crate::conditional_secrets::{GENDER_POSITION, PINCODE_LEN, PINCODE_POSITION, STATE_POSITION};
+use crate::delimiter::{DELIMITER, NUM_DELIMITERS};
+use crate::lookup_set::{identity_sets, LookupSetChip};
+use crate::nullifier::PHOTO_PACK_BYTES;
+use crate::photo::{assign_packed_photo, MAX_PHOTO_LEN};
+use crate::qr::QrFieldLayout;
+
+/// The position of the reference ID, i.e. the last 4 digits of the Aadhaar number followed by the signing time `YYYYMMDDhhmmssSSS`.
+pub const REFERENCE_ID_POSITION: usize = 2;
+
+/// The position of the date of birth, formatted as `DD-MM-YYYY`.
+pub const DOB_POSITION: usize = 4;
+
+/// The position of the photo, the last field of the QR data.
+pub const PHOTO_POSITION: usize = NUM_DELIMITERS;
+
+/// The maximum byte length of the state, which is packed into a single element.
+pub const MAX_STATE_LEN: usize = PHOTO_PACK_BYTES;
+
+/// The number of digits of the Aadhaar number preceding the signing time in the reference ID.
+pub const AADHAAR_DIGITS_LEN: usize = 4;
+
+/// The number of digits of the year, month, day and hour of the signing time.
+const TIMESTAMP_DIGITS_LEN: [usize; 4] = [4, 2, 2, 2];
+
+/// The byte length of a time `YYYYMMDDhh` as parsed by [`FieldExtractorChip::parse_time`].
+pub const TIME_LEN: usize = 10;
+
+/// The bit length of the keys `YYYYMMDDhh` compared by [`FieldExtractorChip::is_before`], which are less than `10^10`.
+const TIME_KEY_BITS: usize = 34;
+
+/// The fields assigned by [`FieldExtractorChip::extract_all`].
+///
+/// The fields absent from the layout of a masked QR code are the constant zero.
+#[derive(Debug, Clone)]
+pub struct ExtractedFields<'v, F: PrimeField> {
+    /// The year, month, day and hour of the signing time in the reference ID, e.g. the inputs of [`crate::timestamp::TimestampCircuit`].
+    pub timestamp: [AssignedValue<'v, F>; 4],
+    /// The age in years at the date of the signing time.
+    pub age: AssignedValue<'v, F>,
+    /// The byte of the gender field.
+    pub gender: AssignedValue<'v, F>,
+    /// The pincode as a decimal number.
+    pub pincode: AssignedValue<'v, F>,
+    /// The ASCII byte of the leading digit of the pincode.
+    pub pincode_leading_digit: AssignedValue<'v, F>,
+    /// The bytes of the state packed little-endian into one element, as [`crate::photo::pack_bytes`] does.
+    pub state: AssignedValue<'v, F>,
+    /// The photo packed as [`crate::photo::pack_photo`] does, or `None` if the layout has no photo.
+    pub photo: Option<Vec<AssignedValue<'v, F>>>,
+}
+
+/// A chip extracting all the revealable fields of the QR data in one pass over `nDelimitedData`.
+///
+/// Each field is selected by [`FieldExtractorChip::select_sub_array`] from the position of its start delimiter, whose remapped byte is checked, so that the fields share the same selection and delimiter constraints.
+#[derive(Clone, Debug)]
+pub struct FieldExtractorChip<F: PrimeField> {
+    range: RangeConfig<F>,
+    comparison: ComparisonConfig<F>,
+}
+
+impl<F: PrimeField> FieldExtractorChip<F> {
+    /// Creates new [`FieldExtractorChip`] from [`RangeConfig`].
+    pub fn construct(range: RangeConfig<F>) -> Self {
+        Self {
+            comparison: ComparisonConfig::construct(range.clone()),
+            range,
+        }
+    }
+
+    /// Getter for [`RangeConfig`].
+    pub fn range(&self) -> &RangeConfig<F> {
+        &self.range
+    }
+
+    /// Returns the `len` bytes of `data` from the index `start`, reading zero past the end of `data`.
+    ///
+    /// `data` is shifted left by each bit of `start` in turn, the most significant first, so only the bytes still reachable by the remaining bits are kept after each shift.
+    /// `start` is constrained to the bit length of `data.len()`.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * data - the assigned bytes to select from.
+    /// * start - the assigned index of the first selected byte.
+    /// * len - the number of selected bytes.
+    pub fn select_sub_array<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        data: &[AssignedValue<'v, F>],
+        start: &AssignedValue<'v, F>,
+        len: usize,
+    ) -> Vec<AssignedValue<'v, F>> {
+        let gate = self.range.gate();
+        let num_bits = (usize::BITS - data.len().leading_zeros()).max(1) as usize;
+        let bits = gate.num_to_bits(ctx, start, num_bits);
+        let mut shifted = data.to_vec();
+        for (i, bit) in bits.iter().enumerate().rev() {
+            let shift = 1 << i;
+            let keep = shifted.len().min(len + shift - 1);
+            shifted = (0..keep)
+                .map(|j| {
+                    let moved = match shifted.get(j + shift) {
+                        Some(byte) => QuantumCell::Existing(byte),
+                        None => QuantumCell::Constant(F::zero()),
+                    };
+                    gate.select(
+                        ctx,
+                        moved,
+                        QuantumCell::Existing(&shifted[j]),
+                        QuantumCell::Existing(bit),
+                    )
+                })
+                .collect();
+        }
+        shifted.truncate(len);
+        while shifted.len() < len {
+            shifted.push(gate.load_constant(ctx, F::zero()));
+        }
+        shifted
+    }
+
+    /// Extracts the timestamp, the age, the gender, the pincode, the state and the photo of the QR data.
+    ///
+    /// `n_delimited_data` must be constrained to the bytes of [`crate::delimiter::DelimiterCircuit::assign_n_delimited_data`], and `delimiter_indices` to the positions derived by the same scan, e.g. by equality constraints in the parent circuit.
+    /// The photo bytes must be 8-bit as required by [`assign_packed_photo`].
+    /// The photo ends at `data_len`, see [`FieldExtractorChip::select_photo`], so the bytes after the signed data, e.g. the signature or the padding, are not part of it.
+    /// The digits of the timestamp, the DOB and the pincode are constrained to be decimal digits.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * n_delimited_data - the assigned bytes of `nDelimitedData`.
+    /// * delimiter_indices - the assigned positions of the delimiters.
+    /// * data_len - the assigned length of the signed data, e.g. the cell of `qr_data_padded_length`.
+    /// * layout - the fields present in the QR data, which must include the reference ID, while the DOB, the gender, the pincode and the state may be absent as in masked QR codes.
+    ///
+    /// # Return values
+    /// Returns the [`ExtractedFields`], where the fields absent from `layout` are zero, as is the age without the DOB, or [`Error::Synthesis`] if the reference ID is absent from `layout` or `delimiter_indices` is too short.
+    pub fn extract_all<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        n_delimited_data: &[AssignedValue<'v, F>],
+        delimiter_indices: &[AssignedValue<'v, F>],
+        data_len: &AssignedValue<'v, F>,
+        layout: &QrFieldLayout,
+    ) -> Result<ExtractedFields<'v, F>, Error> {
+        let gate = self.range.gate();
+        // The number of the start delimiter of the field at `position`, or `None` if the field is absent from `layout`.
+        let number = |position: usize| match layout.delimiter_number(position) {
+            Some(number) if number < delimiter_indices.len() => Ok(Some(number)),
+            Some(_) => Err(Error::Synthesis),
+            None => Ok(None),
+        };
+
+        // The start delimiter and the reference ID up to the hour of the signing time.
+        let reference_id_len = AADHAAR_DIGITS_LEN + TIMESTAMP_DIGITS_LEN.iter().sum::<usize>();
+        let reference_id = self.select_field(
+            ctx,
+            n_delimited_data,
+            delimiter_indices,
+            number(REFERENCE_ID_POSITION)?.ok_or(Error::Synthesis)?,
+            reference_id_len,
+            false,
+        );
+        let mut offset = 1 + AADHAAR_DIGITS_LEN;
+        let timestamp = TIMESTAMP_DIGITS_LEN.map(|len| {
+            let value = self.decimal(ctx, &reference_id[offset..offset + len]);
+            offset += len;
+            value
+        });
+
+        // The fields omitted by masked QR codes are extracted as the constant zero.
+        let age = match number(DOB_POSITION)? {
+            Some(dob_number) => {
+                let dob = self.select_field(
+                    ctx,
+                    n_delimited_data,
+                    delimiter_indices,
+                    dob_number,
+                    DOB_LEN,
+                    true,
+                );
+                for dash in [&dob[3], &dob[6]] {
+                    gate.assert_is_const(ctx, dash, F::from(b'-' as u64));
+                }
+                let birth_day = self.decimal(ctx, &dob[1..3]);
+                let birth_month = self.decimal(ctx, &dob[4..6]);
+                let birth_year = self.decimal(ctx, &dob[7..11]);
+                self.age(ctx, &timestamp, [&birth_year, &birth_month, &birth_day])
+            }
+            None => gate.load_zero(ctx),
+        };
+
+        let gender = match number(GENDER_POSITION)? {
+            Some(gender_number) => {
+                let gender = self.select_field(
+                    ctx,
+                    n_delimited_data,
+                    delimiter_indices,
+                    gender_number,
+                    1,
+                    true,
+                );
+                gender[1].clone()
+            }
+            None => gate.load_zero(ctx),
+        };
+
+        let (pincode, pincode_leading_digit) = match number(PINCODE_POSITION)? {
+            Some(pincode_number) => {
+                let pincode = self.select_field(
+                    ctx,
+                    n_delimited_data,
+                    delimiter_indices,
+                    pincode_number,
+                    PINCODE_LEN,
+                    true,
+                );
+                (
+                    self.decimal(ctx, &pincode[1..1 + PINCODE_LEN]),
+                    pincode[1].clone(),
+                )
+            }
+            None => (gate.load_zero(ctx), gate.load_zero(ctx)),
+        };
+
+        let state = match number(STATE_POSITION)? {
+            Some(state_number) => {
+                let state_start = &delimiter_indices[state_number - 1];
+                let state_end = delimiter_indices
+                    .get(state_number)
+                    .ok_or(Error::Synthesis)?;
+                let state = self.select_field(
+                    ctx,
+                    n_delimited_data,
+                    delimiter_indices,
+                    state_number,
+                    MAX_STATE_LEN,
+                    false,
+                );
+                self.pack_state(ctx, &state[1..], state_start, state_end)
+            }
+            None => gate.load_zero(ctx),
+        };
+
+        let photo = match layout.delimiter_number(PHOTO_POSITION) {
+            Some(photo_number) if photo_number <= delimiter_indices.len() => {
+                let photo = self.select_photo(
+                    ctx,
+                    n_delimited_data,
+                    delimiter_indices,
+                    photo_number,
+                    data_len,
+                );
+                Some(assign_packed_photo(gate, ctx, &photo))
+            }
+            Some(_) => return Err(Error::Synthesis),
+            None => None,
+        };
+
+        Ok(ExtractedFields {
+            timestamp,
+            age,
+            gender,
+            pincode,
+            pincode_leading_digit,
+            state,
+            photo,
+        })
+    }
+
+    /// Asserts that the gender of `fields` is one of [`crate::lookup_set::GENDERS`] and that the pincode starts with one of [`crate::lookup_set::PINCODE_LEADING_DIGITS`], each by one lookup of `lookup`.
+    ///
+    /// The fields absent from `layout`, e.g. masked ones, are the constant zero and are not checked.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * lookup - a chip whose sets are [`identity_sets`].
+    /// * fields - the fields extracted by [`FieldExtractorChip::extract_all`] with `layout`.
+    /// * layout - the fields present in the QR data.
+    ///
+    /// # Return values
+    /// Returns [`Error::Synthesis`] if the sets of `lookup` are not [`identity_sets`].
+    pub fn assert_valid_fields<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        lookup: &mut LookupSetChip<F>,
+        fields: &ExtractedFields<'v, F>,
+        layout: &QrFieldLayout,
+    ) -> Result<(), Error> {
+        let [genders, leading_digits]: [Vec<F>; 2] = identity_sets::<F>().try_into().unwrap();
+        if layout.delimiter_number(GENDER_POSITION).is_some() {
+            lookup.assert_in_lookup_set(ctx, &fields.gender, &genders)?;
+        }
+        if layout.delimiter_number(PINCODE_POSITION).is_some() {
+            lookup.assert_in_lookup_set(ctx, &fields.pincode_leading_digit, &leading_digits)?;
+        }
+        Ok(())
+    }
+
+    /// Extracts the expiry time of the QR data and returns whether it is after `current_time`.
+    ///
+    /// The expiry field is formatted as the signing time up to the hour, i.e. `YYYYMMDDhh`, and its digits are constrained to be decimal digits.
+    /// The times are compared as the decimal keys `YYYYMMDDhh`, so the document is expired from the hour of its expiry on.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * n_delimited_data - the assigned bytes of `nDelimitedData` as in [`FieldExtractorChip::extract_all`].
+    /// * delimiter_indices - the assigned positions of the delimiters.
+    /// * layout - the fields present in the QR data.
+    /// * expiry_position - the position of the expiry field in the full layout.
+    /// * current_time - the assigned year, month, day and hour of the current time, e.g. cells constrained to instances.
+    ///
+    /// # Return values
+    /// Returns the assigned `not_expired` bit, or [`Error::Synthesis`] if the expiry field is absent from `layout` or is the last field.
+    pub fn is_not_expired<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        n_delimited_data: &[AssignedValue<'v, F>],
+        delimiter_indices: &[AssignedValue<'v, F>],
+        layout: &QrFieldLayout,
+        expiry_position: usize,
+        current_time: &[AssignedValue<'v, F>; 4],
+    ) -> Result<AssignedValue<'v, F>, Error> {
+        let number = layout
+            .delimiter_number(expiry_position)
+            .filter(|number| *number < delimiter_indices.len())
+            .ok_or(Error::Synthesis)?;
+        let expiry = self.select_field(
+            ctx,
+            n_delimited_data,
+            delimiter_indices,
+            number,
+            TIME_LEN,
+            true,
+        );
+        let expiry = self.parse_time(ctx, &expiry[1..1 + TIME_LEN])?;
+        Ok(self.is_before(ctx, current_time, &expiry))
+    }
+
+    /// Parses the ASCII digits `YYYYMMDDhh` into the year, month, day and hour, constraining each byte to be a digit.
+    ///
+    /// # Return values
+    /// Returns the assigned year, month, day and hour, or [`Error::Synthesis`] if `bytes` is not [`TIME_LEN`] bytes long.
+    pub fn parse_time<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        bytes: &[AssignedValue<'v, F>],
+    ) -> Result<[AssignedValue<'v, F>; 4], Error> {
+        if bytes.len() != TIME_LEN {
+            return Err(Error::Synthesis);
+        }
+        let mut offset = 0;
+        Ok(TIMESTAMP_DIGITS_LEN.map(|len| {
+            let value = self.decimal(ctx, &bytes[offset..offset + len]);
+            offset += len;
+            value
+        }))
+    }
+
+    /// Returns the assigned bit that is one if `time` is strictly before `deadline`, both given as the year, month, day and hour.
+    ///
+    /// The times are compared as the decimal keys `YYYYMMDDhh`, which are range-checked to be less than `2^34`, so the components must be at most as many digits as those of [`FieldExtractorChip::parse_time`].
+    pub fn is_before<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        time: &[AssignedValue<'v, F>; 4],
+        deadline: &[AssignedValue<'v, F>; 4],
+    ) -> AssignedValue<'v, F> {
+        let key = self.time_key(ctx, time);
+        let deadline_key = self.time_key(ctx, deadline);
+        self.comparison
+            .less_than(ctx, &key, &deadline_key, TIME_KEY_BITS)
+    }
+
+    /// Returns the assigned bit that is one if `current_time` is at or after `signed_time` and strictly before the same time `max_age_years` later, all given as the year, month, day and hour.
+    ///
+    /// E.g. `signed_time` is the [`ExtractedFields::timestamp`] of the verified QR data and `current_time` the time verified by [`crate::RSASignatureVerifier::verify_oracle_time`], so that the freshness depends on no time chosen by the prover.
+    /// The times are compared as the decimal keys of [`FieldExtractorChip::is_before`], within the window of [`ComparisonConfig::is_within_window`].
+    pub fn is_fresh<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        current_time: &[AssignedValue<'v, F>; 4],
+        signed_time: &[AssignedValue<'v, F>; 4],
+        max_age_years: u64,
+    ) -> AssignedValue<'v, F> {
+        let key = self.time_key(ctx, current_time);
+        let signed_key = self.time_key(ctx, signed_time);
+        let last_key = self.range.gate().add(
+            ctx,
+            QuantumCell::Existing(&signed_key),
+            QuantumCell::Constant(F::from(max_age_years * 1_000_000) - F::one()),
+        );
+        self.comparison
+            .is_within_window(ctx, &key, &signed_key, &last_key, TIME_KEY_BITS)
+    }
+
+    /// Returns the decimal key `YYYYMMDDhh` of the year, month, day and hour of `time`.
+    fn time_key<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        time: &[AssignedValue<'v, F>; 4],
+    ) -> AssignedValue<'v, F> {
+        let bases = [1_000_000u64, 10_000, 100, 1]
+            .map(|base| QuantumCell::Constant(F::from(base)))
+            .to_vec();
+        self.range.gate().inner_product(
+            ctx,
+            time.iter().map(QuantumCell::Existing).collect::<Vec<_>>(),
+            bases,
+        )
+    }
+
+    /// Selects the field following the delimiter numbered `number`, preceded by the delimiter itself, and checks the remapped delimiter bytes.
+    ///
+    /// # Return values
+    /// Returns the delimiter and the `len` bytes following it, and the next delimiter if `has_end` is true.
+    fn select_field<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        n_delimited_data: &[AssignedValue<'v, F>],
+        delimiter_indices: &[AssignedValue<'v, F>],
+        number: usize,
+        len: usize,
+        has_end: bool,
+    ) -> Vec<AssignedValue<'v, F>> {
+        let gate = self.range.gate();
+        let field = self.select_sub_array(
+            ctx,
+            n_delimited_data,
+            &delimiter_indices[number - 1],
+            1 + len + has_end as usize,
+        );
+        gate.assert_is_const(
+            ctx,
+            &field[0],
+            F::from((number * DELIMITER as usize) as u64),
+        );
+        if has_end {
+            gate.assert_is_const(
+                ctx,
+                &field[1 + len],
+                F::from(((number + 1) * DELIMITER as usize) as u64),
+            );
+        }
+        field
+    }
+
+    /// Selects the photo following the delimiter numbered `number` up to the end of the signed data at `data_len`.
+    ///
+    /// The photo end is derived from `data_len` rather than given, and `photo_start <= photo_end <= n_delimited_data.len()` is constrained, where `photo_start` is the index following the delimiter.
+    /// The bytes from `data_len` on are masked to zero, so the photo neither reads past the data nor includes the signature bytes.
+    ///
+    /// # Return values
+    /// Returns the photo bytes, at most [`MAX_PHOTO_LEN`] of them, zero-padded after the photo end.
+    pub fn select_photo<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        n_delimited_data: &[AssignedValue<'v, F>],
+        delimiter_indices: &[AssignedValue<'v, F>],
+        number: usize,
+        data_len: &AssignedValue<'v, F>,
+    ) -> Vec<AssignedValue<'v, F>> {
+        let gate = self.range.gate();
+        let num_bits = (usize::BITS - n_delimited_data.len().leading_zeros()) as usize;
+        // `photo_end <= n_delimited_data.len()`.
+        self.range.range_check(ctx, data_len, num_bits);
+        let is_in_data = self.range.is_less_than(
+            ctx,
+            QuantumCell::Existing(data_len),
+            QuantumCell::Constant(F::from(n_delimited_data.len() as u64 + 1)),
+            num_bits + 1,
+        );
+        gate.assert_is_const(ctx, &is_in_data, F::one());
+        // `photo_start <= photo_end`, i.e. the length `data_len - delimiter - 1` is not negative.
+        let span = gate.sub(
+            ctx,
+            QuantumCell::Existing(data_len),
+            QuantumCell::Existing(&delimiter_indices[number - 1]),
+        );
+        let len = gate.sub(
+            ctx,
+            QuantumCell::Existing(&span),
+            QuantumCell::Constant(F::one()),
+        );
+        self.range.range_check(ctx, &len, num_bits);
+
+        let photo = self.select_field(
+            ctx,
+            n_delimited_data,
+            delimiter_indices,
+            number,
+            MAX_PHOTO_LEN.min(n_delimited_data.len()),
+            false,
+        );
+        self.mask_from(ctx, &photo[1..], &len)
+    }
+
+    /// Returns `bytes` with the bytes from the index `len` on set to zero.
+    fn mask_from<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        bytes: &[AssignedValue<'v, F>],
+        len: &AssignedValue<'v, F>,
+    ) -> Vec<AssignedValue<'v, F>> {
+        let gate = self.range.gate();
+        let mut in_field = gate.load_constant(ctx, F::one());
+        let mut masked = Vec::with_capacity(bytes.len());
+        for (i, byte) in bytes.iter().enumerate() {
+            let is_end = gate.is_equal(
+                ctx,
+                QuantumCell::Constant(F::from(i as u64)),
+                QuantumCell::Existing(len),
+            );
+            let is_not_end = gate.not(ctx, QuantumCell::Existing(&is_end));
+            in_field = gate.and(
+                ctx,
+                QuantumCell::Existing(&in_field),
+                QuantumCell::Existing(&is_not_end),
+            );
+            masked.push(gate.mul(
+                ctx,
+                QuantumCell::Existing(byte),
+                QuantumCell::Existing(&in_field),
+            ));
+        }
+        masked
+    }
+
+    /// Returns the decimal number of the ASCII digits `bytes`, the most significant first, constraining each byte to be a digit.
+    fn decimal<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        bytes: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        let gate = self.range.gate();
+        let digits = bytes
+            .iter()
+            .map(|byte| {
+                let digit = gate.sub(
+                    ctx,
+                    QuantumCell::Existing(byte),
+                    QuantumCell::Constant(F::from(b'0' as u64)),
+                );
+                self.range.range_check(ctx, &digit, 4);
+                let is_digit = self.range.is_less_than(
+                    ctx,
+                    QuantumCell::Existing(&digit),
+                    QuantumCell::Constant(F::from(10)),
+                    4,
+                );
+                gate.assert_is_const(ctx, &is_digit, F::one());
+                digit
+            })
+            .collect::<Vec<AssignedValue<F>>>();
+        let powers = (0..digits.len())
+            .rev()
+            .map(|i| QuantumCell::Constant(F::from(10u64.pow(i as u32))))
+            .collect::<Vec<_>>();
+        gate.inner_product(
+            ctx,
+            digits.iter().map(QuantumCell::Existing).collect::<Vec<_>>(),
+            powers,
+        )
+    }
+
+    /// Returns the age in years at the date of `timestamp` of a person born at the year, month and day of `birth_date`.
+    ///
+    /// The age is range-checked to 8 bits, so a birth date after the signing time is not satisfiable.
+    fn age<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        timestamp: &[AssignedValue<'v, F>; 4],
+        birth_date: [&AssignedValue<'v, F>; 3],
+    ) -> AssignedValue<'v, F> {
+        let gate = self.range.gate();
+        let [year, month, day, _] = timestamp;
+        let [birth_year, birth_month, birth_day] = birth_date;
+        // The month and the day are two decimal digits each, so the keys `100 * month + day` fit in 14 bits.
+        let key = gate.mul_add(
+            ctx,
+            QuantumCell::Existing(month),
+            QuantumCell::Constant(F::from(100)),
+            QuantumCell::Existing(day),
+        );
+        let birth_key = gate.mul_add(
+            ctx,
+            QuantumCell::Existing(birth_month),
+            QuantumCell::Constant(F::from(100)),
+            QuantumCell::Existing(birth_day),
+        );
+        let is_before_birthday = self.comparison.less_than(ctx, &key, &birth_key, 14);
+        let years = gate.sub(
+            ctx,
+            QuantumCell::Existing(year),
+            QuantumCell::Existing(birth_year),
+        );
+        let age = gate.sub(
+            ctx,
+            QuantumCell::Existing(&years),
+            QuantumCell::Existing(&is_before_birthday),
+        );
+        self.range.range_check(ctx, &age, 8);
+        age
+    }
+
+    /// Packs the bytes of the state between the delimiters at `start` and `end` little-endian, masking the bytes from `end` on.
+    ///
+    /// The length `end - start - 1` is range-checked to 5 bits, i.e. at most [`MAX_STATE_LEN`] bytes.
+    fn pack_state<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        bytes: &[AssignedValue<'v, F>],
+        start: &AssignedValue<'v, F>,
+        end: &AssignedValue<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        let gate = self.range.gate();
+        let span = gate.sub(
+            ctx,
+            QuantumCell::Existing(end),
+            QuantumCell::Existing(start),
+        );
+        let len = gate.sub(
+            ctx,
+            QuantumCell::Existing(&span),
+            QuantumCell::Constant(F::one()),
+        );
+        self.range.range_check(ctx, &len, 5);
+        let masked = self.mask_from(ctx, bytes, &len);
+        let bases = (0..masked.len())
+            .scan(F::one(), |base, _| {
+                let current = *base;
+                *base *= F::from(256);
+                Some(QuantumCell::Constant(current))
+            })
+            .collect::<Vec<_>>();
+        gate.inner_product(
+            ctx,
+            masked.iter().map(QuantumCell::Existing).collect::<Vec<_>>(),
+            bases,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delimiter::DelimiterCircuit;
+    use crate::lookup_set::test_utils::TestLookupSetCircuit;
+    use crate::name::NAME_POSITION;
+    use crate::nullifier::DEFAULT_PHOTO_PACK_SIZE;
+    use crate::photo::pack_photo;
+    use crate::poseidon_chip::test_utils::TestGateCircuit;
+    use crate::qr::test_utils::{generate_test_qr, mask_fields, TestQrFields};
+    use crate::qr::{decompress_qr, qr_fields_with_layout, signed_region};
+    use halo2_base::halo2_proofs::{circuit::Value, dev::MockProver, halo2curves::bn256::Fr};
+    use std::rc::Rc;
+
+    /// Runs [`FieldExtractorChip::extract_all`] over `data` of length `data_len` with the delimiter of the field at `shifted_position` moved one byte ahead, and constrains the fields equal to `expected`.
+    fn extract_all_circuit(
+        data: Vec<u8>,
+        data_len: usize,
+        shifted_position: Option<usize>,
+        expected: [Fr; 8],
+        photo: [Fr; DEFAULT_PHOTO_PACK_SIZE],
+    ) -> TestGateCircuit {
+        TestGateCircuit::with_range(Rc::new(
+            move |range: &RangeConfig<Fr>, ctx: &mut Context<Fr>| {
+                let gate = range.gate();
+                let delimiter = DelimiterCircuit::<Fr>::new(data.clone());
+                let mut indices = delimiter.delimiter_indices();
+                if let Some(position) = shifted_position {
+                    indices[position - 1] += 1;
+                }
+                let n_delimited_data = delimiter
+                    .n_delimited_data()
+                    .iter()
+                    .map(|byte| gate.load_witness(ctx, Value::known(Fr::from(*byte))))
+                    .collect::<Vec<AssignedValue<Fr>>>();
+                let delimiter_indices = indices
+                    .iter()
+                    .map(|index| gate.load_witness(ctx, Value::known(Fr::from(*index as u64))))
+                    .collect::<Vec<AssignedValue<Fr>>>();
+                let data_len = gate.load_witness(ctx, Value::known(Fr::from(data_len as u64)));
+                let chip = FieldExtractorChip::construct(range.clone());
+                let fields = chip.extract_all(
+                    ctx,
+                    &n_delimited_data,
+                    &delimiter_indices,
+                    &data_len,
+                    &QrFieldLayout::default(),
+                )?;
+                let values = fields.timestamp.iter().chain([
+                    &fields.age,
+                    &fields.gender,
+                    &fields.pincode,
+                    &fields.state,
+                ]);
+                for (value, expected) in values.zip(expected) {
+                    gate.assert_is_const(ctx, value, expected);
+                }
+                for (element, expected) in fields.photo.unwrap().iter().zip(photo) {
+                    gate.assert_is_const(ctx, element, expected);
+                }
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_extract_all() {
+        let k = TestGateCircuit::K;
+        let (qr, _, _) = generate_test_qr(TestQrFields::default());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = signed_region(&decompressed, 2048).unwrap().to_vec();
+
+        // Signed at 2024-07-18 12h by a person born on 01-01-1984, i.e. aged 40, in Delhi 110051.
+        let expected = [
+            Fr::from(2024),
+            Fr::from(7),
+            Fr::from(18),
+            Fr::from(12),
+            Fr::from(40),
+            Fr::from(b'M' as u64),
+            Fr::from(110051),
+            // b"Delhi" as a little-endian integer.
+            Fr::from(452723500356),
+        ];
+        // The photo bytes [255, 79, 255, 81, 0, 47, 0, 0] as a little-endian integer.
+        let photo = pack_photo::<Fr>(&[255, 79, 255, 81, 0, 47, 0, 0]).unwrap();
+        assert_eq!(photo[0], Fr::from(51678422192127));
+        assert!(photo[1..].iter().all(|element| *element == Fr::zero()));
+
+        let len = signed_data.len();
+        let circuit = extract_all_circuit(signed_data.clone(), len, None, expected, photo);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Another age is rejected.
+        let mut wrong = expected;
+        wrong[4] = Fr::from(39);
+        let circuit = extract_all_circuit(signed_data.clone(), len, None, wrong, photo);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // A position that is not the start delimiter of the pincode is rejected.
+        let circuit = extract_all_circuit(
+            signed_data.clone(),
+            len,
+            Some(PINCODE_POSITION),
+            expected,
+            photo,
+        );
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Runs [`FieldExtractorChip::extract_all`] over `data` laid out by `layout` and asserts its gender and pincode valid with [`FieldExtractorChip::assert_valid_fields`].
+    fn valid_fields_circuit(data: Vec<u8>, layout: QrFieldLayout) -> TestLookupSetCircuit {
+        TestLookupSetCircuit::new(
+            identity_sets(),
+            Rc::new(
+                move |range: &RangeConfig<Fr>,
+                      lookup: &mut LookupSetChip<Fr>,
+                      ctx: &mut Context<Fr>| {
+                    let gate = range.gate();
+                    let delimiter = DelimiterCircuit::<Fr>::new(data.clone());
+                    let n_delimited_data = delimiter
+                        .n_delimited_data()
+                        .iter()
+                        .map(|byte| gate.load_witness(ctx, Value::known(Fr::from(*byte))))
+                        .collect::<Vec<AssignedValue<Fr>>>();
+                    let delimiter_indices = delimiter
+                        .delimiter_indices()
+                        .iter()
+                        .map(|index| gate.load_witness(ctx, Value::known(Fr::from(*index as u64))))
+                        .collect::<Vec<AssignedValue<Fr>>>();
+                    let data_len =
+                        gate.load_witness(ctx, Value::known(Fr::from(data.len() as u64)));
+                    let chip = FieldExtractorChip::construct(range.clone());
+                    let fields = chip.extract_all(
+                        ctx,
+                        &n_delimited_data,
+                        &delimiter_indices,
+                        &data_len,
+                        &layout,
+                    )?;
+                    chip.assert_valid_fields(ctx, lookup, &fields, &layout)
+                },
+            ),
+        )
+    }
+
+    #[test]
+    fn test_assert_valid_fields() {
+        let k = TestLookupSetCircuit::K;
+        let run = |fields: TestQrFields, layout: QrFieldLayout| {
+            let (qr, _, _) = generate_test_qr(fields);
+            let decompressed = decompress_qr(&qr).unwrap();
+            let signed_data = signed_region(&decompressed, 2048).unwrap();
+            let data = mask_fields(signed_data, &layout);
+            MockProver::run(k, &valid_fields_circuit(data, layout), vec![])
+                .unwrap()
+                .verify()
+        };
+
+        assert_eq!(
+            run(TestQrFields::default(), QrFieldLayout::default()),
+            Ok(())
+        );
+        let female = TestQrFields {
+            gender: "F".to_string(),
+            pincode: "930001".to_string(),
+            ..TestQrFields::default()
+        };
+        assert_eq!(run(female, QrFieldLayout::default()), Ok(()));
+
+        // A gender out of the set is rejected.
+        let unknown_gender = TestQrFields {
+            gender: "X".to_string(),
+            ..TestQrFields::default()
+        };
+        assert!(run(unknown_gender.clone(), QrFieldLayout::default()).is_err());
+
+        // So is a pincode starting with zero.
+        let zero_pincode = TestQrFields {
+            pincode: "010051".to_string(),
+            ..TestQrFields::default()
+        };
+        assert!(run(zero_pincode, QrFieldLayout::default()).is_err());
+
+        // A masked gender is zero and is not checked.
+        let layout = QrFieldLayout::default().without(GENDER_POSITION);
+        assert_eq!(run(unknown_gender, layout), Ok(()));
+    }
+
+    /// Runs [`FieldExtractorChip::extract_all`] over the masked `data` laid out by `layout`, and constrains the fields equal to `expected`.
+    fn extract_masked_circuit(
+        data: Vec<u8>,
+        layout: QrFieldLayout,
+        expected: [Fr; 8],
+    ) -> TestGateCircuit {
+        TestGateCircuit::with_range(Rc::new(
+            move |range: &RangeConfig<Fr>, ctx: &mut Context<Fr>| {
+                let gate = range.gate();
+                let delimiter = DelimiterCircuit::<Fr>::new(data.clone());
+                let n_delimited_data = delimiter
+                    .n_delimited_data()
+                    .iter()
+                    .map(|byte| gate.load_witness(ctx, Value::known(Fr::from(*byte))))
+                    .collect::<Vec<AssignedValue<Fr>>>();
+                let delimiter_indices = delimiter
+                    .delimiter_indices()
+                    .iter()
+                    .map(|index| gate.load_witness(ctx, Value::known(Fr::from(*index as u64))))
+                    .collect::<Vec<AssignedValue<Fr>>>();
+                let data_len = gate.load_witness(ctx, Value::known(Fr::from(data.len() as u64)));
+                let chip = FieldExtractorChip::construct(range.clone());
+                let fields = chip.extract_all(
+                    ctx,
+                    &n_delimited_data,
+                    &delimiter_indices,
+                    &data_len,
+                    &layout,
+                )?;
+                let values = fields.timestamp.iter().chain([
+                    &fields.age,
+                    &fields.gender,
+                    &fields.pincode,
+                    &fields.state,
+                ]);
+                for (value, expected) in values.zip(expected) {
+                    gate.assert_is_const(ctx, value, expected);
+                }
+                assert!(fields.photo.is_none());
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_extract_all_masked() {
+        let k = TestGateCircuit::K;
+        let fields = TestQrFields {
+            photo: vec![],
+            ..TestQrFields::default()
+        };
+        let (qr, _, _) = generate_test_qr(fields);
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = signed_region(&decompressed, 2048).unwrap();
+
+        // A masked QR code omitting the gender, the state and the photo.
+        let layout = QrFieldLayout::without_photo()
+            .without(GENDER_POSITION)
+            .without(STATE_POSITION);
+        let masked = mask_fields(signed_data, &layout);
+        assert!(qr_fields_with_layout(&masked, &layout).is_some());
+
+        // The present fields are extracted, and the absent ones are zero.
+        let expected = [
+            Fr::from(2024),
+            Fr::from(7),
+            Fr::from(18),
+            Fr::from(12),
+            Fr::from(40),
+            Fr::zero(),
+            Fr::from(110051),
+            Fr::zero(),
+        ];
+        let circuit = extract_masked_circuit(masked.clone(), layout.clone(), expected);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // An absent field cannot be claimed.
+        let mut claimed = expected;
+        claimed[5] = Fr::from(b'M' as u64);
+        let circuit = extract_masked_circuit(masked.clone(), layout.clone(), claimed);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Without the DOB, the age is zero as well.
+        let layout = layout.without(DOB_POSITION);
+        let masked = mask_fields(signed_data, &layout);
+        let mut expected = expected;
+        expected[4] = Fr::zero();
+        let circuit = extract_masked_circuit(masked, layout, expected);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    /// Runs [`FieldExtractorChip::is_not_expired`] over `data` with the expiry field at `expiry_position`, and constrains the bit equal to `expected`.
+    fn not_expired_circuit(
+        data: Vec<u8>,
+        expiry_position: usize,
+        current_time: [u64; 4],
+        expected: bool,
+    ) -> TestGateCircuit {
+        TestGateCircuit::with_range(Rc::new(
+            move |range: &RangeConfig<Fr>, ctx: &mut Context<Fr>| {
+                let gate = range.gate();
+                let delimiter = DelimiterCircuit::<Fr>::new(data.clone());
+                let n_delimited_data = delimiter
+                    .n_delimited_data()
+                    .iter()
+                    .map(|byte| gate.load_witness(ctx, Value::known(Fr::from(*byte))))
+                    .collect::<Vec<AssignedValue<Fr>>>();
+                let delimiter_indices = delimiter
+                    .delimiter_indices()
+                    .iter()
+                    .map(|index| gate.load_witness(ctx, Value::known(Fr::from(*index as u64))))
+                    .collect::<Vec<AssignedValue<Fr>>>();
+                let current_time =
+                    current_time.map(|value| gate.load_witness(ctx, Value::known(Fr::from(value))));
+                let chip = FieldExtractorChip::construct(range.clone());
+                let not_expired = chip.is_not_expired(
+                    ctx,
+                    &n_delimited_data,
+                    &delimiter_indices,
+                    &QrFieldLayout::default(),
+                    expiry_position,
+                    &current_time,
+                )?;
+                gate.assert_is_const(ctx, &not_expired, Fr::from(expected as u64));
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_not_expired() {
+        let k = TestGateCircuit::K;
+        // The sample payload has no expiry field, so the name field stands in for one expiring at 2025-07-18 12h.
+        let fields = TestQrFields {
+            name: "2025071812".to_string(),
+            ..TestQrFields::default()
+        };
+        let (qr, _, _) = generate_test_qr(fields);
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = signed_region(&decompressed, 2048).unwrap().to_vec();
+
+        for (current_time, not_expired) in [
+            ([2024, 7, 18, 12], true),
+            ([2025, 7, 18, 11], true),
+            ([2025, 7, 18, 12], false),
+            ([2026, 1, 1, 0], false),
+        ] {
+            let circuit = not_expired_circuit(
+                signed_data.clone(),
+                NAME_POSITION,
+                current_time,
+                not_expired,
+            );
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // The opposite bit is rejected.
+            let circuit = not_expired_circuit(
+                signed_data.clone(),
+                NAME_POSITION,
+                current_time,
+                !not_expired,
+            );
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // A field that is not `YYYYMMDDhh` digits is rejected.
+        let circuit = not_expired_circuit(
+            signed_data.clone(),
+            PINCODE_POSITION,
+            [2024, 7, 18, 12],
+            true,
+        );
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Runs [`FieldExtractorChip::is_fresh`] of `current_time` against the signing time extracted from `data`, and constrains the bit equal to `expected`.
+    fn fresh_circuit(data: Vec<u8>, current_time: [u64; 4], expected: bool) -> TestGateCircuit {
+        TestGateCircuit::with_range(Rc::new(
+            move |range: &RangeConfig<Fr>, ctx: &mut Context<Fr>| {
+                let gate = range.gate();
+                let delimiter = DelimiterCircuit::<Fr>::new(data.clone());
+                let n_delimited_data = delimiter
+                    .n_delimited_data()
+                    .iter()
+                    .map(|byte| gate.load_witness(ctx, Value::known(Fr::from(*byte))))
+                    .collect::<Vec<AssignedValue<Fr>>>();
+                let delimiter_indices = delimiter
+                    .delimiter_indices()
+                    .iter()
+                    .map(|index| gate.load_witness(ctx, Value::known(Fr::from(*index as u64))))
+                    .collect::<Vec<AssignedValue<Fr>>>();
+                let data_len = gate.load_witness(ctx, Value::known(Fr::from(data.len() as u64)));
+                let current_time =
+                    current_time.map(|value| gate.load_witness(ctx, Value::known(Fr::from(value))));
+                let chip = FieldExtractorChip::construct(range.clone());
+                let fields = chip.extract_all(
+                    ctx,
+                    &n_delimited_data,
+                    &delimiter_indices,
+                    &data_len,
+                    &QrFieldLayout::default(),
+                )?;
+                let is_fresh = chip.is_fresh(ctx, &current_time, &fields.timestamp, 1);
+                gate.assert_is_const(ctx, &is_fresh, Fr::from(expected as u64));
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_fresh() {
+        let k = TestGateCircuit::K;
+        // The sample payload is signed at 2024-07-18 12h.
+        let (qr, _, _) = generate_test_qr(TestQrFields::default());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = signed_region(&decompressed, 2048).unwrap().to_vec();
+
+        for (current_time, is_fresh) in [
+            ([2024, 7, 18, 12], true),
+            ([2025, 7, 18, 11], true),
+            ([2025, 7, 18, 12], false),
+            ([2024, 7, 18, 11], false),
+        ] {
+            let circuit = fresh_circuit(signed_data.clone(), current_time, is_fresh);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // The opposite bit is rejected.
+            let circuit = fresh_circuit(signed_data.clone(), current_time, !is_fresh);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn test_photo_end() {
+        let k = TestGateCircuit::K;
+        let fields = TestQrFields {
+            photo: vec![255, 79, 255, 81, 7],
+            ..TestQrFields::default()
+        };
+        let (qr, _, _) = generate_test_qr(fields.clone());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_len = signed_region(&decompressed, 2048).unwrap().len();
+        let expected = [
+            Fr::from(2024),
+            Fr::from(7),
+            Fr::from(18),
+            Fr::from(12),
+            Fr::from(40),
+            Fr::from(b'M' as u64),
+            Fr::from(110051),
+            Fr::from(452723500356),
+        ];
+        let photo = pack_photo::<Fr>(&fields.photo).unwrap();
+
+        // The data is followed by the signature, but the photo ends at the derived end of the signed data.
+        let circuit = extract_all_circuit(decompressed.clone(), signed_len, None, expected, photo);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The whole payload as data includes the signature bytes in the photo.
+        let len = decompressed.len();
+        let circuit = extract_all_circuit(decompressed.clone(), len, None, expected, photo);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The photo end can neither precede the photo start nor exceed the data.
+        let photo_start = signed_len - fields.photo.len();
+        for data_len in [photo_start - 1, decompressed.len() + 1] {
+            let circuit =
+                extract_all_circuit(decompressed.clone(), data_len, None, expected, photo);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        // An empty photo ends at its start.
+        let circuit = extract_all_circuit(
+            decompressed[..photo_start].to_vec(),
+            photo_start,
+            None,
+            expected,
+            pack_photo::<Fr>(&[]).unwrap(),
+        );
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
@@ -0,0 +1,395 @@
+//! Proving that the signing key is in a published registry of UIDAI keys without revealing which one it is.
+use halo2_base::halo2_proofs::{circuit::Value, halo2curves::bn256::Fr};
+#[cfg(feature = "sha256")]
+use halo2_base::halo2_proofs::{
+    circuit::{Cell, Layouter, SimpleFloorPlanner},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+#[cfg(feature = "sha256")]
+use halo2_base::SKIP_FIRST_PASS;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    AssignedValue, Context,
+};
+#[cfg(feature = "sha256")]
+use halo2_dynamic_sha256::Sha256DynamicConfig;
+use num_bigint::BigUint;
+#[cfg(feature = "sha256")]
+use rsa::RsaPublicKey;
+#[cfg(feature = "sha256")]
+use std::marker::PhantomData;
+
+use crate::big_uint::decompose_biguint;
+use crate::nullifier::{RATE, T};
+#[cfg(feature = "sha256")]
+use crate::params::{Mainnet2048, Preset};
+use crate::pincode::{
+    assign_merkle_root, merkle_levels, merkle_path, MerklePath, MERKLE_RATE, MERKLE_T,
+};
+use crate::poseidon_chip::{poseidon_hash, PoseidonChip};
+#[cfg(feature = "sha256")]
+use crate::{
+    configure_rsa_sha256, BigUintInstructions, HashAlg, RSAConfig, RSAInstructions, RSAPublicKey,
+    RSASignature, RSASignatureVerifier,
+};
+
+/// The bit length of the limbs of the modulus hashed into the public key hash, i.e. those of [`crate::BigUintConfig`] in the RSA verification.
+pub const KEY_LIMB_BITS: usize = 64;
+
+/// Computes the Poseidon hash of the little-endian [`KEY_LIMB_BITS`]-bit limbs of `modulus` out of the circuit, as [`KeyRegistryChip::assign_pubkey_hash`] does in it.
+///
+/// # Arguments
+/// * modulus - the modulus of the RSA public key.
+/// * bits_len - the bit length of the modulus, e.g. 2048, which sets the number of limbs.
+pub fn poseidon_pubkey_hash(modulus: &BigUint, bits_len: usize) -> Fr {
+    let limbs = decompose_biguint::<Fr>(modulus, bits_len / KEY_LIMB_BITS, KEY_LIMB_BITS);
+    poseidon_hash::<T, RATE>(&limbs)
+}
+
+/// A Merkle tree of the Poseidon hashes of the valid UIDAI keys, whose root is published by the service.
+///
+/// The leaves are the hashes of [`poseidon_pubkey_hash`], padded with zeros to a power of two, and the nodes are hashed as in [`crate::pincode::PincodeAllowlist`].
+/// A key is rotated by publishing the root of the updated registry, so the verifiers keep their verifying key.
+#[derive(Clone, Debug)]
+pub struct KeyRegistry {
+    pubkey_hashes: Vec<Fr>,
+    /// The nodes of each level, from the leaves to the root.
+    levels: Vec<Vec<Fr>>,
+}
+
+impl KeyRegistry {
+    /// Creates new [`KeyRegistry`] from the moduli of the valid keys of `bits_len` bits.
+    pub fn new(moduli: &[BigUint], bits_len: usize) -> Self {
+        let pubkey_hashes = moduli
+            .iter()
+            .map(|modulus| poseidon_pubkey_hash(modulus, bits_len))
+            .collect::<Vec<Fr>>();
+        Self::from_pubkey_hashes(&pubkey_hashes)
+    }
+
+    /// Creates new [`KeyRegistry`] from the public key hashes of the valid keys.
+    pub fn from_pubkey_hashes(pubkey_hashes: &[Fr]) -> Self {
+        let num_leaves = pubkey_hashes.len().max(2).next_power_of_two();
+        let mut leaves = pubkey_hashes.to_vec();
+        leaves.resize(num_leaves, Fr::zero());
+        Self {
+            pubkey_hashes: pubkey_hashes.to_vec(),
+            levels: merkle_levels(leaves),
+        }
+    }
+
+    /// Returns the number of levels below the root.
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Returns the root to publish.
+    pub fn root(&self) -> Fr {
+        self.levels[self.depth()][0]
+    }
+
+    /// Returns the Merkle path of the key whose hash is `pubkey_hash`, or `None` if it is not registered.
+    pub fn path(&self, pubkey_hash: Fr) -> Option<MerklePath> {
+        let index = self
+            .pubkey_hashes
+            .iter()
+            .position(|hash| *hash == pubkey_hash)?;
+        Some(merkle_path(&self.levels, index))
+    }
+}
+
+/// A chip constraining the signing key to be registered in a [`KeyRegistry`].
+///
+/// The path is private, so the proof only reveals the root, i.e. the registry, and not which key signed the QR data.
+#[derive(Clone, Debug)]
+pub struct KeyRegistryChip {
+    pubkey_hash: PoseidonChip<T, RATE>,
+    merkle: PoseidonChip<MERKLE_T, MERKLE_RATE>,
+}
+
+impl KeyRegistryChip {
+    /// Creates new [`KeyRegistryChip`] from [`FlexGateConfig`].
+    pub fn construct(gate: FlexGateConfig<Fr>) -> Self {
+        Self {
+            pubkey_hash: PoseidonChip::construct(gate.clone()),
+            merkle: PoseidonChip::construct(gate),
+        }
+    }
+
+    /// Getter for [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<Fr> {
+        self.merkle.gate()
+    }
+
+    /// Computes the public key hash of [`poseidon_pubkey_hash`] from the assigned limbs of the modulus.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * modulus_limbs - the assigned little-endian [`KEY_LIMB_BITS`]-bit limbs of the modulus, e.g. [`crate::Pkcs1v15Result::modulus_limbs`] so that the hashed key is the one the signature is verified against.
+    pub fn assign_pubkey_hash<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        modulus_limbs: &[AssignedValue<'v, Fr>],
+    ) -> AssignedValue<'v, Fr> {
+        self.pubkey_hash.hash(ctx, modulus_limbs)
+    }
+
+    /// Computes the root of the registry tree from the assigned limbs of the modulus and the witnessed Merkle path of their hash.
+    ///
+    /// The index is decomposed into `path.siblings.len()` constrained bits, which select the order of each pair of nodes.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * modulus_limbs - the assigned limbs of the modulus as in [`KeyRegistryChip::assign_pubkey_hash`].
+    /// * path - the Merkle path as computed by [`KeyRegistry::path`].
+    ///
+    /// # Return values
+    /// Returns the assigned root, which the caller must constrain equal to the published root, e.g. an instance, for the membership to hold.
+    pub fn assign_root<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        modulus_limbs: &[AssignedValue<'v, Fr>],
+        path: &MerklePath,
+    ) -> AssignedValue<'v, Fr> {
+        let pubkey_hash = self.assign_pubkey_hash(ctx, modulus_limbs);
+        let index = self
+            .gate()
+            .load_witness(ctx, Value::known(Fr::from(path.index)));
+        assign_merkle_root(&self.merkle, ctx, &pubkey_hash, &index, &path.siblings)
+    }
+}
+
+/// A circuit proving that the QR data `msg` is signed by a key registered in a [`KeyRegistry`], exposing only the root of the registry.
+///
+/// The key is hashed from the limbs of the modulus the signature is verified against, so the Merkle path cannot be proven for another key than the signing one.
+/// The root is exposed in the first row of the instance column, and the verifier compares it with the published one.
+#[cfg(feature = "sha256")]
+#[derive(Clone, Debug)]
+pub struct KeyRegistryCircuit<C: Preset = Mainnet2048> {
+    msg: Vec<u8>,
+    signature: Vec<u8>,
+    public_key: RsaPublicKey,
+    path: MerklePath,
+    _c: PhantomData<C>,
+}
+
+#[cfg(feature = "sha256")]
+impl<C: Preset> KeyRegistryCircuit<C> {
+    /// Creates new [`KeyRegistryCircuit`].
+    ///
+    /// # Arguments
+    /// * msg - the signed data, at most [`Preset::MSG_LEN`] bytes.
+    /// * signature - the big-endian bytes of the pkcs1v15 signature of `msg`.
+    /// * public_key - the `C::BITS_LEN`-bit signing key.
+    /// * path - the Merkle path of the signing key as computed by [`KeyRegistry::path`].
+    pub fn new(
+        msg: Vec<u8>,
+        signature: Vec<u8>,
+        public_key: RsaPublicKey,
+        path: MerklePath,
+    ) -> Self {
+        Self {
+            msg,
+            signature,
+            public_key,
+            path,
+            _c: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "sha256")]
+impl<C: Preset> Circuit<Fr> for KeyRegistryCircuit<C> {
+    type Config = (RSAConfig<Fr>, Sha256DynamicConfig<Fr>, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let (rsa_config, sha256_config) = configure_rsa_sha256::<Fr, C>(meta, vec![C::MSG_LEN]);
+        let root = meta.instance_column();
+        meta.enable_equality(root);
+        (rsa_config, sha256_config, root)
+    }
+
+    fn synthesize(
+        &self,
+        (rsa_config, sha256_config, root_instance): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let biguint_config = rsa_config.biguint_config().clone();
+        sha256_config.load(&mut layouter)?;
+        biguint_config.range().load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        let mut root = None::<Cell>;
+        layouter.assign_region(
+            || "key registry",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let mut aux = biguint_config.new_context(region);
+                let ctx = &mut aux;
+                let signature = rsa_config.assign_signature(
+                    ctx,
+                    RSASignature::new(Value::known(BigUint::from_bytes_be(&self.signature))),
+                )?;
+                let public_key =
+                    rsa_config.assign_public_key(ctx, RSAPublicKey::from(&self.public_key))?;
+                let mut verifier =
+                    RSASignatureVerifier::new(rsa_config.clone(), sha256_config.clone());
+                let result = verifier.verify_pkcs1v15_signature(
+                    ctx,
+                    &public_key,
+                    &self.msg,
+                    &signature,
+                    HashAlg::Sha256,
+                )?;
+                let gate = biguint_config.gate();
+                gate.assert_is_const(ctx, &result.is_valid, Fr::one());
+                let chip = KeyRegistryChip::construct(gate.clone());
+                root = Some(
+                    chip.assign_root(ctx, &result.modulus_limbs, &self.path)
+                        .cell(),
+                );
+                biguint_config.range().finalize(ctx);
+                Ok(())
+            },
+        )?;
+        layouter.constrain_instance(root.unwrap(), root_instance, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon_chip::test_utils::TestGateCircuit;
+    use halo2_base::halo2_proofs::dev::MockProver;
+    use std::rc::Rc;
+
+    const BITS_LEN: usize = 2048;
+
+    fn modulus(e: u64) -> BigUint {
+        BigUint::from(e) << (BITS_LEN - 32)
+    }
+
+    fn registry_circuit(modulus: BigUint, path: MerklePath, root: Fr) -> TestGateCircuit {
+        TestGateCircuit::new(Rc::new(
+            move |gate: &FlexGateConfig<Fr>, ctx: &mut Context<Fr>| {
+                let chip = KeyRegistryChip::construct(gate.clone());
+                let limbs =
+                    decompose_biguint::<Fr>(&modulus, BITS_LEN / KEY_LIMB_BITS, KEY_LIMB_BITS)
+                        .into_iter()
+                        .map(|limb| gate.load_witness(ctx, Value::known(limb)))
+                        .collect::<Vec<_>>();
+                let computed_root = chip.assign_root(ctx, &limbs, &path);
+                gate.assert_is_const(ctx, &computed_root, root);
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_key_registry() {
+        let k = TestGateCircuit::K;
+        let moduli = [modulus(65537), modulus(65539), modulus(65543)];
+        let registry = KeyRegistry::new(&moduli, BITS_LEN);
+        assert_eq!(registry.depth(), 2);
+
+        // A registered key is proven in the registry.
+        let pubkey_hash = poseidon_pubkey_hash(&moduli[1], BITS_LEN);
+        let path = registry.path(pubkey_hash).unwrap();
+        let circuit = registry_circuit(moduli[1].clone(), path.clone(), registry.root());
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // After a rotation, the same key is proven against the new root.
+        let rotated = KeyRegistry::new(&[moduli[1].clone(), modulus(65551)], BITS_LEN);
+        let rotated_path = rotated.path(pubkey_hash).unwrap();
+        let circuit = registry_circuit(moduli[1].clone(), rotated_path, rotated.root());
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // An unregistered key has no path, and the path of a registered key does not lead to the root from it.
+        let forged_key = modulus(65549);
+        assert!(registry
+            .path(poseidon_pubkey_hash(&forged_key, BITS_LEN))
+            .is_none());
+        let circuit = registry_circuit(forged_key, path.clone(), registry.root());
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Neither does a path with another index, nor the path against the root of another registry.
+        let forged_path = MerklePath {
+            index: path.index ^ 1,
+            ..path.clone()
+        };
+        let circuit = registry_circuit(moduli[1].clone(), forged_path, registry.root());
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+        let circuit = registry_circuit(moduli[1].clone(), path, rotated.root());
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_key_registry_circuit() {
+        use crate::params::Testing;
+        use rsa::{pkcs1v15::SigningKey, signature::SignatureEncoding, signature::Signer};
+        use rsa::{traits::PublicKeyParts, RsaPrivateKey};
+
+        let k = Testing::K;
+        let mut rng = rand::thread_rng();
+        let private_key = RsaPrivateKey::new(&mut rng, Testing::BITS_LEN).unwrap();
+        let public_key = RsaPublicKey::from(&private_key);
+        let msg = b"V2\xff2024071812\xffexample".to_vec();
+        let sign = |key: &RsaPrivateKey| {
+            SigningKey::<rsa::sha2::Sha256>::new(key.clone())
+                .sign(&msg)
+                .to_vec()
+        };
+        let signature = sign(&private_key);
+        let n = BigUint::from_bytes_be(&public_key.n().to_bytes_be());
+        let registry = KeyRegistry::new(&[modulus(65537), n.clone()], Testing::BITS_LEN);
+        let path = registry
+            .path(poseidon_pubkey_hash(&n, Testing::BITS_LEN))
+            .unwrap();
+
+        // The signing key is proven in the registry, whose root is the only instance.
+        let circuit = KeyRegistryCircuit::<Testing>::new(
+            msg.clone(),
+            signature.clone(),
+            public_key.clone(),
+            path.clone(),
+        );
+        let prover = MockProver::run(k, &circuit, vec![vec![registry.root()]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Another root is rejected.
+        let other = KeyRegistry::new(&[modulus(65537), modulus(65539)], Testing::BITS_LEN);
+        let prover = MockProver::run(k, &circuit, vec![vec![other.root()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // A valid signature by an unregistered key does not lead to the root with the path of a registered one.
+        let forged_key = RsaPrivateKey::new(&mut rng, Testing::BITS_LEN).unwrap();
+        let circuit = KeyRegistryCircuit::<Testing>::new(
+            msg.clone(),
+            sign(&forged_key),
+            RsaPublicKey::from(&forged_key),
+            path.clone(),
+        );
+        let prover = MockProver::run(k, &circuit, vec![vec![registry.root()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Nor is the registered key proven without its signature.
+        let mut tampered = signature;
+        *tampered.last_mut().unwrap() ^= 1;
+        let circuit = KeyRegistryCircuit::<Testing>::new(msg, tampered, public_key, path);
+        let prover = MockProver::run(k, &circuit, vec![vec![registry.root()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
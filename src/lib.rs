@@ -6,6 +6,8 @@
 //!
 //! In addition to [`RSAConfig`], this library also provides a high-level circuit implementation to verify pkcs1v15 signatures, [`RSASignatureVerifier`].  
 //! The verification function in [`RSAConfig`] requires as input a hashed message, whereas the function in [`RSASignatureVerifier`] computes a SHA256 hash of the given message and verifies the given signature for that hash.
+//! [`RSASignatureVerifier`] can also verify RSASSA-PSS signatures, computing the MGF1 mask with the same SHA256 chip.
+//! With the `legacy-sha1` feature, it also verifies pkcs1v15 signatures with SHA-1 of legacy Aadhaar QR codes, which is insecure for new use.
 
 pub mod big_uint;
 pub use big_uint::*;
@@ -17,34 +19,63 @@ use halo2_base::halo2_proofs::{
     plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
 };
 
-use halo2_base::{gates::range::RangeStrategy::Vertical, QuantumCell, SKIP_FIRST_PASS};
+use base64ct::{Base64, Encoding};
+use halo2_base::{gates::range::RangeStrategy::Vertical, SKIP_FIRST_PASS};
 use halo2_base::{
-    gates::{range::RangeConfig, GateInstructions},
-    utils::PrimeField,
-    AssignedValue, Context,
+    gates::{flex_gate::FlexGateConfig, range::RangeConfig, GateInstructions},
+    utils::{fe_to_biguint, PrimeField},
+    AssignedValue, Context, QuantumCell,
 };
 use num_bigint::BigUint;
 
 use rsa::{
     pkcs1v15::SigningKey,
+    sha2::Digest,
     signature::{SignatureEncoding, Signer},
     traits::PublicKeyParts,
     RsaPublicKey,
 };
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
+pub mod age;
+pub mod commitment;
+pub mod comparison;
 pub mod conditional_secrets;
-mod qr_data_extractor;
+pub mod delimiter;
+pub mod field_extractor;
+pub mod key_registry;
+pub mod lookup_set;
+pub mod name;
+pub mod nullifier;
+pub mod params;
+pub mod photo;
+pub mod pincode;
+pub mod poseidon_chip;
+pub mod prover;
+pub mod qr;
+pub mod qr_data_extractor;
+pub mod revocation;
+#[cfg(feature = "legacy-sha1")]
+pub mod sha1;
 pub mod signal;
 pub mod timestamp;
+pub mod utils;
 
 mod chip;
 mod instructions;
 pub use chip::*;
+/// The `halo2_proofs` of [`halo2_base`], the only path to it this crate uses.
+///
+/// The circuits and chips of this crate take and return its types, so a parent circuit should import `Layouter`, `Value`, `Fr`, etc. from here rather than from another `halo2_proofs` dependency, whose types do not unify with these.
+/// The separate `halo2curves` dependency only provides the field of the native `poseidon` crate, converted by [`poseidon_chip::to_circuit_fr`] and [`poseidon_chip::to_native_fr`].
+pub use halo2_base::halo2_proofs;
 #[cfg(feature = "sha256")]
 pub use halo2_dynamic_sha256;
 #[cfg(feature = "sha256")]
 use halo2_dynamic_sha256::Sha256DynamicConfig;
 pub use instructions::*;
+use params::{validate_msg_len, Mainnet2048, ParamsError, Preset, RangeParams, Sha256Params};
+use utils::{CircuitShape, DryRun};
 #[cfg(feature = "sha256")]
 
 /// A parameter `e` in the RSA public key that is about to be assigned.
@@ -103,6 +134,15 @@ impl<F: PrimeField> RSAPublicKey<F> {
     }
 }
 
+impl<F: PrimeField> From<&RsaPublicKey> for RSAPublicKey<F> {
+    /// Converts a key of the `rsa` crate into a known modulus `n` and a fixed exponent `e` read from the key.
+    fn from(public_key: &RsaPublicKey) -> Self {
+        let n = BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap();
+        let e = BigUint::from_radix_le(&public_key.e().to_radix_le(16), 16).unwrap();
+        Self::new(Value::known(n), RSAPubE::Fix(e))
+    }
+}
+
 /// An assigned RSA public key.
 #[derive(Clone, Debug)]
 pub struct AssignedRSAPublicKey<'v, F: PrimeField> {
@@ -150,6 +190,33 @@ impl<F: PrimeField> RSASignature<F> {
         let c = Value::unknown();
         Self { c, _f: PhantomData }
     }
+
+    /// Creates new [`RSASignature`] from a base64 signature, i.e. the big-endian bytes of its integer as passed by front-ends.
+    ///
+    /// # Arguments
+    /// * signature - a signature encoded in standard padded base64, e.g. by [`RSASignature::encode_base64`].
+    ///
+    /// # Return values
+    /// Returns new [`RSASignature`], or the [`base64ct::Error`] if `signature` is not valid base64.
+    pub fn from_base64(signature: &str) -> Result<Self, base64ct::Error> {
+        let bytes = Base64::decode_vec(signature.trim())?;
+        Ok(Self::new(Value::known(BigUint::from_bytes_be(&bytes))))
+    }
+
+    /// Encodes the integer `c` of a signature in base64, as decoded by [`RSASignature::from_base64`].
+    ///
+    /// # Arguments
+    /// * c - an integer of the signature.
+    /// * bits_len - the bit length of the modulus, whose byte length the big-endian bytes of `c` are left-padded to with zeros.
+    ///
+    /// # Return values
+    /// Returns the base64 string of the signature bytes.
+    pub fn encode_base64(c: &BigUint, bits_len: usize) -> String {
+        let bytes = c.to_bytes_be();
+        let mut padded = vec![0; (bits_len / 8).saturating_sub(bytes.len())];
+        padded.extend(bytes);
+        Base64::encode_string(&padded)
+    }
 }
 
 /// An assigned RSA signature.
@@ -172,12 +239,62 @@ impl<'v, F: PrimeField> AssignedRSASignature<'v, F> {
     }
 }
 
+/// The result of verifying a pkcs1v15 signature by [`RSASignatureVerifier`].
+#[derive(Clone, Debug)]
+pub struct Pkcs1v15Result<'v, F: PrimeField> {
+    /// The assigned bit that is one if the signature is valid, and zero otherwise.
+    pub is_valid: AssignedValue<'v, F>,
+    /// The assigned bytes of the message hash in the [`HashByteOrder`] of the verifier, big-endian by default.
+    pub hashed_msg: Vec<AssignedValue<'v, F>>,
+    /// The assigned limbs of the modulus used in the verification.
+    pub modulus_limbs: Vec<AssignedValue<'v, F>>,
+    /// The assigned bytes of the signed message as input to the hash function, without the padding.
+    pub msg: Vec<AssignedValue<'v, F>>,
+    /// The assigned byte length of `msg` as input to the hash function.
+    pub msg_len: AssignedValue<'v, F>,
+    /// The assigned bytes input to the hash function up to its maximum size, i.e. `msg` followed by the padding.
+    ///
+    /// Unlike `msg`, their number does not depend on the length of the message, so a circuit can bind them to other cells with the same copy constraints for any message.
+    pub padded_msg: Vec<AssignedValue<'v, F>>,
+}
+
+/// The result of verifying the signature of a timestamp oracle by [`RSASignatureVerifier::verify_oracle_time`].
+#[derive(Clone, Debug)]
+pub struct OracleTime<'v, F: PrimeField> {
+    /// The result of the signature verification, whose modulus limbs are those of the oracle key.
+    pub signature: Pkcs1v15Result<'v, F>,
+    /// The assigned year, month, day and hour of the signed time.
+    pub time: [AssignedValue<'v, F>; 4],
+}
+
+/// The byte order of the message hash returned by [`RSASignatureVerifier`], e.g. to match the public inputs expected by a Solidity verifier.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HashByteOrder {
+    /// The order of the digest as output by the hash function, e.g. by `sha2::Sha256::digest`.
+    #[default]
+    BigEndian,
+    /// The reverse order, i.e. the least significant byte of the digest first.
+    LittleEndian,
+}
+
+impl HashByteOrder {
+    /// Returns the bytes of `digest`, given in big-endian order, in this order.
+    pub fn order<T: Clone>(&self, digest: &[T]) -> Vec<T> {
+        match self {
+            Self::BigEndian => digest.to_vec(),
+            Self::LittleEndian => digest.iter().rev().cloned().collect(),
+        }
+    }
+}
+
 #[cfg(feature = "sha256")]
-/// A circuit implementation to verify pkcs1v15 signatures.
+/// A circuit implementation to verify pkcs1v15 and RSASSA-PSS signatures.
 #[derive(Clone, Debug)]
 pub struct RSASignatureVerifier<F: PrimeField> {
     rsa_config: RSAConfig<F>,
     sha256_config: Sha256DynamicConfig<F>,
+    /// The order of the returned hash bytes.
+    hash_byte_order: HashByteOrder,
 }
 
 #[cfg(feature = "sha256")]
@@ -194,55 +311,585 @@ impl<F: PrimeField> RSASignatureVerifier<F> {
         Self {
             rsa_config,
             sha256_config,
+            hash_byte_order: HashByteOrder::default(),
         }
     }
 
-    /// Given a RSA public key, signed message bytes, and a pkcs1v15 signature, verifies the signature with SHA256 hash function.
+    /// Checks that a message of `msg_len` bytes fits in the maximum byte size of the next digest, as recorded by [`Sha256DynamicConfig::configure`], e.g. [`Preset::MSG_LEN`], as [`params::validate_msg_len`] does.
+    ///
+    /// The verification functions fail with [`Error::Synthesis`] on a longer message, so a caller checks it with this function before the synthesis to get the typed error.
+    ///
+    /// # Return values
+    /// Returns [`ParamsError::MessageTooLong`] if it does not fit, with a maximum of zero if all the configured digests are computed, or `Ok(())` if it fits.
+    pub fn check_msg_len(&self, msg_len: usize) -> Result<(), ParamsError> {
+        let max_msg_len = self
+            .sha256_config
+            .max_byte_sizes
+            .get(self.sha256_config.cur_hash_idx)
+            .copied()
+            .unwrap_or(0);
+        validate_msg_len(msg_len, max_msg_len)
+    }
+
+    /// Sets the order of the hash bytes returned by the verification functions, [`HashByteOrder::BigEndian`] by default.
+    ///
+    /// The order only affects the returned bytes, e.g. those constrained to the hash instances, and not the verification itself.
+    pub fn with_hash_byte_order(mut self, hash_byte_order: HashByteOrder) -> Self {
+        self.hash_byte_order = hash_byte_order;
+        self
+    }
+
+    /// Getter for [`HashByteOrder`].
+    pub fn hash_byte_order(&self) -> HashByteOrder {
+        self.hash_byte_order
+    }
+
+    /// Given a RSA public key, signed message bytes, and a pkcs1v15 signature, verifies the signature with the hash function selected by `hash_alg`.
     ///
     /// # Arguments
     /// * public_key - an assigned public key used for the verification.
     /// * msg - signed message bytes.
     /// * signature - a pkcs1v15 signature to be verified.
+    /// * hash_alg - the hash function of the signature, which selects the chip computing the hash, the digest width and the DigestInfo prefix.
     ///
     /// # Return values
-    /// Returns the assigned bit as `AssignedValue<F>`.
+    /// Returns [`Pkcs1v15Result`] holding the assigned validity bit, the assigned bytes of the message hash in the [`HashByteOrder`] of the verifier, the assigned limbs of the modulus of `public_key`, and the assigned bytes of `msg`.
     /// If `signature` is valid for `public_key` and `msg`, the bit is equivalent to one.
     /// Otherwise, the bit is equivalent to zero.
+    ///
+    /// The bytes of `msg` are the input bytes of the hash chip, so that the extractors can be bound to the verified message with [`delimiter::AssignedFields::constrain_signed_data`].
+    ///
+    /// The returned bytes are the very cells packed into the hash limbs consumed by [`RSAInstructions::verify_pkcs1v15_signature`], not copies of them.
+    /// Hence constraining them to instances exposes the hash whose signature is verified.
+    /// Likewise, the returned limbs are the cells of the modulus used in the verification, so that a caller can constrain them equal to a key committed elsewhere, e.g. in a key registry proof to be aggregated with this one.
+    ///
+    /// For a secure QR code, `msg` must be exactly [`qr::signed_region`] of the decompressed payload, i.e. everything but the trailing signature bytes.
+    ///
+    /// With [`HashAlg::Sha256`], returns [`Error::Synthesis`] if `msg` does not fit in the maximum the [`Sha256DynamicConfig`] is configured with, as [`RSASignatureVerifier::check_msg_len`] reports.
     pub fn verify_pkcs1v15_signature<'a, 'b: 'a>(
         &'a mut self,
         ctx: &mut Context<'b, F>,
         public_key: &AssignedRSAPublicKey<'b, F>,
         msg: &'a [u8],
         signature: &AssignedRSASignature<'b, F>,
-    ) -> Result<(AssignedValue<'b, F>, Vec<AssignedValue<'b, F>>), Error> {
+        hash_alg: HashAlg,
+    ) -> Result<Pkcs1v15Result<'b, F>, Error> {
+        let mut result = match hash_alg {
+            HashAlg::Sha256 => {
+                self.verify_pkcs1v15_signature_sha256(ctx, public_key, msg, signature)?
+            }
+            #[cfg(feature = "legacy-sha1")]
+            HashAlg::Sha1 => {
+                self.verify_pkcs1v15_signature_sha1(ctx, public_key, msg, signature)?
+            }
+        };
+        debug_assert_eq!(result.hashed_msg.len(), hash_alg.digest_len());
+        result.hashed_msg = self.hash_byte_order.order(&result.hashed_msg);
+        Ok(result)
+    }
+
+    /// Verifies a pkcs1v15 signature as [`RSASignatureVerifier::verify_pkcs1v15_signature`] with [`HashAlg::Sha256`].
+    fn verify_pkcs1v15_signature_sha256<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        msg: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+    ) -> Result<Pkcs1v15Result<'b, F>, Error> {
+        self.check_msg_len(msg.len())
+            .map_err(|_| Error::Synthesis)?;
         let sha256 = &mut self.sha256_config;
         let rsa = self.rsa_config.clone();
         let biguint = &rsa.biguint_config();
         let result = sha256.digest(ctx, msg, None)?;
-        let mut hashed_bytes = result.output_bytes;
-        hashed_bytes.reverse();
-        let bytes_bits = hashed_bytes.len() * 8;
-        let limb_bits = biguint.limb_bits();
-        let limb_bytes = limb_bits / 8;
-        let mut hashed_u64s = vec![];
-        let bases = (0..limb_bytes)
-            .map(|i| F::from((1u64 << (8 * i)) as u64))
-            .map(QuantumCell::Constant)
-            .collect::<Vec<QuantumCell<F>>>();
-        for i in 0..(bytes_bits / limb_bits) {
-            let left = hashed_bytes[limb_bytes * i..limb_bytes * (i + 1)]
+        let msg_bytes = result.input_bytes[..msg.len()].to_vec();
+        let hashed_bytes = result.output_bytes;
+        let hashed_u64s = biguint.pack_hash_limbs(ctx, &hashed_bytes)?;
+        let is_sign_valid =
+            rsa.verify_pkcs1v15_signature(ctx, public_key, &hashed_u64s, signature)?;
+
+        Ok(Pkcs1v15Result {
+            is_valid: is_sign_valid,
+            hashed_msg: hashed_bytes,
+            modulus_limbs: public_key.n.limbs().to_vec(),
+            msg: msg_bytes,
+            msg_len: result.input_len,
+            padded_msg: result.input_bytes,
+        })
+    }
+
+    /// Verifies a pkcs1v15 signature as [`RSASignatureVerifier::verify_pkcs1v15_signature`] with [`HashAlg::Sha1`].
+    ///
+    /// The hash is computed by [`sha1::Sha1Config`] over the range chip of [`RSAConfig`], so the [`Sha256DynamicConfig`] is left untouched.
+    #[cfg(feature = "legacy-sha1")]
+    fn verify_pkcs1v15_signature_sha1<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        public_key: &AssignedRSAPublicKey<'v, F>,
+        msg: &[u8],
+        signature: &AssignedRSASignature<'v, F>,
+    ) -> Result<Pkcs1v15Result<'v, F>, Error> {
+        let sha1 = sha1::Sha1Config::construct(self.rsa_config.range().clone());
+        let result = sha1.digest(ctx, msg);
+        let hashed_msg = result.output_bytes;
+        let is_valid = self.rsa_config.verify_pkcs1v15_signature_sha1(
+            ctx,
+            public_key,
+            &hashed_msg,
+            signature,
+        )?;
+        // The padding of SHA-1 is fixed by the message length, so no padded bytes are assigned beyond the message.
+        let msg_len = self
+            .rsa_config
+            .gate()
+            .load_constant(ctx, F::from(msg.len() as u64));
+        Ok(Pkcs1v15Result {
+            is_valid,
+            hashed_msg,
+            modulus_limbs: public_key.n.limbs().to_vec(),
+            msg: result.input_bytes.clone(),
+            msg_len,
+            padded_msg: result.input_bytes,
+        })
+    }
+
+    /// Verifies pkcs1v15 signatures of several messages, e.g. QR codes of the same issuer, with a single assigned public key.
+    ///
+    /// Each signature is verified as [`RSASignatureVerifier::verify_pkcs1v15_signature`], but the modulus is assigned and range-checked once by the caller instead of once per signature.
+    ///
+    /// # Requirements
+    /// The [`Sha256DynamicConfig`] must be configured with the maximum sizes of all messages in order.
+    ///
+    /// # Arguments
+    /// * public_key - an assigned public key shared by all the signatures.
+    /// * msgs - signed message bytes.
+    /// * signatures - pkcs1v15 signatures to be verified, one per message.
+    /// * hash_alg - the hash function of all the signatures.
+    ///
+    /// # Return values
+    /// Returns the result of each verification in the order of `msgs`, or [`Error::Synthesis`] if `msgs` and `signatures` differ in length.
+    pub fn verify_pkcs1v15_signatures<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        msgs: &[&'a [u8]],
+        signatures: &[&AssignedRSASignature<'b, F>],
+        hash_alg: HashAlg,
+    ) -> Result<Vec<Pkcs1v15Result<'b, F>>, Error> {
+        if msgs.len() != signatures.len() {
+            return Err(Error::Synthesis);
+        }
+        msgs.iter()
+            .zip(signatures)
+            .map(|(msg, signature)| {
+                self.verify_pkcs1v15_signature(ctx, public_key, msg, signature, hash_alg)
+            })
+            .collect()
+    }
+
+    /// Given two RSA public keys, signed messages, and pkcs1v15 signatures, verifies both signatures and checks whether they share the signer.
+    ///
+    /// # Requirements
+    /// The [`Sha256DynamicConfig`] must be configured with the maximum sizes of both messages in order.
+    ///
+    /// # Arguments
+    /// * public_keys - assigned public keys used for the verification of each signature.
+    /// * msgs - signed message bytes.
+    /// * signatures - pkcs1v15 signatures to be verified.
+    ///
+    /// # Return values
+    /// Returns the assigned bit that is one if both signatures are valid, the assigned bit `same_signer` that is one if both public keys have the same modulus, and the assigned bytes of each message hash.
+    pub fn verify_pkcs1v15_signatures_same_signer<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_keys: [&AssignedRSAPublicKey<'b, F>; 2],
+        msgs: [&'a [u8]; 2],
+        signatures: [&AssignedRSASignature<'b, F>; 2],
+    ) -> Result<
+        (
+            AssignedValue<'b, F>,
+            AssignedValue<'b, F>,
+            [Vec<AssignedValue<'b, F>>; 2],
+        ),
+        Error,
+    > {
+        let result0 = self.verify_pkcs1v15_signature(
+            ctx,
+            public_keys[0],
+            msgs[0],
+            signatures[0],
+            HashAlg::Sha256,
+        )?;
+        let result1 = self.verify_pkcs1v15_signature(
+            ctx,
+            public_keys[1],
+            msgs[1],
+            signatures[1],
+            HashAlg::Sha256,
+        )?;
+        let rsa = self.rsa_config.clone();
+        let is_valid = rsa.gate().and(
+            ctx,
+            QuantumCell::Existing(&result0.is_valid),
+            QuantumCell::Existing(&result1.is_valid),
+        );
+        let same_signer =
+            rsa.biguint_config()
+                .is_equal_fresh(ctx, &public_keys[0].n, &public_keys[1].n)?;
+        Ok((
+            is_valid,
+            same_signer,
+            [result0.hashed_msg, result1.hashed_msg],
+        ))
+    }
+
+    /// Verifies the pkcs1v15 signature of a timestamp oracle over the current time, so that the time compared against the QR data is attested rather than chosen by the prover.
+    ///
+    /// The oracle signs the ASCII digits `YYYYMMDDhh`, which are parsed from the very bytes input to the hash by [`field_extractor::FieldExtractorChip::parse_time`].
+    /// The time is only trusted if the returned validity bit is constrained to one and the oracle modulus is constrained to a published key, e.g. an instance.
+    ///
+    /// # Requirements
+    /// The [`Sha256DynamicConfig`] must be configured with the maximum size of `time_msg` at the position of this verification among the others.
+    ///
+    /// # Arguments
+    /// * oracle_key - the assigned public key of the oracle, a second signer besides the UIDAI key.
+    /// * time_msg - the [`field_extractor::TIME_LEN`] bytes signed by the oracle.
+    /// * signature - the pkcs1v15 signature of the oracle.
+    /// * hash_alg - the hash function of the signature.
+    ///
+    /// # Return values
+    /// Returns the [`OracleTime`], or [`Error::Synthesis`] if `time_msg` is not [`field_extractor::TIME_LEN`] bytes long.
+    pub fn verify_oracle_time<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        oracle_key: &AssignedRSAPublicKey<'b, F>,
+        time_msg: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+        hash_alg: HashAlg,
+    ) -> Result<OracleTime<'b, F>, Error> {
+        if time_msg.len() != field_extractor::TIME_LEN {
+            return Err(Error::Synthesis);
+        }
+        let signature =
+            self.verify_pkcs1v15_signature(ctx, oracle_key, time_msg, signature, hash_alg)?;
+        let chip = field_extractor::FieldExtractorChip::construct(self.rsa_config.range().clone());
+        let time = chip.parse_time(ctx, &signature.msg)?;
+        Ok(OracleTime { signature, time })
+    }
+
+    /// Returns the assigned bit that is one if the signature of `result` is valid and every bit of `others` is one.
+    ///
+    /// The validity bit is not asserted by the verification, so a composed circuit can AND it with the validity bits of its other subcircuits, e.g. the identity constraints or the freshness of [`field_extractor::FieldExtractorChip::is_before`], and expose the result as one overall `proof_valid` instance.
+    ///
+    /// # Requirements
+    /// The values of `others` must be constrained to be bits by the subcircuits computing them.
+    pub fn all_valid<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        result: &Pkcs1v15Result<'v, F>,
+        others: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        let gate = self.rsa_config.gate();
+        let mut is_valid = result.is_valid.clone();
+        for other in others {
+            is_valid = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_valid),
+                QuantumCell::Existing(other),
+            );
+        }
+        is_valid
+    }
+
+    /// Commits to assigned bytes by their SHA256 digest, e.g. to bind `qr_data_padded` without revealing it.
+    ///
+    /// The digest is computed by the same [`Sha256DynamicConfig`] as the message hash, but separately from it, so that a verifier can later open specific bytes with a second proof against the commitment.
+    ///
+    /// # Requirements
+    /// The [`Sha256DynamicConfig`] must be configured with the maximum size of `bytes` at the position of this digest among the others.
+    ///
+    /// # Arguments
+    /// * bytes - the assigned bytes to be committed, constrained to 8 bits by the caller.
+    ///
+    /// # Return values
+    /// Returns the assigned bytes of the commitment, whose input bytes in the SHA256 chip are constrained to equal `bytes`.
+    pub fn commit_bytes<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        bytes: &[AssignedValue<'b, F>],
+    ) -> Result<Vec<AssignedValue<'b, F>>, Error> {
+        let sha256 = &mut self.sha256_config;
+        let gate = self.rsa_config.gate();
+        let values = Self::byte_values(bytes);
+        let result = sha256.digest(ctx, &values, None)?;
+        gate.assert_is_const(ctx, &result.input_len, F::from(bytes.len() as u64));
+        for (assigned, byte) in result.input_bytes.iter().zip(bytes.iter()) {
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(assigned),
+                QuantumCell::Existing(byte),
+            );
+        }
+        Ok(result.output_bytes)
+    }
+
+    /// Returns the maximum input sizes to configure [`Sha256DynamicConfig`] with for [`RSASignatureVerifier::verify_pss_signature`].
+    ///
+    /// The SHA256 chip hashes the message first, then each block of MGF1, and finally the encoded message `M'`.
+    ///
+    /// # Arguments
+    /// * msg_len - the maximum byte size of the (padded) signed message.
+    /// * bits_len - the bit length of the RSA modulus.
+    pub fn pss_sha256_max_byte_sizes(msg_len: usize, bits_len: usize) -> Vec<usize> {
+        let db_len = bits_len / 8 - PSS_HASH_LEN - 1;
+        let num_mgf1_blocks = (db_len + PSS_HASH_LEN - 1) / PSS_HASH_LEN;
+        let mut sizes = vec![msg_len];
+        // `H || counter` fits in a single block.
+        sizes.extend(vec![64; num_mgf1_blocks]);
+        // `0x00 * 8 || mHash || salt` takes two blocks.
+        sizes.push(128);
+        sizes
+    }
+
+    /// Given a RSA public key, signed message bytes, and a RSASSA-PSS signature, verifies the signature with SHA256 hash function.
+    ///
+    /// The encoded message is checked as in EMSA-PSS-VERIFY of [RFC 8017](https://www.rfc-editor.org/rfc/rfc8017#section-9.1.2), with MGF1 over SHA256 and a salt as long as the hash, the defaults of [`rsa::pss::SigningKey`].
+    /// The bit length of the modulus must be a multiple of 8, so only the leftmost bit of the encoded message is cleared.
+    ///
+    /// # Requirements
+    /// The [`Sha256DynamicConfig`] must be configured with [`RSASignatureVerifier::pss_sha256_max_byte_sizes`], and no digest must be computed with it before this function.
+    ///
+    /// # Arguments
+    /// * public_key - an assigned public key used for the verification.
+    /// * msg - signed message bytes.
+    /// * signature - a RSASSA-PSS signature to be verified.
+    ///
+    /// # Return values
+    /// Returns the assigned bit as `AssignedValue<F>` and the assigned bytes of the message hash in the [`HashByteOrder`] of the verifier.
+    /// If `signature` is valid for `public_key` and `msg`, the bit is equivalent to one.
+    /// Otherwise, the bit is equivalent to zero.
+    pub fn verify_pss_signature<'a, 'b: 'a>(
+        &'a mut self,
+        ctx: &mut Context<'b, F>,
+        public_key: &AssignedRSAPublicKey<'b, F>,
+        msg: &'a [u8],
+        signature: &AssignedRSASignature<'b, F>,
+    ) -> Result<(AssignedValue<'b, F>, Vec<AssignedValue<'b, F>>), Error> {
+        let sha256 = &mut self.sha256_config;
+        let rsa = self.rsa_config.clone();
+        let biguint = rsa.biguint_config();
+        let gate = rsa.gate();
+        let hashed_msg = sha256.digest(ctx, msg, None)?.output_bytes;
+        let powed = rsa.modpow_public_key(ctx, &signature.c, public_key)?;
+        // EM = maskedDB || H || 0xbc in big-endian order.
+        let mut em = biguint.decompose_bytes(ctx, &powed)?;
+        em.reverse();
+        let db_len = em.len() - PSS_HASH_LEN - 1;
+        let (masked_db, rest) = em.split_at(db_len);
+        let (h, trailer) = rest.split_at(PSS_HASH_LEN);
+
+        // 1. Check the trailer field.
+        let mut is_valid = gate.is_equal(
+            ctx,
+            QuantumCell::Existing(&trailer[0]),
+            QuantumCell::Constant(F::from(0xbc)),
+        );
+
+        // 2. Generate dbMask = MGF1(H) block by block.
+        let h_values = Self::byte_values(h);
+        let mut db_mask = vec![];
+        let mut counter = 0u32;
+        while db_mask.len() < db_len {
+            let counter_bytes = counter.to_be_bytes();
+            let input = [h_values.as_slice(), &counter_bytes].concat();
+            let result = sha256.digest(ctx, &input, None)?;
+            gate.assert_is_const(ctx, &result.input_len, F::from(input.len() as u64));
+            for (assigned, h_byte) in result.input_bytes.iter().zip(h.iter()) {
+                gate.assert_equal(
+                    ctx,
+                    QuantumCell::Existing(assigned),
+                    QuantumCell::Existing(h_byte),
+                );
+            }
+            for (assigned, byte) in result.input_bytes[PSS_HASH_LEN..]
+                .iter()
+                .zip(counter_bytes.iter())
+            {
+                gate.assert_is_const(ctx, assigned, F::from(*byte as u64));
+            }
+            db_mask.extend(result.output_bytes);
+            counter += 1;
+        }
+
+        // 3. Unmask DB = maskedDB xor dbMask, whose leftmost bit is cleared.
+        let mut db = Vec::with_capacity(db_len);
+        for (i, (masked, mask)) in masked_db.iter().zip(db_mask.iter()).enumerate() {
+            let masked_bits = gate.num_to_bits(ctx, masked, 8);
+            let mask_bits = gate.num_to_bits(ctx, mask, 8);
+            let mut bits = masked_bits
+                .iter()
+                .zip(mask_bits.iter())
+                .map(|(a, b)| Self::xor_bit(gate, ctx, a, b))
+                .collect::<Vec<AssignedValue<F>>>();
+            if i == 0 {
+                // The leftmost bit of EM must be zero.
+                let is_top_zero = gate.is_zero(ctx, &masked_bits[7]);
+                is_valid = gate.and(
+                    ctx,
+                    QuantumCell::Existing(&is_valid),
+                    QuantumCell::Existing(&is_top_zero),
+                );
+                bits.pop();
+            }
+            let bases = (0..bits.len())
+                .map(|j| QuantumCell::Constant(F::from(1u64 << j)))
+                .collect::<Vec<QuantumCell<F>>>();
+            let bits = bits
                 .iter()
                 .map(QuantumCell::Existing)
                 .collect::<Vec<QuantumCell<F>>>();
-            let sum = biguint.gate().inner_product(ctx, left, bases.clone());
-            hashed_u64s.push(sum);
+            db.push(gate.inner_product(ctx, bits, bases));
         }
-        let is_sign_valid =
-            rsa.verify_pkcs1v15_signature(ctx, public_key, &hashed_u64s, signature)?;
 
-        hashed_bytes.reverse();
-        Ok((is_sign_valid, hashed_bytes))
+        // 4. Check DB = PS || 0x01 || salt, where PS is zeros.
+        let ps_len = db_len - PSS_SALT_LEN - 1;
+        for byte in db[..ps_len].iter() {
+            let is_ps_zero = gate.is_zero(ctx, byte);
+            is_valid = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_valid),
+                QuantumCell::Existing(&is_ps_zero),
+            );
+        }
+        let is_separator_eq = gate.is_equal(
+            ctx,
+            QuantumCell::Existing(&db[ps_len]),
+            QuantumCell::Constant(F::one()),
+        );
+        let mut is_valid = gate.and(
+            ctx,
+            QuantumCell::Existing(&is_valid),
+            QuantumCell::Existing(&is_separator_eq),
+        );
+        let salt = &db[ps_len + 1..];
+
+        // 5. Check H = SHA256(0x00 * 8 || mHash || salt).
+        let m_prime = [
+            vec![0u8; 8],
+            Self::byte_values(&hashed_msg),
+            Self::byte_values(salt),
+        ]
+        .concat();
+        let result = sha256.digest(ctx, &m_prime, None)?;
+        gate.assert_is_const(ctx, &result.input_len, F::from(m_prime.len() as u64));
+        for assigned in result.input_bytes[..8].iter() {
+            gate.assert_is_const(ctx, assigned, F::zero());
+        }
+        for (assigned, byte) in result.input_bytes[8..m_prime.len()]
+            .iter()
+            .zip(hashed_msg.iter().chain(salt.iter()))
+        {
+            gate.assert_equal(
+                ctx,
+                QuantumCell::Existing(assigned),
+                QuantumCell::Existing(byte),
+            );
+        }
+        for (h_prime, h_byte) in result.output_bytes.iter().zip(h.iter()) {
+            let is_h_eq = gate.is_equal(
+                ctx,
+                QuantumCell::Existing(h_prime),
+                QuantumCell::Existing(h_byte),
+            );
+            is_valid = gate.and(
+                ctx,
+                QuantumCell::Existing(&is_valid),
+                QuantumCell::Existing(&is_h_eq),
+            );
+        }
+        Ok((is_valid, self.hash_byte_order.order(&hashed_msg)))
+    }
+
+    /// Returns `a xor b` for assigned bits `a` and `b`.
+    fn xor_bit<'v>(
+        gate: &FlexGateConfig<F>,
+        ctx: &mut Context<'v, F>,
+        a: &AssignedValue<'v, F>,
+        b: &AssignedValue<'v, F>,
+    ) -> AssignedValue<'v, F> {
+        let sum = gate.add(ctx, QuantumCell::Existing(a), QuantumCell::Existing(b));
+        let prod = gate.mul(ctx, QuantumCell::Existing(a), QuantumCell::Existing(b));
+        gate.mul_add(
+            ctx,
+            QuantumCell::Existing(&prod),
+            QuantumCell::Constant(-F::from(2)),
+            QuantumCell::Existing(&sum),
+        )
     }
+
+    /// Returns the values of assigned bytes, which are zeros if unknown.
+    ///
+    /// The values are fed back into the SHA256 chip, whose input bytes must be constrained to equal the assigned ones.
+    fn byte_values(bytes: &[AssignedValue<'_, F>]) -> Vec<u8> {
+        bytes
+            .iter()
+            .map(|byte| {
+                let mut value = 0;
+                byte.value().map(|v| value = fe_to_biguint(v).to_bytes_le()[0]);
+                value
+            })
+            .collect()
+    }
+}
+
+/// The byte length of SHA256 digests.
+#[cfg(feature = "sha256")]
+const PSS_HASH_LEN: usize = 32;
+/// The byte length of salts in RSASSA-PSS signatures, the same as the hash.
+#[cfg(feature = "sha256")]
+const PSS_SALT_LEN: usize = PSS_HASH_LEN;
+
+/// The bit length of the exponent limbs of the [`RSAConfig`] configured by [`configure_rsa_sha256`].
+#[cfg(feature = "sha256")]
+const RSA_EXP_LIMB_BITS: usize = 5;
+
+/// Configures the chips of a RSA-SHA256 verification circuit with the columns, lookup bits and degree of the [`Preset`] type `C`.
+///
+/// The [`RSAConfig`] and the [`Sha256DynamicConfig`] share one range chip, so a circuit loads its lookup table once besides that of the SHA256 chip.
+///
+/// # Arguments
+/// * meta - a constraint system of the circuit.
+/// * msg_lens - the maximum byte sizes of the digests in order, e.g. `vec![C::MSG_LEN]` for one signature or [`RSASignatureVerifier::pss_sha256_max_byte_sizes`] for a RSASSA-PSS signature.
+///
+/// # Return values
+/// Returns the [`RSAConfig`] for `C::BITS_LEN`-bit moduli and the [`Sha256DynamicConfig`], as taken by [`RSASignatureVerifier::new`].
+#[cfg(feature = "sha256")]
+pub fn configure_rsa_sha256<F: PrimeField, C: Preset>(
+    meta: &mut ConstraintSystem<F>,
+    msg_lens: Vec<usize>,
+) -> (RSAConfig<F>, Sha256DynamicConfig<F>) {
+    let range_config = RangeConfig::configure(
+        meta,
+        Vertical,
+        &[C::NUM_ADVICE],
+        &[C::NUM_LOOKUP_ADVICE],
+        C::NUM_FIXED,
+        C::Range::LOOKUP_BITS,
+        0,
+        C::K as usize,
+    );
+    let bigint_config = BigUintConfig::construct(range_config.clone(), 64);
+    let rsa_config = RSAConfig::construct(bigint_config, C::BITS_LEN, RSA_EXP_LIMB_BITS)
+        .expect("RSA_EXP_LIMB_BITS fits in the field");
+    let sha256_config = Sha256DynamicConfig::configure(
+        meta,
+        msg_lens,
+        range_config,
+        C::Sha256::LOOKUP_BITS,
+        C::Sha256::NUM_LOOKUP_ADVICE,
+        true,
+    );
+    (rsa_config, sha256_config)
 }
 
 #[derive(Debug, Clone)]
@@ -252,203 +899,3112 @@ struct TestRSASignatureWithHashConfig1<F: PrimeField> {
     n_instance: Column<Instance>,
     hash_instance: Column<Instance>,
 }
+/// Cost of a circuit measured by assigning its witnesses without proving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostReport {
+    /// The number of assigned advice cells.
+    pub advice_cells: usize,
+    /// The number of assigned cells in the advice columns queried by lookups.
+    pub lookup_cells: usize,
+    /// The maximum number of rows used by a fixed column, e.g. a lookup table.
+    pub fixed_rows: usize,
+    /// The number of rows used by the layout of the circuit, across all of its columns.
+    pub rows: usize,
+    /// The smallest degree whose usable rows hold the above rows.
+    pub min_k: u32,
+}
+
+/// Estimates the cost of `circuit` by assigning its witnesses without checking the constraints.
+///
+/// Unlike [`MockProver`](halo2_base::halo2_proofs::dev::MockProver), the assignment does not take a degree, so the estimate also covers a circuit too large for the degree it is meant for.
+/// The layout of the chips of this crate depends on the degree they are configured with, e.g. [`Preset::K`], so `min_k` is the degree the circuit can be proven in as configured.
+///
+/// # Return values
+/// Returns the [`CostReport`] of `circuit`, or the error of its synthesis.
+pub fn estimate_cost<F: PrimeField, C: Circuit<F>>(circuit: &C) -> Result<CostReport, Error> {
+    let (run, meta) = DryRun::of(circuit)?;
+    Ok(CostReport {
+        advice_cells: run.advice_cells.len(),
+        lookup_cells: run.lookup_cells(&meta),
+        fixed_rows: run.fixed_rows.values().copied().max().unwrap_or(0),
+        rows: run.rows,
+        min_k: run.min_k(&meta),
+    })
+}
+
+/// Describes the layout of `circuit`, one `name: count` line per item of its [`CircuitShape`] followed by its [`CostReport`].
+///
+/// # Return values
+/// Returns the description, or the error of the synthesis of [`estimate_cost`].
+pub fn describe_layout<F: PrimeField, C: Circuit<F>>(circuit: &C) -> Result<String, Error> {
+    let shape = CircuitShape::of::<F, C>();
+    let cost = estimate_cost(circuit)?;
+    Ok([
+        format!("advice columns: {}", shape.num_advice_columns),
+        format!("fixed columns: {}", shape.num_fixed_columns),
+        format!("instance columns: {}", shape.num_instance_columns),
+        format!("selectors: {}", shape.num_selectors),
+        format!("gates: {}", shape.num_gates),
+        format!("lookup arguments: {}", shape.num_lookups),
+        format!("degree: {}", shape.degree),
+        format!("advice cells: {}", cost.advice_cells),
+        format!("lookup cells: {}", cost.lookup_cells),
+        format!("fixed rows: {}", cost.fixed_rows),
+        format!("rows: {}", cost.rows),
+        format!("min_k: {}", cost.min_k),
+    ]
+    .join("\n"))
+}
+
+/// RSA-SHA256 verification circuit whose columns, chips and degree are set by the [`Preset`] type `C`.
 #[derive(Debug, Clone)]
-struct TestRSASignatureWithHashCircuit1<F: PrimeField> {
+struct TestRSASignatureWithHashCircuit<F: PrimeField, C: Preset = Mainnet2048> {
     private_key: RsaPrivateKey,
     public_key: RsaPublicKey,
-    msg: Vec<u8>,
-    _f: PhantomData<F>,
+    /// The signed message, zeroized on drop.
+    msg: Zeroizing<Vec<u8>>,
+    /// The big-endian signature bytes, or `None` to sign `msg` with `private_key`, zeroized on drop.
+    signature: Option<Zeroizing<Vec<u8>>>,
+    /// The expected value of the validity bit output by the verification.
+    expect_valid: bool,
+    _f: PhantomData<(F, C)>,
+}
+
+/// Zeroizes the message and the signature, e.g. the signed QR data of a resident.
+///
+/// Both are also zeroized on drop, and the private key by its own [`Drop`] implementation.
+impl<F: PrimeField, C: Preset> Zeroize for TestRSASignatureWithHashCircuit<F, C> {
+    fn zeroize(&mut self) {
+        self.msg.zeroize();
+        self.signature.zeroize();
+    }
+}
+
+impl<F: PrimeField, C: Preset> ZeroizeOnDrop for TestRSASignatureWithHashCircuit<F, C> {}
+
+/// [`TestRSASignatureWithHashCircuit`] with the [`Mainnet2048`] preset.
+type TestRSASignatureWithHashCircuit1<F> = TestRSASignatureWithHashCircuit<F, Mainnet2048>;
+
+impl<F: PrimeField, C: Preset> TestRSASignatureWithHashCircuit<F, C> {
+    const K: u32 = C::K;
+    const BITS_LEN: usize = C::BITS_LEN;
+    const MSG_LEN: usize = C::MSG_LEN;
+    const DEFAULT_E: u128 = 65537;
+    const NUM_ADVICE: usize = C::NUM_ADVICE;
+    const NUM_FIXED: usize = C::NUM_FIXED;
+    const NUM_LOOKUP_ADVICE: usize = C::NUM_LOOKUP_ADVICE;
+}
+
+impl<F: PrimeField, C: Preset> TestRSASignatureWithHashCircuit<F, C> {
+    pub fn new(private_key: RsaPrivateKey, public_key: RsaPublicKey, msg: Vec<u8>) -> Self {
+        Self {
+            private_key,
+            public_key,
+            msg: Zeroizing::new(msg),
+            signature: None,
+            expect_valid: true,
+            _f: PhantomData,
+        }
+    }
+
+    /// Creates a circuit as [`TestRSASignatureWithHashCircuit::new`] after checking that its parameters fit in `2^K` rows and that `msg` fits in [`Preset::MSG_LEN`] bytes.
+    ///
+    /// A lookup table larger than the circuit otherwise only fails in the key generation or the proving, and a longer message in the synthesis.
+    pub fn try_new(
+        private_key: RsaPrivateKey,
+        public_key: RsaPublicKey,
+        msg: Vec<u8>,
+    ) -> Result<Self, ParamsError> {
+        Self::validate(Self::K)?;
+        validate_msg_len(msg.len(), Self::MSG_LEN)?;
+        Ok(Self::new(private_key, public_key, msg))
+    }
+
+    /// Creates a circuit whose signature has its least significant byte corrupted.
+    ///
+    /// The circuit is satisfied only if the verification outputs the validity bit equal to zero, so negative cases can be tested without making the circuit unsatisfiable.
+    pub fn new_invalid(private_key: RsaPrivateKey, public_key: RsaPublicKey, msg: Vec<u8>) -> Self {
+        let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone());
+        let mut signature = signing_key.sign(&msg).to_vec();
+        // Corrupting the last byte keeps the signature smaller than the modulus.
+        *signature.last_mut().unwrap() ^= 1;
+        Self {
+            private_key,
+            public_key,
+            msg: Zeroizing::new(msg),
+            signature: Some(Zeroizing::new(signature)),
+            expect_valid: false,
+            _f: PhantomData,
+        }
+    }
+
+    /// Returns the public inputs of this circuit, i.e. the limbs of the modulus and the bytes of the message hash.
+    fn instances(&self) -> Vec<Vec<F>> {
+        let n = BigUint::from_radix_le(&self.public_key.n().to_radix_le(16), 16).unwrap();
+        let n_fes = decompose_biguint::<F>(&n, Self::BITS_LEN / 64, 64);
+        let hash_fes = rsa::sha2::Sha256::digest(self.msg.as_slice())
+            .iter()
+            .map(|byte| F::from(*byte as u64))
+            .collect::<Vec<F>>();
+        vec![n_fes, hash_fes]
+    }
+
+    /// Checks that the SHA256 and range parameters of the preset fit in a circuit of `2^k` rows.
+    pub fn validate(k: u32) -> Result<(), ParamsError> {
+        C::Sha256::validate(k)?;
+        C::Range::validate(k)
+    }
 }
 
-impl<F: PrimeField> TestRSASignatureWithHashCircuit1<F> {
-    const BITS_LEN: usize = 2048;
-    const MSG_LEN: usize = 1024;
-    const EXP_LIMB_BITS: usize = 5;
-    const DEFAULT_E: u128 = 65537;
-    const NUM_ADVICE: usize = 80;
-    const NUM_FIXED: usize = 1;
-    const NUM_LOOKUP_ADVICE: usize = 16;
-    const LOOKUP_BITS: usize = 12;
-    const SHA256_LOOKUP_BITS: usize = 8;
-    const SHA256_LOOKUP_ADVICE: usize = 8;
-}
+impl<F: PrimeField, C: Preset> Circuit<F> for TestRSASignatureWithHashCircuit<F, C> {
+    type Config = TestRSASignatureWithHashConfig1<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        unimplemented!();
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let (rsa_config, sha256_config) = configure_rsa_sha256::<F, C>(meta, vec![Self::MSG_LEN]);
+        let n_instance = meta.instance_column();
+        let hash_instance = meta.instance_column();
+        meta.enable_equality(n_instance);
+        meta.enable_equality(hash_instance);
+        Self::Config {
+            rsa_config,
+            sha256_config,
+            n_instance,
+            hash_instance,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let biguint_config = config.rsa_config.biguint_config();
+        config.sha256_config.load(&mut layouter)?;
+        biguint_config.range().load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        let (public_key_cells, hashed_msg_cells) = layouter.assign_region(
+            || "random rsa modpow test with 2048 bits public keys",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok((vec![], vec![]));
+                }
+
+                let mut aux = biguint_config.new_context(region);
+                let ctx = &mut aux;
+                let sign = match &self.signature {
+                    Some(signature) => signature.to_vec(),
+                    None => {
+                        let signing_key =
+                            SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
+                        signing_key.sign(&self.msg).to_vec()
+                    }
+                };
+                let sign_big = BigUint::from_bytes_be(&sign);
+                let sign = config
+                    .rsa_config
+                    .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                let n_big =
+                    BigUint::from_radix_le(&self.public_key.n().clone().to_radix_le(16), 16)
+                        .unwrap();
+                let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                let public_key = config
+                    .rsa_config
+                    .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                let mut verifier = RSASignatureVerifier::new(
+                    config.rsa_config.clone(),
+                    config.sha256_config.clone(),
+                );
+                let result = verifier.verify_pkcs1v15_signature(
+                    ctx,
+                    &public_key,
+                    &self.msg,
+                    &sign,
+                    HashAlg::Sha256,
+                )?;
+                biguint_config.gate().assert_is_const(
+                    ctx,
+                    &result.is_valid,
+                    F::from(self.expect_valid as u64),
+                );
+                biguint_config.range().finalize(ctx);
+                let public_key_cells = result
+                    .modulus_limbs
+                    .into_iter()
+                    .map(|v| v.cell())
+                    .collect::<Vec<Cell>>();
+                let hashed_msg_cells = result
+                    .hashed_msg
+                    .into_iter()
+                    .map(|v| v.cell())
+                    .collect::<Vec<Cell>>();
+                Ok((public_key_cells, hashed_msg_cells))
+            },
+        )?;
+        for (i, cell) in public_key_cells.into_iter().enumerate() {
+            layouter.constrain_instance(cell, config.n_instance, i)?;
+        }
+        for (i, cell) in hashed_msg_cells.into_iter().enumerate() {
+            layouter.constrain_instance(cell, config.hash_instance, i)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sha256")]
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::age::is_adult;
+    use crate::big_uint::decompose_biguint;
+    use crate::comparison::ComparisonConfig;
+    use crate::conditional_secrets::IdentityCircuit;
+    use crate::delimiter::{field_range, field_start, DelimiterCircuit, DELIMITER, NUM_DELIMITERS};
+    use crate::params::{DefaultRangeParams, DefaultSha256Params, Testing, Tuned};
+    use crate::qr_data_extractor::DEFAULT_MAX_DATA_LEN;
+    use crate::signal::SquareCircuit;
+    use crate::timestamp::TimestampCircuit;
+    use halo2_base::gates::RangeInstructions;
+    use halo2_base::halo2_proofs::halo2curves::pasta::Fp;
+    use halo2_base::halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use num_bigint::RandBigInt;
+    use rand::{thread_rng, Rng};
+    use rsa::{signature::RandomizedSigner, traits::PublicKeyParts, RsaPrivateKey, RsaPublicKey};
+    use sha2::{Digest, Sha256};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    type TestGadget<F> = Rc<dyn for<'v> Fn(&RSAConfig<F>, &mut Context<'v, F>) -> Result<(), Error>>;
+
+    /// A circuit running a gadget over [`RSAConfig`] in a single region, for testing the chips directly.
+    #[derive(Clone)]
+    struct TestRSAConfigCircuit<F: PrimeField> {
+        gadget: TestGadget<F>,
+    }
+
+    impl<F: PrimeField> TestRSAConfigCircuit<F> {
+        const BITS_LEN: usize = 2048;
+        const EXP_LIMB_BITS: usize = 5;
+        const NUM_ADVICE: usize = 80;
+        const NUM_FIXED: usize = 1;
+        const NUM_LOOKUP_ADVICE: usize = 16;
+        const LOOKUP_BITS: usize = 12;
+        const K: u32 = 15;
+
+        fn new(gadget: TestGadget<F>) -> Self {
+            Self { gadget }
+        }
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestRSAConfigCircuit<F> {
+        type Config = RSAConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let range_config = RangeConfig::configure(
+                meta,
+                Vertical,
+                &[Self::NUM_ADVICE],
+                &[Self::NUM_LOOKUP_ADVICE],
+                Self::NUM_FIXED,
+                Self::LOOKUP_BITS,
+                0,
+                Self::K as usize,
+            );
+            let bigint_config = BigUintConfig::construct(range_config, 64);
+            RSAConfig::construct(bigint_config, Self::BITS_LEN, Self::EXP_LIMB_BITS).unwrap()
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa config gadget test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    (self.gadget)(&config, ctx)?;
+                    config.range().finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn random_modulus(bits_len: usize) -> BigUint {
+        let mut rng = thread_rng();
+        let private_key = RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap()
+    }
+
+    fn assign_modulus_circuit<F: PrimeField>(n: BigUint) -> TestRSAConfigCircuit<F> {
+        TestRSAConfigCircuit::new(Rc::new(move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+            let e_fix = RSAPubE::Fix(BigUint::from(65537u64));
+            config.assign_public_key(ctx, RSAPublicKey::new(Value::known(n.clone()), e_fix))?;
+            Ok(())
+        }))
+    }
+
+    #[test]
+    fn test_assign_public_key_rejects_non_reduced_modulus() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let n = random_modulus(TestRSAConfigCircuit::<F>::BITS_LEN);
+            let prover = MockProver::run(k, &assign_modulus_circuit::<F>(n.clone()), vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // A spurious limb above the 2048-bit width is rejected during assignment.
+            let spurious = &n + (BigUint::from(1u64) << TestRSAConfigCircuit::<F>::BITS_LEN);
+            assert!(MockProver::run(k, &assign_modulus_circuit::<F>(spurious), vec![]).is_err());
+
+            // A modulus shorter than 2048 bits leaves the top limb zero.
+            let short = &n >> 64;
+            let prover = MockProver::run(k, &assign_modulus_circuit::<F>(short), vec![]).unwrap();
+            assert!(prover.verify().is_err());
+
+            // A 2047-bit modulus has a nonzero top limb, but not its top bit.
+            let short = &n >> 1;
+            assert_eq!(short.bits(), TestRSAConfigCircuit::<F>::BITS_LEN as u64 - 1);
+            let prover = MockProver::run(k, &assign_modulus_circuit::<F>(short), vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_public_key_from_rsa_key() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let mut rng = thread_rng();
+            let private_key = RsaPrivateKey::new(&mut rng, TestRSAConfigCircuit::<F>::BITS_LEN)
+                .expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let circuit = TestRSAConfigCircuit::new(Rc::new(
+                move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                    let converted =
+                        config.assign_public_key(ctx, RSAPublicKey::from(&public_key))?;
+                    let n = BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap();
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u64));
+                    let manual =
+                        config.assign_public_key(ctx, RSAPublicKey::new(Value::known(n), e_fix))?;
+                    assert_eq!(converted.n.num_limbs(), manual.n.num_limbs());
+                    for (a, b) in converted.n.limbs().iter().zip(manual.n.limbs()) {
+                        config.gate().assert_equal(
+                            ctx,
+                            QuantumCell::Existing(a),
+                            QuantumCell::Existing(b),
+                        );
+                    }
+                    match converted.e {
+                        AssignedRSAPubE::Fix(e) => assert_eq!(e, BigUint::from(65537u64)),
+                        AssignedRSAPubE::Var(_) => panic!("the exponent should be fixed"),
+                    }
+                    Ok(())
+                },
+            ));
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_signature_base64() {
+        fn run<F: PrimeField>() {
+            // A 2048-bit signature with a leading zero byte, so its integer has fewer bytes than the modulus.
+            let known = concat!(
+                "ADBVep/E6Q4zWH2ix+wRNluApcrvFDleg6jN8hc8YYar0PUaP2SJrtP4HUJnjLHW+yBFao+02f4j",
+                "SG2St9wBJktwlbrfBClOc5i94gcsUXabwOUKL1R5nsPoDTJXfKHG6xA1Wn+kye4TOF2Cp8zxFjtg",
+                "harP9Bk+Y4it0vccQWaLsNX6H0RpjrPY/SJHbJG22wAlSm+Uud4DKE1yl7zhBitQdZq/5AkuU3id",
+                "wucMMVZ7oMXqDzRZfqPI7RI3XIGmy/AVOl+Eqc7zGD1ih6zR9htAZYqv1PkeQ2iNstf8IUZrkLXa",
+                "/yRJbpO43QInTHGWu+AFKk90mb7jCC1Sd5zB5g==",
+            );
+            let mut bytes = (0..256)
+                .map(|i| ((i * 37 + 11) % 256) as u8)
+                .collect::<Vec<u8>>();
+            bytes[0] = 0;
+            let c = BigUint::from_bytes_be(&bytes);
+            let signature = RSASignature::<F>::from_base64(known).unwrap();
+            signature.c.assert_if_known(|decoded| *decoded == c);
+            assert_eq!(RSASignature::<F>::encode_base64(&c, 2048), known);
+
+            // A signature produced by the rsa crate round-trips as well.
+            let mut rng = thread_rng();
+            let private_key = RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+            let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let signature = SigningKey::<rsa::sha2::Sha256>::new(private_key)
+                .sign(&msg)
+                .to_vec();
+            let c = BigUint::from_bytes_be(&signature);
+            let encoded = RSASignature::<F>::encode_base64(&c, 2048);
+            assert_eq!(Base64::decode_vec(&encoded).unwrap(), signature);
+            RSASignature::<F>::from_base64(&encoded)
+                .unwrap()
+                .c
+                .assert_if_known(|decoded| *decoded == c);
+
+            assert!(RSASignature::<F>::from_base64("not base64!").is_err());
+        }
+        run::<Fr>();
+    }
+
+    /// A circuit computing `c^e mod n` and exposing the exponent returned by [`RSAConfig::assign_exponent`] as an instance.
+    #[derive(Debug, Clone)]
+    struct TestRSAExponentInstanceCircuit<F: PrimeField> {
+        n: BigUint,
+        e: BigUint,
+        /// Whether `e` is assigned as [`RSAPubE::Var`] instead of [`RSAPubE::Fix`].
+        is_var: bool,
+        c: BigUint,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestRSAExponentInstanceCircuit<F> {
+        type Config = (RSAConfig<F>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let rsa_config = TestRSAConfigCircuit::<F>::configure(meta);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (rsa_config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let (config, instance) = config;
+            config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let exponent_cell = layouter.assign_region(
+                || "rsa exponent instance test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(None);
+                    }
+                    let mut aux = config.new_context(region);
+                    let ctx = &mut aux;
+                    let e = if self.is_var {
+                        RSAPubE::Var(Value::known(self.e.clone()))
+                    } else {
+                        RSAPubE::Fix(self.e.clone())
+                    };
+                    let public_key = config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(self.n.clone()), e))?;
+                    let c = config.biguint_config().assign_integer(
+                        ctx,
+                        Value::known(self.c.clone()),
+                        TestRSAConfigCircuit::<F>::BITS_LEN,
+                    )?;
+                    let powed = config.modpow_public_key(ctx, &c, &public_key)?;
+                    let expected = decompose_biguint::<F>(
+                        &self.c.modpow(&self.e, &self.n),
+                        powed.num_limbs(),
+                        config.biguint_config().limb_bits(),
+                    );
+                    for (limb, expected) in powed.limbs().iter().zip(expected) {
+                        config.gate().assert_is_const(ctx, limb, expected);
+                    }
+                    let exponent = config.assign_exponent(ctx, &public_key);
+                    config.range().finalize(ctx);
+                    Ok(Some(exponent.cell()))
+                },
+            )?;
+            layouter.constrain_instance(exponent_cell.unwrap(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn test_exponent_instance() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let n = random_modulus(TestRSAConfigCircuit::<F>::BITS_LEN);
+            let c = &n >> 1;
+            let circuit = |e: u64, is_var: bool| TestRSAExponentInstanceCircuit::<F> {
+                n: n.clone(),
+                e: BigUint::from(e),
+                is_var,
+                c: c.clone(),
+                _f: PhantomData,
+            };
+
+            // The fixed exponent appears in the instance, and no other claim is accepted.
+            let fixed = circuit(65537, false);
+            let prover = MockProver::run(k, &fixed, vec![vec![F::from(65537)]]).unwrap();
+            prover.verify().unwrap();
+            let prover = MockProver::run(k, &fixed, vec![vec![F::from(3)]]).unwrap();
+            assert!(prover.verify().is_err());
+
+            // A variable exponent is exposed as the very cell used by the modular power.
+            let var = circuit(17, true);
+            let prover = MockProver::run(k, &var, vec![vec![F::from(17)]]).unwrap();
+            prover.verify().unwrap();
+            let prover = MockProver::run(k, &var, vec![vec![F::from(65537)]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_exp_bits() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let n = random_modulus(TestRSAConfigCircuit::<F>::BITS_LEN);
+            let c = &n >> 1;
+            let e = BigUint::from(65537u64);
+            let powed = c.modpow(&e, &n);
+            let circuit = |exp_bits: usize| {
+                let (n, c, e, expected) = (n.clone(), c.clone(), e.clone(), powed.clone());
+                TestRSAConfigCircuit::new(Rc::new(
+                    move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                        let config = RSAConfig::construct(
+                            config.biguint_config().clone(),
+                            TestRSAConfigCircuit::<F>::BITS_LEN,
+                            exp_bits,
+                        )
+                        .unwrap();
+                        assert_eq!(config.exp_bits(), exp_bits);
+                        let public_key = config.assign_public_key(
+                            ctx,
+                            RSAPublicKey::new(
+                                Value::known(n.clone()),
+                                RSAPubE::Var(Value::known(e.clone())),
+                            ),
+                        )?;
+                        let c = config.biguint_config().assign_integer(
+                            ctx,
+                            Value::known(c.clone()),
+                            TestRSAConfigCircuit::<F>::BITS_LEN,
+                        )?;
+                        let powed = config.modpow_public_key(ctx, &c, &public_key)?;
+                        let expected = decompose_biguint::<F>(
+                            &expected,
+                            powed.num_limbs(),
+                            config.biguint_config().limb_bits(),
+                        );
+                        for (limb, expected) in powed.limbs().iter().zip(expected) {
+                            config.gate().assert_is_const(ctx, limb, expected);
+                        }
+                        Ok(())
+                    },
+                ))
+            };
+
+            // The bit length must be positive and less than the capacity of the field.
+            let mut meta = ConstraintSystem::<F>::default();
+            let config = TestRSAConfigCircuit::<F>::configure(&mut meta);
+            for exp_bits in [0, F::CAPACITY as usize] {
+                assert_eq!(
+                    RSAConfig::construct(
+                        config.biguint_config().clone(),
+                        TestRSAConfigCircuit::<F>::BITS_LEN,
+                        exp_bits,
+                    )
+                    .err(),
+                    Some(ParamsError::InvalidExpBits {
+                        exp_bits,
+                        capacity: F::CAPACITY
+                    })
+                );
+            }
+
+            // 65537 needs 17 bits.
+            let prover = MockProver::run(k, &circuit(17), vec![]).unwrap();
+            prover.verify().unwrap();
+            // With fewer bits, the exponent is rejected rather than truncated.
+            for exp_bits in [TestRSAConfigCircuit::<F>::EXP_LIMB_BITS, 16] {
+                assert!(MockProver::run(k, &circuit(exp_bits), vec![]).is_err());
+            }
+        }
+        run::<Fr>();
+    }
+
+    /// Returns the pkcs1v15 encoded message of `hash` for a 2048-bit modulus, with the DigestInfo shifted by `shift` bytes towards the most significant one.
+    fn shifted_encoded_message(hash: &[u8], shift: usize) -> BigUint {
+        let t = [SHA256_DIGEST_INFO.as_slice(), hash].concat();
+        let ps_len = 2048 / 8 - t.len() - 3 - shift;
+        let em = [
+            vec![0x00, 0x01],
+            vec![0xff; ps_len],
+            vec![0x00],
+            t,
+            vec![0x00; shift],
+        ]
+        .concat();
+        BigUint::from_bytes_be(&em)
+    }
+
+    #[test]
+    fn test_check_sha256_digest_info() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let mut rng = thread_rng();
+            let private_key = RsaPrivateKey::new(&mut rng, TestRSAConfigCircuit::<F>::BITS_LEN)
+                .expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let hash = Sha256::digest(&msg).to_vec();
+            let signature = SigningKey::<rsa::sha2::Sha256>::new(private_key)
+                .sign(&msg)
+                .to_vec();
+            let n = BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap();
+            let c = BigUint::from_bytes_be(&signature);
+            // The decrypted signature is the encoded message without any shift.
+            assert_eq!(
+                c.modpow(&BigUint::from(65537u64), &n),
+                shifted_encoded_message(&hash, 0)
+            );
+
+            let circuit = |em: Option<BigUint>, expected: bool| {
+                let (n, c, hash) = (n.clone(), c.clone(), hash.clone());
+                TestRSAConfigCircuit::new(Rc::new(
+                    move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                        assert_eq!(config.sha256_digest_info_offset(), 205);
+                        let em = match &em {
+                            Some(em) => config.biguint_config().assign_integer(
+                                ctx,
+                                Value::known(em.clone()),
+                                TestRSAConfigCircuit::<F>::BITS_LEN,
+                            )?,
+                            None => {
+                                let e_fix = RSAPubE::Fix(BigUint::from(65537u64));
+                                let public_key = config.assign_public_key(
+                                    ctx,
+                                    RSAPublicKey::new(Value::known(n.clone()), e_fix),
+                                )?;
+                                let signature = config.assign_signature(
+                                    ctx,
+                                    RSASignature::new(Value::known(c.clone())),
+                                )?;
+                                config.decrypt_signature(ctx, &public_key, &signature)?
+                            }
+                        };
+                        let hashed_msg = hash
+                            .iter()
+                            .map(|byte| {
+                                config
+                                    .gate()
+                                    .load_witness(ctx, Value::known(F::from(*byte as u64)))
+                            })
+                            .collect::<Vec<AssignedValue<F>>>();
+                        let is_valid = config.check_sha256_digest_info(ctx, &em, &hashed_msg)?;
+                        config
+                            .gate()
+                            .assert_is_const(ctx, &is_valid, F::from(expected as u64));
+                        Ok(())
+                    },
+                ))
+            };
+
+            // The decrypted block of a valid signature has the hash at the offset.
+            let prover = MockProver::run(k, &circuit(None, true), vec![]).unwrap();
+            prover.verify().unwrap();
+            let correct = shifted_encoded_message(&hash, 0);
+            let prover = MockProver::run(k, &circuit(Some(correct), true), vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // A hash shifted by a byte is rejected, though the padding before it still looks valid.
+            let shifted = shifted_encoded_message(&hash, 1);
+            let prover =
+                MockProver::run(k, &circuit(Some(shifted.clone()), false), vec![]).unwrap();
+            prover.verify().unwrap();
+            let prover = MockProver::run(k, &circuit(Some(shifted), true), vec![]).unwrap();
+            assert!(prover.verify().is_err());
+            let shifted = shifted_encoded_message(&hash, 0) >> 8;
+            let prover = MockProver::run(k, &circuit(Some(shifted), false), vec![]).unwrap();
+            prover.verify().unwrap();
+        }
+        run::<Fr>();
+    }
+
+    fn pack_bytes_circuit<F: PrimeField>(bytes: Vec<u8>, expected: BigUint) -> TestRSAConfigCircuit<F> {
+        TestRSAConfigCircuit::new(Rc::new(move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+            let biguint = config.biguint_config();
+            let assigned = bytes
+                .iter()
+                .map(|byte| config.gate().load_witness(ctx, Value::known(F::from(*byte as u64))))
+                .collect::<Vec<AssignedValue<F>>>();
+            let packed = biguint.pack_bytes(ctx, &assigned)?;
+            let expected = halo2_base::utils::biguint_to_fe(&expected);
+            config.gate().assert_is_const(ctx, &packed, expected);
+            Ok(())
+        }))
+    }
+
+    fn pack_hash_limbs_circuit<F: PrimeField>(bytes: Vec<u64>, expected: Vec<u64>) -> TestRSAConfigCircuit<F> {
+        TestRSAConfigCircuit::new(Rc::new(move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+            let biguint = config.biguint_config();
+            let assigned = bytes
+                .iter()
+                .map(|byte| config.gate().load_witness(ctx, Value::known(F::from(*byte))))
+                .collect::<Vec<AssignedValue<F>>>();
+            let limbs = biguint.pack_hash_limbs(ctx, &assigned)?;
+            for (limb, expected) in limbs.iter().zip(&expected) {
+                config.gate().assert_is_const(ctx, limb, F::from(*expected));
+            }
+            Ok(())
+        }))
+    }
+
+    #[test]
+    fn test_pack_hash_limbs() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let digest = Sha256::digest(b"aadhaar");
+            let bytes = digest.iter().map(|byte| *byte as u64).collect::<Vec<u64>>();
+            let expected = digest
+                .rchunks(8)
+                .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+                .collect::<Vec<u64>>();
+            let circuit = pack_hash_limbs_circuit::<F>(bytes.clone(), expected.clone());
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // A byte of 256 carried into the next byte packs into the same limb, but is out of the byte range.
+            let mut forged = bytes.clone();
+            assert_ne!(forged[30], 0);
+            forged[30] -= 1;
+            forged[31] += 256;
+            let circuit = pack_hash_limbs_circuit::<F>(forged, expected);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+
+            // The bytes must fill whole limbs.
+            let circuit = pack_hash_limbs_circuit::<F>(bytes[..31].to_vec(), vec![]);
+            assert!(MockProver::run(k, &circuit, vec![]).is_err());
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_pack_bytes() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            assert_eq!(BigUintConfig::<F>::max_packed_bytes(), 31);
+            let mut rng = thread_rng();
+            for n in [1, 8, 31] {
+                let bytes = (0..n).map(|_| rng.gen::<u8>()).collect::<Vec<u8>>();
+                let expected = BigUint::from_bytes_le(&bytes);
+                let circuit = pack_bytes_circuit::<F>(bytes.clone(), expected.clone());
+                let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+                prover.verify().unwrap();
+
+                let circuit = pack_bytes_circuit::<F>(bytes, expected + 1u64);
+                let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+                assert!(prover.verify().is_err());
+            }
+
+            // 32 bytes may exceed the field modulus.
+            let circuit = pack_bytes_circuit::<F>(vec![0xff; 32], BigUint::from(0u64));
+            assert!(MockProver::run(k, &circuit, vec![]).is_err());
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_biguint_config_accessors() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let circuit = TestRSAConfigCircuit::new(Rc::new(
+                |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                    let gate = config.biguint_config().gate();
+                    let a = [2u64, 3, 5]
+                        .iter()
+                        .map(|v| gate.load_witness(ctx, Value::known(F::from(*v))))
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let b = [7u64, 11, 13].map(|v| QuantumCell::Constant(F::from(v)));
+                    let sum = gate.inner_product(ctx, a.iter().map(QuantumCell::Existing), b);
+                    gate.assert_is_const(ctx, &sum, F::from(2 * 7 + 3 * 11 + 5 * 13));
+
+                    let mut config = config.clone();
+                    assert_eq!(config.biguint_config_mut().limb_bits(), 64);
+                    config.biguint_config_mut().limb_bits = 32;
+                    assert_eq!(config.biguint_config().limb_bits(), 32);
+                    Ok(())
+                },
+            ));
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+        }
+        run::<Fr>();
+    }
+
+    fn less_than_circuit<F: PrimeField>(
+        a: u64,
+        b: u64,
+        num_bits: usize,
+        expected: bool,
+    ) -> TestRSAConfigCircuit<F> {
+        TestRSAConfigCircuit::new(Rc::new(
+            move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                let comparison = ComparisonConfig::construct(config.range().clone());
+                let a = config.gate().load_witness(ctx, Value::known(F::from(a)));
+                let b = config.gate().load_witness(ctx, Value::known(F::from(b)));
+                let is_less = comparison.less_than(ctx, &a, &b, num_bits);
+                config
+                    .gate()
+                    .assert_is_const(ctx, &is_less, F::from(expected as u64));
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_less_than() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let max = (1u64 << 8) - 1;
+            for (a, b, expected) in [
+                (5, 5, false),
+                (4, 5, true),
+                (6, 5, false),
+                (0, 0, false),
+                (0, 1, true),
+                (1, 0, false),
+                (max, max, false),
+                (max - 1, max, true),
+                (max, max - 1, false),
+            ] {
+                let prover =
+                    MockProver::run(k, &less_than_circuit::<F>(a, b, 8, expected), vec![]).unwrap();
+                prover.verify().unwrap();
+                let prover =
+                    MockProver::run(k, &less_than_circuit::<F>(a, b, 8, !expected), vec![])
+                        .unwrap();
+                assert!(prover.verify().is_err());
+            }
+
+            // A value wider than the declared bit width is rejected.
+            let prover =
+                MockProver::run(k, &less_than_circuit::<F>(max + 1, 1, 8, false), vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    fn last_n_digits_circuit<F: PrimeField>(
+        packed_int: u64,
+        n: usize,
+        expected: u64,
+    ) -> TestRSAConfigCircuit<F> {
+        TestRSAConfigCircuit::new(Rc::new(
+            move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                let comparison = ComparisonConfig::construct(config.range().clone());
+                let packed_int = config
+                    .gate()
+                    .load_witness(ctx, Value::known(F::from(packed_int)));
+                let digits = comparison.last_n_digits(ctx, &packed_int, n);
+                config
+                    .gate()
+                    .assert_is_const(ctx, &digits, F::from(expected));
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_last_n_digits() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            // The last 4 digits of a 12-digit number, including a leading zero.
+            for (packed_int, expected) in [
+                (269720240718, 718),
+                (123456789012, 9012),
+                (100000000000, 0),
+                (999999999999, 9999),
+            ] {
+                let prover = MockProver::run(
+                    k,
+                    &last_n_digits_circuit::<F>(packed_int, 4, expected),
+                    vec![],
+                )
+                .unwrap();
+                prover.verify().unwrap();
+                let prover = MockProver::run(
+                    k,
+                    &last_n_digits_circuit::<F>(packed_int, 4, expected + 1),
+                    vec![],
+                )
+                .unwrap();
+                assert!(prover.verify().is_err());
+            }
+
+            // Keeping all the digits or none of them.
+            let prover = MockProver::run(
+                k,
+                &last_n_digits_circuit::<F>(269720240718, 12, 269720240718),
+                vec![],
+            )
+            .unwrap();
+            prover.verify().unwrap();
+            let prover =
+                MockProver::run(k, &last_n_digits_circuit::<F>(269720240718, 0, 0), vec![])
+                    .unwrap();
+            prover.verify().unwrap();
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_assigned_biguint_bits() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let bits_len = TestRSAConfigCircuit::<F>::BITS_LEN;
+            let n = random_modulus(bits_len);
+            let circuit = TestRSAConfigCircuit::new(Rc::new(
+                move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                    let biguint = config.biguint_config();
+                    let n = biguint.assign_integer(ctx, Value::known(n.clone()), bits_len)?;
+                    assert_eq!(n.bits(), 2048);
+                    assert_eq!(n.num_limbs(), 2048 / biguint.limb_bits());
+                    Ok(())
+                },
+            ));
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+        }
+        run::<Fr>();
+    }
+
+    fn is_zero_circuit<F: PrimeField>(a: BigUint, expected: bool) -> TestRSAConfigCircuit<F> {
+        let bits_len = TestRSAConfigCircuit::<F>::BITS_LEN;
+        TestRSAConfigCircuit::new(Rc::new(
+            move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                let biguint = config.biguint_config();
+                let a = biguint.assign_integer(ctx, Value::known(a.clone()), bits_len)?;
+                let is_zero = biguint.is_zero(ctx, &a)?;
+                biguint
+                    .gate()
+                    .assert_is_const(ctx, &is_zero, F::from(expected as u64));
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_is_zero() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let bits_len = TestRSAConfigCircuit::<F>::BITS_LEN;
+            let mut rng = thread_rng();
+            for (a, expected) in [
+                (BigUint::from(0u64), true),
+                (BigUint::from(1u64), false),
+                // Only the top limb is nonzero.
+                (BigUint::from(1u64) << (bits_len - 1), false),
+                (rng.gen_biguint(bits_len as u64) | BigUint::from(1u64), false),
+            ] {
+                let prover =
+                    MockProver::run(k, &is_zero_circuit::<F>(a.clone(), expected), vec![]).unwrap();
+                prover.verify().unwrap();
+                let prover =
+                    MockProver::run(k, &is_zero_circuit::<F>(a, !expected), vec![]).unwrap();
+                assert!(prover.verify().is_err());
+            }
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        type Circuit1 = TestRSASignatureWithHashCircuit1<Fr>;
+        let mut rng = thread_rng();
+        let private_key =
+            RsaPrivateKey::new(&mut rng, Circuit1::BITS_LEN).expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+        let circuit = Circuit1::new(private_key, public_key, msg);
+        let report = estimate_cost(&circuit).unwrap();
+        assert!(report.advice_cells > 0);
+        assert!(report.lookup_cells > 0);
+        assert!(report.lookup_cells < report.advice_cells);
+        // The range lookup table takes `2^LOOKUP_BITS` rows of a fixed column.
+        assert!(report.fixed_rows >= 1 << DefaultRangeParams::LOOKUP_BITS);
+        // The range chip fills the rows of the degree it is configured with, so the circuit is proven in that degree.
+        assert_eq!(report.min_k, Circuit1::K);
+        let prover = MockProver::run(report.min_k, &circuit, circuit.instances()).unwrap();
+        prover.verify().unwrap();
+
+        // A circuit laid out row by row is measured whatever the degree, and fits from `min_k` on.
+        let data = (0..NUM_DELIMITERS as u8 + 2)
+            .flat_map(|i| [b'a' + i, DELIMITER])
+            .collect::<Vec<u8>>();
+        let circuit = DelimiterCircuit::<Fr>::new(data);
+        let instance = circuit
+            .delimiter_indices()
+            .into_iter()
+            .map(|index| Fr::from(index as u64))
+            .collect::<Vec<Fr>>();
+        let report = estimate_cost(&circuit).unwrap();
+        assert!(report.rows >= DEFAULT_MAX_DATA_LEN);
+        let prover = MockProver::run(report.min_k, &circuit, vec![instance.clone()]).unwrap();
+        prover.verify().unwrap();
+        assert!(MockProver::run(report.min_k - 1, &circuit, vec![instance]).is_err());
+    }
+
+    #[test]
+    fn test_describe_layout() {
+        type Circuit1 = TestRSASignatureWithHashCircuit1<Fr>;
+        let mut rng = thread_rng();
+        let private_key =
+            RsaPrivateKey::new(&mut rng, Circuit1::BITS_LEN).expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+        let circuit = Circuit1::new(private_key, public_key, msg);
+        let report = estimate_cost(&circuit).unwrap();
+        let description = describe_layout(&circuit).unwrap();
+
+        let count = |name: &str| {
+            description
+                .lines()
+                .find_map(|line| line.strip_prefix(&format!("{name}: ")))
+                .unwrap_or_else(|| panic!("{name} is not described"))
+                .parse::<usize>()
+                .unwrap()
+        };
+        // The modulus limbs and the message hash.
+        assert_eq!(count("instance columns"), 2);
+        // The SHA256 chip configures its own columns besides those of the range chip.
+        assert!(count("advice columns") >= Circuit1::NUM_ADVICE + Circuit1::NUM_LOOKUP_ADVICE);
+        assert!(count("fixed columns") >= Circuit1::NUM_FIXED);
+        assert!(count("lookup arguments") > 0);
+        assert_eq!(count("degree"), CircuitShape::of::<Fr, Circuit1>().degree);
+        assert_eq!(count("advice cells"), report.advice_cells);
+        assert_eq!(count("lookup cells"), report.lookup_cells);
+        assert_eq!(count("fixed rows"), report.fixed_rows);
+        assert_eq!(count("rows"), report.rows);
+        assert_eq!(count("min_k"), report.min_k as usize);
+    }
+
+    #[test]
+    fn test_pow_mont() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let bits_len = TestRSAConfigCircuit::<F>::BITS_LEN;
+            let n = random_modulus(bits_len);
+            let mut rng = thread_rng();
+            let x = rng.gen_biguint(bits_len as u64) % &n;
+            let e = BigUint::from(65537u64);
+            let expected = x.modpow(&e, &n);
+            // The advice cells used by `pow_mod_fixed_exp` and by `pow_mont`.
+            let cost = Rc::new(RefCell::new(None));
+            let recorded = cost.clone();
+            let (pow_n, pow_x, pow_e) = (n.clone(), x.clone(), e.clone());
+            let circuit = TestRSAConfigCircuit::new(Rc::new(
+                move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                    let biguint = config.biguint_config();
+                    let n = biguint.assign_integer(ctx, Value::known(pow_n.clone()), bits_len)?;
+                    let x = biguint.assign_integer(ctx, Value::known(pow_x.clone()), bits_len)?;
+                    let before = ctx.total_advice;
+                    let powed = biguint.pow_mod_fixed_exp(ctx, &x, &pow_e, &n)?;
+                    let middle = ctx.total_advice;
+                    let powed_mont = biguint.pow_mont(ctx, &x, &pow_e, &n)?;
+                    *recorded.borrow_mut() = Some((middle - before, ctx.total_advice - middle));
+                    biguint.assert_equal_fresh(ctx, &powed, &powed_mont)?;
+                    let expected =
+                        biguint.assign_integer(ctx, Value::known(expected.clone()), bits_len)?;
+                    biguint.assert_equal_fresh(ctx, &powed_mont, &expected)?;
+                    Ok(())
+                },
+            ));
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // The quotient of `mul_mod` is a witness as well, so the Montgomery form does not save the reduction itself.
+            // It saves the re-assignment of the modulus and two operations, against the conversions and the bound `t < n` of each step.
+            let (fixed_exp_cells, mont_cells) = cost.borrow().unwrap();
+            assert!(fixed_exp_cells > 0);
+            assert!(mont_cells < fixed_exp_cells * 5 / 4);
+
+            // An even modulus has no Montgomery form, which fails the synthesis instead of panicking.
+            let even = &n + 1u32;
+            let circuit = TestRSAConfigCircuit::new(Rc::new(
+                move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                    let biguint = config.biguint_config();
+                    let n = biguint.assign_integer(ctx, Value::known(even.clone()), bits_len)?;
+                    let x = biguint.assign_integer(ctx, Value::known(x.clone()), bits_len)?;
+                    biguint.pow_mont(ctx, &x, &e, &n)?;
+                    Ok(())
+                },
+            ));
+            assert!(MockProver::run(k, &circuit, vec![]).is_err());
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_add_sub_mod() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            let bits_len = TestRSAConfigCircuit::<F>::BITS_LEN;
+            let n = random_modulus(bits_len);
+            let mut rng = thread_rng();
+            let a = rng.gen_biguint(bits_len as u64) % &n;
+            let b = rng.gen_biguint(bits_len as u64) % &n;
+            let circuit = |a: BigUint, b: BigUint, n: BigUint| {
+                let added = (&a + &b) % &n;
+                let subed = (&a + &n - &b) % &n;
+                TestRSAConfigCircuit::new(Rc::new(
+                    move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                        let biguint = config.biguint_config();
+                        let mut assign = |value: &BigUint| {
+                            biguint.assign_integer(ctx, Value::known(value.clone()), bits_len)
+                        };
+                        let [a, b, n, added, subed] =
+                            [&a, &b, &n, &added, &subed].map(|value| assign(value));
+                        let (a, b, n) = (a?, b?, n?);
+                        let result = biguint.add_mod(ctx, &a, &b, &n)?;
+                        biguint.assert_equal_fresh(ctx, &result, &added?)?;
+                        let result = biguint.sub_mod(ctx, &a, &b, &n)?;
+                        biguint.assert_equal_fresh(ctx, &result, &subed?)?;
+                        Ok(())
+                    },
+                ))
+            };
+
+            // Random operands, in both orders so that the subtraction wraps once.
+            for (a, b) in [(a.clone(), b.clone()), (b, a.clone())] {
+                let prover = MockProver::run(k, &circuit(a, b, n.clone()), vec![]).unwrap();
+                prover.verify().unwrap();
+            }
+            // The sum reaching the modulus, the difference of equal operands, and the largest operands.
+            let complement = &n - &a;
+            for (a, b) in [
+                (a.clone(), complement),
+                (a.clone(), a),
+                (n.clone() - 1u32, n.clone() - 1u32),
+            ] {
+                let prover = MockProver::run(k, &circuit(a, b, n.clone()), vec![]).unwrap();
+                prover.verify().unwrap();
+            }
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_rsa_signature_with_hash_circuit1() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let private_key =
+                RsaPrivateKey::new(&mut rng, TestRSASignatureWithHashCircuit1::<F>::BITS_LEN)
+                    .expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let n = BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap();
+            let mut msg: [u8; 128] = [0; 128];
+            for i in 0..128 {
+                msg[i] = rng.gen();
+            }
+            let hashed_msg = Sha256::digest(&msg);
+            let circuit = TestRSASignatureWithHashCircuit1::<F> {
+                private_key,
+                public_key,
+                msg: msg.to_vec().into(),
+                signature: None,
+                expect_valid: true,
+                _f: PhantomData,
+            };
+            let num_limbs = 2048 / 64;
+            let limb_bits = 64;
+            let n_fes = decompose_biguint::<F>(&n, num_limbs, limb_bits);
+            let hash_fes = hashed_msg
+                .iter()
+                .map(|byte| F::from(*byte as u64))
+                .collect::<Vec<F>>();
+            let public_inputs = vec![n_fes, hash_fes];
+            let k = 15;
+            let prover = match MockProver::run(k, &circuit, public_inputs) {
+                Ok(prover) => prover,
+                Err(e) => panic!("{:#?}", e),
+            };
+            prover.verify().unwrap();
+        }
+        run::<Fr>();
+        // The RSA and SHA256 chips are generic over the field, so catch any assumption specific to bn256.
+        run::<Fp>();
+    }
+
+    #[test]
+    fn test_verify_rsa_sha256_with_message() {
+        use crate::prover::{
+            gen_pk, gen_proof, gen_srs, verify_rsa_sha256_with_message, VerifyAadhaarError,
+        };
+
+        let mut rng = thread_rng();
+        let private_key =
+            RsaPrivateKey::new(&mut rng, TestRSASignatureWithHashCircuit1::<Fr>::BITS_LEN)
+                .expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+        let circuit =
+            TestRSASignatureWithHashCircuit1::<Fr>::new(private_key, public_key, msg.clone());
+        let instances = circuit.instances();
+        let instances = instances.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let params = gen_srs(TestRSASignatureWithHashCircuit1::<Fr>::K);
+        let pk = gen_pk(&params, &circuit, None).unwrap();
+        let proof = gen_proof(&params, &pk, circuit, &instances).unwrap();
+
+        verify_rsa_sha256_with_message(&params, pk.get_vk(), &proof, &instances, &msg).unwrap();
+        let mut other = msg.clone();
+        other[0] ^= 1;
+        assert!(matches!(
+            verify_rsa_sha256_with_message(&params, pk.get_vk(), &proof, &instances, &other),
+            Err(VerifyAadhaarError::MessageHashMismatch)
+        ));
+        // Instances other than the digest of the message are rejected before the proof is verified.
+        let mut forged = instances[1].to_vec();
+        forged[0] += Fr::from(1);
+        let forged_instances = [instances[0], forged.as_slice()];
+        assert!(matches!(
+            verify_rsa_sha256_with_message(&params, pk.get_vk(), &proof, &forged_instances, &msg),
+            Err(VerifyAadhaarError::MessageHashMismatch)
+        ));
+        assert!(matches!(
+            verify_rsa_sha256_with_message(&params, pk.get_vk(), &proof[1..], &instances, &msg),
+            Err(VerifyAadhaarError::Proof(_))
+        ));
+    }
+
+    #[test]
+    fn test_decompose_biguint_endianness() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let private_key =
+                RsaPrivateKey::new(&mut rng, TestRSASignatureWithHashCircuit1::<F>::BITS_LEN)
+                    .expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let n = BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap();
+            let little =
+                decompose_biguint_with_endianness::<F>(&n, 2048 / 64, 64, Endianness::Little);
+            let mut big =
+                decompose_biguint_with_endianness::<F>(&n, 2048 / 64, 64, Endianness::Big);
+            assert_eq!(little, decompose_biguint::<F>(&n, 2048 / 64, 64));
+            assert_eq!(fe_to_biguint(&little[0]), &n % (BigUint::from(1u64) << 64));
+            big.reverse();
+            assert_eq!(big, little);
+
+            // The modulus instances are little-endian.
+            let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let circuit = TestRSASignatureWithHashCircuit1::<F>::new(private_key, public_key, msg);
+            let mut public_inputs = circuit.instances();
+            assert_eq!(public_inputs[0], little);
+            let k = 15;
+            let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+            prover.verify().unwrap();
+            public_inputs[0].reverse();
+            let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    /// A circuit written only against [`crate::halo2_proofs`], which builds only if the types of the crate chips are those of this path.
+    #[derive(Default)]
+    struct CanonicalPathCircuit {
+        nullifier: nullifier::PoseidonCircuit,
+    }
+
+    impl crate::halo2_proofs::plonk::Circuit<crate::halo2_proofs::halo2curves::bn256::Fr>
+        for CanonicalPathCircuit
+    {
+        type Config = (
+            nullifier::PoseidonConfig,
+            crate::halo2_proofs::plonk::Column<crate::halo2_proofs::plonk::Instance>,
+        );
+        type FloorPlanner = crate::halo2_proofs::circuit::SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(
+            meta: &mut crate::halo2_proofs::plonk::ConstraintSystem<
+                crate::halo2_proofs::halo2curves::bn256::Fr,
+            >,
+        ) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (nullifier::PoseidonConfig::configure(meta), instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl crate::halo2_proofs::circuit::Layouter<
+                crate::halo2_proofs::halo2curves::bn256::Fr,
+            >,
+        ) -> Result<(), crate::halo2_proofs::plonk::Error> {
+            let cells = self
+                .nullifier
+                .assign(&config.0, layouter.namespace(|| "nullifier"))?;
+            layouter.constrain_instance(cells.nullifier, config.1, 0)
+        }
+    }
+
+    #[test]
+    fn test_canonical_halo2_proofs_path() {
+        let circuit = CanonicalPathCircuit {
+            nullifier: nullifier::PoseidonCircuit::new(12345678, vec![1, 2, 3, 4]),
+        };
+        let nullifier = circuit.nullifier.nullifier().unwrap();
+        let prover = crate::halo2_proofs::dev::MockProver::run(
+            nullifier::PoseidonCircuit::K,
+            &circuit,
+            vec![vec![nullifier]],
+        )
+        .unwrap();
+        prover.verify().unwrap();
+    }
+
+    #[test]
+    fn test_verify_signed_region() {
+        fn run<F: PrimeField>() {
+            let (qr, private_key, public_key) =
+                qr::test_utils::generate_test_qr(qr::test_utils::TestQrFields::default());
+            let qr = qr::decompress_qr(&qr).unwrap();
+            let region = qr::signed_region(&qr, TestRSASignatureWithHashCircuit1::<F>::BITS_LEN)
+                .unwrap()
+                .to_vec();
+            let signature = qr[region.len()..].to_vec();
+            let k = 15;
+
+            // The signature of the QR code verifies over the exact region.
+            let circuit = TestRSASignatureWithHashCircuit1::<F> {
+                private_key: private_key.clone(),
+                public_key: public_key.clone(),
+                msg: region.clone().into(),
+                signature: Some(signature.clone().into()),
+                expect_valid: true,
+                _f: PhantomData,
+            };
+            let prover = MockProver::run(k, &circuit, circuit.instances()).unwrap();
+            prover.verify().unwrap();
+
+            // Changing any single signed byte invalidates it.
+            for i in [0, region.len() / 2, region.len() - 1] {
+                let mut msg = region.clone();
+                msg[i] ^= 1;
+                let circuit = TestRSASignatureWithHashCircuit1::<F> {
+                    private_key: private_key.clone(),
+                    public_key: public_key.clone(),
+                    msg: msg.into(),
+                    signature: Some(signature.clone().into()),
+                    expect_valid: false,
+                    _f: PhantomData,
+                };
+                let prover = MockProver::run(k, &circuit, circuit.instances()).unwrap();
+                prover.verify().unwrap();
+            }
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_rsa_signature_with_hash_circuit_invalid_signature() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let private_key =
+                RsaPrivateKey::new(&mut rng, TestRSASignatureWithHashCircuit1::<F>::BITS_LEN)
+                    .expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let n = BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap();
+            let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let hashed_msg = Sha256::digest(&msg);
+            let n_fes = decompose_biguint::<F>(&n, 2048 / 64, 64);
+            let hash_fes = hashed_msg
+                .iter()
+                .map(|byte| F::from(*byte as u64))
+                .collect::<Vec<F>>();
+            let public_inputs = vec![n_fes, hash_fes];
+            let k = 15;
+
+            // The circuit is satisfied only if the validity bit is zero.
+            let circuit = TestRSASignatureWithHashCircuit1::<F>::new_invalid(
+                private_key.clone(),
+                public_key.clone(),
+                msg.clone(),
+            );
+            let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+            prover.verify().unwrap();
+
+            let circuit = TestRSASignatureWithHashCircuit1::<F> {
+                expect_valid: true,
+                ..circuit
+            };
+            let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+        run::<Fp>();
+    }
+
+    #[test]
+    fn test_zeroize_witnesses() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<RsaPrivateKey>();
+        assert_zeroize_on_drop::<TestRSASignatureWithHashCircuit1<Fr>>();
+
+        let mut rng = thread_rng();
+        let private_key =
+            RsaPrivateKey::new(&mut rng, TestRSASignatureWithHashCircuit1::<Fr>::BITS_LEN)
+                .expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let msg = b"2023070812345600\xffresident".to_vec();
+        let mut circuit =
+            TestRSASignatureWithHashCircuit1::<Fr>::new_invalid(private_key, public_key, msg);
+        circuit.zeroize();
+        assert!(circuit.msg.is_empty());
+        assert!(circuit.signature.is_none());
+    }
+
+    #[derive(Debug, Clone)]
+    struct TestRSAPssSignatureConfig<F: PrimeField> {
+        rsa_config: RSAConfig<F>,
+        sha256_config: Sha256DynamicConfig<F>,
+    }
+
+    /// A circuit verifying a RSASSA-PSS signature of `msg` with [`RSASignatureVerifier::verify_pss_signature`].
+    #[derive(Debug, Clone)]
+    struct TestRSAPssSignatureCircuit<F: PrimeField> {
+        public_key: RsaPublicKey,
+        msg: Vec<u8>,
+        signature: Vec<u8>,
+        /// The expected value of the validity bit output by the verification.
+        expect_valid: bool,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestRSAPssSignatureCircuit<F> {
+        const BITS_LEN: usize = Mainnet2048::BITS_LEN;
+        const MSG_LEN: usize = 1024;
+        const DEFAULT_E: u128 = 65537;
+        const K: u32 = Mainnet2048::K;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestRSAPssSignatureCircuit<F> {
+        type Config = TestRSAPssSignatureConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (rsa_config, sha256_config) = configure_rsa_sha256::<F, Mainnet2048>(
+                meta,
+                RSASignatureVerifier::<F>::pss_sha256_max_byte_sizes(Self::MSG_LEN, Self::BITS_LEN),
+            );
+            Self::Config {
+                rsa_config,
+                sha256_config,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa pss signature test with 2048 bits public keys",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let sign_big = BigUint::from_bytes_be(&self.signature);
+                    let sign = config
+                        .rsa_config
+                        .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                    let n_big =
+                        BigUint::from_radix_le(&self.public_key.n().to_radix_le(16), 16).unwrap();
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let (is_valid, hashed_msg) =
+                        verifier.verify_pss_signature(ctx, &public_key, &self.msg, &sign)?;
+                    biguint_config.gate().assert_is_const(
+                        ctx,
+                        &is_valid,
+                        F::from(self.expect_valid as u64),
+                    );
+                    for (assigned, byte) in hashed_msg.iter().zip(Sha256::digest(&self.msg)) {
+                        biguint_config
+                            .gate()
+                            .assert_is_const(ctx, assigned, F::from(byte as u64));
+                    }
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_rsa_pss_signature() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let private_key =
+                RsaPrivateKey::new(&mut rng, TestRSAPssSignatureCircuit::<F>::BITS_LEN)
+                    .expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let signing_key = rsa::pss::SigningKey::<Sha256>::new(private_key);
+            let signature = signing_key.sign_with_rng(&mut rng, &msg).to_vec();
+            let k = TestRSAPssSignatureCircuit::<F>::K;
+
+            let circuit = TestRSAPssSignatureCircuit::<F> {
+                public_key,
+                msg,
+                signature,
+                expect_valid: true,
+                _f: PhantomData,
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // Corrupting the last byte keeps the signature smaller than the modulus.
+            let mut corrupted = circuit.signature.clone();
+            *corrupted.last_mut().unwrap() ^= 1;
+            let circuit = TestRSAPssSignatureCircuit::<F> {
+                signature: corrupted,
+                expect_valid: false,
+                ..circuit
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            let circuit = TestRSAPssSignatureCircuit::<F> {
+                expect_valid: true,
+                ..circuit
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    /// A circuit checking each field of the [`Pkcs1v15Result`] of a signature against the values computed outside the circuit.
+    #[derive(Debug, Clone)]
+    struct TestPkcs1v15ResultCircuit<F: PrimeField> {
+        public_key: RsaPublicKey,
+        msg: Vec<u8>,
+        signature: Vec<u8>,
+        expect_valid: bool,
+        hash_byte_order: HashByteOrder,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestPkcs1v15ResultCircuit<F> {
+        type Config = TestRSAPssSignatureConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (rsa_config, sha256_config) = configure_rsa_sha256::<F, Mainnet2048>(
+                meta,
+                vec![TestRSASignatureWithHashCircuit1::<F>::MSG_LEN],
+            );
+            TestRSAPssSignatureConfig {
+                rsa_config,
+                sha256_config,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "pkcs1v15 result test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let sign_big = BigUint::from_bytes_be(&self.signature);
+                    let sign = config
+                        .rsa_config
+                        .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                    let n_big =
+                        BigUint::from_radix_le(&self.public_key.n().to_radix_le(16), 16).unwrap();
+                    let e_fix = RSAPubE::Fix(BigUint::from(65537u64));
+                    let public_key = config.rsa_config.assign_public_key(
+                        ctx,
+                        RSAPublicKey::new(Value::known(n_big.clone()), e_fix),
+                    )?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    )
+                    .with_hash_byte_order(self.hash_byte_order);
+                    let result = verifier.verify_pkcs1v15_signature(
+                        ctx,
+                        &public_key,
+                        &self.msg,
+                        &sign,
+                        HashAlg::Sha256,
+                    )?;
+                    let gate = biguint_config.gate();
+                    gate.assert_is_const(ctx, &result.is_valid, F::from(self.expect_valid as u64));
+                    assert_eq!(result.hashed_msg.len(), 32);
+                    let digest = self.hash_byte_order.order(&Sha256::digest(&self.msg));
+                    for (assigned, byte) in result.hashed_msg.iter().zip(digest) {
+                        gate.assert_is_const(ctx, assigned, F::from(byte as u64));
+                    }
+                    let limbs = decompose_biguint::<F>(&n_big, 2048 / 64, 64);
+                    assert_eq!(result.modulus_limbs.len(), limbs.len());
+                    for (assigned, limb) in result.modulus_limbs.iter().zip(limbs) {
+                        gate.assert_is_const(ctx, assigned, limb);
+                    }
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_pkcs1v15_result() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let private_key = RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let signature = SigningKey::<rsa::sha2::Sha256>::new(private_key)
+                .sign(&msg)
+                .to_vec();
+            let k = TestRSAConfigCircuit::<F>::K;
+
+            let circuit = TestPkcs1v15ResultCircuit::<F> {
+                public_key,
+                msg,
+                signature,
+                expect_valid: true,
+                hash_byte_order: HashByteOrder::default(),
+                _f: PhantomData,
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // A corrupted signature only clears the validity bit, leaving the hash and the modulus as they are.
+            let mut corrupted = circuit.signature.clone();
+            *corrupted.last_mut().unwrap() ^= 1;
+            let circuit = TestPkcs1v15ResultCircuit::<F> {
+                signature: corrupted,
+                expect_valid: false,
+                ..circuit
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_hash_byte_order() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let private_key = RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let signature = SigningKey::<rsa::sha2::Sha256>::new(private_key)
+                .sign(&msg)
+                .to_vec();
+            let k = TestRSAConfigCircuit::<F>::K;
+
+            let digest = Sha256::digest(&msg).to_vec();
+            assert_eq!(HashByteOrder::BigEndian.order(&digest), digest);
+            let mut reversed = digest.clone();
+            reversed.reverse();
+            assert_eq!(HashByteOrder::LittleEndian.order(&digest), reversed);
+
+            // The returned hash bytes match the digest in either order.
+            for hash_byte_order in [HashByteOrder::BigEndian, HashByteOrder::LittleEndian] {
+                let circuit = TestPkcs1v15ResultCircuit::<F> {
+                    public_key: public_key.clone(),
+                    msg: msg.clone(),
+                    signature: signature.clone(),
+                    expect_valid: true,
+                    hash_byte_order,
+                    _f: PhantomData,
+                };
+                let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+                prover.verify().unwrap();
+            }
+        }
+        run::<Fr>();
+    }
+
+    /// A circuit committing to the zero-padded `data` with [`RSASignatureVerifier::commit_bytes`] and exposing the commitment as instances.
+    #[derive(Debug, Clone)]
+    struct TestCommitBytesCircuit<F: PrimeField> {
+        data: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestCommitBytesCircuit<F> {
+        const MAX_DATA_LEN: usize = 1024;
+
+        /// Returns `data` padded with zeros up to [`TestCommitBytesCircuit::MAX_DATA_LEN`] bytes.
+        fn data_padded(&self) -> Vec<u8> {
+            let mut padded = self.data.clone();
+            padded.resize(Self::MAX_DATA_LEN, 0);
+            padded
+        }
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestCommitBytesCircuit<F> {
+        type Config = (TestRSAPssSignatureConfig<F>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (rsa_config, sha256_config) =
+                configure_rsa_sha256::<F, Mainnet2048>(meta, vec![Self::MAX_DATA_LEN]);
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                TestRSAPssSignatureConfig {
+                    rsa_config,
+                    sha256_config,
+                },
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let (config, instance) = config;
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            let commitment_cells = layouter.assign_region(
+                || "sha256 commitment test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(vec![]);
+                    }
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let range = biguint_config.range();
+                    let bytes = self
+                        .data_padded()
+                        .iter()
+                        .map(|byte| {
+                            let assigned = range
+                                .gate()
+                                .load_witness(ctx, Value::known(F::from(*byte as u64)));
+                            range.range_check(ctx, &assigned, 8);
+                            assigned
+                        })
+                        .collect::<Vec<AssignedValue<F>>>();
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let commitment = verifier.commit_bytes(ctx, &bytes)?;
+                    range.finalize(ctx);
+                    Ok(commitment
+                        .into_iter()
+                        .map(|v| v.cell())
+                        .collect::<Vec<Cell>>())
+                },
+            )?;
+            for (i, cell) in commitment_cells.into_iter().enumerate() {
+                layouter.constrain_instance(cell, instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_commit_bytes() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let data = (0..700).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let circuit = TestCommitBytesCircuit::<F> {
+                data,
+                _f: PhantomData,
+            };
+            let commitment = Sha256::digest(circuit.data_padded())
+                .iter()
+                .map(|byte| F::from(*byte as u64))
+                .collect::<Vec<F>>();
+            let k = TestRSAConfigCircuit::<F>::K;
+            let prover = MockProver::run(k, &circuit, vec![commitment.clone()]).unwrap();
+            prover.verify().unwrap();
+
+            // The commitment is over the padded data, not the data alone.
+            let unpadded = Sha256::digest(&circuit.data)
+                .iter()
+                .map(|byte| F::from(*byte as u64))
+                .collect::<Vec<F>>();
+            let prover = MockProver::run(k, &circuit, vec![unpadded]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_rsa_signature_hash_instance_binds_verified_hash() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let private_key =
+                RsaPrivateKey::new(&mut rng, TestRSASignatureWithHashCircuit1::<F>::BITS_LEN)
+                    .expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let circuit = TestRSASignatureWithHashCircuit1::<F>::new(private_key, public_key, msg);
+            let public_inputs = circuit.instances();
+            let k = 15;
+            let prover = MockProver::run(k, &circuit, public_inputs.clone()).unwrap();
+            prover.verify().unwrap();
+
+            // The exposed hash cells are the ones packed into the limbs of the pkcs1v15 check, so every byte is bound to the verified hash.
+            for i in [0, 7, 8, 31] {
+                let mut public_inputs = public_inputs.clone();
+                public_inputs[1][i] += F::one();
+                let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+                assert!(prover.verify().is_err());
+            }
+
+            // So is the hash of another message.
+            let mut public_inputs = public_inputs;
+            public_inputs[1] = Sha256::digest(b"another message")
+                .iter()
+                .map(|byte| F::from(*byte as u64))
+                .collect();
+            let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    /// A circuit verifying the signatures of two messages and whether they share the signer.
+    #[derive(Debug, Clone)]
+    struct TestRSASameSignerCircuit<F: PrimeField> {
+        public_keys: [RsaPublicKey; 2],
+        msgs: [Vec<u8>; 2],
+        signatures: [Vec<u8>; 2],
+        same_signer: bool,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestRSASameSignerCircuit<F> {
+        const BITS_LEN: usize = Mainnet2048::BITS_LEN;
+        const MSG_LEN: usize = 256;
+        const DEFAULT_E: u128 = 65537;
+        const K: u32 = Mainnet2048::K;
+
+        fn new(private_keys: [&RsaPrivateKey; 2], msgs: [Vec<u8>; 2]) -> Self {
+            let sign = |private_key: &RsaPrivateKey, msg: &[u8]| {
+                SigningKey::<rsa::sha2::Sha256>::new(private_key.clone())
+                    .sign(msg)
+                    .to_vec()
+            };
+            let public_keys = private_keys.map(RsaPublicKey::from);
+            Self {
+                same_signer: public_keys[0] == public_keys[1],
+                public_keys,
+                signatures: [
+                    sign(private_keys[0], &msgs[0]),
+                    sign(private_keys[1], &msgs[1]),
+                ],
+                msgs,
+                _f: PhantomData,
+            }
+        }
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestRSASameSignerCircuit<F> {
+        type Config = TestRSAPssSignatureConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (rsa_config, sha256_config) =
+                configure_rsa_sha256::<F, Mainnet2048>(meta, vec![Self::MSG_LEN, Self::MSG_LEN]);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa same signer test with 2048 bits public keys",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let mut public_keys = vec![];
+                    let mut signatures = vec![];
+                    for (public_key, signature) in
+                        self.public_keys.iter().zip(self.signatures.iter())
+                    {
+                        let sign_big = BigUint::from_bytes_be(signature);
+                        signatures.push(
+                            config
+                                .rsa_config
+                                .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?,
+                        );
+                        let n_big =
+                            BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap();
+                        let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                        public_keys.push(config.rsa_config.assign_public_key(
+                            ctx,
+                            RSAPublicKey::new(Value::known(n_big), e_fix),
+                        )?);
+                    }
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let (is_valid, same_signer, _) = verifier
+                        .verify_pkcs1v15_signatures_same_signer(
+                            ctx,
+                            [&public_keys[0], &public_keys[1]],
+                            [&self.msgs[0], &self.msgs[1]],
+                            [&signatures[0], &signatures[1]],
+                        )?;
+                    let gate = biguint_config.gate();
+                    gate.assert_is_const(ctx, &is_valid, F::one());
+                    gate.assert_is_const(ctx, &same_signer, F::from(self.same_signer as u64));
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_rsa_signatures_same_signer() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let bits_len = TestRSASameSignerCircuit::<F>::BITS_LEN;
+            let private_key =
+                RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+            let private_key2 =
+                RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+            let msgs = [
+                (0..128).map(|_| rng.gen()).collect::<Vec<u8>>(),
+                (0..128).map(|_| rng.gen()).collect::<Vec<u8>>(),
+            ];
+            let k = TestRSASameSignerCircuit::<F>::K;
+
+            let circuit =
+                TestRSASameSignerCircuit::<F>::new([&private_key, &private_key], msgs.clone());
+            assert!(circuit.same_signer);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            let circuit = TestRSASameSignerCircuit::<F>::new([&private_key, &private_key2], msgs);
+            assert!(!circuit.same_signer);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // The bit is constrained, not only computed.
+            let circuit = TestRSASameSignerCircuit::<F> {
+                same_signer: true,
+                ..circuit
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    /// A circuit verifying the signatures of three messages by the same key, assigned once if `share_key` is true and once per signature otherwise.
+    #[derive(Debug, Clone)]
+    struct TestRSASharedKeyCircuit<F: PrimeField> {
+        public_key: RsaPublicKey,
+        msgs: [Vec<u8>; 3],
+        signatures: [Vec<u8>; 3],
+        share_key: bool,
+        /// The numbers of advice cells and lookup cells recorded by the last synthesis, and those of one key assignment.
+        cost: Rc<RefCell<Option<((usize, usize), (usize, usize))>>>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestRSASharedKeyCircuit<F> {
+        const BITS_LEN: usize = Mainnet2048::BITS_LEN;
+        const MSG_LEN: usize = 64;
+        const K: u32 = Mainnet2048::K;
+
+        fn new(private_key: &RsaPrivateKey, msgs: [Vec<u8>; 3], share_key: bool) -> Self {
+            let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone());
+            Self {
+                public_key: RsaPublicKey::from(private_key),
+                signatures: msgs.clone().map(|msg| signing_key.sign(&msg).to_vec()),
+                msgs,
+                share_key,
+                cost: Rc::new(RefCell::new(None)),
+                _f: PhantomData,
+            }
+        }
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestRSASharedKeyCircuit<F> {
+        type Config = TestRSAPssSignatureConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (rsa_config, sha256_config) =
+                configure_rsa_sha256::<F, Mainnet2048>(meta, vec![Self::MSG_LEN; 3]);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa shared key test with 2048 bits public keys",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let signatures = self
+                        .signatures
+                        .iter()
+                        .map(|signature| {
+                            let sign_big = BigUint::from_bytes_be(signature);
+                            config
+                                .rsa_config
+                                .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+                    let cells = |ctx: &Context<F>| (ctx.total_advice, ctx.cells_to_lookup.len());
+                    let before = cells(ctx);
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::from(&self.public_key))?;
+                    let after = cells(ctx);
+                    let key_cost = (after.0 - before.0, after.1 - before.1);
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let results = if self.share_key {
+                        verifier.verify_pkcs1v15_signatures(
+                            ctx,
+                            &public_key,
+                            &[&self.msgs[0], &self.msgs[1], &self.msgs[2]],
+                            &[&signatures[0], &signatures[1], &signatures[2]],
+                            HashAlg::Sha256,
+                        )?
+                    } else {
+                        let mut results = vec![];
+                        for (i, (msg, signature)) in
+                            self.msgs.iter().zip(signatures.iter()).enumerate()
+                        {
+                            let public_key = if i == 0 {
+                                public_key.clone()
+                            } else {
+                                config
+                                    .rsa_config
+                                    .assign_public_key(ctx, RSAPublicKey::from(&self.public_key))?
+                            };
+                            results.push(verifier.verify_pkcs1v15_signature(
+                                ctx,
+                                &public_key,
+                                msg,
+                                signature,
+                                HashAlg::Sha256,
+                            )?);
+                        }
+                        results
+                    };
+                    let gate = biguint_config.gate();
+                    for result in results.iter() {
+                        gate.assert_is_const(ctx, &result.is_valid, F::one());
+                    }
+                    biguint_config.range().finalize(ctx);
+                    *self.cost.borrow_mut() = Some((cells(ctx), key_cost));
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_rsa_signatures_shared_key() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let private_key = RsaPrivateKey::new(&mut rng, TestRSASharedKeyCircuit::<F>::BITS_LEN)
+                .expect("failed to generate a key");
+            let msgs = [(); 3].map(|_| (0..48).map(|_| rng.gen()).collect::<Vec<u8>>());
+            let k = TestRSASharedKeyCircuit::<F>::K;
+
+            let shared = TestRSASharedKeyCircuit::<F>::new(&private_key, msgs.clone(), true);
+            let prover = MockProver::run(k, &shared, vec![]).unwrap();
+            prover.verify().unwrap();
+            let separate = TestRSASharedKeyCircuit::<F>::new(&private_key, msgs.clone(), false);
+            let prover = MockProver::run(k, &separate, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // The shared key saves exactly the two extra assignments of the modulus and their range checks.
+            let (shared_cells, key_cells) = shared.cost.borrow().unwrap();
+            let (separate_cells, _) = separate.cost.borrow().unwrap();
+            assert!(key_cells.0 > 0 && key_cells.1 > 0);
+            assert_eq!(separate_cells.0 - shared_cells.0, 2 * key_cells.0);
+            assert_eq!(separate_cells.1 - shared_cells.1, 2 * key_cells.1);
+
+            // A signature of another message is rejected by the shared key.
+            let mut circuit = TestRSASharedKeyCircuit::<F>::new(&private_key, msgs, true);
+            circuit.signatures.swap(0, 1);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    /// A circuit verifying the signature of `msg` by the UIDAI key and that of `time_msg` by a timestamp oracle, and checking that the oracle time is within a year of the signing time of `msg`.
+    ///
+    /// `msg` stands in for the QR data, whose signing time `YYYYMMDDhh` are its first [`field_extractor::TIME_LEN`] bytes.
+    #[derive(Debug, Clone)]
+    struct TestOracleTimeCircuit<F: PrimeField> {
+        public_key: RsaPublicKey,
+        msg: Vec<u8>,
+        signature: Vec<u8>,
+        oracle_key: RsaPublicKey,
+        time_msg: Vec<u8>,
+        oracle_signature: Vec<u8>,
+        /// The expected year, month, day and hour of the oracle time.
+        time: [u64; 4],
+        /// The expected bit of the oracle time being within a year of the signing time.
+        is_fresh: bool,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestOracleTimeCircuit<F> {
+        const BITS_LEN: usize = Mainnet2048::BITS_LEN;
+        const MSG_LEN: usize = 64;
+        const K: u32 = Mainnet2048::K;
+
+        fn new(
+            private_key: &RsaPrivateKey,
+            msg: Vec<u8>,
+            oracle_private_key: &RsaPrivateKey,
+            time_msg: Vec<u8>,
+            time: [u64; 4],
+        ) -> Self {
+            let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone());
+            let oracle_signing_key =
+                SigningKey::<rsa::sha2::Sha256>::new(oracle_private_key.clone());
+            Self {
+                public_key: RsaPublicKey::from(private_key),
+                signature: signing_key.sign(&msg).to_vec(),
+                msg,
+                oracle_key: RsaPublicKey::from(oracle_private_key),
+                oracle_signature: oracle_signing_key.sign(&time_msg).to_vec(),
+                time_msg,
+                time,
+                is_fresh: true,
+                _f: PhantomData,
+            }
+        }
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestOracleTimeCircuit<F> {
+        type Config = TestRSAPssSignatureConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (rsa_config, sha256_config) =
+                configure_rsa_sha256::<F, Mainnet2048>(meta, vec![Self::MSG_LEN; 2]);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa oracle time test with 2048 bits public keys",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let mut assign = |key: &RsaPublicKey, signature: &[u8]| {
+                        let sign_big = BigUint::from_bytes_be(signature);
+                        let signature = config
+                            .rsa_config
+                            .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                        let public_key = config
+                            .rsa_config
+                            .assign_public_key(ctx, RSAPublicKey::from(key))?;
+                        Ok::<_, Error>((public_key, signature))
+                    };
+                    let (public_key, signature) = assign(&self.public_key, &self.signature)?;
+                    let (oracle_key, oracle_signature) =
+                        assign(&self.oracle_key, &self.oracle_signature)?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let result = verifier.verify_pkcs1v15_signature(
+                        ctx,
+                        &public_key,
+                        &self.msg,
+                        &signature,
+                        HashAlg::Sha256,
+                    )?;
+                    let oracle_time = verifier.verify_oracle_time(
+                        ctx,
+                        &oracle_key,
+                        &self.time_msg,
+                        &oracle_signature,
+                        HashAlg::Sha256,
+                    )?;
+                    let gate = biguint_config.gate();
+                    gate.assert_is_const(ctx, &result.is_valid, F::one());
+                    gate.assert_is_const(ctx, &oracle_time.signature.is_valid, F::one());
+                    for (value, expected) in oracle_time.time.iter().zip(self.time) {
+                        gate.assert_is_const(ctx, value, F::from(expected));
+                    }
+
+                    // The verified time is compared for freshness against the signed one, so neither is chosen by the prover.
+                    let chip = field_extractor::FieldExtractorChip::construct(
+                        biguint_config.range().clone(),
+                    );
+                    let signed_time =
+                        chip.parse_time(ctx, &result.msg[..field_extractor::TIME_LEN])?;
+                    let is_fresh = chip.is_fresh(ctx, &oracle_time.time, &signed_time, 1);
+                    gate.assert_is_const(ctx, &is_fresh, F::from(self.is_fresh as u64));
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_oracle_time() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let bits_len = TestOracleTimeCircuit::<F>::BITS_LEN;
+            let private_key =
+                RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+            let oracle_private_key =
+                RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+            let mut msg = b"2024071812".to_vec();
+            msg.extend((0..38).map(|_| rng.gen::<u8>()));
+            let time_msg = b"2024071812".to_vec();
+            let time = [2024, 7, 18, 12];
+            let k = TestOracleTimeCircuit::<F>::K;
+
+            let circuit = TestOracleTimeCircuit::<F>::new(
+                &private_key,
+                msg.clone(),
+                &oracle_private_key,
+                time_msg.clone(),
+                time,
+            );
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // A time a year after the signing time is stale, and cannot be claimed fresh.
+            let mut stale = TestOracleTimeCircuit::<F>::new(
+                &private_key,
+                msg.clone(),
+                &oracle_private_key,
+                b"2025071812".to_vec(),
+                [2025, 7, 18, 12],
+            );
+            let prover = MockProver::run(k, &stale, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+            stale.is_fresh = false;
+            let prover = MockProver::run(k, &stale, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // So is the same time against data signed later.
+            let mut later = msg.clone();
+            later[..field_extractor::TIME_LEN].copy_from_slice(b"2024071813");
+            let mut early = TestOracleTimeCircuit::<F>::new(
+                &private_key,
+                later,
+                &oracle_private_key,
+                time_msg.clone(),
+                time,
+            );
+            early.is_fresh = false;
+            let prover = MockProver::run(k, &early, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // Another time than the signed one is rejected.
+            let mut forged = circuit.clone();
+            forged.time_msg = b"2023071812".to_vec();
+            forged.time = [2023, 7, 18, 12];
+            let prover = MockProver::run(k, &forged, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+
+            // A time signed by the UIDAI key instead of the oracle is rejected by the oracle key.
+            let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone());
+            let mut wrong_signer = circuit;
+            wrong_signer.oracle_signature = signing_key.sign(&time_msg).to_vec();
+            let prover = MockProver::run(k, &wrong_signer, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    /// A circuit ANDing the validity bit of the signature of `msg` with an identity bit, that the first byte of `msg` is `gender`, and a freshness bit, that `time` is within a year of the signing time `YYYYMMDDhh` following the gender in `msg`.
+    #[derive(Debug, Clone)]
+    struct TestProofValidCircuit<F: PrimeField> {
+        public_key: RsaPublicKey,
+        msg: Vec<u8>,
+        signature: Vec<u8>,
+        gender: u8,
+        /// The current time, e.g. verified by [`RSASignatureVerifier::verify_oracle_time`].
+        time: [u64; 4],
+        /// The expected value of the overall validity bit.
+        proof_valid: bool,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestProofValidCircuit<F> {
+        const BITS_LEN: usize = Mainnet2048::BITS_LEN;
+        const MSG_LEN: usize = 64;
+        const K: u32 = Mainnet2048::K;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestProofValidCircuit<F> {
+        type Config = TestRSAPssSignatureConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (rsa_config, sha256_config) =
+                configure_rsa_sha256::<F, Mainnet2048>(meta, vec![Self::MSG_LEN]);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa proof validity test with 2048 bits public keys",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let sign_big = BigUint::from_bytes_be(&self.signature);
+                    let signature = config
+                        .rsa_config
+                        .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::from(&self.public_key))?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let result = verifier.verify_pkcs1v15_signature(
+                        ctx,
+                        &public_key,
+                        &self.msg,
+                        &signature,
+                        HashAlg::Sha256,
+                    )?;
+
+                    let gate = biguint_config.gate();
+                    let identity_valid = gate.is_equal(
+                        ctx,
+                        QuantumCell::Existing(&result.msg[0]),
+                        QuantumCell::Constant(F::from(self.gender as u64)),
+                    );
+                    let time = self
+                        .time
+                        .map(|value| gate.load_witness(ctx, Value::known(F::from(value))));
+                    let chip = field_extractor::FieldExtractorChip::construct(
+                        biguint_config.range().clone(),
+                    );
+                    let signed_time =
+                        chip.parse_time(ctx, &result.msg[1..1 + field_extractor::TIME_LEN])?;
+                    let fresh = chip.is_fresh(ctx, &time, &signed_time, 1);
+
+                    let proof_valid = verifier.all_valid(ctx, &result, &[identity_valid, fresh]);
+                    gate.assert_is_const(ctx, &proof_valid, F::from(self.proof_valid as u64));
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_all_valid() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let private_key = RsaPrivateKey::new(&mut rng, TestProofValidCircuit::<F>::BITS_LEN)
+                .expect("failed to generate a key");
+            let msg = b"M2024071812 110051 Delhi".to_vec();
+            let signing_key = SigningKey::<rsa::sha2::Sha256>::new(private_key.clone());
+            let circuit = TestProofValidCircuit::<F> {
+                public_key: RsaPublicKey::from(&private_key),
+                signature: signing_key.sign(&msg).to_vec(),
+                msg,
+                gender: b'M',
+                time: [2025, 1, 1, 0],
+                proof_valid: true,
+                _f: PhantomData,
+            };
+            let k = TestProofValidCircuit::<F>::K;
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // Any bit of zero makes the proof invalid, which cannot be claimed valid.
+            let mut invalid_signature = circuit.clone();
+            *invalid_signature.signature.last_mut().unwrap() ^= 1;
+            let mut other_identity = circuit.clone();
+            other_identity.gender = b'F';
+            let mut stale = circuit.clone();
+            stale.time = [2025, 7, 18, 12];
+            for mut invalid in [invalid_signature, other_identity, stale] {
+                let prover = MockProver::run(k, &invalid, vec![]).unwrap();
+                assert!(prover.verify().is_err());
+                invalid.proof_valid = false;
+                let prover = MockProver::run(k, &invalid, vec![]).unwrap();
+                prover.verify().unwrap();
+            }
+        }
+        run::<Fr>();
+    }
+
+    /// A circuit constraining the modulus cells returned by [`RSASignatureVerifier::verify_pkcs1v15_signature`] equal to a key assigned apart, as a key registry would commit to.
+    #[derive(Debug, Clone)]
+    struct TestRSAModulusCellsCircuit<F: PrimeField> {
+        public_key: RsaPublicKey,
+        registry_key: RsaPublicKey,
+        msg: Vec<u8>,
+        signature: Vec<u8>,
+        _f: PhantomData<F>,
+    }
+
+    impl<F: PrimeField> TestRSAModulusCellsCircuit<F> {
+        const BITS_LEN: usize = Mainnet2048::BITS_LEN;
+        const MSG_LEN: usize = 256;
+        const DEFAULT_E: u128 = 65537;
+        const K: u32 = Mainnet2048::K;
+    }
+
+    impl<F: PrimeField> Circuit<F> for TestRSAModulusCellsCircuit<F> {
+        type Config = TestRSAPssSignatureConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
 
-impl<F: PrimeField> TestRSASignatureWithHashCircuit1<F> {
-    pub fn new(private_key: RsaPrivateKey, public_key: RsaPublicKey, msg: Vec<u8>) -> Self {
-        Self {
-            private_key,
-            public_key,
-            msg,
-            _f: PhantomData,
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (rsa_config, sha256_config) =
+                configure_rsa_sha256::<F, Mainnet2048>(meta, vec![Self::MSG_LEN]);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa modulus cells test with 2048 bits public keys",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let sign_big = BigUint::from_bytes_be(&self.signature);
+                    let sign = config
+                        .rsa_config
+                        .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                    let mut assign_key = |key: &RsaPublicKey| {
+                        let n_big = BigUint::from_radix_le(&key.n().to_radix_le(16), 16).unwrap();
+                        let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                        config
+                            .rsa_config
+                            .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))
+                    };
+                    let public_key = assign_key(&self.public_key)?;
+                    let registry_key = assign_key(&self.registry_key)?;
+                    let mut verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let result = verifier.verify_pkcs1v15_signature(
+                        ctx,
+                        &public_key,
+                        &self.msg,
+                        &sign,
+                        HashAlg::Sha256,
+                    )?;
+                    let gate = biguint_config.gate();
+                    gate.assert_is_const(ctx, &result.is_valid, F::one());
+                    for (limb, registry_limb) in
+                        result.modulus_limbs.iter().zip(registry_key.n.limbs())
+                    {
+                        gate.assert_equal(
+                            ctx,
+                            QuantumCell::Existing(limb),
+                            QuantumCell::Existing(registry_limb),
+                        );
+                    }
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )
         }
     }
-}
 
-impl<F: PrimeField> Circuit<F> for TestRSASignatureWithHashCircuit1<F> {
-    type Config = TestRSASignatureWithHashConfig1<F>;
-    type FloorPlanner = SimpleFloorPlanner;
+    #[test]
+    fn test_rsa_signature_modulus_cells() {
+        fn run<F: PrimeField>() {
+            let mut rng = thread_rng();
+            let bits_len = TestRSAModulusCellsCircuit::<F>::BITS_LEN;
+            let private_key =
+                RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let signature = SigningKey::<rsa::sha2::Sha256>::new(private_key)
+                .sign(&msg)
+                .to_vec();
+            let k = TestRSAModulusCellsCircuit::<F>::K;
 
-    fn without_witnesses(&self) -> Self {
-        unimplemented!();
+            let circuit = TestRSAModulusCellsCircuit::<F> {
+                public_key: public_key.clone(),
+                registry_key: public_key,
+                msg,
+                signature,
+                _f: PhantomData,
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            // A valid signature under a key other than the registered one is rejected.
+            let other_key =
+                RsaPrivateKey::new(&mut rng, bits_len).expect("failed to generate a key");
+            let circuit = TestRSAModulusCellsCircuit::<F> {
+                registry_key: RsaPublicKey::from(&other_key),
+                ..circuit
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
     }
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let range_config = RangeConfig::configure(
-            meta,
-            Vertical,
-            &[Self::NUM_ADVICE],
-            &[Self::NUM_LOOKUP_ADVICE],
-            Self::NUM_FIXED,
-            Self::LOOKUP_BITS,
-            0,
-            15,
-        );
-        let bigint_config = BigUintConfig::construct(range_config.clone(), 64);
-        let rsa_config = RSAConfig::construct(bigint_config, Self::BITS_LEN, Self::EXP_LIMB_BITS);
-        let sha256_config = Sha256DynamicConfig::configure(
-            meta,
-            vec![Self::MSG_LEN],
-            range_config,
-            Self::SHA256_LOOKUP_BITS,
-            Self::SHA256_LOOKUP_ADVICE,
-            true,
-        );
-        let n_instance = meta.instance_column();
-        let hash_instance = meta.instance_column();
-        meta.enable_equality(n_instance);
-        meta.enable_equality(hash_instance);
-        Self::Config {
-            rsa_config,
-            sha256_config,
-            n_instance,
-            hash_instance,
+    /// The SHA-1 digests of the messages of FIPS 180-2, the second of which takes two blocks.
+    #[cfg(feature = "legacy-sha1")]
+    const SHA1_TEST_VECTORS: [(&[u8], &str); 3] = [
+        (b"", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+        (b"abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+        (
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmjklmnklmnlmnomnopnopq",
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+        ),
+    ];
+
+    #[cfg(feature = "legacy-sha1")]
+    #[test]
+    fn test_sha1_digest() {
+        fn run<F: PrimeField>() {
+            let k = TestRSAConfigCircuit::<F>::K;
+            for (msg, digest) in SHA1_TEST_VECTORS {
+                let digest = BigUint::parse_bytes(digest.as_bytes(), 16)
+                    .unwrap()
+                    .to_bytes_be();
+                let circuit = TestRSAConfigCircuit::new(Rc::new(
+                    move |config: &RSAConfig<F>, ctx: &mut Context<F>| {
+                        let sha1 = sha1::Sha1Config::construct(config.range().clone());
+                        let result = sha1.digest(ctx, msg);
+                        assert_eq!(result.input_bytes.len(), msg.len());
+                        for (assigned, byte) in result.output_bytes.iter().zip(digest.iter()) {
+                            config
+                                .gate()
+                                .assert_is_const(ctx, assigned, F::from(*byte as u64));
+                        }
+                        Ok(())
+                    },
+                ));
+                let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+                prover.verify().unwrap();
+            }
         }
+        run::<Fr>();
     }
 
-    fn synthesize(
-        &self,
-        config: Self::Config,
-        mut layouter: impl Layouter<F>,
-    ) -> Result<(), Error> {
-        let biguint_config = config.rsa_config.biguint_config();
-        config.sha256_config.load(&mut layouter)?;
-        biguint_config.range().load_lookup_table(&mut layouter)?;
-        let mut first_pass = SKIP_FIRST_PASS;
-        let (public_key_cells, hashed_msg_cells) = layouter.assign_region(
-            || "random rsa modpow test with 2048 bits public keys",
-            |region| {
-                if first_pass {
-                    first_pass = false;
-                    return Ok((vec![], vec![]));
-                }
+    /// A circuit verifying a pkcs1v15 signature with SHA-1 of a legacy QR code.
+    #[cfg(feature = "legacy-sha1")]
+    #[derive(Debug, Clone)]
+    struct TestRSASha1SignatureCircuit<F: PrimeField> {
+        public_key: RsaPublicKey,
+        msg: Vec<u8>,
+        signature: Vec<u8>,
+        expect_valid: bool,
+        _f: PhantomData<F>,
+    }
 
-                let mut aux = biguint_config.new_context(region);
-                let ctx = &mut aux;
-                let signing_key = SigningKey::<rsa::sha2::Sha256>::new(self.private_key.clone());
-                let sign = signing_key.sign(&self.msg).to_vec();
-                let sign_big = BigUint::from_bytes_be(&sign);
-                let sign = config
-                    .rsa_config
-                    .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
-                let n_big =
-                    BigUint::from_radix_le(&self.public_key.n().clone().to_radix_le(16), 16)
-                        .unwrap();
-                let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
-                let public_key = config
-                    .rsa_config
-                    .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
-                let mut verifier = RSASignatureVerifier::new(
-                    config.rsa_config.clone(),
-                    config.sha256_config.clone(),
-                );
-                let (is_valid, hashed_msg) =
-                    verifier.verify_pkcs1v15_signature(ctx, &public_key, &self.msg, &sign)?;
-                biguint_config
-                    .gate()
-                    .assert_is_const(ctx, &is_valid, F::one());
-                biguint_config.range().finalize(ctx);
-                {
-                    println!("total advice cells: {}", ctx.total_advice);
-                    let const_rows = ctx.total_fixed + 1;
-                    println!("maximum rows used by a fixed column: {const_rows}");
-                    println!("lookup cells used: {}", ctx.cells_to_lookup.len());
-                }
-                let public_key_cells = public_key
-                    .n
-                    .limbs()
-                    .into_iter()
-                    .map(|v| v.cell())
-                    .collect::<Vec<Cell>>();
-                let hashed_msg_cells = hashed_msg
-                    .into_iter()
-                    .map(|v| v.cell())
-                    .collect::<Vec<Cell>>();
-                Ok((public_key_cells, hashed_msg_cells))
-            },
-        )?;
-        for (i, cell) in public_key_cells.into_iter().enumerate() {
-            layouter.constrain_instance(cell, config.n_instance, i)?;
+    #[cfg(feature = "legacy-sha1")]
+    impl<F: PrimeField> TestRSASha1SignatureCircuit<F> {
+        const BITS_LEN: usize = Mainnet2048::BITS_LEN;
+        const MSG_LEN: usize = 64;
+        const DEFAULT_E: u128 = 65537;
+        const K: u32 = Mainnet2048::K;
+    }
+
+    #[cfg(feature = "legacy-sha1")]
+    impl<F: PrimeField> Circuit<F> for TestRSASha1SignatureCircuit<F> {
+        type Config = TestRSAPssSignatureConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
         }
-        for (i, cell) in hashed_msg_cells.into_iter().enumerate() {
-            layouter.constrain_instance(cell, config.hash_instance, i)?;
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let (rsa_config, sha256_config) =
+                configure_rsa_sha256::<F, Mainnet2048>(meta, vec![Self::MSG_LEN]);
+            Self::Config {
+                rsa_config,
+                sha256_config,
+            }
         }
-        Ok(())
-    }
-}
 
-#[cfg(feature = "sha256")]
-#[cfg(test)]
-mod test {
-    use super::*;
-    use crate::big_uint::decompose_biguint;
-    use crate::conditional_secrets::IdentityCircuit;
-    use crate::signal::SquareCircuit;
-    use crate::timestamp::TimestampCircuit;
-    use halo2_base::halo2_proofs::halo2curves::pasta::Fp;
-    use halo2_base::halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
-    use halo2curves::bn256::Fr as FR;
-    use poseidon::Poseidon;
-    use rand::{thread_rng, Rng};
-    use rsa::{traits::PublicKeyParts, RsaPrivateKey, RsaPublicKey};
-    use sha2::{Digest, Sha256};
-    use std::time::Instant;
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let biguint_config = config.rsa_config.biguint_config();
+            config.sha256_config.load(&mut layouter)?;
+            biguint_config.range().load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "rsa sha1 signature test with 2048 bits public keys",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = biguint_config.new_context(region);
+                    let ctx = &mut aux;
+                    let sign_big = BigUint::from_bytes_be(&self.signature);
+                    let sign = config
+                        .rsa_config
+                        .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                    let n_big =
+                        BigUint::from_radix_le(&self.public_key.n().to_radix_le(16), 16).unwrap();
+                    let e_fix = RSAPubE::Fix(BigUint::from(Self::DEFAULT_E));
+                    let public_key = config
+                        .rsa_config
+                        .assign_public_key(ctx, RSAPublicKey::new(Value::known(n_big), e_fix))?;
+                    let verifier = RSASignatureVerifier::new(
+                        config.rsa_config.clone(),
+                        config.sha256_config.clone(),
+                    );
+                    let result = verifier.verify_pkcs1v15_signature(
+                        ctx,
+                        &public_key,
+                        &self.msg,
+                        &sign,
+                        HashAlg::Sha1,
+                    )?;
+                    biguint_config.gate().assert_is_const(
+                        ctx,
+                        &result.is_valid,
+                        F::from(self.expect_valid as u64),
+                    );
+                    biguint_config.range().finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
 
+    #[cfg(feature = "legacy-sha1")]
     #[test]
-    fn test_rsa_signature_with_hash_circuit1() {
+    fn test_rsa_signature_sha1() {
         fn run<F: PrimeField>() {
             let mut rng = thread_rng();
             let private_key =
-                RsaPrivateKey::new(&mut rng, TestRSASignatureWithHashCircuit1::<F>::BITS_LEN)
+                RsaPrivateKey::new(&mut rng, TestRSASha1SignatureCircuit::<F>::BITS_LEN)
                     .expect("failed to generate a key");
             let public_key = RsaPublicKey::from(&private_key);
-            let n = BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap();
-            let mut msg: [u8; 128] = [0; 128];
-            for i in 0..128 {
-                msg[i] = rng.gen();
-            }
-            let hashed_msg = Sha256::digest(&msg);
-            let circuit = TestRSASignatureWithHashCircuit1::<F> {
-                private_key,
+            // The DigestInfo of SHA-1 followed by the digest of "abc", signed as is.
+            let (msg, digest) = SHA1_TEST_VECTORS[1];
+            let digest_info = [
+                vec![
+                    0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00,
+                    0x04, 0x14,
+                ],
+                BigUint::parse_bytes(digest.as_bytes(), 16)
+                    .unwrap()
+                    .to_bytes_be(),
+            ]
+            .concat();
+            let signature = private_key
+                .sign(rsa::Pkcs1v15Sign::new_unprefixed(), &digest_info)
+                .unwrap();
+            let k = TestRSASha1SignatureCircuit::<F>::K;
+
+            let circuit = TestRSASha1SignatureCircuit::<F> {
                 public_key,
                 msg: msg.to_vec(),
+                signature,
+                expect_valid: true,
                 _f: PhantomData,
             };
-            let num_limbs = 2048 / 64;
-            let limb_bits = 64;
-            let n_fes = decompose_biguint::<F>(&n, num_limbs, limb_bits);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            let circuit = TestRSASha1SignatureCircuit::<F> {
+                msg: b"abd".to_vec(),
+                expect_valid: false,
+                ..circuit
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.verify().unwrap();
+
+            let circuit = TestRSASha1SignatureCircuit::<F> {
+                expect_valid: true,
+                ..circuit
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        run::<Fr>();
+    }
+
+    #[test]
+    fn test_sha256_digest_info() {
+        // The DER encoding of the DigestInfo of SHA-256 in Note 1 of RFC 3447, section 9.2.
+        let rfc3447 = [
+            0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x01, 0x05, 0x00, 0x04, 0x20,
+        ];
+        assert_eq!(SHA256_DIGEST_INFO, rfc3447);
+        assert_eq!(HashAlg::Sha256.digest_info_prefix(), rfc3447.as_slice());
+        let scheme = rsa::Pkcs1v15Sign::new::<rsa::sha2::Sha256>();
+        assert_eq!(&*scheme.prefix, SHA256_DIGEST_INFO.as_slice());
+    }
+
+    #[test]
+    fn test_hash_alg() {
+        let alg = HashAlg::Sha256;
+        assert_eq!(alg.digest_len(), 32);
+        assert_eq!(alg.digest_info_prefix(), SHA256_DIGEST_INFO.as_slice());
+        // The DigestInfo ends with the OCTET STRING header of the digest.
+        let prefix = alg.digest_info_prefix();
+        assert_eq!(prefix[prefix.len() - 2..], [0x04, alg.digest_len() as u8]);
+
+        #[cfg(feature = "legacy-sha1")]
+        {
+            let alg = HashAlg::Sha1;
+            assert_eq!(alg.digest_len(), 20);
+            assert_eq!(
+                alg.digest_info_prefix(),
+                [
+                    0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00,
+                    0x04, 0x14,
+                ]
+                .as_slice()
+            );
+            let prefix = alg.digest_info_prefix();
+            assert_eq!(prefix[prefix.len() - 2..], [0x04, alg.digest_len() as u8]);
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct WideSha256Params;
+
+    impl Sha256Params for WideSha256Params {
+        const LOOKUP_BITS: usize = 8;
+        const NUM_LOOKUP_ADVICE: usize = 16;
+    }
+
+    #[test]
+    fn test_rsa_signature_with_sha256_params() {
+        fn run<F: PrimeField, P: Sha256Params>() {
+            let k = 15;
+            TestRSASignatureWithHashCircuit::<F, Tuned<P>>::validate(k).unwrap();
+            let mut rng = thread_rng();
+            let private_key = RsaPrivateKey::new(
+                &mut rng,
+                TestRSASignatureWithHashCircuit::<F, Tuned<P>>::BITS_LEN,
+            )
+            .expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let n = BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap();
+            let msg = (0..128).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let hashed_msg = Sha256::digest(&msg);
+            let circuit =
+                TestRSASignatureWithHashCircuit::<F, Tuned<P>>::new(private_key, public_key, msg);
+            let n_fes = decompose_biguint::<F>(&n, 2048 / 64, 64);
             let hash_fes = hashed_msg
                 .iter()
                 .map(|byte| F::from(*byte as u64))
                 .collect::<Vec<F>>();
-            let public_inputs = vec![n_fes, hash_fes];
-            let k = 15;
-            let prover = match MockProver::run(k, &circuit, public_inputs) {
+            let prover = match MockProver::run(k, &circuit, vec![n_fes, hash_fes]) {
                 Ok(prover) => prover,
                 Err(e) => panic!("{:#?}", e),
             };
             prover.verify().unwrap();
         }
-        run::<Fr>();
+        run::<Fr, DefaultSha256Params>();
+        run::<Fr, WideSha256Params>();
+    }
+
+    #[derive(Clone, Debug)]
+    struct OversizedRangeParams;
+
+    impl RangeParams for OversizedRangeParams {
+        const LOOKUP_BITS: usize = 15;
+    }
+
+    type OversizedRangeCircuit<const K: u32> =
+        TestRSASignatureWithHashCircuit<Fr, Tuned<DefaultSha256Params, OversizedRangeParams, K>>;
+
+    #[test]
+    fn test_rsa_signature_with_range_params() {
+        let mut rng = thread_rng();
+        let private_key =
+            RsaPrivateKey::new(&mut rng, TestRSASignatureWithHashCircuit1::<Fr>::BITS_LEN)
+                .expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let msg = b"hello".to_vec();
+
+        assert!(TestRSASignatureWithHashCircuit1::<Fr>::try_new(
+            private_key.clone(),
+            public_key.clone(),
+            msg.clone()
+        )
+        .is_ok());
+
+        // A table of 2^15 rows does not fit in 2^15 rows, which is reported before any key generation.
+        let err = OversizedRangeCircuit::<15>::try_new(
+            private_key.clone(),
+            public_key.clone(),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ParamsError::LookupBitsTooLarge {
+                lookup_bits: 15,
+                k: 15
+            }
+        );
+        assert!(err
+            .to_string()
+            .contains("lookup bits 15 must be less than k = 15"));
+
+        // It fits in a larger circuit.
+        assert!(OversizedRangeCircuit::<16>::try_new(private_key, public_key, msg).is_ok());
+    }
+
+    /// [`Mainnet2048`] with the SHA256 chip configured for messages of up to 700 bytes, fewer than the signed region of a QR code.
+    #[derive(Clone, Debug)]
+    struct ShortMsgPreset;
+
+    impl Preset for ShortMsgPreset {
+        type Sha256 = DefaultSha256Params;
+        type Range = DefaultRangeParams;
+        const K: u32 = Mainnet2048::K;
+        const BITS_LEN: usize = Mainnet2048::BITS_LEN;
+        const MSG_LEN: usize = 700;
+        const NUM_ADVICE: usize = Mainnet2048::NUM_ADVICE;
+        const NUM_LOOKUP_ADVICE: usize = Mainnet2048::NUM_LOOKUP_ADVICE;
+        const NUM_FIXED: usize = Mainnet2048::NUM_FIXED;
+    }
+
+    #[test]
+    fn test_rsa_signature_msg_len() {
+        let mut rng = thread_rng();
+        let private_key = RsaPrivateKey::new(&mut rng, ShortMsgPreset::BITS_LEN)
+            .expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let msg = (0..1015).map(|_| rng.gen()).collect::<Vec<u8>>();
+
+        // The message is rejected when the circuit is constructed, with both lengths to fix the preset.
+        let err = TestRSASignatureWithHashCircuit::<Fr, ShortMsgPreset>::try_new(
+            private_key.clone(),
+            public_key.clone(),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ParamsError::MessageTooLong {
+                msg_len: 1015,
+                max_msg_len: 700
+            }
+        );
+
+        // It fits in the 1024 bytes of the mainnet preset.
+        assert!(
+            TestRSASignatureWithHashCircuit1::<Fr>::try_new(private_key, public_key, msg).is_ok()
+        );
+
+        // The verifier of a configured circuit reports the same error before the synthesis.
+        let mut meta = ConstraintSystem::<Fr>::default();
+        let config = TestRSASignatureWithHashCircuit::<Fr, ShortMsgPreset>::configure(&mut meta);
+        let verifier = RSASignatureVerifier::new(config.rsa_config, config.sha256_config);
+        assert_eq!(verifier.check_msg_len(1015), Err(err));
+        assert_eq!(verifier.check_msg_len(600), Ok(()));
+
+        // A circuit constructed without the check fails in the synthesis with an error rather than in the SHA256 chip.
+        let private_key =
+            RsaPrivateKey::new(&mut rng, Testing::BITS_LEN).expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let msg = (0..Testing::MSG_LEN + 1)
+            .map(|_| rng.gen())
+            .collect::<Vec<u8>>();
+        let circuit =
+            TestRSASignatureWithHashCircuit::<Fr, Testing>::new(private_key, public_key, msg);
+        assert!(MockProver::run(Testing::K, &circuit, circuit.instances()).is_err());
+    }
+
+    #[test]
+    fn test_presets() {
+        fn run<C: Preset>() {
+            C::validate().unwrap();
+            let mut rng = thread_rng();
+            let private_key =
+                RsaPrivateKey::new(&mut rng, C::BITS_LEN).expect("failed to generate a key");
+            let public_key = RsaPublicKey::from(&private_key);
+            let msg = (0..C::MSG_LEN / 2).map(|_| rng.gen()).collect::<Vec<u8>>();
+            let circuit =
+                TestRSASignatureWithHashCircuit::<Fr, C>::new(private_key, public_key, msg);
+            let prover = MockProver::run(C::K, &circuit, circuit.instances()).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+        run::<Mainnet2048>();
+        run::<Testing>();
     }
 
     #[test]
@@ -566,7 +4122,9 @@ mod test {
             let circuit = TestRSASignatureWithHashCircuit1::<F> {
                 private_key,
                 public_key,
-                msg: byte_vec,
+                msg: byte_vec.into(),
+                signature: None,
+                expect_valid: true,
                 _f: PhantomData,
             };
             let num_limbs = 2048 / 64;
@@ -592,7 +4150,9 @@ mod test {
             let circuit2 = TestRSASignatureWithHashCircuit1::<F> {
                 private_key: private_key2,
                 public_key: public_key2,
-                msg: byte_vec2,
+                msg: byte_vec2.into(),
+                signature: None,
+                expect_valid: true,
                 _f: PhantomData,
             };
             let hash_fes2 = hashed_msg2
@@ -646,6 +4206,64 @@ mod test {
         18
     }
 
+    /// A subcircuit prover run by [`mock_prove_in_parallel`], returning its proving and verification times.
+    type MockProverJob<'a> = Box<dyn FnOnce() -> (Duration, Duration) + Send + 'a>;
+
+    /// Runs [`MockProver`] on `circuit` with `instances`, and asserts that it is satisfied.
+    ///
+    /// # Return values
+    /// Returns the time elapsed to run the prover and the time elapsed to verify it.
+    fn mock_prove<F: PrimeField, C: Circuit<F>>(
+        k: u32,
+        circuit: &C,
+        instances: Vec<Vec<F>>,
+    ) -> (Duration, Duration) {
+        let start_time = Instant::now();
+        let prover = match MockProver::run(k, circuit, instances) {
+            Ok(prover) => prover,
+            Err(e) => panic!("{:#?}", e),
+        };
+        let proving_time = start_time.elapsed();
+        prover.assert_satisfied();
+        (proving_time, start_time.elapsed() - proving_time)
+    }
+
+    /// Runs each of the independent subcircuit provers of `jobs` on its own thread and joins them.
+    ///
+    /// Every job is joined before failing, so that all the subcircuits which do not verify are reported at once.
+    ///
+    /// # Return values
+    /// Returns the name, proving time and verification time of each job in order.
+    fn mock_prove_in_parallel<'a>(
+        jobs: Vec<(&'static str, MockProverJob<'a>)>,
+    ) -> Vec<(&'static str, Duration, Duration)> {
+        let results = thread::scope(|scope| {
+            jobs.into_iter()
+                .map(|(name, job)| (name, scope.spawn(job)))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|(name, handle)| (name, handle.join()))
+                .collect::<Vec<_>>()
+        });
+        let failed = results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>();
+        assert!(
+            failed.is_empty(),
+            "subcircuits failed to verify: {:?}",
+            failed
+        );
+        results
+            .into_iter()
+            .map(|(name, result)| {
+                let (proving_time, verification_time) = result.unwrap();
+                (name, proving_time, verification_time)
+            })
+            .collect()
+    }
+
     #[test]
     fn test_aadhaar_qr_verifier_circuit() {
         fn run<F: PrimeField>() {
@@ -746,7 +4364,9 @@ mod test {
                 2, 4, 26, 38, 49, 51, 68, 79, 80, 96, 97, 104, 118, 124, 152, 165, 179, 184,
             ];
 
-            let timestamp_year_start_index = delimiter_indices[reference_id_position() - 1] + 5;
+            // The signing timestamp follows the last 4 digits of the Aadhaar number.
+            let timestamp_year_start_index =
+                field_start(&delimiter_indices, reference_id_position()) + 4;
             let timestamp_year_end_index = timestamp_year_start_index.clone() + 3;
             let timestamp_month_start_index = timestamp_year_end_index.clone() + 1;
             let timestamp_month_end_index = timestamp_month_start_index.clone() + 1;
@@ -784,7 +4404,7 @@ mod test {
             let hour_data: u64 = hour_vec[0] * 10 + hour_vec[1];
 
             // Calculate the birthday
-            let birth_day_start_index = delimiter_indices[dob_position() - 1] + 1;
+            let birth_day_start_index = field_start(&delimiter_indices, dob_position());
             let birth_day_end_index = birth_day_start_index.clone() + 1;
             let birth_month_start_index = birth_day_end_index.clone() + 2;
             let birth_month_end_index = birth_month_start_index.clone() + 1;
@@ -825,12 +4445,12 @@ mod test {
             }
 
             // Calculate the Gender
-            let gender_index = delimiter_indices[gender_position() - 1] + 1;
+            let gender_index = field_start(&delimiter_indices, gender_position());
             let gender_data = msg[gender_index].parse::<u8>().unwrap();
 
             // Calculate the Pincode of the Address
-            let pincode_start_index = delimiter_indices[pincode_position() - 1] + 1;
-            let pincode_end_index = delimiter_indices[pincode_position()];
+            let (pincode_start_index, pincode_end_index) =
+                field_range(&delimiter_indices, pincode_position());
             let mut pincode_vec: Vec<u32> = Vec::new();
             for i in pincode_start_index..pincode_end_index {
                 pincode_vec.push(to_integer_small(msg[i].parse::<u32>().unwrap()));
@@ -842,8 +4462,8 @@ mod test {
             }
 
             // Calculate the State of the Address
-            let state_start_index = delimiter_indices[state_position() - 1] + 1;
-            let state_end_index = delimiter_indices[state_position()];
+            let (state_start_index, state_end_index) =
+                field_range(&delimiter_indices, state_position());
             let mut state_vec: Vec<u8> = Vec::new();
             for i in state_start_index..state_end_index {
                 state_vec.push(msg[i].parse::<u8>().unwrap());
@@ -856,10 +4476,14 @@ mod test {
                     .expect("failed to generate a key");
             let public_key = RsaPublicKey::from(&private_key);
             let n = BigUint::from_radix_le(&public_key.n().to_radix_le(16), 16).unwrap();
-            let mut byte_vec: Vec<u8> = Vec::new();
-            for i in 0..700 {
-                byte_vec.push(msg[i].parse::<u8>().unwrap());
-            }
+            let qr_bytes = msg
+                .iter()
+                .map(|byte| byte.parse::<u8>().unwrap())
+                .collect::<Vec<u8>>();
+            let byte_vec =
+                qr::signed_region(&qr_bytes, TestRSASignatureWithHashCircuit1::<F>::BITS_LEN)
+                    .unwrap()
+                    .to_vec();
             let hashed_msg = Sha256::digest(&byte_vec);
             let hash_and_sign_circuit =
                 TestRSASignatureWithHashCircuit1::<F>::new(private_key, public_key, byte_vec);
@@ -867,8 +4491,8 @@ mod test {
             // Conditional Secrets Subcircuit
             let cond_secrets_circuit = IdentityCircuit::new(
                 Some(true),
-                Some(age),
-                Some(age),
+                Some(is_adult(age)),
+                Some(is_adult(age)),
                 Some(true),
                 Some(gender_data),
                 Some(gender_data),
@@ -887,28 +4511,19 @@ mod test {
                 nullifier_proof_generation_duration
             );
 
-            const R_F: usize = 8;
-            const R_P: usize = 57;
-            const T: usize = 5;
-            const RATE: usize = 4;
-
-            let mut poseidon = Poseidon::<FR, T, RATE>::new(R_F, R_P);
             let nullifier_seed = "12345678";
-            let mut photo_vec: Vec<u64> = Vec::new();
-
-            let photo_start_index = delimiter_indices[photo_position() - 1] + 1;
-            let photo_end_index = 1137;
+            let photo_start_index = field_start(&delimiter_indices, photo_position());
+            let signed_data =
+                qr::signed_region(&qr_bytes, TestRSASignatureWithHashCircuit1::<F>::BITS_LEN)
+                    .unwrap();
+            let photo_vec = signed_data[photo_start_index..]
+                .iter()
+                .map(|byte| *byte as u64)
+                .collect::<Vec<u64>>();
             let null_fr = nullifier_seed.parse::<u64>().unwrap();
-            photo_vec.push(null_fr);
-            for i in photo_start_index..photo_end_index {
-                photo_vec.push(msg[i].parse::<u64>().unwrap());
-            }
-
-            let fr_vector: Vec<FR> = photo_vec.iter().map(|s| FR::from(*s)).collect();
-
-            let inputs = fr_vector;
-            poseidon.update(&inputs[..]);
-            let nullifier = poseidon.squeeze();
+            let nullifier = nullifier::PoseidonCircuit::new(null_fr, photo_vec)
+                .nullifier()
+                .unwrap();
             println!("Poseidon Output: {:?}", nullifier);
 
             let nullifier_proof_verification_duration = start_time.elapsed();
@@ -947,107 +4562,49 @@ mod test {
             let extraction_duration = start_time.elapsed();
             println!("Extraction of Data Time elapsed: {:?}", extraction_duration);
 
-            let prover = match MockProver::run(k, &hash_and_sign_circuit.clone(), public_inputs) {
-                Ok(prover) => prover,
-                Err(e) => panic!("{:#?}", e),
-            };
-
-            let sha_proof_generation_duration = start_time.elapsed();
-            println!(
-                "RSA-SHA256 Proof Generation Time elapsed: {:?}",
-                sha_proof_generation_duration
-            );
-
-            prover.verify().unwrap();
-
-            let sha_proof_verification_duration = start_time.elapsed();
-            println!(
-                "RSA-SHA256 Proof Verification Time elapsed: {:?}",
-                sha_proof_verification_duration
-            );
-
-            // Verifying the conditional secrets subcircuit
-            let prover: MockProver<Fp> =
-                MockProver::run(k, &cond_secrets_circuit.clone(), vec![]).unwrap();
-
-            let cs_proof_generation_duration = start_time.elapsed();
-            println!(
-                "Conditional Secrets Proof Generation Time elapsed: {:?}",
-                cs_proof_generation_duration
-            );
-
-            assert!(prover.verify().is_ok());
-
-            let cs_proof_verification_duration = start_time.elapsed();
-            println!(
-                "Conditional Secrets Proof Verification Time elapsed: {:?}",
-                cs_proof_verification_duration
-            );
-
-            // Verifying the timestamp subcircuit
-            let public_inputs = vec![];
-            let prover = MockProver::run(k, &timestamp_circuit.clone(), public_inputs).unwrap();
-
-            let timestamp_proof_generation_duration = start_time.elapsed();
-            println!(
-                "Timestamp Proof Generation Time elapsed: {:?}",
-                timestamp_proof_generation_duration
-            );
-
-            assert_eq!(prover.verify(), Ok(()));
-
-            let timestamp_proof_verification_duration = start_time.elapsed();
-            println!(
-                "Timestamp Proof  Verification Time elapsed: {:?}",
-                timestamp_proof_verification_duration
-            );
-
-            // Verifying the signal hash subcircuit
-            let public_inputs = vec![F::from(signal_val * signal_val)];
-
-            let prover = MockProver::run(k, &signal_circuit.clone(), vec![public_inputs]).unwrap();
-
-            let signal_proof_generation_duration = start_time.elapsed();
-            println!(
-                "Signal Proof Generation Time elapsed: {:?}",
-                signal_proof_generation_duration
-            );
-
-            prover.assert_satisfied();
-
-            let signal_proof_verification_duration = start_time.elapsed();
-            println!(
-                "Signal Proof Verification Time elapsed: {:?}",
-                signal_proof_verification_duration
-            );
+            // The subcircuits are independent, so their provers run in parallel.
+            let cond_secrets_instances = cond_secrets_circuit
+                .revealed_values()
+                .into_iter()
+                .map(Fp::from)
+                .collect::<Vec<Fp>>();
+            let signal_instances = vec![F::from(signal_val * signal_val)];
+            let timings = mock_prove_in_parallel(vec![
+                (
+                    "RSA-SHA256",
+                    Box::new(move || mock_prove(k, &hash_and_sign_circuit, public_inputs))
+                        as MockProverJob,
+                ),
+                (
+                    "Conditional Secrets",
+                    Box::new(move || {
+                        mock_prove(k, &cond_secrets_circuit, vec![cond_secrets_instances])
+                    }) as MockProverJob,
+                ),
+                (
+                    "Timestamp",
+                    Box::new(move || mock_prove(k, &timestamp_circuit, vec![])) as MockProverJob,
+                ),
+                (
+                    "Signal",
+                    Box::new(move || mock_prove(k, &signal_circuit, vec![signal_instances]))
+                        as MockProverJob,
+                ),
+            ]);
+            let wall_time = start_time.elapsed() - extraction_duration;
 
             // Benchmarks
             println!("##### Benchmarks #####");
-            let sha_prove_time = sha_proof_generation_duration - extraction_duration;
-            let sha_ver_time = sha_proof_verification_duration - sha_proof_generation_duration;
-            println!("RSA-SHA256 proving time: {:?}", sha_prove_time);
-            println!("RSA-SHA256 verification time: {:?}", sha_ver_time);
             let nullifier_prove_time =
                 nullifier_proof_verification_duration - nullifier_proof_generation_duration;
             let nullifier_ver_time = extraction_duration - nullifier_proof_verification_duration;
             println!("Nullifier proving time: {:?}", nullifier_prove_time);
             println!("Nullifier verification time: {:?}", nullifier_ver_time);
-            let cs_prove_time = cs_proof_generation_duration - sha_proof_verification_duration;
-            let cs_ver_time = cs_proof_verification_duration - cs_proof_generation_duration;
-            println!("Conditional Secrets proving time: {:?}", cs_prove_time);
-            println!("Conditional Secrets verification time: {:?}", cs_ver_time);
-            let timestamp_prove_time =
-                timestamp_proof_generation_duration - cs_proof_verification_duration;
-            let timestamp_ver_time =
-                timestamp_proof_verification_duration - timestamp_proof_generation_duration;
-            println!("Timestamp proving time: {:?}", timestamp_prove_time);
-            println!("Timestamp verification time: {:?}", timestamp_ver_time);
-            let signal_prove_time =
-                signal_proof_generation_duration - timestamp_proof_verification_duration;
-            let signal_ver_time =
-                signal_proof_verification_duration - signal_proof_generation_duration;
-            println!("Signal proving time: {:?}", signal_prove_time);
-            println!("Signal verification time: {:?}", signal_ver_time);
+            for (name, prove_time, ver_time) in timings {
+                println!("{} proving time: {:?}", name, prove_time);
+                println!("{} verification time: {:?}", name, ver_time);
+            }
+            println!("Subcircuits wall time: {:?}", wall_time);
         }
         run::<Fr>();
     }
@@ -0,0 +1,335 @@
+//! Asserting that assigned values are members of small public sets by lookups into a fixed table.
+use halo2_base::halo2_proofs::{
+    circuit::{Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+use halo2_base::{utils::PrimeField, AssignedValue, Context};
+
+/// The valid bytes of the gender field: male, female and transgender.
+pub const GENDERS: [u8; 3] = *b"MFT";
+
+/// The valid leading digits of a pincode, whose first digit is the postal region from 1 to 9.
+pub const PINCODE_LEADING_DIGITS: [u8; 9] = *b"123456789";
+
+/// Returns the sets of [`GENDERS`] and [`PINCODE_LEADING_DIGITS`] as field elements, in the order expected by [`crate::field_extractor::FieldExtractorChip::assert_valid_fields`].
+pub fn identity_sets<F: PrimeField>() -> Vec<Vec<F>> {
+    [&GENDERS[..], &PINCODE_LEADING_DIGITS[..]]
+        .iter()
+        .map(|set| set.iter().map(|byte| F::from(*byte as u64)).collect())
+        .collect()
+}
+
+/// Configuration of the membership lookups into the table of [`LookupSetChip`].
+#[derive(Clone, Debug)]
+pub struct LookupSetConfig {
+    /// Enabled at the rows whose value is looked up.
+    q_lookup: Selector,
+    /// The tag of the set the value of the same row is looked up in.
+    tag: Column<Fixed>,
+    value: Column<Advice>,
+    tag_table: TableColumn,
+    value_table: TableColumn,
+}
+
+impl LookupSetConfig {
+    /// Configures the lookup of the pairs `(tag, value)` into the table of the tagged members.
+    pub fn configure<F: PrimeField>(meta: &mut ConstraintSystem<F>) -> Self {
+        let q_lookup = meta.complex_selector();
+        let tag = meta.fixed_column();
+        let value = meta.advice_column();
+        let tag_table = meta.lookup_table_column();
+        let value_table = meta.lookup_table_column();
+        meta.enable_equality(value);
+
+        // The disabled rows look up `(0, 0)`, which no set holds as their tags start from one.
+        meta.lookup("lookup set", |meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let tag = meta.query_fixed(tag, Rotation::cur());
+            let value = meta.query_advice(value, Rotation::cur());
+            vec![
+                (q_lookup.clone() * tag, tag_table),
+                (q_lookup * value, value_table),
+            ]
+        });
+
+        Self {
+            q_lookup,
+            tag,
+            value,
+            tag_table,
+            value_table,
+        }
+    }
+}
+
+/// A chip asserting that assigned values are members of small public sets, e.g. the valid genders or the leading digits of the pincodes.
+///
+/// The members of all the sets are loaded into one table together with the tag of their set.
+/// A membership then costs one lookup row whatever the size of the set, whereas the product of the differences with the members is a gate whose degree grows with it.
+/// The sets are fixed by the verifying key, so they must be the same at the key generation and the proving.
+///
+/// The membership rows are assigned in the region of the [`Context`] next to its cells, so the chip must be constructed in the region, as the SHA256 chip counts its digests.
+#[derive(Clone, Debug)]
+pub struct LookupSetChip<F: PrimeField> {
+    config: LookupSetConfig,
+    sets: Vec<Vec<F>>,
+    /// The next row of the lookup columns in the region.
+    offset: usize,
+}
+
+impl<F: PrimeField> LookupSetChip<F> {
+    /// Creates new [`LookupSetChip`] from [`LookupSetConfig`] and the sets to be loaded into its table.
+    pub fn construct(config: LookupSetConfig, sets: Vec<Vec<F>>) -> Self {
+        Self {
+            config,
+            sets,
+            offset: 0,
+        }
+    }
+
+    /// Loads the members of the sets, tagged by the index of their set plus one, after the row `(0, 0)` of the disabled lookups.
+    pub fn load_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "lookup sets",
+            |mut table| {
+                let tagged_members = self.sets.iter().enumerate().flat_map(|(i, members)| {
+                    members.iter().map(move |member| (i as u64 + 1, *member))
+                });
+                let rows = [(0, F::zero())].into_iter().chain(tagged_members);
+                for (offset, (tag, member)) in rows.enumerate() {
+                    table.assign_cell(
+                        || format!("tag {}", offset),
+                        self.config.tag_table,
+                        offset,
+                        || Value::known(F::from(tag)),
+                    )?;
+                    table.assign_cell(
+                        || format!("member {}", offset),
+                        self.config.value_table,
+                        offset,
+                        || Value::known(member),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Asserts that `x` is a member of `set` by copying it into the next row of the lookup columns with the tag of `set`.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * x - an assigned value, e.g. the gender byte extracted from the QR data.
+    /// * set - one of the sets of this chip.
+    ///
+    /// # Return values
+    /// Returns [`Error::Synthesis`] if `set` is not one of the sets of this chip, as the table does not hold it.
+    pub fn assert_in_lookup_set<'v>(
+        &mut self,
+        ctx: &mut Context<'v, F>,
+        x: &AssignedValue<'v, F>,
+        set: &[F],
+    ) -> Result<(), Error> {
+        let tag = self
+            .sets
+            .iter()
+            .position(|members| members.as_slice() == set)
+            .ok_or(Error::Synthesis)?
+            + 1;
+        let offset = self.offset;
+        self.config.q_lookup.enable(&mut ctx.region, offset)?;
+        ctx.region.assign_fixed(
+            || "tag",
+            self.config.tag,
+            offset,
+            || Value::known(F::from(tag as u64)),
+        )?;
+        let value = ctx.region.assign_advice(
+            || "value",
+            self.config.value,
+            offset,
+            || x.value().copied(),
+        )?;
+        ctx.region.constrain_equal(x.cell(), value.cell())?;
+        self.offset += 1;
+        Ok(())
+    }
+}
+
+/// A circuit running a gadget over [`RangeConfig`] and [`LookupSetChip`] in a single region, for testing the memberships of assigned values.
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::*;
+    use halo2_base::gates::{
+        range::{RangeConfig, RangeStrategy::Vertical},
+        RangeInstructions,
+    };
+    use halo2_base::halo2_proofs::{
+        circuit::SimpleFloorPlanner, halo2curves::bn256::Fr, plonk::Circuit,
+    };
+    use halo2_base::{ContextParams, SKIP_FIRST_PASS};
+    use std::rc::Rc;
+
+    pub(crate) type TestLookupSetGadget = Rc<
+        dyn for<'v> Fn(
+            &RangeConfig<Fr>,
+            &mut LookupSetChip<Fr>,
+            &mut Context<'v, Fr>,
+        ) -> Result<(), Error>,
+    >;
+
+    #[derive(Clone)]
+    pub(crate) struct TestLookupSetCircuit {
+        sets: Vec<Vec<Fr>>,
+        gadget: TestLookupSetGadget,
+    }
+
+    impl TestLookupSetCircuit {
+        pub(crate) const K: u32 = 14;
+
+        pub(crate) fn new(sets: Vec<Vec<Fr>>, gadget: TestLookupSetGadget) -> Self {
+            Self { sets, gadget }
+        }
+    }
+
+    impl Circuit<Fr> for TestLookupSetCircuit {
+        type Config = (RangeConfig<Fr>, LookupSetConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let range =
+                RangeConfig::configure(meta, Vertical, &[4], &[1], 1, 8, 0, Self::K as usize);
+            (range, LookupSetConfig::configure(meta))
+        }
+
+        fn synthesize(
+            &self,
+            (range, lookup): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            range.load_lookup_table(&mut layouter)?;
+            LookupSetChip::construct(lookup.clone(), self.sets.clone())
+                .load_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "lookup set gadget test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = Context::new(
+                        region,
+                        ContextParams {
+                            max_rows: range.gate.max_rows,
+                            num_context_ids: 1,
+                            fixed_columns: range.gate.constants.clone(),
+                        },
+                    );
+                    let ctx = &mut aux;
+                    let mut chip = LookupSetChip::construct(lookup.clone(), self.sets.clone());
+                    (self.gadget)(&range, &mut chip, ctx)?;
+                    range.finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::TestLookupSetCircuit;
+    use super::*;
+    use halo2_base::gates::{range::RangeConfig, GateInstructions};
+    use halo2_base::halo2_proofs::{dev::MockProver, halo2curves::bn256::Fr};
+    use std::rc::Rc;
+
+    /// The allowed leading digits of a pincode in the tested region.
+    const DIGITS: [u8; 5] = *b"12345";
+
+    fn to_fes(bytes: &[u8]) -> Vec<Fr> {
+        bytes.iter().map(|byte| Fr::from(*byte as u64)).collect()
+    }
+
+    /// A circuit asserting that `x` is a member of `set` with a chip of the sets [`DIGITS`] and [`GENDERS`].
+    fn lookup_set_circuit(x: u8, set: &[u8]) -> TestLookupSetCircuit {
+        let set = to_fes(set);
+        TestLookupSetCircuit::new(
+            vec![to_fes(&DIGITS), to_fes(&GENDERS)],
+            Rc::new(
+                move |range: &RangeConfig<Fr>,
+                      chip: &mut LookupSetChip<Fr>,
+                      ctx: &mut Context<Fr>| {
+                    let x = range
+                        .gate
+                        .load_witness(ctx, Value::known(Fr::from(x as u64)));
+                    chip.assert_in_lookup_set(ctx, &x, &set)
+                },
+            ),
+        )
+    }
+
+    #[test]
+    fn test_assert_in_lookup_set() {
+        let k = TestLookupSetCircuit::K;
+        let run = |x: u8, set: &[u8]| {
+            MockProver::run(k, &lookup_set_circuit(x, set), vec![]).map(|prover| prover.verify())
+        };
+
+        // Every member passes.
+        for digit in DIGITS {
+            assert_eq!(run(digit, &DIGITS).unwrap(), Ok(()));
+        }
+
+        // A non-member fails, including zero, which the disabled rows look up.
+        for non_member in [b'0', b'6', 0] {
+            assert!(run(non_member, &DIGITS).unwrap().is_err());
+        }
+
+        // The member of another set in the same table is not one of this set.
+        assert_eq!(run(b'M', &GENDERS).unwrap(), Ok(()));
+        assert!(run(b'M', &DIGITS).unwrap().is_err());
+        assert!(run(b'1', &GENDERS).unwrap().is_err());
+
+        // A set out of the table cannot be asserted.
+        assert!(run(b'1', b"123").is_err());
+    }
+
+    #[test]
+    fn test_several_memberships() {
+        // Each membership takes the next row, so that a failing one is not hidden by another.
+        let circuit = |gender: u8, digit: u8| {
+            TestLookupSetCircuit::new(
+                identity_sets(),
+                Rc::new(
+                    move |range: &RangeConfig<Fr>,
+                          chip: &mut LookupSetChip<Fr>,
+                          ctx: &mut Context<Fr>| {
+                        let sets = identity_sets::<Fr>();
+                        let gender = range
+                            .gate
+                            .load_witness(ctx, Value::known(Fr::from(gender as u64)));
+                        let digit = range
+                            .gate
+                            .load_witness(ctx, Value::known(Fr::from(digit as u64)));
+                        chip.assert_in_lookup_set(ctx, &gender, &sets[0])?;
+                        chip.assert_in_lookup_set(ctx, &digit, &sets[1])
+                    },
+                ),
+            )
+        };
+        let k = TestLookupSetCircuit::K;
+        let prover = MockProver::run(k, &circuit(b'F', b'9'), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        let prover = MockProver::run(k, &circuit(b'F', b'0'), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+        let prover = MockProver::run(k, &circuit(b'X', b'1'), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
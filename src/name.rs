@@ -0,0 +1,754 @@
+use halo2_base::gates::{GateInstructions, RangeInstructions};
+use halo2_base::halo2_proofs::{
+    circuit::{AssignedCell, Cell, Layouter, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector},
+    poly::Rotation,
+};
+use halo2_base::utils::PrimeField;
+use halo2_base::{Context, ContextParams, SKIP_FIRST_PASS};
+
+use crate::delimiter::DELIMITER;
+use crate::nullifier::{PoseidonConfig, PHOTO_PACK_BYTES, RATE, T};
+use crate::photo::{pack_bytes, PackError};
+use crate::poseidon_chip::{poseidon_hash, PoseidonChip};
+use crate::qr_data_extractor::DEFAULT_MAX_DATA_LEN;
+
+/// The position of the name field, i.e. the number of its start delimiter in `nDelimitedData`.
+pub const NAME_POSITION: usize = 3;
+
+/// The number of name bytes packed into a field element, as for the photo.
+pub const NAME_PACK_BYTES: usize = PHOTO_PACK_BYTES;
+
+/// The number of field elements the name is packed into.
+pub const NAME_PACK_SIZE: usize = 2;
+
+/// The maximum byte length of the name, which is UTF-8 encoded and may take several bytes per character.
+pub const MAX_NAME_LEN: usize = NAME_PACK_BYTES * NAME_PACK_SIZE;
+
+#[derive(Debug, Clone)]
+pub struct NameExtractorConfig {
+    /// Enabled at the [`DEFAULT_MAX_DATA_LEN`] rows of `n_delimited_data`.
+    q_scan: Selector,
+    q_first: Selector,
+    q_step: Selector,
+    /// Enabled at the [`MAX_NAME_LEN`] rows of `name`.
+    q_name: Selector,
+    q_name_first: Selector,
+    q_name_step: Selector,
+    /// Enabled at the rows of `name` whose byte is not the last one of a packed element.
+    q_pack_step: Selector,
+    /// Enabled at the rows of `name` whose byte is the last one of a packed element.
+    q_pack_last: Selector,
+    q_reveal: Selector,
+    n_delimited_data: Column<Advice>,
+    /// The inverse of `n_delimited_data - NAME_POSITION * 255`, or zero if it is not invertible.
+    start_diff_inv: Column<Advice>,
+    is_start: Column<Advice>,
+    /// The inverse of `n_delimited_data - (NAME_POSITION + 1) * 255`, or zero if it is not invertible.
+    end_diff_inv: Column<Advice>,
+    is_end: Column<Advice>,
+    /// One if the byte belongs to the name, and zero otherwise.
+    in_name: Column<Advice>,
+    /// The number of name bytes up to and including the row.
+    name_len: Column<Advice>,
+    /// The row of the start delimiter, the same in every row.
+    name_start: Column<Advice>,
+    row_index: Column<Fixed>,
+    /// The name bytes shifted to the first rows and padded with zeros.
+    name: Column<Advice>,
+    /// The 1-indexed position in the name of the byte assigned in the same row of `name`.
+    name_number: Column<Fixed>,
+    /// One if the row of `name` is within the name length, and zero otherwise.
+    has_byte: Column<Advice>,
+    /// The running sum of `has_byte`, which equals the name length in the last row.
+    has_byte_sum: Column<Advice>,
+    /// The little-endian packing of the name bytes from the row up to the end of the packed element.
+    pack: Column<Advice>,
+    reveal_name_hash: Column<Advice>,
+    name_hash: Column<Advice>,
+    revealed_name_hash: Column<Advice>,
+    /// The range chip hashing the packed name, which needs `2^PoseidonCircuit::K` rows.
+    poseidon: PoseidonConfig,
+    instance: Column<Instance>,
+}
+
+/// The cells assigned by [`NameExtractorChip::assign`].
+#[derive(Debug, Clone)]
+pub struct AssignedName {
+    /// The assigned `nDelimitedData` over [`DEFAULT_MAX_DATA_LEN`] rows, to be constrained equal to the cells of [`crate::delimiter::DelimiterCircuit::assign_n_delimited_data`].
+    pub n_delimited_data: Vec<AssignedCell<Fr, Fr>>,
+    /// The [`NAME_PACK_SIZE`] field elements packing the name.
+    pub packed_name: Vec<AssignedCell<Fr, Fr>>,
+    /// The hash of the packed name if it is revealed, and zero otherwise.
+    pub revealed_name_hash: AssignedCell<Fr, Fr>,
+}
+
+/// A chip extracting the name field from `nDelimitedData` and exposing a Poseidon hash of the packed name instead of the name itself.
+///
+/// The name bytes are those between the delimiters remapped to `NAME_POSITION * 255` and `(NAME_POSITION + 1) * 255`.
+/// They are shifted to the first rows of the `name` column by a lookup into the scanned data, and packed into [`NAME_PACK_SIZE`] field elements of [`NAME_PACK_BYTES`] little-endian bytes each.
+/// The hash is computed in the circuit by [`PoseidonChip`] over copies of the packed cells, as the nullifier of [`crate::nullifier::PoseidonCircuit`] is.
+/// The circuit must have at least `2^PoseidonCircuit::K` rows for the hash.
+#[derive(Debug, Clone)]
+pub struct NameExtractorChip {
+    config: NameExtractorConfig,
+}
+
+impl NameExtractorChip {
+    /// Creates new [`NameExtractorChip`] from [`NameExtractorConfig`].
+    pub fn construct(config: NameExtractorConfig) -> Self {
+        Self { config }
+    }
+
+    /// Getter for [`NameExtractorConfig`].
+    pub fn config(&self) -> &NameExtractorConfig {
+        &self.config
+    }
+
+    /// Returns the name bytes in `n_delimited_data` computed out of the circuit, or an empty name if the delimiters are missing.
+    pub fn name(n_delimited_data: &[u64]) -> Vec<u8> {
+        let position = |number: usize| {
+            n_delimited_data
+                .iter()
+                .position(|byte| *byte == (number * DELIMITER as usize) as u64)
+        };
+        match (position(NAME_POSITION), position(NAME_POSITION + 1)) {
+            (Some(start), Some(end)) if start < end => n_delimited_data[start + 1..end]
+                .iter()
+                .map(|byte| *byte as u8)
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Packs `name` into [`NAME_PACK_SIZE`] field elements of [`NAME_PACK_BYTES`] little-endian bytes each, padded with zeros, by [`pack_bytes`].
+    ///
+    /// # Return values
+    /// Returns the elements, or [`PackError::TooLong`] if the name is longer than [`MAX_NAME_LEN`] bytes, which the circuit rejects likewise.
+    pub fn pack_name(name: &[u8]) -> Result<Vec<Fr>, PackError> {
+        pack_bytes(name, NAME_PACK_SIZE)
+    }
+
+    /// Computes the Poseidon hash of the packed name with the parameters of the nullifier.
+    ///
+    /// # Return values
+    /// Returns the hash, or [`PackError::TooLong`] if the name is longer than [`MAX_NAME_LEN`] bytes.
+    pub fn name_hash(name: &[u8]) -> Result<Fr, PackError> {
+        Ok(poseidon_hash::<T, RATE>(&Self::pack_name(name)?))
+    }
+
+    /// Configures the columns and the constraints of [`NameExtractorChip`].
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> NameExtractorConfig {
+        let q_scan = meta.complex_selector();
+        let q_first = meta.selector();
+        let q_step = meta.selector();
+        let q_name = meta.complex_selector();
+        let q_name_first = meta.selector();
+        let q_name_step = meta.selector();
+        let q_pack_step = meta.selector();
+        let q_pack_last = meta.selector();
+        let q_reveal = meta.selector();
+        let n_delimited_data = meta.advice_column();
+        let start_diff_inv = meta.advice_column();
+        let is_start = meta.advice_column();
+        let end_diff_inv = meta.advice_column();
+        let is_end = meta.advice_column();
+        let in_name = meta.advice_column();
+        let name_len = meta.advice_column();
+        let name_start = meta.advice_column();
+        let row_index = meta.fixed_column();
+        let name = meta.advice_column();
+        let name_number = meta.fixed_column();
+        let has_byte = meta.advice_column();
+        let has_byte_sum = meta.advice_column();
+        let pack = meta.advice_column();
+        let reveal_name_hash = meta.advice_column();
+        let name_hash = meta.advice_column();
+        let revealed_name_hash = meta.advice_column();
+        let instance = meta.instance_column();
+        meta.enable_equality(n_delimited_data);
+        meta.enable_equality(name_len);
+        meta.enable_equality(has_byte_sum);
+        meta.enable_equality(pack);
+        meta.enable_equality(name_hash);
+        meta.enable_equality(revealed_name_hash);
+        meta.enable_equality(instance);
+        let poseidon = PoseidonConfig::configure(meta);
+
+        let delimiter =
+            |number: usize| Expression::Constant(Fr::from((number * DELIMITER as usize) as u64));
+        meta.create_gate("name delimiters", |meta| {
+            let q_scan = meta.query_selector(q_scan);
+            let data = meta.query_advice(n_delimited_data, Rotation::cur());
+            let start_diff_inv = meta.query_advice(start_diff_inv, Rotation::cur());
+            let is_start = meta.query_advice(is_start, Rotation::cur());
+            let end_diff_inv = meta.query_advice(end_diff_inv, Rotation::cur());
+            let is_end = meta.query_advice(is_end, Rotation::cur());
+            let start_diff = data.clone() - delimiter(NAME_POSITION);
+            let end_diff = data - delimiter(NAME_POSITION + 1);
+            vec![
+                q_scan.clone()
+                    * (is_start.clone()
+                        - (Expression::Constant(Fr::one()) - start_diff.clone() * start_diff_inv)),
+                q_scan.clone() * start_diff * is_start,
+                q_scan.clone()
+                    * (is_end.clone()
+                        - (Expression::Constant(Fr::one()) - end_diff.clone() * end_diff_inv)),
+                q_scan * end_diff * is_end,
+            ]
+        });
+
+        meta.create_gate("in name first row", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let in_name = meta.query_advice(in_name, Rotation::cur());
+            let name_len = meta.query_advice(name_len, Rotation::cur());
+            vec![q_first.clone() * in_name, q_first * name_len]
+        });
+
+        // The name starts right after the start delimiter and stops at the end delimiter, both of which appear once in `nDelimitedData`.
+        meta.create_gate("in name step", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let in_name_prev = meta.query_advice(in_name, Rotation::prev());
+            let is_start_prev = meta.query_advice(is_start, Rotation::prev());
+            let name_len_prev = meta.query_advice(name_len, Rotation::prev());
+            let name_start_prev = meta.query_advice(name_start, Rotation::prev());
+            let in_name = meta.query_advice(in_name, Rotation::cur());
+            let is_end = meta.query_advice(is_end, Rotation::cur());
+            let name_len = meta.query_advice(name_len, Rotation::cur());
+            let name_start = meta.query_advice(name_start, Rotation::cur());
+            vec![
+                q_step.clone() * (in_name.clone() - in_name_prev - is_start_prev + is_end),
+                q_step.clone() * (name_len - name_len_prev - in_name),
+                q_step * (name_start - name_start_prev),
+            ]
+        });
+
+        meta.create_gate("name start", |meta| {
+            let q_scan = meta.query_selector(q_scan);
+            let is_start = meta.query_advice(is_start, Rotation::cur());
+            let name_start = meta.query_advice(name_start, Rotation::cur());
+            let row_index = meta.query_fixed(row_index, Rotation::cur());
+            vec![q_scan * is_start * (name_start - row_index)]
+        });
+
+        // `has_byte` is a run of ones followed by zeros, whose length is constrained to the name length in `assign`.
+        meta.create_gate("has byte", |meta| {
+            let q_name = meta.query_selector(q_name);
+            let has_byte = meta.query_advice(has_byte, Rotation::cur());
+            vec![q_name * has_byte.clone() * (Expression::Constant(Fr::one()) - has_byte)]
+        });
+
+        meta.create_gate("has byte first row", |meta| {
+            let q_name_first = meta.query_selector(q_name_first);
+            let has_byte = meta.query_advice(has_byte, Rotation::cur());
+            let has_byte_sum = meta.query_advice(has_byte_sum, Rotation::cur());
+            vec![q_name_first * (has_byte_sum - has_byte)]
+        });
+
+        meta.create_gate("has byte step", |meta| {
+            let q_name_step = meta.query_selector(q_name_step);
+            let has_byte_prev = meta.query_advice(has_byte, Rotation::prev());
+            let has_byte_sum_prev = meta.query_advice(has_byte_sum, Rotation::prev());
+            let has_byte = meta.query_advice(has_byte, Rotation::cur());
+            let has_byte_sum = meta.query_advice(has_byte_sum, Rotation::cur());
+            vec![
+                q_name_step.clone()
+                    * has_byte.clone()
+                    * (Expression::Constant(Fr::one()) - has_byte_prev),
+                q_name_step * (has_byte_sum - has_byte_sum_prev - has_byte),
+            ]
+        });
+
+        meta.create_gate("pack step", |meta| {
+            let q_pack_step = meta.query_selector(q_pack_step);
+            let name = meta.query_advice(name, Rotation::cur());
+            let pack = meta.query_advice(pack, Rotation::cur());
+            let pack_next = meta.query_advice(pack, Rotation::next());
+            vec![q_pack_step * (pack - name - pack_next * Expression::Constant(Fr::from(256)))]
+        });
+
+        meta.create_gate("pack last", |meta| {
+            let q_pack_last = meta.query_selector(q_pack_last);
+            let name = meta.query_advice(name, Rotation::cur());
+            let pack = meta.query_advice(pack, Rotation::cur());
+            vec![q_pack_last * (pack - name)]
+        });
+
+        meta.create_gate("reveal name hash", |meta| {
+            let q_reveal = meta.query_selector(q_reveal);
+            let reveal = meta.query_advice(reveal_name_hash, Rotation::cur());
+            let name_hash = meta.query_advice(name_hash, Rotation::cur());
+            let revealed = meta.query_advice(revealed_name_hash, Rotation::cur());
+            vec![
+                q_reveal.clone()
+                    * reveal.clone()
+                    * (Expression::Constant(Fr::one()) - reveal.clone()),
+                q_reveal * (revealed - reveal * name_hash),
+            ]
+        });
+
+        // `(j, b)` is in the table only if `b` is the `j`-th byte of the name, and `(0, 0)` is in it for the rows out of the name.
+        meta.lookup_any("name byte", |meta| {
+            let q_name = meta.query_selector(q_name);
+            let name_number = meta.query_fixed(name_number, Rotation::cur());
+            let has_byte = meta.query_advice(has_byte, Rotation::cur());
+            let name = meta.query_advice(name, Rotation::cur());
+            let q_scan = meta.query_selector(q_scan);
+            let in_name = meta.query_advice(in_name, Rotation::cur());
+            let row_index = meta.query_fixed(row_index, Rotation::cur());
+            let name_start = meta.query_advice(name_start, Rotation::cur());
+            let data = meta.query_advice(n_delimited_data, Rotation::cur());
+            let found = q_scan * in_name;
+            vec![
+                (
+                    q_name.clone() * name_number * has_byte,
+                    found.clone() * (row_index - name_start),
+                ),
+                (q_name * name, found * data),
+            ]
+        });
+
+        NameExtractorConfig {
+            q_scan,
+            q_first,
+            q_step,
+            q_name,
+            q_name_first,
+            q_name_step,
+            q_pack_step,
+            q_pack_last,
+            q_reveal,
+            n_delimited_data,
+            start_diff_inv,
+            is_start,
+            end_diff_inv,
+            is_end,
+            in_name,
+            name_len,
+            name_start,
+            row_index,
+            name,
+            name_number,
+            has_byte,
+            has_byte_sum,
+            pack,
+            reveal_name_hash,
+            name_hash,
+            revealed_name_hash,
+            poseidon,
+            instance,
+        }
+    }
+
+    /// Assigns `n_delimited_data`, the name extracted from it, and its hash.
+    ///
+    /// # Arguments
+    /// * n_delimited_data - `nDelimitedData` as computed by [`crate::delimiter::DelimiterCircuit::n_delimited_data`], padded with zeros to [`DEFAULT_MAX_DATA_LEN`] bytes.
+    /// * reveal_name_hash - whether the hash of the name is revealed.
+    ///
+    /// # Return values
+    /// Returns the assigned cells as [`AssignedName`].
+    /// If the name is longer than [`MAX_NAME_LEN`] bytes, the circuit is not satisfied.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        n_delimited_data: &[u64],
+        reveal_name_hash: bool,
+    ) -> Result<AssignedName, Error> {
+        if n_delimited_data.len() > DEFAULT_MAX_DATA_LEN {
+            return Err(Error::Synthesis);
+        }
+        let config = &self.config;
+        let mut data = n_delimited_data.to_vec();
+        data.resize(DEFAULT_MAX_DATA_LEN, 0);
+        let name = Self::name(&data);
+        let name_start = data
+            .iter()
+            .position(|byte| *byte == (NAME_POSITION * DELIMITER as usize) as u64)
+            .unwrap_or(0);
+
+        let (n_delimited_data, packed_name) = layouter.assign_region(
+            || "name extraction",
+            |mut region| {
+                let start_delimiter = (NAME_POSITION * DELIMITER as usize) as u64;
+                let end_delimiter = ((NAME_POSITION + 1) * DELIMITER as usize) as u64;
+                let mut in_name = false;
+                let mut name_len = 0u64;
+                let mut n_delimited_data = Vec::with_capacity(DEFAULT_MAX_DATA_LEN);
+                let mut name_len_cell = None;
+                for (i, byte) in data.iter().enumerate() {
+                    config.q_scan.enable(&mut region, i)?;
+                    if i == 0 {
+                        config.q_first.enable(&mut region, i)?;
+                    } else {
+                        config.q_step.enable(&mut region, i)?;
+                    }
+                    let value = Fr::from(*byte);
+                    let is_start = *byte == start_delimiter;
+                    let is_end = *byte == end_delimiter;
+                    in_name &= !is_end;
+                    name_len += in_name as u64;
+                    n_delimited_data.push(region.assign_advice(
+                        || format!("n_delimited_data {}", i),
+                        config.n_delimited_data,
+                        i,
+                        || Value::known(value),
+                    )?);
+                    region.assign_advice(
+                        || format!("start_diff_inv {}", i),
+                        config.start_diff_inv,
+                        i,
+                        || Value::known(diff_inv(value - Fr::from(start_delimiter))),
+                    )?;
+                    region.assign_advice(
+                        || format!("is_start {}", i),
+                        config.is_start,
+                        i,
+                        || Value::known(Fr::from(is_start as u64)),
+                    )?;
+                    region.assign_advice(
+                        || format!("end_diff_inv {}", i),
+                        config.end_diff_inv,
+                        i,
+                        || Value::known(diff_inv(value - Fr::from(end_delimiter))),
+                    )?;
+                    region.assign_advice(
+                        || format!("is_end {}", i),
+                        config.is_end,
+                        i,
+                        || Value::known(Fr::from(is_end as u64)),
+                    )?;
+                    region.assign_advice(
+                        || format!("in_name {}", i),
+                        config.in_name,
+                        i,
+                        || Value::known(Fr::from(in_name as u64)),
+                    )?;
+                    name_len_cell = Some(region.assign_advice(
+                        || format!("name_len {}", i),
+                        config.name_len,
+                        i,
+                        || Value::known(Fr::from(name_len)),
+                    )?);
+                    region.assign_advice(
+                        || format!("name_start {}", i),
+                        config.name_start,
+                        i,
+                        || Value::known(Fr::from(name_start as u64)),
+                    )?;
+                    region.assign_fixed(
+                        || format!("row_index {}", i),
+                        config.row_index,
+                        i,
+                        || Value::known(Fr::from(i as u64)),
+                    )?;
+                    // The byte after the start delimiter is the first one of the name.
+                    in_name |= is_start;
+                }
+
+                let mut padded_name = name.clone();
+                padded_name.resize(MAX_NAME_LEN, 0);
+                let mut packs = vec![Fr::zero(); MAX_NAME_LEN];
+                for j in (0..MAX_NAME_LEN).rev() {
+                    let is_last = j % NAME_PACK_BYTES == NAME_PACK_BYTES - 1;
+                    let next = if is_last { Fr::zero() } else { packs[j + 1] };
+                    packs[j] = Fr::from(padded_name[j] as u64) + next * Fr::from(256);
+                }
+                let mut packed_name = Vec::with_capacity(NAME_PACK_SIZE);
+                let mut has_byte_sum_cell = None;
+                for (j, byte) in padded_name.iter().enumerate() {
+                    config.q_name.enable(&mut region, j)?;
+                    if j == 0 {
+                        config.q_name_first.enable(&mut region, j)?;
+                    } else {
+                        config.q_name_step.enable(&mut region, j)?;
+                    }
+                    if j % NAME_PACK_BYTES == NAME_PACK_BYTES - 1 {
+                        config.q_pack_last.enable(&mut region, j)?;
+                    } else {
+                        config.q_pack_step.enable(&mut region, j)?;
+                    }
+                    region.assign_advice(
+                        || format!("name {}", j),
+                        config.name,
+                        j,
+                        || Value::known(Fr::from(*byte as u64)),
+                    )?;
+                    region.assign_fixed(
+                        || format!("name_number {}", j),
+                        config.name_number,
+                        j,
+                        || Value::known(Fr::from(j as u64 + 1)),
+                    )?;
+                    region.assign_advice(
+                        || format!("has_byte {}", j),
+                        config.has_byte,
+                        j,
+                        || Value::known(Fr::from((j < name.len()) as u64)),
+                    )?;
+                    has_byte_sum_cell = Some(region.assign_advice(
+                        || format!("has_byte_sum {}", j),
+                        config.has_byte_sum,
+                        j,
+                        || Value::known(Fr::from(name.len().min(j + 1) as u64)),
+                    )?);
+                    let pack = region.assign_advice(
+                        || format!("pack {}", j),
+                        config.pack,
+                        j,
+                        || Value::known(packs[j]),
+                    )?;
+                    if j % NAME_PACK_BYTES == 0 {
+                        packed_name.push(pack);
+                    }
+                }
+                region.constrain_equal(
+                    name_len_cell.unwrap().cell(),
+                    has_byte_sum_cell.unwrap().cell(),
+                )?;
+                Ok((n_delimited_data, packed_name))
+            },
+        )?;
+
+        let (name_hash_cell, name_hash) =
+            self.assign_name_hash(layouter.namespace(|| "name hash"), &packed_name)?;
+        let revealed_name_hash = layouter.assign_region(
+            || "reveal name hash",
+            |mut region| {
+                config.q_reveal.enable(&mut region, 0)?;
+                region.assign_advice(
+                    || "reveal_name_hash",
+                    config.reveal_name_hash,
+                    0,
+                    || Value::known(Fr::from(reveal_name_hash as u64)),
+                )?;
+                let name_hash_copy =
+                    region.assign_advice(|| "name_hash", config.name_hash, 0, || name_hash)?;
+                region.constrain_equal(name_hash_copy.cell(), name_hash_cell)?;
+                region.assign_advice(
+                    || "revealed_name_hash",
+                    config.revealed_name_hash,
+                    0,
+                    || {
+                        if reveal_name_hash {
+                            name_hash
+                        } else {
+                            Value::known(Fr::zero())
+                        }
+                    },
+                )
+            },
+        )?;
+        Ok(AssignedName {
+            n_delimited_data,
+            packed_name,
+            revealed_name_hash,
+        })
+    }
+
+    /// Hashes copies of the packed name with [`PoseidonChip`], as [`NameExtractorChip::name_hash`] does out of the circuit.
+    ///
+    /// # Return values
+    /// Returns the cell of the assigned hash with its value.
+    fn assign_name_hash(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        packed_name: &[AssignedCell<Fr, Fr>],
+    ) -> Result<(Cell, Value<Fr>), Error> {
+        let range = self.config.poseidon.range();
+        range.load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        let mut name_hash = None;
+        layouter.assign_region(
+            || "name hash",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let mut aux = Context::new(
+                    region,
+                    ContextParams {
+                        max_rows: range.gate.max_rows,
+                        num_context_ids: 1,
+                        fixed_columns: range.gate.constants.clone(),
+                    },
+                );
+                let ctx = &mut aux;
+                let mut packed = Vec::with_capacity(packed_name.len());
+                for cell in packed_name {
+                    let copy = range.gate().load_witness(ctx, cell.value().copied());
+                    ctx.region.constrain_equal(copy.cell(), cell.cell())?;
+                    packed.push(copy);
+                }
+                let hash =
+                    PoseidonChip::<T, RATE>::construct(range.gate.clone()).hash(ctx, &packed);
+                range.finalize(ctx);
+                name_hash = Some((hash.cell(), hash.value().copied()));
+                Ok(())
+            },
+        )?;
+        name_hash.ok_or(Error::Synthesis)
+    }
+
+    /// Exposes the revealed hash of [`AssignedName`] in the instance column at `row`.
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        name: &AssignedName,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(name.revealed_name_hash.cell(), self.config.instance, row)
+    }
+}
+
+/// Returns the inverse of `diff`, or zero if it is not invertible.
+fn diff_inv<F: PrimeField>(diff: F) -> F {
+    diff.invert().unwrap_or(F::zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delimiter::DelimiterCircuit;
+    use crate::nullifier::{PoseidonCircuit, R_F, R_P};
+    use crate::poseidon_chip::to_circuit_fr;
+    use crate::qr::{
+        decompress_qr, qr_fields,
+        test_utils::{generate_test_qr, TestQrFields},
+    };
+    use crate::qr_data_extractor::SIGNATURE_LEN;
+    use halo2_base::halo2_proofs::{circuit::SimpleFloorPlanner, dev::MockProver, plonk::Circuit};
+    use halo2curves::bn256::Fr as FR;
+    use poseidon::Poseidon;
+
+    #[derive(Clone, Default)]
+    struct NameCircuit {
+        n_delimited_data: Vec<u64>,
+        reveal_name_hash: bool,
+    }
+
+    impl Circuit<Fr> for NameCircuit {
+        type Config = NameExtractorConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            NameExtractorChip::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let chip = NameExtractorChip::construct(config);
+            let name = chip.assign(
+                layouter.namespace(|| "name"),
+                &self.n_delimited_data,
+                self.reveal_name_hash,
+            )?;
+            chip.expose_public(layouter.namespace(|| "name hash"), &name, 0)
+        }
+    }
+
+    /// Hashes the name packed out of the circuit as the verifier would.
+    fn expected_name_hash(name: &[u8]) -> Fr {
+        let mut packed = vec![];
+        for chunk in name.chunks(NAME_PACK_BYTES) {
+            let mut repr = [0; 32];
+            repr[..chunk.len()].copy_from_slice(chunk);
+            packed.push(FR::from_bytes(&repr).unwrap());
+        }
+        packed.resize(NAME_PACK_SIZE, FR::from(0));
+        let mut poseidon = Poseidon::<FR, T, RATE>::new(R_F, R_P);
+        poseidon.update(&packed);
+        to_circuit_fr(&poseidon.squeeze())
+    }
+
+    #[test]
+    fn test_name_extractor() {
+        let k = PoseidonCircuit::K;
+
+        // The head of the sample payload, whose name is "Sumit Kumar".
+        let data = vec![
+            86, 50, 255, 51, 255, 50, 54, 57, 55, 50, 48, 50, 52, 48, 55, 49, 56, 49, 50, 52, 53,
+            53, 55, 51, 56, 55, 255, 83, 117, 109, 105, 116, 32, 75, 117, 109, 97, 114, 255, 48,
+            49, 45, 48, 49, 45, 49, 57, 56, 52, 255, 77, 255,
+        ];
+        let n_delimited_data = DelimiterCircuit::<Fr>::new(data).n_delimited_data();
+        assert_eq!(NameExtractorChip::name(&n_delimited_data), b"Sumit Kumar");
+        let name_hash = expected_name_hash(b"Sumit Kumar");
+        assert_eq!(NameExtractorChip::name_hash(b"Sumit Kumar"), Ok(name_hash));
+
+        let circuit = NameCircuit {
+            n_delimited_data,
+            reveal_name_hash: true,
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![name_hash]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![Fr::zero()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The hash is computed in the circuit over the packed name, so it is not that of another name.
+        let other_hash = expected_name_hash(b"Sumit Kumaz");
+        let prover = MockProver::run(k, &circuit, vec![vec![other_hash]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The hash is not revealed.
+        let circuit = NameCircuit {
+            reveal_name_hash: false,
+            ..circuit
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![Fr::zero()]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A name longer than the packed elements is rejected rather than truncated.
+        let long_name = [b'a'; MAX_NAME_LEN + 1];
+        assert_eq!(
+            NameExtractorChip::name_hash(&long_name),
+            Err(PackError::TooLong {
+                len: MAX_NAME_LEN + 1,
+                max_len: MAX_NAME_LEN
+            })
+        );
+        assert_eq!(
+            NameExtractorChip::name_hash(&long_name[..MAX_NAME_LEN]),
+            Ok(expected_name_hash(&long_name[..MAX_NAME_LEN]))
+        );
+    }
+
+    #[test]
+    fn test_name_extractor_utf8() {
+        let k = PoseidonCircuit::K;
+        let fields = TestQrFields {
+            name: "सुमित कुमार शर्मा".to_string(),
+            ..TestQrFields::default()
+        };
+        let (qr, _, _) = generate_test_qr(fields.clone());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = &decompressed[..decompressed.len() - SIGNATURE_LEN];
+        assert_eq!(
+            qr_fields(signed_data).unwrap()[NAME_POSITION],
+            fields.name.as_bytes()
+        );
+
+        let n_delimited_data = DelimiterCircuit::<Fr>::new(signed_data.to_vec()).n_delimited_data();
+        let name = NameExtractorChip::name(&n_delimited_data);
+        assert_eq!(name, fields.name.as_bytes());
+        assert!(name.len() > NAME_PACK_BYTES);
+
+        let circuit = NameCircuit {
+            n_delimited_data,
+            reveal_name_hash: true,
+        };
+        let name_hash = expected_name_hash(fields.name.as_bytes());
+        let prover = MockProver::run(k, &circuit, vec![vec![name_hash]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
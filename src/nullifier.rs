@@ -1,104 +1,609 @@
+use halo2_base::gates::{
+    flex_gate::FlexGateConfig,
+    range::{RangeConfig, RangeStrategy::Vertical},
+    GateInstructions, RangeInstructions,
+};
 use halo2_base::halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner}, halo2curves::pasta::pallas, 
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Instance, Selector, Error}
+    circuit::{Cell, Layouter, SimpleFloorPlanner, Value},
+    halo2curves::bn256::Fr,
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
 };
+use halo2_base::{AssignedValue, Context, ContextParams, SKIP_FIRST_PASS};
+use zeroize::Zeroizing;
+
+use crate::delimiter::NUM_DELIMITERS;
+use crate::photo::{assign_packed_bytes, pack_bytes, PackError};
+use crate::poseidon_chip::{poseidon_hash, PoseidonChip};
+use crate::qr::{qr_fields_with_layout, QrFieldLayout};
+
+pub(crate) const R_F: usize = 8;
+pub(crate) const R_P: usize = 57;
+pub(crate) const T: usize = 5;
+pub(crate) const RATE: usize = 4;
 
-use poseidon::Poseidon;
+/// The number of photo bytes packed into a field element, as in the circom circuits.
+pub const PHOTO_PACK_BYTES: usize = 31;
 
-#[derive(Default, Clone)]
-struct PoseidonCircuit {
-    nullifier_seed: u64,          // Nullifier seed as u64
-    photo: Vec<u64>,              // Photo as a vector of u64
+/// The default number of field elements the photo is packed into, i.e. `photoPackSize()` of the circom circuits.
+pub const DEFAULT_PHOTO_PACK_SIZE: usize = 32;
+
+#[derive(Clone)]
+pub struct PoseidonCircuit {
+    nullifier_seed: u64,        // Nullifier seed as u64
+    photo: Zeroizing<Vec<u64>>, // Photo as a vector of u64, zeroized on drop
+    /// The number of field elements the photo is packed into before hashing.
+    photo_pack_size: usize,
+    /// The blinding of the identity commitment, zero unless set by [`PoseidonCircuit::with_blinding`].
+    blinding: Fr,
 }
 
-#[derive(Default, Clone)]
-struct PoseidonConfig {
-    poseidon_selector: Selector,
-    advice: Column<Advice>,
-    instance: Column<Instance>,
+impl Default for PoseidonCircuit {
+    fn default() -> Self {
+        Self::new(0, vec![])
+    }
 }
 
-impl Circuit<pallas::Scalar> for PoseidonCircuit {
-    type Config = PoseidonConfig;
-    type FloorPlanner = SimpleFloorPlanner;
+/// The range chip over which [`PoseidonCircuit`] hashes, shared with the chips of a parent circuit.
+#[derive(Clone, Debug)]
+pub struct PoseidonConfig {
+    range: RangeConfig<Fr>,
+}
 
-    fn configure(meta: &mut ConstraintSystem<pallas::Scalar>) -> Self::Config {
-        let poseidon_selector = meta.selector();
-        let advice = meta.advice_column();
-        let instance = meta.instance_column();
+impl PoseidonConfig {
+    /// Configures the range chip of `2^PoseidonCircuit::K` rows, whose lookup table of 8 bits range-checks the photo bytes.
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let range = RangeConfig::configure(
+            meta,
+            Vertical,
+            &[PoseidonCircuit::NUM_ADVICE],
+            &[PoseidonCircuit::NUM_LOOKUP_ADVICE],
+            PoseidonCircuit::NUM_FIXED,
+            PoseidonCircuit::LOOKUP_BITS,
+            0,
+            PoseidonCircuit::K as usize,
+        );
+        Self { range }
+    }
+
+    /// Getter for [`RangeConfig`].
+    pub fn range(&self) -> &RangeConfig<Fr> {
+        &self.range
+    }
+}
 
-        meta.create_gate("Poseidon Hash", |v| {
-            // Example constraint for Poseidon hash
-            let s = poseidon_selector;
-            vec![s.clone() * (v[0] - v[1])] // Placeholder for actual constraints
-        });
+/// The cells assigned by [`PoseidonCircuit::assign`], for a parent circuit to expose or constrain equal to cells in other regions.
+#[derive(Clone, Copy, Debug)]
+pub struct PoseidonCells {
+    pub nullifier: Cell,
+    pub identity_commitment: Cell,
+}
+
+/// Computes the nullifier in the circuit, i.e. the Poseidon hash of `nullifier_seed` followed by `packed_photo`, as [`PoseidonCircuit::nullifier`] does out of it.
+///
+/// # Arguments
+/// * gate - a configuration for [`FlexGateConfig`].
+/// * ctx - a region context.
+/// * nullifier_seed - the assigned seed of the nullifier.
+/// * packed_photo - the assigned photo packed by [`crate::photo::assign_packed_photo`] or [`assign_packed_bytes`], e.g. the photo of [`crate::field_extractor::ExtractedFields`].
+pub fn assign_nullifier_hash<'v>(
+    gate: &FlexGateConfig<Fr>,
+    ctx: &mut Context<'v, Fr>,
+    nullifier_seed: &AssignedValue<'v, Fr>,
+    packed_photo: &[AssignedValue<'v, Fr>],
+) -> AssignedValue<'v, Fr> {
+    let mut inputs = vec![nullifier_seed.clone()];
+    inputs.extend(packed_photo.iter().cloned());
+    PoseidonChip::<T, RATE>::construct(gate.clone()).hash(ctx, &inputs)
+}
 
-        PoseidonConfig {
-            poseidon_selector,
-            advice,
-            instance,
+impl PoseidonCircuit {
+    /// The degree of the circuit, whose rows fit the Poseidon permutations of a photo of [`DEFAULT_PHOTO_PACK_SIZE`] elements.
+    pub const K: u32 = 14;
+    const NUM_ADVICE: usize = 4;
+    const NUM_LOOKUP_ADVICE: usize = 1;
+    const NUM_FIXED: usize = 1;
+    const LOOKUP_BITS: usize = 8;
+
+    pub fn new(nullifier_seed: u64, photo: Vec<u64>) -> Self {
+        Self {
+            nullifier_seed,
+            photo: Zeroizing::new(photo),
+            photo_pack_size: DEFAULT_PHOTO_PACK_SIZE,
+            blinding: Fr::zero(),
         }
     }
 
-    fn synthesize(
-        &self,
-        config: Self::Config,
-        mut layouter: impl Layouter<pallas::Scalar>,
-    ) -> Result<(), Error> {
-        layouter.assign_region(
-            || "Poseidon Hash",
-            |mut region| {
-                let mut poseidon = Poseidon::new();
+    /// Sets the number of field elements the photo is packed into, [`DEFAULT_PHOTO_PACK_SIZE`] by default.
+    pub fn with_photo_pack_size(mut self, photo_pack_size: usize) -> Self {
+        self.photo_pack_size = photo_pack_size;
+        self
+    }
+
+    /// Sets the blinding of the identity commitment, which should be sampled afresh for each verification context.
+    ///
+    /// The nullifier does not depend on the blinding, so it still detects a second use of the same QR data, while identity commitments of different blindings cannot be linked.
+    pub fn with_blinding(mut self, blinding: Fr) -> Self {
+        self.blinding = blinding;
+        self
+    }
 
-                // Assign nullifier seed
-                let nullifier_seed = self.nullifier_seed;
+    /// Packs `photo` into `photo_pack_size` field elements of [`PHOTO_PACK_BYTES`] little-endian bytes each, padded with zeros.
+    ///
+    /// This is the layout of `PackBytes` in the circom circuits, so the same photo gives the same elements.
+    ///
+    /// # Return values
+    /// Returns the elements, or [`PackError::TooLong`] if the photo is longer than `photo_pack_size * PHOTO_PACK_BYTES` bytes, as the circom circuits bound the photo length likewise.
+    /// Returns [`PackError::NotAByte`] if a value of the photo exceeds 255, which the circuit rejects by its range check, rather than truncating it.
+    pub fn pack_photo(photo: &[u64], photo_pack_size: usize) -> Result<Vec<Fr>, PackError> {
+        let photo = photo
+            .iter()
+            .enumerate()
+            .map(|(index, value)| {
+                u8::try_from(*value).map_err(|_| PackError::NotAByte {
+                    index,
+                    value: *value,
+                })
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+        pack_bytes(&photo, photo_pack_size)
+    }
+
+    /// Computes the Poseidon hash of the nullifier seed followed by the packed photo.
+    ///
+    /// The photo is packed by [`PoseidonCircuit::pack_photo`] into `photo_pack_size` elements, so the hash takes `photo_pack_size + 1` inputs whatever the photo length.
+    /// The hash is computed by [`poseidon_hash`], as [`PoseidonChip`] does in the circuit.
+    ///
+    /// The packing is that of the circom circuits, but the hash is not: this is the Poseidon of the PSE `poseidon` crate with `T = 5`, `R_F = 8` and `R_P = 57`, whose constants differ from those of circomlib.
+    /// The nullifier therefore differs from the one of the circom circuits for the same seed and photo.
+    ///
+    /// # Return values
+    /// Returns the nullifier, or [`PackError::TooLong`] if the photo does not fit in the packed elements, or [`PackError::NotAByte`] if a value of the photo exceeds 255.
+    pub fn nullifier(&self) -> Result<Fr, PackError> {
+        let mut inputs = vec![Fr::from(self.nullifier_seed)];
+        inputs.extend(Self::pack_photo(&self.photo, self.photo_pack_size)?);
+        Ok(poseidon_hash::<T, RATE>(&inputs))
+    }
+
+    /// Computes the Poseidon hash of the nullifier followed by the blinding, mapped to [`Fr`] as the nullifier is.
+    ///
+    /// With the default zero blinding, the commitment is as linkable as the nullifier itself.
+    pub fn identity_commitment(&self) -> Result<Fr, PackError> {
+        Ok(poseidon_hash::<T, RATE>(&[
+            self.nullifier()?,
+            self.blinding,
+        ]))
+    }
+
+    /// Returns the public inputs of the instance column of this circuit, i.e. the nullifier followed by the identity commitment.
+    ///
+    /// # Return values
+    /// Returns the instances, or [`PackError::TooLong`] if the photo does not fit in the packed elements, or [`PackError::NotAByte`] if a value of the photo exceeds 255.
+    pub fn instances(&self) -> Result<Vec<Vec<Fr>>, PackError> {
+        Ok(vec![vec![self.nullifier()?, self.identity_commitment()?]])
+    }
 
-                // Compute the Poseidon hash
-                let hash = poseidon.hash(&[nullifier_seed], &self.photo);
+    /// Assigns the nullifier seed and the photo bytes, and computes the nullifier over them with [`assign_nullifier_hash`].
+    ///
+    /// The photo bytes are range-checked to 8 bits and packed by [`assign_packed_bytes`] into `photo_pack_size` elements.
+    ///
+    /// # Return values
+    /// Returns the assigned nullifier, or [`Error::Synthesis`] if the photo does not fit in the packed elements.
+    pub fn assign_nullifier<'v>(
+        &self,
+        range: &RangeConfig<Fr>,
+        ctx: &mut Context<'v, Fr>,
+    ) -> Result<AssignedValue<'v, Fr>, Error> {
+        if self.photo.len() > self.photo_pack_size * PHOTO_PACK_BYTES {
+            return Err(Error::Synthesis);
+        }
+        let gate = range.gate();
+        let nullifier_seed = gate.load_witness(ctx, Value::known(Fr::from(self.nullifier_seed)));
+        let photo = self
+            .photo
+            .iter()
+            .map(|byte| {
+                let byte = gate.load_witness(ctx, Value::known(Fr::from(*byte)));
+                range.range_check(ctx, &byte, 8);
+                byte
+            })
+            .collect::<Vec<_>>();
+        let packed_photo = assign_packed_bytes(gate, ctx, &photo, self.photo_pack_size);
+        Ok(assign_nullifier_hash(
+            gate,
+            ctx,
+            &nullifier_seed,
+            &packed_photo,
+        ))
+    }
 
-                // Assign the hash output to the circuit
-                region.assign_advice(
-                    || "hash output",
-                    config.advice,
-                    0,
-                    || Ok(hash.into()),
-                )?;
+    /// Assigns the blinding and computes the identity commitment in the circuit, i.e. the Poseidon hash of `nullifier` followed by the blinding, as [`PoseidonCircuit::identity_commitment`] does out of it.
+    ///
+    /// # Arguments
+    /// * gate - a configuration for [`FlexGateConfig`].
+    /// * ctx - a region context.
+    /// * nullifier - the nullifier assigned by [`PoseidonCircuit::assign_nullifier`].
+    ///
+    /// # Return values
+    /// Returns the assigned commitment.
+    pub fn assign_identity_commitment<'v>(
+        &self,
+        gate: &FlexGateConfig<Fr>,
+        ctx: &mut Context<'v, Fr>,
+        nullifier: &AssignedValue<'v, Fr>,
+    ) -> AssignedValue<'v, Fr> {
+        let blinding = gate.load_witness(ctx, Value::known(self.blinding));
+        PoseidonChip::<T, RATE>::construct(gate.clone()).hash(ctx, &[nullifier.clone(), blinding])
+    }
 
+    /// Assigns the nullifier and the identity commitment in one region of the range chip of `config`.
+    ///
+    /// # Return values
+    /// Returns the [`PoseidonCells`], or [`Error::Synthesis`] if the photo is too long.
+    pub fn assign(
+        &self,
+        config: &PoseidonConfig,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<PoseidonCells, Error> {
+        let range = &config.range;
+        range.load_lookup_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        let mut cells = None;
+        layouter.assign_region(
+            || "nullifier",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(());
+                }
+                let mut aux = Context::new(
+                    region,
+                    ContextParams {
+                        max_rows: range.gate.max_rows,
+                        num_context_ids: 1,
+                        fixed_columns: range.gate.constants.clone(),
+                    },
+                );
+                let ctx = &mut aux;
+                let nullifier = self.assign_nullifier(range, ctx)?;
+                let identity_commitment =
+                    self.assign_identity_commitment(range.gate(), ctx, &nullifier);
+                range.finalize(ctx);
+                cells = Some(PoseidonCells {
+                    nullifier: nullifier.cell(),
+                    identity_commitment: identity_commitment.cell(),
+                });
                 Ok(())
             },
-        )
+        )?;
+        cells.ok_or(Error::Synthesis)
     }
-    
+}
+
+/// Computes the nullifier of the QR data out of the circuit, i.e. the value the in-circuit gadgets must match.
+///
+/// The photo is the field at position [`NUM_DELIMITERS`] of `layout`, packed as in the circom circuits and hashed after `nullifier_seed` by [`PoseidonCircuit::nullifier`].
+///
+/// # Arguments
+/// * nullifier_seed - the seed of the nullifier chosen by the application.
+/// * signed_data - the signed data of the QR code, e.g. [`crate::qr::signed_region`] of the decompressed payload.
+/// * layout - the fields present in `signed_data`.
+///
+/// # Return values
+/// Returns the nullifier, or `None` if `signed_data` does not match `layout`, `layout` has no photo or the photo is too long.
+pub fn compute_nullifier(
+    nullifier_seed: u64,
+    signed_data: &[u8],
+    layout: &QrFieldLayout,
+) -> Option<Fr> {
+    let fields = qr_fields_with_layout(signed_data, layout)?;
+    let photo = fields[NUM_DELIMITERS]?
+        .iter()
+        .map(|byte| *byte as u64)
+        .collect::<Vec<u64>>();
+    PoseidonCircuit::new(nullifier_seed, photo).nullifier().ok()
+}
+
+impl Circuit<Fr> for PoseidonCircuit {
+    type Config = (PoseidonConfig, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
     fn without_witnesses(&self) -> Self {
         unimplemented!()
     }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let poseidon = PoseidonConfig::configure(meta);
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        (poseidon, instance)
+    }
+
+    fn synthesize(
+        &self,
+        (config, instance): Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let cells = self.assign(&config, layouter.namespace(|| "nullifier"))?;
+        layouter.constrain_instance(cells.nullifier, instance, 0)?;
+        layouter.constrain_instance(cells.identity_commitment, instance, 1)
+    }
 }
 
 // Test function for the circuit
 #[cfg(test)]
 mod tests {
     use super::*;
-    use halo2_base::halo2_proofs::dev::MockProver;
+    use crate::photo::MAX_PHOTO_LEN;
+    use crate::poseidon_chip::to_circuit_fr;
+    use crate::prover::{gen_pk, gen_proof, gen_srs, verify};
+    use crate::qr::test_utils::{generate_test_qr, TestQrFields};
+    use crate::qr::{decompress_qr, signed_region};
+    use halo2_base::halo2_proofs::{dev::MockProver, halo2curves::group::ff::Field};
+    use halo2curves::bn256::Fr as FR;
+    use poseidon::Poseidon;
+    use rand::rngs::OsRng;
+
+    /// Exposes only the nullifier of the cells returned by [`PoseidonCircuit::assign`].
+    #[derive(Default, Clone)]
+    struct NullifierInstanceCircuit {
+        poseidon: PoseidonCircuit,
+    }
+
+    impl Circuit<Fr> for NullifierInstanceCircuit {
+        type Config = (PoseidonConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            PoseidonCircuit::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let cells = self
+                .poseidon
+                .assign(&config, layouter.namespace(|| "nullifier"))?;
+            layouter.constrain_instance(cells.nullifier, instance, 0)
+        }
+    }
+
+    #[test]
+    fn test_identity_commitment_blinding() {
+        let params = gen_srs(PoseidonCircuit::K);
+        let pk = gen_pk(&params, &PoseidonCircuit::default(), None).unwrap();
+        let prove = |blinding: Fr| {
+            let poseidon = PoseidonCircuit::new(12345678, vec![1, 2, 3, 4]).with_blinding(blinding);
+            let instance = [
+                poseidon.nullifier().unwrap(),
+                poseidon.identity_commitment().unwrap(),
+            ];
+            let proof = gen_proof(&params, &pk, poseidon, &[&instance]).unwrap();
+            verify(&params, pk.get_vk(), &proof, &[&instance]).unwrap();
+            instance
+        };
+
+        let [nullifier1, commitment1] = prove(Fr::random(OsRng));
+        let [nullifier2, commitment2] = prove(Fr::random(OsRng));
+        assert_eq!(nullifier1, nullifier2);
+        assert_ne!(commitment1, commitment2);
+
+        // The commitment is hashed in the circuit over the blinding of the witness, so it is not that of another blinding.
+        let blinded = PoseidonCircuit::new(12345678, vec![1, 2, 3, 4]).with_blinding(Fr::one());
+        let mut instances = blinded.instances().unwrap();
+        let prover = MockProver::run(PoseidonCircuit::K, &blinded, instances.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        instances[0][1] = commitment1;
+        let prover = MockProver::run(PoseidonCircuit::K, &blinded, instances).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Without a blinding, the commitment is deterministic.
+        let [_, unblinded1] = prove(Fr::zero());
+        let [_, unblinded2] = prove(Fr::zero());
+        assert_eq!(unblinded1, unblinded2);
+        assert_eq!(
+            nullifier1,
+            PoseidonCircuit::new(12345678, vec![1, 2, 3, 4])
+                .nullifier()
+                .unwrap()
+        );
+    }
 
     #[test]
     fn test_poseidon_circuit() {
         let nullifier_seed = 12345678u64; // Example nullifier seed
-        let photo = vec![1u64, 2, 3, 4];     // Example photo data
+        let photo = vec![1u64, 2, 3, 4]; // Example photo data
 
-        let circuit = PoseidonCircuit {
-            nullifier_seed,
-            photo,
+        let circuit = NullifierInstanceCircuit {
+            poseidon: PoseidonCircuit::new(nullifier_seed, photo),
         };
+        let nullifier = circuit.poseidon.nullifier().unwrap();
 
         // Create a mock prover with the circuit
-        let k = 4; // Number of rows in the circuit
-        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        let k = PoseidonCircuit::K; // Number of rows in the circuit
+        let prover = MockProver::run(k, &circuit, vec![vec![nullifier]]).unwrap();
 
         // Verify the proof
         assert!(prover.verify().is_ok());
 
-        // Get the computed hash from the instance column
-        let hash = prover.instance(0, 0).unwrap();
-        println!("Computed hash: {:?}", hash);
+        let prover = MockProver::run(k, &circuit, vec![vec![nullifier + Fr::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The nullifier is hashed in the circuit, so it is not that of another photo or seed.
+        let other_photo = PoseidonCircuit::new(nullifier_seed, vec![1, 2, 3, 5]);
+        let prover = MockProver::run(
+            k,
+            &NullifierInstanceCircuit {
+                poseidon: other_photo,
+            },
+            vec![vec![nullifier]],
+        )
+        .unwrap();
+        assert!(prover.verify().is_err());
+        let other_seed = PoseidonCircuit::new(nullifier_seed + 1, vec![1, 2, 3, 4]);
+        let prover = MockProver::run(
+            k,
+            &NullifierInstanceCircuit {
+                poseidon: other_seed,
+            },
+            vec![vec![nullifier]],
+        )
+        .unwrap();
+        assert!(prover.verify().is_err());
+
+        // Both instances of the circuit are constrained.
+        let circuit = circuit.poseidon;
+        let instances = circuit.instances().unwrap();
+        let prover = MockProver::run(k, &circuit, instances.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        let mut wrong_commitment = instances;
+        wrong_commitment[0][1] += Fr::one();
+        let prover = MockProver::run(k, &circuit, wrong_commitment).unwrap();
+        assert!(prover.verify().is_err());
+
+        // A photo longer than the packed elements cannot be assigned.
+        let tiny = PoseidonCircuit::new(nullifier_seed, vec![0; 40]).with_photo_pack_size(1);
+        assert!(MockProver::run(k, &tiny, vec![vec![Fr::zero(), Fr::zero()]]).is_err());
+    }
+
+    #[test]
+    fn test_compute_nullifier() {
+        let nullifier_seed = 12345678u64;
+        let fields = TestQrFields::default();
+        let (qr, _, _) = generate_test_qr(fields.clone());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = signed_region(&decompressed, 2048).unwrap();
+
+        // The photo holds the delimiter byte, which does not split it.
+        assert_eq!(fields.photo, [255, 79, 255, 81, 0, 47, 0, 0]);
+        // The 8 bytes fit in the first packed element, the first byte as the least significant one, and the other 31 elements are zero.
+        let first_element = fields
+            .photo
+            .iter()
+            .rev()
+            .fold(0u64, |acc, byte| (acc << 8) | *byte as u64);
+        let mut inputs = vec![FR::from(nullifier_seed), FR::from(first_element)];
+        inputs.resize(DEFAULT_PHOTO_PACK_SIZE + 1, FR::from(0));
+        // The nullifier is the sponge of the `poseidon` crate over these inputs, whose hash is less than the order of `Fr` and so kept as is.
+        let mut poseidon = Poseidon::<FR, T, RATE>::new(R_F, R_P);
+        poseidon.update(&inputs);
+        let expected = to_circuit_fr(&poseidon.squeeze());
+        let photo = fields
+            .photo
+            .iter()
+            .map(|byte| *byte as u64)
+            .collect::<Vec<u64>>();
+        assert_eq!(
+            PoseidonCircuit::new(nullifier_seed, photo.clone()).nullifier(),
+            Ok(expected)
+        );
+        assert_eq!(
+            compute_nullifier(nullifier_seed, signed_data, &QrFieldLayout::default()),
+            Some(expected)
+        );
+        // The circuit hashes the assigned photo to the same value.
+        let prover = MockProver::run(
+            PoseidonCircuit::K,
+            &NullifierInstanceCircuit {
+                poseidon: PoseidonCircuit::new(nullifier_seed, photo.clone()),
+            },
+            vec![vec![expected]],
+        )
+        .unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        assert_ne!(
+            compute_nullifier(nullifier_seed + 1, signed_data, &QrFieldLayout::default()),
+            Some(expected)
+        );
+
+        // A photo longer than the packed elements is rejected rather than truncated.
+        let mut longer = photo;
+        longer.resize(MAX_PHOTO_LEN + 1, 0);
+        assert_eq!(
+            PoseidonCircuit::new(nullifier_seed, longer).nullifier(),
+            Err(PackError::TooLong {
+                len: MAX_PHOTO_LEN + 1,
+                max_len: MAX_PHOTO_LEN
+            })
+        );
+
+        // Without a photo there is nothing to hash.
+        let without_photo = &signed_data[..signed_data.len() - fields.photo.len()];
+        assert_eq!(
+            compute_nullifier(
+                nullifier_seed,
+                without_photo,
+                &QrFieldLayout::without_photo()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_nullifier_photo_packing() {
+        // A photo spanning two packed elements, starting as a JPEG 2000 codestream.
+        let photo = (0..40)
+            .map(|i| [255, 79, 255, 81, 0, 47, 0, 0][i % 8] ^ (i as u64 / 8))
+            .collect::<Vec<u64>>();
+        let nullifier_seed = 12345678u64;
+
+        // As `PackBytes` of circom: the first byte is the least significant one of the first element.
+        let packed = PoseidonCircuit::pack_photo(&photo, DEFAULT_PHOTO_PACK_SIZE).unwrap();
+        assert_eq!(packed.len(), DEFAULT_PHOTO_PACK_SIZE);
+        let mut expected = vec![Fr::zero(); DEFAULT_PHOTO_PACK_SIZE];
+        for (i, byte) in photo.iter().enumerate() {
+            let base = (0..i % PHOTO_PACK_BYTES).fold(Fr::one(), |acc, _| acc * Fr::from(256));
+            expected[i / PHOTO_PACK_BYTES] += base * Fr::from(*byte);
+        }
+        assert_eq!(packed, expected);
+
+        // The nullifier hashes the seed followed by the packed elements, not the bytes.
+        let mut poseidon = Poseidon::<FR, T, RATE>::new(R_F, R_P);
+        let mut inputs = vec![FR::from(nullifier_seed)];
+        inputs.extend(
+            expected
+                .iter()
+                .map(|packed| FR::from_bytes(&packed.to_bytes()).unwrap()),
+        );
+        poseidon.update(&inputs);
+        let circuit = PoseidonCircuit::new(nullifier_seed, photo.clone());
+        assert_eq!(circuit.nullifier(), Ok(to_circuit_fr(&poseidon.squeeze())));
+
+        // The pack size is configurable, and changes the nullifier.
+        let smaller = PoseidonCircuit::new(nullifier_seed, photo.clone()).with_photo_pack_size(2);
+        assert_ne!(smaller.nullifier(), circuit.nullifier());
+
+        // The photo must fit in the elements.
+        let tiny = PoseidonCircuit::new(nullifier_seed, photo.clone()).with_photo_pack_size(1);
+        assert_eq!(
+            tiny.nullifier(),
+            Err(PackError::TooLong {
+                len: 40,
+                max_len: PHOTO_PACK_BYTES
+            })
+        );
+
+        // A value above 255 is rejected rather than truncated to its low byte.
+        let mut not_bytes = photo;
+        not_bytes[3] = 256 + 81;
+        assert_eq!(
+            PoseidonCircuit::pack_photo(&not_bytes, DEFAULT_PHOTO_PACK_SIZE),
+            Err(PackError::NotAByte {
+                index: 3,
+                value: 256 + 81
+            })
+        );
+        assert_eq!(
+            PoseidonCircuit::new(nullifier_seed, not_bytes).nullifier(),
+            Err(PackError::NotAByte {
+                index: 3,
+                value: 256 + 81
+            })
+        );
     }
 }
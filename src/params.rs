@@ -0,0 +1,318 @@
+//! Tunable parameters of the circuits in this library.
+//!
+//! [`halo2_base::halo2_proofs::plonk::Circuit::configure`] cannot read values stored in a circuit instance, so parameters that change the column layout are carried at the type level.
+//! A circuit takes a type implementing the corresponding trait, e.g. [`Sha256Params`], and validates it against the circuit degree `k` before proving.
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Error returned when circuit parameters are inconsistent with each other or with the circuit degree `k`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParamsError {
+    /// A lookup table of `2^lookup_bits` rows does not fit in a circuit of `2^k` rows.
+    LookupBitsTooLarge { lookup_bits: usize, k: u32 },
+    /// The lookup table must have at least one bit.
+    ZeroLookupBits,
+    /// At least one lookup advice column is required.
+    ZeroLookupAdvice,
+    /// A message of `msg_len` bytes does not fit in the `max_msg_len` bytes the SHA256 chip is configured with once padded.
+    MessageTooLong { msg_len: usize, max_msg_len: usize },
+    /// The bit length of variable RSA exponents must be positive and less than the `capacity` bits of the field.
+    InvalidExpBits { exp_bits: usize, capacity: u32 },
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LookupBitsTooLarge { lookup_bits, k } => write!(
+                f,
+                "lookup bits {lookup_bits} must be less than k = {k}, the table of 2^{lookup_bits} rows does not fit"
+            ),
+            Self::ZeroLookupBits => write!(f, "lookup bits must be positive"),
+            Self::ZeroLookupAdvice => write!(f, "at least one lookup advice column is required"),
+            Self::MessageTooLong {
+                msg_len,
+                max_msg_len,
+            } => write!(
+                f,
+                "message of {msg_len} bytes is padded to {} bytes, more than the maximum message length {max_msg_len}",
+                sha256_padded_len(*msg_len)
+            ),
+            Self::InvalidExpBits { exp_bits, capacity } => write!(
+                f,
+                "exponent bits {exp_bits} must be positive and less than the field capacity {capacity}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+/// Parameters of the SHA256 chip used by [`crate::RSASignatureVerifier`] circuits.
+///
+/// More lookup advice columns shorten the circuit at the cost of memory, so the defaults can be tuned per machine by implementing this trait for a new type.
+pub trait Sha256Params: Clone + fmt::Debug {
+    /// The bit length of the SHA256 spread lookup table.
+    const LOOKUP_BITS: usize;
+    /// The number of advice columns used for SHA256 lookups.
+    const NUM_LOOKUP_ADVICE: usize;
+
+    /// Checks that the parameters fit in a circuit of `2^k` rows.
+    fn validate(k: u32) -> Result<(), ParamsError> {
+        validate_lookup_bits(Self::LOOKUP_BITS, k)?;
+        if Self::NUM_LOOKUP_ADVICE == 0 {
+            return Err(ParamsError::ZeroLookupAdvice);
+        }
+        Ok(())
+    }
+
+    /// Returns the number of rows of the lookup advice columns used by the SHA256 chip configured with the maximum byte size `max_msg_len`.
+    ///
+    /// The chip decomposes the 32-bit words into limbs of `LOOKUP_BITS` bits for all the blocks up to `max_msg_len`, whatever the length of the hashed message.
+    fn spread_rows(max_msg_len: usize) -> usize {
+        let limbs = (32 + Self::LOOKUP_BITS - 1) / Self::LOOKUP_BITS;
+        let cells = max_msg_len / 64 * SHA256_SPREAD_WORDS_PER_BLOCK * limbs;
+        (cells + Self::NUM_LOOKUP_ADVICE - 1) / Self::NUM_LOOKUP_ADVICE
+    }
+}
+
+/// The number of 32-bit words decomposed by the spread table of the SHA256 chip per 64-byte block: four per word of the message schedule and eight per round of the compression.
+pub const SHA256_SPREAD_WORDS_PER_BLOCK: usize = 48 * 4 + 64 * 8;
+
+/// The SHA256 parameters used unless specified otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct DefaultSha256Params;
+
+impl Sha256Params for DefaultSha256Params {
+    const LOOKUP_BITS: usize = 8;
+    const NUM_LOOKUP_ADVICE: usize = 8;
+}
+
+/// Parameters of the range chip underlying the big integer and RSA chips.
+pub trait RangeParams: Clone + fmt::Debug {
+    /// The bit length of the range lookup table, whose `2^LOOKUP_BITS` rows must fit in the circuit.
+    const LOOKUP_BITS: usize;
+
+    /// Checks that the parameters fit in a circuit of `2^k` rows.
+    fn validate(k: u32) -> Result<(), ParamsError> {
+        validate_lookup_bits(Self::LOOKUP_BITS, k)
+    }
+}
+
+/// The range parameters used unless specified otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct DefaultRangeParams;
+
+impl RangeParams for DefaultRangeParams {
+    const LOOKUP_BITS: usize = 12;
+}
+
+/// All the parameters of the RSA-SHA256 verification circuit bundled for a deployment target, so that the column counts, the lookup parameters and `k` are chosen together.
+///
+/// A preset is a type as the other parameters, since [`halo2_base::halo2_proofs::plonk::Circuit::configure`] reads it without a circuit instance.
+pub trait Preset: Clone + fmt::Debug {
+    /// The parameters of the SHA256 chip.
+    type Sha256: Sha256Params;
+    /// The parameters of the range chip.
+    type Range: RangeParams;
+    /// The degree of the circuit, which has `2^K` rows.
+    const K: u32;
+    /// The bit length of the RSA modulus.
+    const BITS_LEN: usize;
+    /// The maximum byte length of the signed message.
+    const MSG_LEN: usize;
+    /// The number of advice columns of the range chip.
+    const NUM_ADVICE: usize;
+    /// The number of lookup advice columns of the range chip.
+    const NUM_LOOKUP_ADVICE: usize;
+    /// The number of fixed columns of the range chip.
+    const NUM_FIXED: usize;
+
+    /// Checks that the parameters fit in a circuit of `2^K` rows.
+    fn validate() -> Result<(), ParamsError> {
+        Self::Sha256::validate(Self::K)?;
+        Self::Range::validate(Self::K)?;
+        if Self::NUM_LOOKUP_ADVICE == 0 {
+            return Err(ParamsError::ZeroLookupAdvice);
+        }
+        Ok(())
+    }
+}
+
+/// The preset for production: 2048-bit UIDAI keys and QR data of up to 1024 bytes in `2^15` rows.
+#[derive(Clone, Debug, Default)]
+pub struct Mainnet2048;
+
+impl Preset for Mainnet2048 {
+    type Sha256 = DefaultSha256Params;
+    type Range = DefaultRangeParams;
+    const K: u32 = 15;
+    const BITS_LEN: usize = 2048;
+    const MSG_LEN: usize = 1024;
+    const NUM_ADVICE: usize = 80;
+    const NUM_LOOKUP_ADVICE: usize = 16;
+    const NUM_FIXED: usize = 1;
+}
+
+/// The preset for tests: 1024-bit keys and messages of up to 256 bytes, which are faster to generate and to prove.
+#[derive(Clone, Debug, Default)]
+pub struct Testing;
+
+impl Preset for Testing {
+    type Sha256 = DefaultSha256Params;
+    type Range = DefaultRangeParams;
+    const K: u32 = 15;
+    const BITS_LEN: usize = 1024;
+    const MSG_LEN: usize = 256;
+    const NUM_ADVICE: usize = 80;
+    const NUM_LOOKUP_ADVICE: usize = 16;
+    const NUM_FIXED: usize = 1;
+}
+
+/// [`Mainnet2048`] with the SHA256 parameters `P`, the range parameters `R` and the degree `K` tuned.
+#[derive(Clone, Debug, Default)]
+pub struct Tuned<P = DefaultSha256Params, R = DefaultRangeParams, const K: u32 = 15>(
+    PhantomData<(P, R)>,
+);
+
+impl<P: Sha256Params, R: RangeParams, const K: u32> Preset for Tuned<P, R, K> {
+    type Sha256 = P;
+    type Range = R;
+    const K: u32 = K;
+    const BITS_LEN: usize = Mainnet2048::BITS_LEN;
+    const MSG_LEN: usize = Mainnet2048::MSG_LEN;
+    const NUM_ADVICE: usize = Mainnet2048::NUM_ADVICE;
+    const NUM_LOOKUP_ADVICE: usize = Mainnet2048::NUM_LOOKUP_ADVICE;
+    const NUM_FIXED: usize = Mainnet2048::NUM_FIXED;
+}
+
+/// Checks that a lookup table of `2^lookup_bits` rows fits in a circuit of `2^k` rows.
+fn validate_lookup_bits(lookup_bits: usize, k: u32) -> Result<(), ParamsError> {
+    if lookup_bits == 0 {
+        return Err(ParamsError::ZeroLookupBits);
+    }
+    if lookup_bits >= k as usize {
+        return Err(ParamsError::LookupBitsTooLarge { lookup_bits, k });
+    }
+    Ok(())
+}
+
+/// Returns the byte length of a message of `msg_len` bytes padded by SHA256, i.e. with the `0x80` byte and the 8-byte length, rounded up to the 64-byte blocks.
+pub fn sha256_padded_len(msg_len: usize) -> usize {
+    (msg_len + 9 + 63) / 64 * 64
+}
+
+/// Checks that a message of `msg_len` bytes fits in a SHA256 chip configured with the maximum byte size `max_msg_len`, e.g. [`Preset::MSG_LEN`].
+///
+/// The chip hashes the padded message, so a message of up to `max_msg_len` bytes does not fit if its padding crosses the last block.
+pub fn validate_msg_len(msg_len: usize, max_msg_len: usize) -> Result<(), ParamsError> {
+    if sha256_padded_len(msg_len) > max_msg_len {
+        return Err(ParamsError::MessageTooLong {
+            msg_len,
+            max_msg_len,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Debug)]
+    struct OversizedSha256Params;
+
+    impl Sha256Params for OversizedSha256Params {
+        const LOOKUP_BITS: usize = 15;
+        const NUM_LOOKUP_ADVICE: usize = 8;
+    }
+
+    #[test]
+    fn test_sha256_params_validation() {
+        assert_eq!(DefaultSha256Params::validate(15), Ok(()));
+        assert_eq!(
+            OversizedSha256Params::validate(15),
+            Err(ParamsError::LookupBitsTooLarge {
+                lookup_bits: 15,
+                k: 15
+            })
+        );
+        assert_eq!(OversizedSha256Params::validate(16), Ok(()));
+    }
+
+    #[test]
+    fn test_sha256_spread_rows() {
+        // Four 8-bit limbs per word of the 16 blocks of the mainnet preset, in 8 columns.
+        assert_eq!(
+            DefaultSha256Params::spread_rows(Mainnet2048::MSG_LEN),
+            16 * SHA256_SPREAD_WORDS_PER_BLOCK * 4 / 8
+        );
+        // More columns or longer limbs take fewer rows.
+        assert!(
+            OversizedSha256Params::spread_rows(Mainnet2048::MSG_LEN)
+                < DefaultSha256Params::spread_rows(Mainnet2048::MSG_LEN)
+        );
+        assert_eq!(DefaultSha256Params::spread_rows(0), 0);
+    }
+
+    #[derive(Clone, Debug)]
+    struct ZeroRangeParams;
+
+    impl RangeParams for ZeroRangeParams {
+        const LOOKUP_BITS: usize = 0;
+    }
+
+    #[test]
+    fn test_preset_validation() {
+        assert_eq!(Mainnet2048::validate(), Ok(()));
+        assert_eq!(Testing::validate(), Ok(()));
+        assert_eq!(<Tuned>::validate(), Ok(()));
+        assert_eq!(
+            <Tuned<OversizedSha256Params>>::validate(),
+            Err(ParamsError::LookupBitsTooLarge {
+                lookup_bits: 15,
+                k: 15
+            })
+        );
+        assert_eq!(
+            <Tuned<OversizedSha256Params, DefaultRangeParams, 16>>::validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_range_params_validation() {
+        assert_eq!(DefaultRangeParams::validate(15), Ok(()));
+        assert_eq!(
+            DefaultRangeParams::validate(12),
+            Err(ParamsError::LookupBitsTooLarge {
+                lookup_bits: 12,
+                k: 12
+            })
+        );
+        assert_eq!(
+            ZeroRangeParams::validate(15),
+            Err(ParamsError::ZeroLookupBits)
+        );
+    }
+
+    #[test]
+    fn test_validate_msg_len() {
+        // The padding of 55 bytes fits in their block, but that of 56 bytes crosses it.
+        assert_eq!(sha256_padded_len(55), 64);
+        assert_eq!(sha256_padded_len(56), 128);
+
+        // The signed region of a QR code of 1015 bytes fits in the 1024 bytes of the mainnet preset, but not one byte more.
+        assert_eq!(validate_msg_len(1015, Mainnet2048::MSG_LEN), Ok(()));
+        assert_eq!(
+            validate_msg_len(1016, Mainnet2048::MSG_LEN),
+            Err(ParamsError::MessageTooLong {
+                msg_len: 1016,
+                max_msg_len: 1024
+            })
+        );
+        let err = validate_msg_len(1015, 700).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("message of 1015 bytes is padded to 1024 bytes"));
+    }
+}
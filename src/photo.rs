@@ -0,0 +1,215 @@
+//! Packing the photo into field elements as `PackBytes` of the circom circuits does.
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+use std::fmt;
+
+use crate::nullifier::{DEFAULT_PHOTO_PACK_SIZE, PHOTO_PACK_BYTES};
+
+/// The maximum byte length of the photo, i.e. `photoPackSize() * maxFieldByteSize()` of the circom circuits.
+pub const MAX_PHOTO_LEN: usize = DEFAULT_PHOTO_PACK_SIZE * PHOTO_PACK_BYTES;
+
+/// Error returned when bytes do not fit in the elements they are packed into, or are not bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PackError {
+    /// `len` bytes are more than the `max_len` bytes of the packed elements, e.g. a photo longer than [`MAX_PHOTO_LEN`].
+    TooLong { len: usize, max_len: usize },
+    /// The byte at `index` is `value`, which does not fit in a byte.
+    NotAByte { index: usize, value: u64 },
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLong { len, max_len } => write!(
+                f,
+                "{len} bytes do not fit in the {max_len} bytes of the packed elements"
+            ),
+            Self::NotAByte { index, value } => {
+                write!(
+                    f,
+                    "the byte {value} at index {index} does not fit in a byte"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+/// Packs `bytes` into `pack_size` field elements of [`PHOTO_PACK_BYTES`] little-endian bytes each, zero-padding the last chunks.
+///
+/// # Return values
+/// Returns the `pack_size` elements, or [`PackError::TooLong`] if `bytes` is longer than `pack_size * PHOTO_PACK_BYTES`, as dropping the rest would let two inputs share their elements.
+pub fn pack_bytes<F: PrimeField>(bytes: &[u8], pack_size: usize) -> Result<Vec<F>, PackError> {
+    let max_len = pack_size * PHOTO_PACK_BYTES;
+    if bytes.len() > max_len {
+        return Err(PackError::TooLong {
+            len: bytes.len(),
+            max_len,
+        });
+    }
+    let mut padded = bytes.to_vec();
+    padded.resize(max_len, 0);
+    Ok(padded
+        .chunks(PHOTO_PACK_BYTES)
+        .map(|chunk| {
+            chunk.iter().rev().fold(F::zero(), |acc, byte| {
+                acc * F::from(256) + F::from(*byte as u64)
+            })
+        })
+        .collect())
+}
+
+/// Packs the photo into the [`DEFAULT_PHOTO_PACK_SIZE`] elements hashed by the nullifier.
+///
+/// The `i`-th element holds the bytes `31 * i..31 * (i + 1)`, the first of them as the least significant one.
+///
+/// # Return values
+/// Returns the elements, or [`PackError::TooLong`] if the photo is longer than [`MAX_PHOTO_LEN`] bytes, which the circom circuits reject likewise.
+pub fn pack_photo<F: PrimeField>(photo: &[u8]) -> Result<[F; DEFAULT_PHOTO_PACK_SIZE], PackError> {
+    Ok(pack_bytes(photo, DEFAULT_PHOTO_PACK_SIZE)?
+        .try_into()
+        .unwrap())
+}
+
+/// Packs the assigned bytes into `pack_size` elements as [`pack_bytes`] does, zero-padding the last chunks.
+///
+/// The bytes are not range-checked here, so they must be constrained to 8 bits by the caller, e.g. as the input bytes of the SHA256 chip are; otherwise an element could hide bytes of other chunks.
+///
+/// # Arguments
+/// * gate - a configuration for [`FlexGateConfig`].
+/// * ctx - a region context.
+/// * bytes - the assigned bytes, at most `pack_size * PHOTO_PACK_BYTES`.
+/// * pack_size - the number of elements to pack the bytes into.
+///
+/// # Return values
+/// Returns the `pack_size` assigned elements.
+pub fn assign_packed_bytes<'v, F: PrimeField>(
+    gate: &FlexGateConfig<F>,
+    ctx: &mut Context<'v, F>,
+    bytes: &[AssignedValue<'v, F>],
+    pack_size: usize,
+) -> Vec<AssignedValue<'v, F>> {
+    assert!(bytes.len() <= pack_size * PHOTO_PACK_BYTES);
+    let bases = (0..PHOTO_PACK_BYTES)
+        .scan(F::one(), |base, _| {
+            let current = *base;
+            *base *= F::from(256);
+            Some(current)
+        })
+        .collect::<Vec<F>>();
+    (0..pack_size)
+        .map(|i| {
+            let start = (i * PHOTO_PACK_BYTES).min(bytes.len());
+            let end = ((i + 1) * PHOTO_PACK_BYTES).min(bytes.len());
+            let chunk = &bytes[start..end];
+            if chunk.is_empty() {
+                return gate.load_constant(ctx, F::zero());
+            }
+            gate.inner_product(
+                ctx,
+                chunk.iter().map(QuantumCell::Existing).collect::<Vec<_>>(),
+                bases[..chunk.len()]
+                    .iter()
+                    .map(|base| QuantumCell::Constant(*base))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect()
+}
+
+/// Packs the assigned photo bytes as [`pack_photo`] does, zero-padding them up to [`MAX_PHOTO_LEN`] bytes.
+///
+/// The bytes must be 8-bit as required by [`assign_packed_bytes`].
+///
+/// # Arguments
+/// * gate - a configuration for [`FlexGateConfig`].
+/// * ctx - a region context.
+/// * photo - the assigned photo bytes, at most [`MAX_PHOTO_LEN`].
+///
+/// # Return values
+/// Returns the [`DEFAULT_PHOTO_PACK_SIZE`] assigned elements.
+pub fn assign_packed_photo<'v, F: PrimeField>(
+    gate: &FlexGateConfig<F>,
+    ctx: &mut Context<'v, F>,
+    photo: &[AssignedValue<'v, F>],
+) -> Vec<AssignedValue<'v, F>> {
+    assign_packed_bytes(gate, ctx, photo, DEFAULT_PHOTO_PACK_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon_chip::test_utils::TestGateCircuit;
+    use halo2_base::halo2_proofs::{circuit::Value, dev::MockProver, halo2curves::bn256::Fr};
+    use std::rc::Rc;
+
+    #[test]
+    fn test_pack_photo() {
+        let photo = (0..MAX_PHOTO_LEN)
+            .map(|i| (i * 7 + 3) as u8)
+            .collect::<Vec<u8>>();
+        let packed = pack_photo::<Fr>(&photo).unwrap();
+        assert_eq!(packed.len(), 32);
+        for (element, chunk) in packed.iter().zip(photo.chunks(31)) {
+            // The little-endian representation of each element is its chunk followed by a zero byte.
+            let repr = element.to_bytes();
+            assert_eq!(&repr[..31], chunk);
+            assert_eq!(repr[31], 0);
+        }
+
+        // The last chunk of a shorter photo is zero-padded, and the following elements are zero.
+        let packed = pack_photo::<Fr>(&photo[..40]).unwrap();
+        assert_eq!(packed[0].to_bytes()[..31], photo[..31]);
+        assert_eq!(packed[1].to_bytes()[..9], photo[31..40]);
+        assert!(packed[1].to_bytes()[9..].iter().all(|byte| *byte == 0));
+        assert!(packed[2..].iter().all(|element| *element == Fr::zero()));
+
+        // A longer photo is rejected rather than truncated.
+        let mut longer = photo.clone();
+        longer.push(0);
+        assert_eq!(
+            pack_photo::<Fr>(&longer),
+            Err(PackError::TooLong {
+                len: MAX_PHOTO_LEN + 1,
+                max_len: MAX_PHOTO_LEN
+            })
+        );
+    }
+
+    #[test]
+    fn test_pack_photo_circom() {
+        // `PackBytes(992)` of the circom circuits over the sample photo of the QR tests, zero-padded as `photo` of the circuit inputs.
+        let packed = pack_photo::<Fr>(&[255, 79, 255, 81, 0, 47, 0, 0]).unwrap();
+        assert_eq!(packed[0], Fr::from(51678422192127u64));
+        assert!(packed[1..].iter().all(|element| *element == Fr::zero()));
+    }
+
+    #[test]
+    fn test_assign_packed_photo() {
+        let k = TestGateCircuit::K;
+        for len in [MAX_PHOTO_LEN, 40] {
+            let photo = (0..len).map(|i| (i * 7 + 3) as u8).collect::<Vec<u8>>();
+            let expected = pack_photo::<Fr>(&photo).unwrap();
+            let circuit = TestGateCircuit::new(Rc::new(
+                move |gate: &FlexGateConfig<Fr>, ctx: &mut Context<Fr>| {
+                    let assigned = photo
+                        .iter()
+                        .map(|byte| gate.load_witness(ctx, Value::known(Fr::from(*byte as u64))))
+                        .collect::<Vec<AssignedValue<Fr>>>();
+                    let packed = assign_packed_photo(gate, ctx, &assigned);
+                    assert_eq!(packed.len(), DEFAULT_PHOTO_PACK_SIZE);
+                    for (element, expected) in packed.iter().zip(expected) {
+                        gate.assert_is_const(ctx, element, expected);
+                    }
+                    Ok(())
+                },
+            ));
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! Proving that the pincode is in a published allowlist without revealing which one it is.
+use halo2_base::halo2_proofs::{circuit::Value, halo2curves::bn256::Fr};
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    AssignedValue, Context, QuantumCell,
+};
+
+use crate::poseidon_chip::{poseidon_hash, PoseidonChip};
+
+/// The width of the Poseidon permutation hashing two nodes of the allowlist tree.
+pub const MERKLE_T: usize = 3;
+
+/// The rate of the Poseidon sponge hashing two nodes of the allowlist tree.
+pub const MERKLE_RATE: usize = 2;
+
+/// Hashes two nodes of the allowlist tree out of the circuit, as [`PincodeAllowlistChip`] does in it.
+pub fn hash_nodes(left: Fr, right: Fr) -> Fr {
+    poseidon_hash::<MERKLE_T, MERKLE_RATE>(&[left, right])
+}
+
+/// A Merkle tree of allowed pincodes, whose root is published by the service.
+///
+/// The leaves are the pincodes as field elements, padded with zeros to a power of two.
+/// No pincode is zero, so the padding leaves are not members.
+#[derive(Clone, Debug)]
+pub struct PincodeAllowlist {
+    pincodes: Vec<u32>,
+    /// The nodes of each level, from the leaves to the root.
+    levels: Vec<Vec<Fr>>,
+}
+
+/// The Merkle path of a leaf, e.g. a pincode in a [`PincodeAllowlist`] or a key in a [`crate::key_registry::KeyRegistry`], witnessed by the prover.
+#[derive(Clone, Debug)]
+pub struct MerklePath {
+    /// The position of the leaf, whose `i`-th bit is one if the node at the `i`-th level is a right child.
+    pub index: u64,
+    /// The sibling at each level, from the leaves up to the children of the root.
+    pub siblings: Vec<Fr>,
+}
+
+impl PincodeAllowlist {
+    /// Creates new [`PincodeAllowlist`] from the allowed pincodes.
+    pub fn new(pincodes: &[u32]) -> Self {
+        let num_leaves = pincodes.len().max(2).next_power_of_two();
+        let mut leaves = pincodes
+            .iter()
+            .map(|pincode| Fr::from(*pincode as u64))
+            .collect::<Vec<Fr>>();
+        leaves.resize(num_leaves, Fr::zero());
+        Self {
+            pincodes: pincodes.to_vec(),
+            levels: merkle_levels(leaves),
+        }
+    }
+
+    /// Returns the number of levels below the root.
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Returns the root to publish.
+    pub fn root(&self) -> Fr {
+        self.levels[self.depth()][0]
+    }
+
+    /// Returns the Merkle path of `pincode`, or `None` if it is not allowed.
+    pub fn path(&self, pincode: u32) -> Option<MerklePath> {
+        let index = self.pincodes.iter().position(|p| *p == pincode)?;
+        Some(merkle_path(&self.levels, index))
+    }
+}
+
+/// Returns the nodes of each level of the Merkle tree of `leaves`, from the leaves to the root.
+///
+/// The number of leaves must be a power of two.
+pub(crate) fn merkle_levels(leaves: Vec<Fr>) -> Vec<Vec<Fr>> {
+    assert!(leaves.len().is_power_of_two());
+    let mut levels = vec![leaves];
+    while levels.last().unwrap().len() > 1 {
+        let level = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| hash_nodes(pair[0], pair[1]))
+            .collect();
+        levels.push(level);
+    }
+    levels
+}
+
+/// Returns the Merkle path of the leaf at `index` in the tree of `levels`, as returned by [`merkle_levels`].
+pub(crate) fn merkle_path(levels: &[Vec<Fr>], index: usize) -> MerklePath {
+    let siblings = levels[..levels.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(level, nodes)| nodes[(index >> level) ^ 1])
+        .collect();
+    MerklePath {
+        index: index as u64,
+        siblings,
+    }
+}
+
+/// Computes the root of a Merkle tree from an assigned leaf, its assigned index and the siblings along its path.
+///
+/// The index is decomposed into `siblings.len()` constrained bits, which select the order of each pair of nodes.
+pub(crate) fn assign_merkle_root<'v>(
+    poseidon: &PoseidonChip<MERKLE_T, MERKLE_RATE>,
+    ctx: &mut Context<'v, Fr>,
+    leaf: &AssignedValue<'v, Fr>,
+    index: &AssignedValue<'v, Fr>,
+    siblings: &[Fr],
+) -> AssignedValue<'v, Fr> {
+    let gate = poseidon.gate();
+    let index_bits = gate.num_to_bits(ctx, index, siblings.len());
+    let mut node = leaf.clone();
+    for (sibling, is_right) in siblings.iter().zip(index_bits.iter()) {
+        let sibling = gate.load_witness(ctx, Value::known(*sibling));
+        let left = gate.select(
+            ctx,
+            QuantumCell::Existing(&sibling),
+            QuantumCell::Existing(&node),
+            QuantumCell::Existing(is_right),
+        );
+        let right = gate.select(
+            ctx,
+            QuantumCell::Existing(&node),
+            QuantumCell::Existing(&sibling),
+            QuantumCell::Existing(is_right),
+        );
+        node = poseidon.hash(ctx, &[left, right]);
+    }
+    node
+}
+
+/// A chip constraining the extracted pincode to be a leaf of a [`PincodeAllowlist`].
+///
+/// The path is private, so the proof only reveals the root, i.e. the allowlist, and not the pincode or its position.
+/// The nodes are hashed with [`PoseidonChip`] with the round numbers of [`crate::nullifier::PoseidonCircuit`].
+#[derive(Clone, Debug)]
+pub struct PincodeAllowlistChip {
+    poseidon: PoseidonChip<MERKLE_T, MERKLE_RATE>,
+}
+
+impl PincodeAllowlistChip {
+    /// Creates new [`PincodeAllowlistChip`] from [`FlexGateConfig`].
+    pub fn construct(gate: FlexGateConfig<Fr>) -> Self {
+        Self {
+            poseidon: PoseidonChip::construct(gate),
+        }
+    }
+
+    /// Getter for [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<Fr> {
+        self.poseidon.gate()
+    }
+
+    /// Computes the root of the allowlist tree from the assigned pincode and its witnessed Merkle path.
+    ///
+    /// The index is decomposed into `path.siblings.len()` constrained bits, which select the order of each pair of nodes.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * pincode - the assigned pincode extracted from the QR data.
+    /// * path - the Merkle path as computed by [`PincodeAllowlist::path`].
+    ///
+    /// # Return values
+    /// Returns the assigned root, which the caller must constrain equal to the published root, e.g. an instance, for the membership to hold.
+    pub fn assign_root<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        pincode: &AssignedValue<'v, Fr>,
+        path: &MerklePath,
+    ) -> AssignedValue<'v, Fr> {
+        let index = self
+            .gate()
+            .load_witness(ctx, Value::known(Fr::from(path.index)));
+        assign_merkle_root(&self.poseidon, ctx, pincode, &index, &path.siblings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon_chip::test_utils::TestGateCircuit;
+    use halo2_base::halo2_proofs::dev::MockProver;
+    use std::rc::Rc;
+
+    const ALLOWLIST: [u32; 5] = [110051, 560001, 400001, 700001, 600001];
+
+    fn membership_circuit(pincode: u32, path: MerklePath, root: Fr) -> TestGateCircuit {
+        TestGateCircuit::new(Rc::new(
+            move |gate: &FlexGateConfig<Fr>, ctx: &mut Context<Fr>| {
+                let chip = PincodeAllowlistChip::construct(gate.clone());
+                let pincode = gate.load_witness(ctx, Value::known(Fr::from(pincode as u64)));
+                let computed_root = chip.assign_root(ctx, &pincode, &path);
+                gate.assert_is_const(ctx, &computed_root, root);
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_pincode_allowlist() {
+        let allowlist = PincodeAllowlist::new(&ALLOWLIST);
+        assert_eq!(allowlist.depth(), 3);
+        assert!(allowlist.path(110052).is_none());
+
+        // The path of the last pincode recomputes the root out of the circuit.
+        let path = allowlist.path(600001).unwrap();
+        let mut node = Fr::from(600001);
+        for (level, sibling) in path.siblings.iter().enumerate() {
+            node = if (path.index >> level) & 1 == 1 {
+                hash_nodes(*sibling, node)
+            } else {
+                hash_nodes(node, *sibling)
+            };
+        }
+        assert_eq!(node, allowlist.root());
+    }
+
+    #[test]
+    fn test_pincode_allowlist_chip() {
+        let k = TestGateCircuit::K;
+        let allowlist = PincodeAllowlist::new(&ALLOWLIST);
+        let root = allowlist.root();
+
+        for pincode in ALLOWLIST {
+            let path = allowlist.path(pincode).unwrap();
+            let circuit = membership_circuit(pincode, path, root);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+
+        // A tampered sibling does not lead to the root.
+        let mut path = allowlist.path(400001).unwrap();
+        path.siblings[1] += Fr::one();
+        let circuit = membership_circuit(400001, path, root);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Nor does a tampered index.
+        let mut path = allowlist.path(400001).unwrap();
+        path.index ^= 1;
+        let circuit = membership_circuit(400001, path, root);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // A pincode out of the allowlist cannot reuse the path of an allowed one.
+        let path = allowlist.path(110051).unwrap();
+        let circuit = membership_circuit(110052, path, root);
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,393 @@
+//! A chip computing Poseidon hashes in the circuit, equal to the native hashes of the `poseidon` crate.
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    AssignedValue, Context, QuantumCell,
+};
+use halo2curves::bn256::Fr as FR;
+use poseidon::{Poseidon, Spec};
+
+use crate::nullifier::{R_F, R_P};
+
+/// Converts an element of the field of the `poseidon` crate into the circuit field.
+pub fn to_circuit_fr(value: &FR) -> Fr {
+    Fr::from_bytes(&value.to_bytes()).unwrap()
+}
+
+/// Converts an element of the circuit field into the field of the `poseidon` crate.
+pub fn to_native_fr(value: &Fr) -> FR {
+    FR::from_bytes(&value.to_bytes()).unwrap()
+}
+
+/// Hashes `inputs` out of the circuit with the sponge of `poseidon::Poseidon::<_, T, RATE>::new(R_F, R_P)`, as [`PoseidonChip`] does in it.
+///
+/// The hash is mapped to the circuit field by [`to_circuit_fr`], so every native hash of the crate is the value of the assigned one.
+pub(crate) fn poseidon_hash<const T: usize, const RATE: usize>(inputs: &[Fr]) -> Fr {
+    let mut poseidon = Poseidon::<FR, T, RATE>::new(R_F, R_P);
+    poseidon.update(&inputs.iter().map(to_native_fr).collect::<Vec<FR>>());
+    to_circuit_fr(&poseidon.squeeze())
+}
+
+/// A chip computing the Poseidon sponge of `poseidon::Poseidon::<_, T, RATE>::new(R_F, R_P)` over [`FlexGateConfig`].
+///
+/// The permutation follows the optimized rounds of the `poseidon` crate: the round constants are added after the S-boxes and the partial rounds use sparse MDS matrices.
+#[derive(Clone, Debug)]
+pub struct PoseidonChip<const T: usize, const RATE: usize> {
+    gate: FlexGateConfig<Fr>,
+    start: Vec<[Fr; T]>,
+    partial: Vec<Fr>,
+    end: Vec<[Fr; T]>,
+    mds: [[Fr; T]; T],
+    pre_sparse_mds: [[Fr; T]; T],
+    /// The first row and the lower part of the first column of each sparse MDS matrix.
+    sparse_mds: Vec<([Fr; T], [Fr; RATE])>,
+}
+
+impl<const T: usize, const RATE: usize> PoseidonChip<T, RATE> {
+    /// Creates new [`PoseidonChip`] from [`FlexGateConfig`], with the constants of the Poseidon specification with [`R_F`] full rounds and [`R_P`] partial rounds.
+    pub fn construct(gate: FlexGateConfig<Fr>) -> Self {
+        let spec = Spec::<FR, T, RATE>::new(R_F, R_P);
+        let constants = spec.constants();
+        let matrices = spec.mds_matrices();
+        let convert = |row: &[FR; T]| row.map(|value| to_circuit_fr(&value));
+        Self {
+            gate,
+            start: constants.start().iter().map(convert).collect(),
+            partial: constants.partial().iter().map(to_circuit_fr).collect(),
+            end: constants.end().iter().map(convert).collect(),
+            mds: matrices.mds().rows().map(|row| convert(&row)),
+            pre_sparse_mds: matrices.pre_sparse_mds().rows().map(|row| convert(&row)),
+            sparse_mds: matrices
+                .sparse_matrices()
+                .iter()
+                .map(|matrix| {
+                    let col_hat = matrix.col_hat().map(|value| to_circuit_fr(&value));
+                    (convert(matrix.row()), col_hat)
+                })
+                .collect(),
+        }
+    }
+
+    /// Getter for [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<Fr> {
+        &self.gate
+    }
+
+    /// Computes the Poseidon hash of `inputs`.
+    ///
+    /// As in the native sponge, the inputs are absorbed by chunks of `RATE` elements and the last chunk is padded with a one.
+    ///
+    /// # Return values
+    /// Returns the assigned hash, equal to `squeeze()` after `update(inputs)` in the native sponge.
+    pub fn hash<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        inputs: &[AssignedValue<'v, Fr>],
+    ) -> AssignedValue<'v, Fr> {
+        // The capacity element is initialized to 2^64.
+        let mut state = (0..T)
+            .map(|i| {
+                let value = if i == 0 {
+                    Fr::from(u64::MAX) + Fr::one()
+                } else {
+                    Fr::zero()
+                };
+                self.gate.load_constant(ctx, value)
+            })
+            .collect::<Vec<AssignedValue<Fr>>>();
+        let num_full_chunks = inputs.len() / RATE;
+        for chunk in inputs[..num_full_chunks * RATE].chunks(RATE) {
+            let chunk = chunk.iter().map(QuantumCell::Existing).collect();
+            state = self.absorb(ctx, &state, chunk);
+        }
+        let mut last_chunk = inputs[num_full_chunks * RATE..]
+            .iter()
+            .map(QuantumCell::Existing)
+            .collect::<Vec<QuantumCell<Fr>>>();
+        last_chunk.push(QuantumCell::Constant(Fr::one()));
+        state = self.absorb(ctx, &state, last_chunk);
+        state[1].clone()
+    }
+
+    /// Adds `chunk` to the rate elements of `state` and permutes it.
+    fn absorb<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        state: &[AssignedValue<'v, Fr>],
+        chunk: Vec<QuantumCell<'_, 'v, Fr>>,
+    ) -> Vec<AssignedValue<'v, Fr>> {
+        let mut new_state = state.to_vec();
+        for (word, input) in new_state.iter_mut().skip(1).zip(chunk) {
+            *word = self.gate.add(ctx, QuantumCell::Existing(word), input);
+        }
+        self.permute(ctx, new_state)
+    }
+
+    /// Applies the Poseidon permutation to `state`.
+    fn permute<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        state: Vec<AssignedValue<'v, Fr>>,
+    ) -> Vec<AssignedValue<'v, Fr>> {
+        let r_f = R_F / 2;
+
+        // The first half of the full rounds.
+        let mut state = state
+            .iter()
+            .zip(self.start[0].iter())
+            .map(|(word, constant)| {
+                self.gate.add(
+                    ctx,
+                    QuantumCell::Existing(word),
+                    QuantumCell::Constant(*constant),
+                )
+            })
+            .collect::<Vec<AssignedValue<Fr>>>();
+        for constants in self.start.iter().skip(1).take(r_f - 1) {
+            state = self.sbox_full(ctx, &state, constants);
+            state = self.apply_mds(ctx, &state, &self.mds);
+        }
+        state = self.sbox_full(ctx, &state, self.start.last().unwrap());
+        state = self.apply_mds(ctx, &state, &self.pre_sparse_mds);
+
+        // The partial rounds.
+        for (constant, (row, col_hat)) in self.partial.iter().zip(self.sparse_mds.iter()) {
+            state[0] = self.sbox(ctx, &state[0], *constant);
+            state = self.apply_sparse_mds(ctx, &state, row, col_hat);
+        }
+
+        // The second half of the full rounds.
+        for constants in self.end.iter() {
+            state = self.sbox_full(ctx, &state, constants);
+            state = self.apply_mds(ctx, &state, &self.mds);
+        }
+        state = self.sbox_full(ctx, &state, &[Fr::zero(); T]);
+        self.apply_mds(ctx, &state, &self.mds)
+    }
+
+    /// Returns `word^5 + constant`.
+    fn sbox<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        word: &AssignedValue<'v, Fr>,
+        constant: Fr,
+    ) -> AssignedValue<'v, Fr> {
+        let gate = &self.gate;
+        let squared = gate.mul(
+            ctx,
+            QuantumCell::Existing(word),
+            QuantumCell::Existing(word),
+        );
+        let quad = gate.mul(
+            ctx,
+            QuantumCell::Existing(&squared),
+            QuantumCell::Existing(&squared),
+        );
+        gate.mul_add(
+            ctx,
+            QuantumCell::Existing(&quad),
+            QuantumCell::Existing(word),
+            QuantumCell::Constant(constant),
+        )
+    }
+
+    /// Applies [`PoseidonChip::sbox`] to every word of `state`.
+    fn sbox_full<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        state: &[AssignedValue<'v, Fr>],
+        constants: &[Fr; T],
+    ) -> Vec<AssignedValue<'v, Fr>> {
+        state
+            .iter()
+            .zip(constants.iter())
+            .map(|(word, constant)| self.sbox(ctx, word, *constant))
+            .collect()
+    }
+
+    /// Multiplies `state` by the matrix of `rows`.
+    fn apply_mds<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        state: &[AssignedValue<'v, Fr>],
+        rows: &[[Fr; T]; T],
+    ) -> Vec<AssignedValue<'v, Fr>> {
+        rows.iter()
+            .map(|row| {
+                self.gate.inner_product(
+                    ctx,
+                    state.iter().map(QuantumCell::Existing),
+                    row.iter().map(|value| QuantumCell::Constant(*value)),
+                )
+            })
+            .collect()
+    }
+
+    /// Multiplies `state` by the sparse matrix of the first row `row` and the lower part of the first column `col_hat`, whose other entries are those of the identity.
+    fn apply_sparse_mds<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        state: &[AssignedValue<'v, Fr>],
+        row: &[Fr; T],
+        col_hat: &[Fr; RATE],
+    ) -> Vec<AssignedValue<'v, Fr>> {
+        let mut new_state = vec![self.gate.inner_product(
+            ctx,
+            state.iter().map(QuantumCell::Existing),
+            row.iter().map(|value| QuantumCell::Constant(*value)),
+        )];
+        for (word, value) in state.iter().skip(1).zip(col_hat.iter()) {
+            new_state.push(self.gate.mul_add(
+                ctx,
+                QuantumCell::Existing(&state[0]),
+                QuantumCell::Constant(*value),
+                QuantumCell::Existing(word),
+            ));
+        }
+        new_state
+    }
+}
+
+/// A circuit running a gadget over [`FlexGateConfig`] or [`RangeConfig`] in a single region, for testing the chips over them.
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        halo2curves::bn256::Fr,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use halo2_base::{
+        gates::{
+            flex_gate::FlexGateConfig,
+            range::{RangeConfig, RangeStrategy::Vertical},
+            RangeInstructions,
+        },
+        Context, ContextParams, SKIP_FIRST_PASS,
+    };
+    use std::rc::Rc;
+
+    pub(crate) type TestGateGadget =
+        Rc<dyn for<'v> Fn(&FlexGateConfig<Fr>, &mut Context<'v, Fr>) -> Result<(), Error>>;
+
+    pub(crate) type TestRangeGadget =
+        Rc<dyn for<'v> Fn(&RangeConfig<Fr>, &mut Context<'v, Fr>) -> Result<(), Error>>;
+
+    #[derive(Clone)]
+    pub(crate) struct TestGateCircuit {
+        gadget: TestRangeGadget,
+    }
+
+    impl TestGateCircuit {
+        const NUM_ADVICE: usize = 4;
+        const NUM_FIXED: usize = 1;
+        const NUM_LOOKUP_ADVICE: usize = 1;
+        const LOOKUP_BITS: usize = 8;
+        pub(crate) const K: u32 = 14;
+
+        pub(crate) fn new(gadget: TestGateGadget) -> Self {
+            Self::with_range(Rc::new(
+                move |range: &RangeConfig<Fr>, ctx: &mut Context<Fr>| gadget(&range.gate, ctx),
+            ))
+        }
+
+        /// Creates a circuit whose gadget also reaches the range chip, e.g. for comparisons.
+        pub(crate) fn with_range(gadget: TestRangeGadget) -> Self {
+            Self { gadget }
+        }
+    }
+
+    impl Circuit<Fr> for TestGateCircuit {
+        type Config = RangeConfig<Fr>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            RangeConfig::configure(
+                meta,
+                Vertical,
+                &[Self::NUM_ADVICE],
+                &[Self::NUM_LOOKUP_ADVICE],
+                Self::NUM_FIXED,
+                Self::LOOKUP_BITS,
+                0,
+                Self::K as usize,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            config.load_lookup_table(&mut layouter)?;
+            let mut first_pass = SKIP_FIRST_PASS;
+            layouter.assign_region(
+                || "gate gadget test",
+                |region| {
+                    if first_pass {
+                        first_pass = false;
+                        return Ok(());
+                    }
+                    let mut aux = Context::new(
+                        region,
+                        ContextParams {
+                            max_rows: config.gate.max_rows,
+                            num_context_ids: 1,
+                            fixed_columns: config.gate.constants.clone(),
+                        },
+                    );
+                    let ctx = &mut aux;
+                    (self.gadget)(&config, ctx)?;
+                    config.finalize(ctx);
+                    Ok(())
+                },
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::TestGateCircuit;
+    use super::*;
+    use halo2_base::halo2_proofs::{circuit::Value, dev::MockProver};
+    use poseidon::Poseidon;
+    use std::rc::Rc;
+
+    fn hash_circuit(inputs: Vec<u64>, expected: Fr) -> TestGateCircuit {
+        TestGateCircuit::new(Rc::new(
+            move |gate: &FlexGateConfig<Fr>, ctx: &mut Context<Fr>| {
+                let inputs = inputs
+                    .iter()
+                    .map(|input| gate.load_witness(ctx, Value::known(Fr::from(*input))))
+                    .collect::<Vec<AssignedValue<Fr>>>();
+                let chip = PoseidonChip::<3, 2>::construct(gate.clone());
+                let hash = chip.hash(ctx, &inputs);
+                gate.assert_is_const(ctx, &hash, expected);
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_poseidon_chip() {
+        let k = TestGateCircuit::K;
+        // Empty, partial and full chunks.
+        for len in 0..6 {
+            let inputs = (1..=len).collect::<Vec<u64>>();
+            let mut poseidon = Poseidon::<FR, 3, 2>::new(R_F, R_P);
+            poseidon.update(&inputs.iter().map(|v| FR::from(*v)).collect::<Vec<FR>>());
+            let expected = to_circuit_fr(&poseidon.squeeze());
+
+            let circuit = hash_circuit(inputs.clone(), expected);
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+            let circuit = hash_circuit(inputs, expected + Fr::one());
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+}
@@ -0,0 +1,1075 @@
+//! Key generation, proving and verification of the circuits in this library with the KZG commitment scheme over BN256.
+use halo2_base::halo2_proofs::{
+    arithmetic::CurveAffine,
+    halo2curves::{
+        bn256::{Bn256, Fr, G1Affine},
+        group::ff::PrimeField,
+    },
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, VerifyingKey,
+    },
+    poly::{
+        commitment::{Params, ParamsProver},
+        kzg::{
+            commitment::{KZGCommitmentScheme, ParamsKZG},
+            multiopen::{ProverSHPLONK, VerifierSHPLONK},
+            strategy::{AccumulatorStrategy, SingleStrategy},
+        },
+        VerificationStrategy,
+    },
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+    },
+    SerdeFormat,
+};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::any::type_name;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::age::AgeCircuit;
+use crate::conditional_secrets::IdentityCircuit;
+use crate::nullifier::PoseidonCircuit;
+use crate::qr_data_extractor::{AadhaarQRVerifierCircuit, SIGNATURE_LEN};
+use crate::signal::SquareCircuit;
+use crate::timestamp::TimestampCircuit;
+
+/// Generates a SRS for circuits of `2^k` rows.
+///
+/// The toxic waste is sampled from [`OsRng`], so the SRS is only suitable for testing.
+pub fn gen_srs(k: u32) -> ParamsKZG<Bn256> {
+    ParamsKZG::<Bn256>::setup(k, OsRng)
+}
+
+/// Generates a proving key of `circuit` from `params`, or reads it from `path` if the file exists.
+///
+/// Only the shape of `circuit` is used, so its witnesses may be placeholders.
+/// A generated key is written to `path` in [`SerdeFormat::RawBytes`], creating its missing parent directories.
+/// A key read from `path` is kept only if its verifying key is the one [`keygen_vk`] generates for `circuit` and `params`, and it is regenerated and overwritten otherwise, e.g. after the circuit or the SRS changed.
+///
+/// # Arguments
+/// * params - the SRS.
+/// * circuit - a circuit of the shape to prove.
+/// * path - the file caching the proving key, e.g. `./examples/agg.pk`, or `None` not to cache it.
+///
+/// # Return values
+/// Returns the proving key, or [`Error::Transcript`] if the file cannot be read or written.
+pub fn gen_pk<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+    path: Option<&Path>,
+) -> Result<ProvingKey<G1Affine>, Error> {
+    let vk = keygen_vk(params, circuit)?;
+    if let Some(path) = path.filter(|path| path.exists()) {
+        let mut reader = BufReader::new(File::open(path)?);
+        let pk = ProvingKey::read::<_, C>(&mut reader, SerdeFormat::RawBytes)?;
+        if pk.get_vk().transcript_repr() == vk.transcript_repr() {
+            return Ok(pk);
+        }
+    }
+    let pk = keygen_pk(params, vk, circuit)?;
+    if let Some(path) = path {
+        write_pk(&pk, path)?;
+    }
+    Ok(pk)
+}
+
+fn write_pk(pk: &ProvingKey<G1Affine>, path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut writer = BufWriter::new(File::create(path)?);
+    pk.write(&mut writer, SerdeFormat::RawBytes)?;
+    writer.flush()
+}
+
+/// Creates a proof of `circuit` for `instances`, one slice per instance column.
+pub fn gen_proof<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[&[Fr]],
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[instances],
+        OsRng,
+        &mut transcript,
+    )?;
+    Ok(transcript.finalize())
+}
+
+/// Verifies `proof` against `vk` and `instances`, one slice per instance column.
+pub fn verify(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[&[Fr]],
+) -> Result<(), Error> {
+    verify_transcript(params, vk, proof, instances)
+}
+
+/// Verifies a proof read from `reader` as [`verify`], without buffering the whole proof beforehand.
+///
+/// # Return values
+/// Returns true if the proof is valid, and false if it is not or cannot be read, e.g. the stream ends early.
+pub fn verify_from_reader<R: io::Read>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    reader: R,
+    instances: &[&[Fr]],
+) -> bool {
+    verify_transcript(params, vk, reader, instances).is_ok()
+}
+
+fn verify_transcript<R: io::Read>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    reader: R,
+    instances: &[&[Fr]],
+) -> Result<(), Error> {
+    let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(reader);
+    let strategy = SingleStrategy::new(params);
+    verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+        params.verifier_params(),
+        vk,
+        strategy,
+        &[instances],
+        &mut transcript,
+    )
+}
+
+/// Verifies each proof of `proofs` against `vk` and its instances, one vector per instance column.
+///
+/// The proofs are first checked together with a single pairing on their accumulated MSMs, and only verified one by one if that check fails, so a stream of valid proofs costs one final pairing check.
+///
+/// # Return values
+/// Returns whether each proof is valid, in the order of `proofs`.
+pub fn verify_batch<I>(
+    vk: &VerifyingKey<G1Affine>,
+    params: &ParamsKZG<Bn256>,
+    proofs: I,
+) -> Vec<bool>
+where
+    I: IntoIterator<Item = (Vec<u8>, Vec<Vec<Fr>>)>,
+{
+    let proofs = proofs.into_iter().collect::<Vec<_>>();
+    if verify_accumulated(params, vk, &proofs) {
+        return vec![true; proofs.len()];
+    }
+    proofs
+        .iter()
+        .map(|(proof, instances)| {
+            let slices = instances.iter().map(Vec::as_slice).collect::<Vec<&[Fr]>>();
+            verify_transcript(params, vk, proof.as_slice(), &slices).is_ok()
+        })
+        .collect()
+}
+
+fn verify_accumulated(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proofs: &[(Vec<u8>, Vec<Vec<Fr>>)],
+) -> bool {
+    let mut strategy = AccumulatorStrategy::new(params);
+    for (proof, instances) in proofs {
+        let slices = instances.iter().map(Vec::as_slice).collect::<Vec<&[Fr]>>();
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+        strategy =
+            match verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+                params.verifier_params(),
+                vk,
+                strategy,
+                &[&slices],
+                &mut transcript,
+            ) {
+                Ok(strategy) => strategy,
+                Err(_) => return false,
+            };
+    }
+    strategy.finalize()
+}
+
+/// The index of the instance column of the message hash in the RSA-SHA256 verification circuit over [`crate::configure_rsa_sha256`], which exposes the limbs of the modulus in the column 0.
+///
+/// [`AadhaarQRVerifierCircuit`] does not expose the digest, so its proofs are not checked against this column.
+pub const HASH_INSTANCE_COLUMN: usize = 1;
+
+/// Error returned by [`verify_aadhaar`] and [`verify_rsa_sha256_with_message`].
+#[derive(Debug)]
+pub enum VerifyAadhaarError {
+    /// The proof is not valid for the public inputs.
+    Proof(Error),
+    /// The public inputs do not hold the expected public key hash, e.g. the proof was made against another key.
+    PubkeyHashMismatch { expected: Fr, found: Option<Fr> },
+    /// The hash instances are not the SHA256 digest of the expected message, e.g. the proof was made for another message.
+    MessageHashMismatch,
+}
+
+impl fmt::Display for VerifyAadhaarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Proof(err) => write!(f, "invalid proof: {err}"),
+            Self::PubkeyHashMismatch { expected, found } => write!(
+                f,
+                "public key hash {found:?} does not match the expected {expected:?}"
+            ),
+            Self::MessageHashMismatch => {
+                write!(f, "message hash does not match the expected message")
+            }
+        }
+    }
+}
+
+impl std::error::Error for VerifyAadhaarError {}
+
+/// Verifies a proof of [`AadhaarQRVerifierCircuit`] as [`verify`], and checks that it was made against the expected public key.
+///
+/// # Arguments
+/// * instances - the public inputs, one slice per instance column as in [`crate::qr_data_extractor::AadhaarInputs::instances`].
+/// * expected_pubkey_hash - the hash of the UIDAI key as [`AadhaarQRVerifierCircuit::pubkey_hash`], which must be the value of the column [`AadhaarQRVerifierCircuit::PUBKEY_HASH_COLUMN`]. It is not checked if `None`.
+pub fn verify_aadhaar(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[&[Fr]],
+    expected_pubkey_hash: Option<Fr>,
+) -> Result<(), VerifyAadhaarError> {
+    if let Some(expected) = expected_pubkey_hash {
+        let column = AadhaarQRVerifierCircuit::<Fr>::PUBKEY_HASH_COLUMN;
+        let found = instances
+            .get(column)
+            .and_then(|column| column.first())
+            .copied();
+        if found != Some(expected) {
+            return Err(VerifyAadhaarError::PubkeyHashMismatch { expected, found });
+        }
+    }
+    verify(params, vk, proof, instances).map_err(VerifyAadhaarError::Proof)
+}
+
+/// Verifies a proof of the RSA-SHA256 verification circuit over [`crate::configure_rsa_sha256`] as [`verify`], after checking that its hash instances are the SHA256 digest of `message`.
+///
+/// A verifier holding the signed message thereby rejects a proof of the signature of another message without verifying the SNARK.
+///
+/// # Arguments
+/// * instances - the public inputs, one slice per instance column, whose column [`HASH_INSTANCE_COLUMN`] must hold the digest bytes one per row.
+/// * message - the signed message, e.g. [`crate::qr::signed_region`] of the decompressed QR data.
+pub fn verify_rsa_sha256_with_message(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[&[Fr]],
+    message: &[u8],
+) -> Result<(), VerifyAadhaarError> {
+    let digest = Sha256::digest(message)
+        .iter()
+        .map(|byte| Fr::from(*byte as u64))
+        .collect::<Vec<Fr>>();
+    if instances.get(HASH_INSTANCE_COLUMN).copied() != Some(digest.as_slice()) {
+        return Err(VerifyAadhaarError::MessageHashMismatch);
+    }
+    verify(params, vk, proof, instances).map_err(VerifyAadhaarError::Proof)
+}
+
+/// The inputs of [`prove_and_extract`].
+#[derive(Clone, Debug)]
+pub struct AadhaarProveInputs<'a> {
+    /// The decompressed QR data, i.e. the signed data followed by the [`SIGNATURE_LEN`]-byte signature.
+    pub qr: &'a [u8],
+    /// The modulus of the UIDAI key the QR data is signed with.
+    pub modulus: &'a BigUint,
+    pub nullifier_seed: u64,
+    pub signal_hash: Fr,
+}
+
+/// A proof of [`AadhaarQRVerifierCircuit`] with the public values derived while proving.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofBundle {
+    /// The transcript bytes of the proof.
+    pub proof: Vec<u8>,
+    /// The nullifier of [`PoseidonCircuit::nullifier`] over the photo of the QR data.
+    pub nullifier: Fr,
+    /// The hash of the UIDAI key as [`AadhaarQRVerifierCircuit::pubkey_hash`].
+    pub pubkey_hash: Fr,
+    /// The public inputs of the proof, one vector per instance column as in [`crate::qr_data_extractor::AadhaarInputs::instances`].
+    pub instances: Vec<Vec<Fr>>,
+}
+
+impl ProofBundle {
+    /// Returns the public inputs as the slices taken by [`verify`] and [`verify_aadhaar`].
+    pub fn instance_slices(&self) -> Vec<&[Fr]> {
+        self.instances.iter().map(Vec::as_slice).collect()
+    }
+}
+
+/// Proves the possession of the QR data of `inputs` as [`AadhaarQRVerifierCircuit::possession_only`], computing the nullifier and the public key hash on the way.
+///
+/// The public inputs are computed by [`AadhaarQRVerifierCircuit::inputs`], so the caller gets the public values of the proof without deriving them again, e.g. to store the nullifier.
+///
+/// # Return values
+/// Returns the [`ProofBundle`], or [`Error::Synthesis`] if the signed data does not have the fields of a QR code, e.g. no photo.
+/// The proof does not verify if the signature of the QR data does not verify against `inputs.modulus`.
+#[cfg(feature = "sha256")]
+pub fn prove_and_extract(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    inputs: &AadhaarProveInputs<'_>,
+) -> Result<ProofBundle, Error> {
+    let circuit = AadhaarQRVerifierCircuit::possession_only(
+        inputs.qr,
+        inputs.modulus,
+        Fr::from(inputs.nullifier_seed),
+        inputs.signal_hash,
+    );
+    let public_inputs = circuit.inputs().ok_or(Error::Synthesis)?;
+    let instances = public_inputs.instances();
+    let slices = instances.iter().map(Vec::as_slice).collect::<Vec<&[Fr]>>();
+    let proof = gen_proof(params, pk, circuit, &slices)?;
+    Ok(ProofBundle {
+        proof,
+        nullifier: public_inputs.nullifier,
+        pubkey_hash: public_inputs.pubkey_hash,
+        instances,
+    })
+}
+
+/// A verifying key in the JSON format exported by [`export_vk_json`].
+///
+/// The field elements are `0x`-prefixed big-endian hex strings, as JS verifiers parse them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct VkJson {
+    /// The circuit is laid out in `2^k` rows.
+    pub k: u32,
+    /// The quotient polynomial is evaluated over `2^extended_k` points.
+    pub extended_k: u32,
+    /// The generator of the `2^k` roots of unity.
+    pub omega: String,
+    /// The `[x, y]` coordinates of the commitment of each fixed column.
+    pub fixed_commitments: Vec<[String; 2]>,
+    /// The `[x, y]` coordinates of the commitment of each permuted column.
+    pub permutation_commitments: Vec<[String; 2]>,
+    /// The whole key serialized by halo2 in [`SerdeFormat::RawBytes`], from which [`import_vk_json`] reads it back.
+    pub raw: String,
+}
+
+/// Encodes `bytes` in order as a `0x`-prefixed hex string.
+fn to_hex(bytes: &[u8]) -> String {
+    let digits = bytes
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("0x{digits}")
+}
+
+/// Encodes `fe` as a big-endian hex string, whereas its representation is little-endian.
+fn fe_to_hex<F: PrimeField>(fe: &F) -> String {
+    let mut bytes = fe.to_repr().as_ref().to_vec();
+    bytes.reverse();
+    to_hex(&bytes)
+}
+
+/// Returns the coordinates of `point` as hex strings, the identity as `[0x0.., 0x0..]`.
+fn point_to_hex(point: &G1Affine) -> [String; 2] {
+    let coordinates = point.coordinates();
+    if bool::from(coordinates.is_some()) {
+        let coordinates = coordinates.unwrap();
+        [fe_to_hex(coordinates.x()), fe_to_hex(coordinates.y())]
+    } else {
+        [to_hex(&[0; 32]), to_hex(&[0; 32])]
+    }
+}
+
+/// Serializes `vk` into the JSON of [`VkJson`], for front-ends verifying proofs client-side.
+pub fn export_vk_json(vk: &VerifyingKey<G1Affine>) -> String {
+    let domain = vk.get_domain();
+    let vk_json = VkJson {
+        k: domain.k(),
+        extended_k: domain.extended_k(),
+        omega: fe_to_hex(&domain.get_omega()),
+        fixed_commitments: vk.fixed_commitments().iter().map(point_to_hex).collect(),
+        permutation_commitments: vk
+            .permutation()
+            .commitments()
+            .iter()
+            .map(point_to_hex)
+            .collect(),
+        raw: to_hex(&vk.to_bytes(SerdeFormat::RawBytes)),
+    };
+    serde_json::to_string(&vk_json).expect("a verifying key is always serializable")
+}
+
+/// Reads back a verifying key of the circuit type `C` exported by [`export_vk_json`].
+///
+/// # Return values
+/// Returns the key, or an [`io::Error`] if the JSON is malformed, or its domain does not match the serialized key.
+pub fn import_vk_json<C: Circuit<Fr>>(json: &str) -> io::Result<VerifyingKey<G1Affine>> {
+    let vk_json: VkJson = serde_json::from_str(json)?;
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let digits = vk_json
+        .raw
+        .strip_prefix("0x")
+        .ok_or_else(|| invalid("the raw key is not a hex string"))?;
+    if digits.len() % 2 != 0 {
+        return Err(invalid("the raw key has an odd number of hex digits"));
+    }
+    let raw = (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| invalid("the raw key is not a hex string"))?;
+    let vk = VerifyingKey::<G1Affine>::read::<_, C>(&mut &raw[..], SerdeFormat::RawBytes)?;
+    if vk.get_domain().k() != vk_json.k || vk.get_domain().extended_k() != vk_json.extended_k {
+        return Err(invalid("the domain does not match the raw key"));
+    }
+    Ok(vk)
+}
+
+/// A proof with its public inputs in a compact byte encoding, e.g. for the submission to a rollup.
+///
+/// The encoding is the number of instance columns, then for each column the number of its values followed by their 32-byte little-endian representations, and finally the transcript bytes of [`gen_proof`] as they are.
+/// The counts are `u32` in little-endian order, so unlike EVM calldata nothing is padded to 32-byte words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactProof {
+    /// The public inputs, one vector per instance column.
+    pub instances: Vec<Vec<Fr>>,
+    /// The transcript bytes of the proof.
+    pub proof: Vec<u8>,
+}
+
+impl CompactProof {
+    /// Creates new [`CompactProof`] from `instances`, one slice per instance column, and the `proof` of [`gen_proof`] for them.
+    pub fn new(instances: &[&[Fr]], proof: Vec<u8>) -> Self {
+        Self {
+            instances: instances.iter().map(|column| column.to_vec()).collect(),
+            proof,
+        }
+    }
+
+    /// Returns the public inputs as the slices taken by [`verify`].
+    pub fn instance_slices(&self) -> Vec<&[Fr]> {
+        self.instances.iter().map(Vec::as_slice).collect()
+    }
+
+    /// Serializes the proof and its public inputs in the encoding of [`CompactProof`].
+    pub fn to_compact_bytes(&self) -> Vec<u8> {
+        let mut bytes = (self.instances.len() as u32).to_le_bytes().to_vec();
+        for column in self.instances.iter() {
+            bytes.extend((column.len() as u32).to_le_bytes());
+            for value in column.iter() {
+                bytes.extend(value.to_repr().as_ref());
+            }
+        }
+        bytes.extend(&self.proof);
+        bytes
+    }
+
+    /// Reads back a proof serialized by [`CompactProof::to_compact_bytes`].
+    ///
+    /// # Return values
+    /// Returns the proof, or an [`io::Error`] if the bytes are truncated or a value is not a canonical field element.
+    /// The transcript bytes are not checked until the proof is verified.
+    pub fn from_compact_bytes(mut bytes: &[u8]) -> io::Result<Self> {
+        let read_len = |bytes: &mut &[u8]| -> io::Result<usize> {
+            let len = split_off_front(bytes, 4)?;
+            Ok(u32::from_le_bytes(len.try_into().unwrap()) as usize)
+        };
+        let num_columns = read_len(&mut bytes)?;
+        let mut instances = Vec::new();
+        for _ in 0..num_columns {
+            let num_values = read_len(&mut bytes)?;
+            let mut column = Vec::new();
+            for _ in 0..num_values {
+                let mut repr = <Fr as PrimeField>::Repr::default();
+                let len = repr.as_ref().len();
+                repr.as_mut()
+                    .copy_from_slice(split_off_front(&mut bytes, len)?);
+                let value = Option::from(Fr::from_repr(repr)).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "a public input is not a field element",
+                    )
+                })?;
+                column.push(value);
+            }
+            instances.push(column);
+        }
+        Ok(Self {
+            instances,
+            proof: bytes.to_vec(),
+        })
+    }
+}
+
+/// Splits the first `len` bytes off `bytes`, or fails if `bytes` is shorter.
+fn split_off_front<'a>(bytes: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if bytes.len() < len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "the compact proof is truncated",
+        ));
+    }
+    let (front, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(front)
+}
+
+/// Proving keys of the subcircuits, all derived from the same SRS.
+#[derive(Debug)]
+pub struct PkBundle {
+    pub identity: ProvingKey<G1Affine>,
+    pub timestamp: ProvingKey<G1Affine>,
+    /// The key of [`SquareCircuit`] without the nonzero check.
+    pub signal: ProvingKey<G1Affine>,
+    pub nullifier: ProvingKey<G1Affine>,
+    pub age: ProvingKey<G1Affine>,
+    /// The key of the RSA-SHA256 verification of the QR data, i.e. of [`AadhaarQRVerifierCircuit`] over the default maximum data length.
+    #[cfg(feature = "sha256")]
+    pub aadhaar: ProvingKey<G1Affine>,
+}
+
+/// Generates the SRS of `2^k` rows once and derives the proving keys of all subcircuits from it.
+///
+/// `k` must be at least [`PoseidonCircuit::K`] to fit the Poseidon hash of the nullifier, which also fits the calendar table of the timestamp and age circuits.
+/// With the `sha256` feature, it must also be at least [`crate::params::Mainnet2048::K`] to fit [`AadhaarQRVerifierCircuit`], or the key generation fails.
+///
+/// # Return values
+/// Returns the SRS and the [`PkBundle`].
+pub fn setup_all(k: u32) -> Result<(ParamsKZG<Bn256>, PkBundle), Error> {
+    let params = gen_srs(k);
+    let timestamp = TimestampCircuit::<Fr>::new(
+        Some(Fr::from(1970u64)),
+        Some(Fr::one()),
+        Some(Fr::one()),
+        Some(Fr::zero()),
+        Some(Fr::zero()),
+        Some(Fr::zero()),
+    );
+    let bundle = PkBundle {
+        identity: gen_pk(&params, &IdentityCircuit::default(), None)?,
+        timestamp: gen_pk(&params, &timestamp, None)?,
+        signal: gen_pk(&params, &SquareCircuit::<Fr>::default(), None)?,
+        nullifier: gen_pk(&params, &PoseidonCircuit::default(), None)?,
+        age: gen_pk(&params, &AgeCircuit::<Fr>::default(), None)?,
+        #[cfg(feature = "sha256")]
+        aadhaar: gen_pk(
+            &params,
+            &AadhaarQRVerifierCircuit::<Fr>::placeholder(),
+            None,
+        )?,
+    };
+    Ok((params, bundle))
+}
+
+/// The key of a proving key in [`PkCache`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PkCacheKey {
+    /// The type name of the circuit.
+    pub circuit_type: &'static str,
+    /// The degree of the SRS.
+    pub k: u32,
+    /// The bit length of the RSA modulus, or zero for circuits without RSA.
+    pub bits_len: usize,
+}
+
+/// The cached proving key of a [`PkCacheKey`], locked on its own during its key generation.
+type PkSlot = Arc<Mutex<Option<Arc<ProvingKey<G1Affine>>>>>;
+
+/// A thread-safe cache of proving keys, computed with [`gen_pk`] on first use.
+///
+/// A cached key is only returned if its verifying key is the one [`keygen_vk`] generates for the circuit, so circuits of the same type but of another shape regenerate it.
+#[derive(Debug, Default)]
+pub struct PkCache {
+    pks: Mutex<HashMap<PkCacheKey, PkSlot>>,
+    num_keygens: Mutex<usize>,
+}
+
+impl PkCache {
+    /// Creates an empty [`PkCache`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the proving key of `circuit` for `params`, generating it if it is not cached yet.
+    ///
+    /// Only the entry of the key is locked during the key generation, so concurrent calls do not generate the same key twice, while other keys are still returned or generated.
+    /// A cached key whose verifying key differs from the one of `circuit`, e.g. of another shape of the same circuit type, is regenerated and replaced.
+    ///
+    /// # Arguments
+    /// * params - the SRS, whose degree is part of the key.
+    /// * circuit - a circuit of the shape to prove, whose witnesses may be placeholders.
+    /// * bits_len - the bit length of the RSA modulus, or zero for circuits without RSA.
+    pub fn get_or_gen<C: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit: &C,
+        bits_len: usize,
+    ) -> Result<Arc<ProvingKey<G1Affine>>, Error> {
+        let key = PkCacheKey {
+            circuit_type: type_name::<C>(),
+            k: params.k(),
+            bits_len,
+        };
+        let slot = self.pks.lock().unwrap().entry(key).or_default().clone();
+        let mut cached = slot.lock().unwrap();
+        let vk = keygen_vk(params, circuit)?;
+        if let Some(pk) = cached
+            .as_ref()
+            .filter(|pk| pk.get_vk().transcript_repr() == vk.transcript_repr())
+        {
+            return Ok(pk.clone());
+        }
+        let pk = Arc::new(keygen_pk(params, vk, circuit)?);
+        *self.num_keygens.lock().unwrap() += 1;
+        *cached = Some(pk.clone());
+        Ok(pk)
+    }
+
+    /// Creates a proof of `circuit` as [`gen_proof`] with the cached proving key.
+    pub fn prove<C: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit: C,
+        bits_len: usize,
+        instances: &[&[Fr]],
+    ) -> Result<Vec<u8>, Error> {
+        let pk = self.get_or_gen(params, &circuit, bits_len)?;
+        gen_proof(params, &pk, circuit, instances)
+    }
+
+    /// Returns the number of proving keys generated so far.
+    pub fn num_keygens(&self) -> usize {
+        *self.num_keygens.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setup_all() {
+        use crate::params::{Mainnet2048, Preset};
+
+        // The degree of the RSA-SHA256 circuit, which also fits the Poseidon hash.
+        let (params, bundle) = setup_all(Mainnet2048::K).unwrap();
+
+        let identity = IdentityCircuit::new(
+            Some(true),
+            Some(true),
+            Some(true),
+            Some(true),
+            Some(1),
+            Some(1),
+            Some(true),
+            Some(123456),
+            Some(123456),
+            Some(true),
+            Some(vec![10, 11, 12, 13, 14]),
+            Some(vec![10, 11, 12, 13, 14]),
+        );
+        let instance = identity
+            .revealed_values()
+            .into_iter()
+            .map(Fr::from)
+            .collect::<Vec<Fr>>();
+        let proof = gen_proof(&params, &bundle.identity, identity, &[&instance]).unwrap();
+        verify(&params, bundle.identity.get_vk(), &proof, &[&instance]).unwrap();
+
+        let timestamp = TimestampCircuit::<Fr>::new(
+            Some(Fr::from(2023u64)),
+            Some(Fr::from(7u64)),
+            Some(Fr::from(8u64)),
+            Some(Fr::from(12u64)),
+            Some(Fr::from(34u64)),
+            Some(Fr::from(56u64)),
+        );
+        let proof = gen_proof(&params, &bundle.timestamp, timestamp, &[]).unwrap();
+        verify(&params, bundle.timestamp.get_vk(), &proof, &[]).unwrap();
+
+        let square = SquareCircuit::instance(Fr::from(5u64));
+        let signal = SquareCircuit::<Fr>::new(Fr::from(5u64));
+        let proof = gen_proof(&params, &bundle.signal, signal, &[&[square]]).unwrap();
+        verify(&params, bundle.signal.get_vk(), &proof, &[&[square]]).unwrap();
+
+        let nullifier = PoseidonCircuit::new(12345678, vec![1, 2, 3, 4]);
+        let instances = nullifier.instances().unwrap();
+        let proof = gen_proof(&params, &bundle.nullifier, nullifier, &[&instances[0]]).unwrap();
+        verify(&params, bundle.nullifier.get_vk(), &proof, &[&instances[0]]).unwrap();
+
+        let age = AgeCircuit::<Fr>::new((1984, 8, 15), (2024, 7, 8), true);
+        let instance = [Fr::from(39u64)];
+        let proof = gen_proof(&params, &bundle.age, age, &[&instance]).unwrap();
+        verify(&params, bundle.age.get_vk(), &proof, &[&instance]).unwrap();
+        assert!(verify(&params, bundle.age.get_vk(), &proof, &[&[Fr::from(40u64)]]).is_err());
+    }
+
+    #[test]
+    fn test_identity_proof_reveals() {
+        let params = gen_srs(6);
+        let pk = gen_pk(&params, &IdentityCircuit::default(), None).unwrap();
+        let identity = |reveal_gender: bool, reveal_state: bool| {
+            IdentityCircuit::new(
+                Some(true),
+                Some(true),
+                Some(true),
+                Some(reveal_gender),
+                Some(if reveal_gender { b'M' } else { 0 }),
+                Some(b'M'),
+                Some(true),
+                Some(110051),
+                Some(110051),
+                Some(reveal_state),
+                Some(if reveal_state {
+                    b"Delhi".to_vec()
+                } else {
+                    vec![0; 5]
+                }),
+                Some(b"Delhi".to_vec()),
+            )
+        };
+
+        let circuit = identity(true, true);
+        let instance = circuit
+            .revealed_values()
+            .into_iter()
+            .map(Fr::from)
+            .collect::<Vec<Fr>>();
+        let mut expected = vec![Fr::one(), Fr::from(b'M' as u64), Fr::from(110051u64)];
+        expected.extend(b"Delhi".iter().map(|&byte| Fr::from(byte as u64)));
+        assert_eq!(instance, expected);
+        let proof = gen_proof(&params, &pk, circuit, &[&instance]).unwrap();
+        verify(&params, pk.get_vk(), &proof, &[&instance]).unwrap();
+        // Each revealed value is bound to its instance.
+        for i in 0..instance.len() {
+            let mut forged = instance.clone();
+            forged[i] += Fr::one();
+            assert!(verify(&params, pk.get_vk(), &proof, &[&forged]).is_err());
+        }
+
+        // The hidden gender and state are exposed as zero.
+        let circuit = identity(false, false);
+        let instance = circuit
+            .revealed_values()
+            .into_iter()
+            .map(Fr::from)
+            .collect::<Vec<Fr>>();
+        assert_eq!(instance[1], Fr::zero());
+        assert!(instance[3..].iter().all(|value| *value == Fr::zero()));
+        let proof = gen_proof(&params, &pk, circuit, &[&instance]).unwrap();
+        verify(&params, pk.get_vk(), &proof, &[&instance]).unwrap();
+        assert!(verify(&params, pk.get_vk(), &proof, &[&expected]).is_err());
+    }
+
+    #[test]
+    fn test_compact_proof() {
+        let params = gen_srs(10);
+        let pk = gen_pk(&params, &AgeCircuit::<Fr>::default(), None).unwrap();
+        let age = AgeCircuit::<Fr>::new((1984, 8, 15), (2024, 7, 8), true);
+        let instance = [Fr::from(39u64)];
+        let proof = gen_proof(&params, &pk, age, &[&instance]).unwrap();
+
+        let compact = CompactProof::new(&[&instance], proof.clone());
+        let bytes = compact.to_compact_bytes();
+        // Two counts and a single value precede the transcript.
+        assert_eq!(bytes.len(), 4 + 4 + 32 + proof.len());
+        let decoded = CompactProof::from_compact_bytes(&bytes).unwrap();
+        assert_eq!(decoded, compact);
+        let instances = decoded.instance_slices();
+        verify(&params, pk.get_vk(), &decoded.proof, &instances).unwrap();
+
+        // A tampered public input is decoded, but the proof does not verify for it.
+        let mut tampered = bytes.clone();
+        tampered[8] ^= 1;
+        let tampered = CompactProof::from_compact_bytes(&tampered).unwrap();
+        assert_eq!(tampered.instances, vec![vec![Fr::from(38u64)]]);
+        let instances = tampered.instance_slices();
+        assert!(verify(&params, pk.get_vk(), &tampered.proof, &instances).is_err());
+
+        // Truncated counts or values are rejected, as is a value out of the field.
+        assert!(CompactProof::from_compact_bytes(&bytes[..20]).is_err());
+        let mut out_of_field = bytes;
+        out_of_field[8..40].copy_from_slice(&[0xff; 32]);
+        assert!(CompactProof::from_compact_bytes(&out_of_field).is_err());
+    }
+
+    #[test]
+    fn test_verify_from_reader() {
+        let params = gen_srs(8);
+        let pk = gen_pk(&params, &SquareCircuit::<Fr>::default(), None).unwrap();
+        let instances: &[&[Fr]] = &[&[SquareCircuit::instance(Fr::from(5u64))]];
+        let signal = SquareCircuit::<Fr>::new(Fr::from(5u64));
+        let proof = gen_proof(&params, &pk, signal, instances).unwrap();
+
+        let reader = io::Cursor::new(proof.clone());
+        assert!(verify_from_reader(&params, pk.get_vk(), reader, instances));
+
+        // A tampered or truncated stream is rejected.
+        let mut tampered = proof.clone();
+        tampered[0] ^= 1;
+        let reader = io::Cursor::new(tampered);
+        assert!(!verify_from_reader(&params, pk.get_vk(), reader, instances));
+        let reader = io::Cursor::new(&proof[..proof.len() / 2]);
+        assert!(!verify_from_reader(&params, pk.get_vk(), reader, instances));
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let params = gen_srs(8);
+        let pk = gen_pk(&params, &SquareCircuit::<Fr>::default(), None).unwrap();
+        let mut proofs = vec![];
+        for x in [5u64, 6, 7] {
+            let square = SquareCircuit::instance(Fr::from(x));
+            let signal = SquareCircuit::<Fr>::new(Fr::from(x));
+            let proof = gen_proof(&params, &pk, signal, &[&[square]]).unwrap();
+            proofs.push((proof, vec![vec![square]]));
+        }
+        assert_eq!(
+            verify_batch(pk.get_vk(), &params, proofs.clone()),
+            vec![true, true, true]
+        );
+
+        // The proof of 6 is checked against the square of another signal.
+        proofs[1].1 = vec![vec![SquareCircuit::instance(Fr::from(8u64))]];
+        assert_eq!(
+            verify_batch(pk.get_vk(), &params, proofs.clone()),
+            vec![true, false, true]
+        );
+        assert!(verify_batch(pk.get_vk(), &params, vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_gen_pk_path() {
+        let root = std::env::temp_dir().join(format!("aadhaar-pk-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let path = root.join("examples").join("keys").join("square.pk");
+        let params = gen_srs(8);
+        let pk = gen_pk(&params, &SquareCircuit::<Fr>::default(), Some(&path)).unwrap();
+        assert!(path.exists());
+
+        // The second call reads the key written by the first one.
+        let read = gen_pk(&params, &SquareCircuit::<Fr>::default(), Some(&path)).unwrap();
+        let square = SquareCircuit::instance(Fr::from(5u64));
+        let signal = SquareCircuit::<Fr>::new(Fr::from(5u64));
+        let proof = gen_proof(&params, &read, signal, &[&[square]]).unwrap();
+        verify(&params, pk.get_vk(), &proof, &[&[square]]).unwrap();
+
+        // A key of another SRS is regenerated and overwritten instead of being read.
+        let other_params = gen_srs(8);
+        let regenerated =
+            gen_pk(&other_params, &SquareCircuit::<Fr>::default(), Some(&path)).unwrap();
+        assert_ne!(
+            regenerated.get_vk().transcript_repr(),
+            pk.get_vk().transcript_repr()
+        );
+        let signal = SquareCircuit::<Fr>::new(Fr::from(5u64));
+        let proof = gen_proof(&other_params, &regenerated, signal, &[&[square]]).unwrap();
+        verify(&other_params, regenerated.get_vk(), &proof, &[&[square]]).unwrap();
+        let read = gen_pk(&other_params, &SquareCircuit::<Fr>::default(), Some(&path)).unwrap();
+        assert_eq!(
+            read.get_vk().transcript_repr(),
+            regenerated.get_vk().transcript_repr()
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_vk_json() {
+        let params = gen_srs(8);
+        let pk = gen_pk(&params, &SquareCircuit::<Fr>::default(), None).unwrap();
+        let square = SquareCircuit::instance(Fr::from(5u64));
+        let signal = SquareCircuit::<Fr>::new(Fr::from(5u64));
+        let proof = gen_proof(&params, &pk, signal, &[&[square]]).unwrap();
+
+        let json = export_vk_json(pk.get_vk());
+        let vk_json: VkJson = serde_json::from_str(&json).unwrap();
+        assert_eq!(vk_json.k, 8);
+        assert_eq!(
+            vk_json.fixed_commitments.len(),
+            pk.get_vk().fixed_commitments().len()
+        );
+
+        let vk = import_vk_json::<SquareCircuit<Fr>>(&json).unwrap();
+        verify(&params, &vk, &proof, &[&[square]]).unwrap();
+        assert_eq!(export_vk_json(&vk), json);
+
+        // A key without the hex prefix is rejected.
+        let json = json.replace("\"raw\":\"0x", "\"raw\":\"");
+        assert!(import_vk_json::<SquareCircuit<Fr>>(&json).is_err());
+    }
+
+    #[test]
+    fn test_pk_cache() {
+        let params = gen_srs(PoseidonCircuit::K);
+        let cache = PkCache::new();
+
+        let mut proofs = vec![];
+        for x in [5u64, 7] {
+            let signal = SquareCircuit::<Fr>::new(Fr::from(x));
+            let square = SquareCircuit::instance(Fr::from(x));
+            let proof = cache.prove(&params, signal, 0, &[&[square]]).unwrap();
+            proofs.push((proof, square));
+        }
+        assert_eq!(cache.num_keygens(), 1);
+        let pk = cache
+            .get_or_gen(&params, &SquareCircuit::<Fr>::default(), 0)
+            .unwrap();
+        for (proof, square) in proofs {
+            verify(&params, pk.get_vk(), &proof, &[&[square]]).unwrap();
+        }
+        assert_eq!(cache.num_keygens(), 1);
+
+        // Another circuit type or bit length is keyed separately.
+        let nullifier = PoseidonCircuit::new(12345678, vec![1, 2, 3, 4]);
+        let instances = nullifier.instances().unwrap();
+        cache
+            .prove(&params, nullifier, 0, &[&instances[0]])
+            .unwrap();
+        assert_eq!(cache.num_keygens(), 2);
+        cache
+            .get_or_gen(&params, &SquareCircuit::<Fr>::default(), 2048)
+            .unwrap();
+        assert_eq!(cache.num_keygens(), 3);
+
+        // A circuit of the same type but of another shape does not get the cached key.
+        let nullifier = PoseidonCircuit::new(12345678, vec![1, 2, 3, 4]);
+        let default_pk = cache.get_or_gen(&params, &nullifier, 0).unwrap();
+        assert_eq!(cache.num_keygens(), 3);
+        let smaller = nullifier.with_photo_pack_size(2);
+        let smaller_pk = cache.get_or_gen(&params, &smaller, 0).unwrap();
+        assert_eq!(cache.num_keygens(), 4);
+        assert_ne!(
+            smaller_pk.get_vk().transcript_repr(),
+            default_pk.get_vk().transcript_repr()
+        );
+        let instances = smaller.instances().unwrap();
+        let proof = cache.prove(&params, smaller, 0, &[&instances[0]]).unwrap();
+        verify(&params, smaller_pk.get_vk(), &proof, &[&instances[0]]).unwrap();
+        assert_eq!(cache.num_keygens(), 4);
+    }
+
+    /// Returns a test QR code signed with a fresh key, the modulus of the key and the proving key of [`AadhaarQRVerifierCircuit`].
+    ///
+    /// The key is generated over [`AadhaarQRVerifierCircuit::placeholder`] as in [`setup_all`], so the proofs also check that it holds for other QR data.
+    #[cfg(feature = "sha256")]
+    fn aadhaar_test_setup() -> (ParamsKZG<Bn256>, Vec<u8>, BigUint, ProvingKey<G1Affine>) {
+        use crate::params::{Mainnet2048, Preset};
+        use crate::qr::decompress_qr;
+        use crate::qr::test_utils::{generate_test_qr, TestQrFields};
+        use rsa::traits::PublicKeyParts;
+
+        let params = gen_srs(Mainnet2048::K);
+        let (qr, _, public_key) = generate_test_qr(TestQrFields::default());
+        let qr = decompress_qr(&qr).unwrap();
+        let modulus = BigUint::from_bytes_be(&public_key.n().to_bytes_be());
+        let pk = gen_pk(
+            &params,
+            &AadhaarQRVerifierCircuit::<Fr>::placeholder(),
+            None,
+        )
+        .unwrap();
+        (params, qr, modulus, pk)
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_verify_aadhaar_pubkey_hash() {
+        let (params, qr, modulus, pk) = aadhaar_test_setup();
+        let inputs = AadhaarProveInputs {
+            qr: &qr,
+            modulus: &modulus,
+            nullifier_seed: 12345678,
+            signal_hash: Fr::from(42),
+        };
+        let bundle = prove_and_extract(&params, &pk, &inputs).unwrap();
+        let instances = bundle.instance_slices();
+        let pubkey_hash = AadhaarQRVerifierCircuit::<Fr>::pubkey_hash(&modulus);
+
+        verify_aadhaar(
+            &params,
+            pk.get_vk(),
+            &bundle.proof,
+            &instances,
+            Some(pubkey_hash),
+        )
+        .unwrap();
+        verify_aadhaar(&params, pk.get_vk(), &bundle.proof, &instances, None).unwrap();
+        let attacker_key = BigUint::from(65539u64) << 2000;
+        let expected = AadhaarQRVerifierCircuit::<Fr>::pubkey_hash(&attacker_key);
+        assert!(matches!(
+            verify_aadhaar(&params, pk.get_vk(), &bundle.proof, &instances, Some(expected)),
+            Err(VerifyAadhaarError::PubkeyHashMismatch { found: Some(found), .. }) if found == pubkey_hash
+        ));
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_prove_and_extract() {
+        use crate::nullifier::compute_nullifier;
+        use crate::qr::{signed_region, QrFieldLayout};
+
+        let (params, qr, modulus, pk) = aadhaar_test_setup();
+        let inputs = AadhaarProveInputs {
+            qr: &qr,
+            modulus: &modulus,
+            nullifier_seed: 12345678,
+            signal_hash: Fr::from(42),
+        };
+
+        let bundle = prove_and_extract(&params, &pk, &inputs).unwrap();
+        let signed_data = signed_region(&qr, SIGNATURE_LEN * 8).unwrap();
+        assert_eq!(
+            Some(bundle.nullifier),
+            compute_nullifier(12345678, signed_data, &QrFieldLayout::default())
+        );
+        assert_eq!(
+            bundle.pubkey_hash,
+            AadhaarQRVerifierCircuit::<Fr>::pubkey_hash(&modulus)
+        );
+        // The returned values are the public inputs the proof was made for.
+        assert_eq!(
+            bundle.instances[AadhaarQRVerifierCircuit::<Fr>::NULLIFIER_COLUMN],
+            vec![bundle.nullifier]
+        );
+        assert_eq!(
+            bundle.instances[AadhaarQRVerifierCircuit::<Fr>::PUBKEY_HASH_COLUMN],
+            vec![bundle.pubkey_hash]
+        );
+        let instances = bundle.instance_slices();
+        verify_aadhaar(
+            &params,
+            pk.get_vk(),
+            &bundle.proof,
+            &instances,
+            Some(bundle.pubkey_hash),
+        )
+        .unwrap();
+
+        // QR data without a photo has no nullifier.
+        let truncated = AadhaarProveInputs {
+            qr: &qr[..4],
+            ..inputs.clone()
+        };
+        assert!(prove_and_extract(&params, &pk, &truncated).is_err());
+
+        // Nor does a proof hold for QR data signed with another key than the given one.
+        let other_modulus = &modulus + BigUint::from(2u64);
+        let forged = AadhaarProveInputs {
+            modulus: &other_modulus,
+            ..inputs
+        };
+        let bundle = prove_and_extract(&params, &pk, &forged).unwrap();
+        let instances = bundle.instance_slices();
+        assert!(verify_aadhaar(&params, pk.get_vk(), &bundle.proof, &instances, None).is_err());
+    }
+}
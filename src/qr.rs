@@ -0,0 +1,570 @@
+//! Helpers to recover the signed bytes from a scanned Aadhaar secure QR code.
+use flate2::read::{GzDecoder, ZlibDecoder};
+use num_bigint::BigUint;
+use std::io::{self, Read};
+
+use crate::conditional_secrets::{GENDER_POSITION, PINCODE_POSITION, STATE_POSITION};
+use crate::delimiter::{DELIMITER, NUM_DELIMITERS};
+use crate::field_extractor::{DOB_POSITION, PHOTO_POSITION, REFERENCE_ID_POSITION};
+use crate::name::NAME_POSITION;
+
+/// The magic bytes at the head of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// The JPEG 2000 start of codestream and image size markers at the head of the photo.
+const PHOTO_MARKER: [u8; 4] = [0xff, 0x4f, 0xff, 0x51];
+
+/// Converts the decimal string encoded in a secure QR code into the compressed payload bytes.
+///
+/// Returns `None` if `qr` is not a decimal number.
+pub fn qr_bytes_from_decimal(qr: &str) -> Option<Vec<u8>> {
+    BigUint::parse_bytes(qr.trim().as_bytes(), 10).map(|v| v.to_bytes_be())
+}
+
+/// Inflates the compressed payload of a secure QR code.
+///
+/// The payload is usually a zlib stream, but gzip streams are accepted as well.
+///
+/// # Return values
+/// Returns the decompressed bytes, i.e. the delimited fields followed by the signature, that the circuits expect.
+pub fn decompress_qr(scanned_bytes: &[u8]) -> io::Result<Vec<u8>> {
+    let mut decompressed = vec![];
+    if scanned_bytes.starts_with(&GZIP_MAGIC) {
+        GzDecoder::new(scanned_bytes).read_to_end(&mut decompressed)?;
+    } else {
+        ZlibDecoder::new(scanned_bytes).read_to_end(&mut decompressed)?;
+    }
+    Ok(decompressed)
+}
+
+/// Splits the signed data of a secure QR code into its fields.
+///
+/// Only the first [`NUM_DELIMITERS`] delimiters separate fields, so the last field is the photo, which may contain the delimiter byte itself.
+///
+/// # Return values
+/// Returns the fields in order, or `None` if the data has fewer delimiters.
+pub fn qr_fields(signed_data: &[u8]) -> Option<Vec<&[u8]>> {
+    let fields = signed_data
+        .splitn(NUM_DELIMITERS + 1, |byte| *byte == DELIMITER)
+        .collect::<Vec<&[u8]>>();
+    (fields.len() == NUM_DELIMITERS + 1).then_some(fields)
+}
+
+/// The fields present in the signed data of a secure QR code, as some variants omit optional fields.
+///
+/// The positions are those of the full layout, i.e. the field at position `i` follows the `i`-th delimiter, and the photo is at position [`NUM_DELIMITERS`].
+/// The fields are indexed by these positions whatever fields are absent, so the positions of the full layout keep naming the same fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QrFieldLayout {
+    present: [bool; NUM_DELIMITERS + 1],
+}
+
+impl Default for QrFieldLayout {
+    /// Returns the full layout with every field present.
+    fn default() -> Self {
+        Self {
+            present: [true; NUM_DELIMITERS + 1],
+        }
+    }
+}
+
+impl QrFieldLayout {
+    /// Returns this layout with the field at `position` absent.
+    pub fn without(mut self, position: usize) -> Self {
+        self.present[position] = false;
+        self
+    }
+
+    /// Returns the layout of masked QR codes without the photo.
+    pub fn without_photo() -> Self {
+        Self::default().without(NUM_DELIMITERS)
+    }
+
+    /// Returns true if the field at `position` is present.
+    pub fn is_present(&self, position: usize) -> bool {
+        self.present[position]
+    }
+
+    /// Returns the number of the delimiter preceding the field at `position`, i.e. the number of present fields before it.
+    ///
+    /// The delimiter numbered `n` is remapped to `n * 255` in `nDelimitedData` and found at `delimiter_indices[n - 1]`.
+    ///
+    /// # Return values
+    /// Returns `None` if the field is absent or is the first field, which no delimiter precedes.
+    pub fn delimiter_number(&self, position: usize) -> Option<usize> {
+        let number = self.present[..position]
+            .iter()
+            .filter(|present| **present)
+            .count();
+        (self.is_present(position) && number > 0).then_some(number)
+    }
+}
+
+/// Splits the signed data of a secure QR code into its fields as laid out by `layout`.
+///
+/// The present fields are separated by delimiters, and a trailing delimiter after the last one is ignored.
+/// Only the photo may contain the delimiter byte, so it is the rest of the data if present.
+///
+/// # Return values
+/// Returns the fields indexed by their positions in the full layout, with `None` for the absent ones, or `None` if the number of delimiters does not match `layout`.
+pub fn qr_fields_with_layout<'a>(
+    signed_data: &'a [u8],
+    layout: &QrFieldLayout,
+) -> Option<Vec<Option<&'a [u8]>>> {
+    let num_present = layout.present.iter().filter(|present| **present).count();
+    let mut data = signed_data;
+    if !layout.is_present(NUM_DELIMITERS) {
+        data = data.strip_suffix(&[DELIMITER]).unwrap_or(data);
+    }
+    let mut present_fields = data.splitn(num_present, |byte| *byte == DELIMITER);
+    let fields = layout
+        .present
+        .iter()
+        .map(|present| present.then(|| present_fields.next()).flatten())
+        .collect::<Vec<Option<&[u8]>>>();
+    if fields.iter().flatten().count() != num_present {
+        return None;
+    }
+    // Without the photo, the last field is a text field, which cannot hold the remaining delimiters.
+    let last = fields.iter().rev().flatten().next()?;
+    if !layout.is_present(NUM_DELIMITERS) && last.contains(&DELIMITER) {
+        return None;
+    }
+    Some(fields)
+}
+
+/// The signed data of a secure QR code split at the delimiters found by [`parse_aadhaar_qr`].
+///
+/// The field at position `i` follows the `i`-th delimiter, and the photo follows the last one, so a QR code with fewer delimiters lacks the trailing text fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AadhaarQr<'a> {
+    signed_data: &'a [u8],
+    delimiter_indices: Vec<usize>,
+}
+
+impl<'a> AadhaarQr<'a> {
+    /// Returns the indices of the delimiters in the signed data, up to the photo.
+    pub fn delimiter_indices(&self) -> &[usize] {
+        &self.delimiter_indices
+    }
+
+    /// Returns the number of delimiters found, e.g. [`NUM_DELIMITERS`] in the full layout.
+    pub fn num_delimiters(&self) -> usize {
+        self.delimiter_indices.len()
+    }
+
+    /// Returns the field at `position`, or `None` if there are fewer fields.
+    ///
+    /// The field at position [`AadhaarQr::num_delimiters`] is the photo.
+    pub fn field(&self, position: usize) -> Option<&'a [u8]> {
+        let start = match position {
+            0 => 0,
+            _ => self.delimiter_indices.get(position - 1)? + 1,
+        };
+        let end = match self.delimiter_indices.get(position) {
+            Some(end) => *end,
+            None if position == self.num_delimiters() => self.signed_data.len(),
+            None => return None,
+        };
+        Some(&self.signed_data[start..end])
+    }
+
+    /// Returns the last 4 digits of the Aadhaar number followed by the signing timestamp.
+    pub fn reference_id(&self) -> Option<&'a [u8]> {
+        self.text_field(REFERENCE_ID_POSITION)
+    }
+
+    pub fn name(&self) -> Option<&'a [u8]> {
+        self.text_field(NAME_POSITION)
+    }
+
+    /// Returns the date of birth, formatted as `DD-MM-YYYY`.
+    pub fn dob(&self) -> Option<&'a [u8]> {
+        self.text_field(DOB_POSITION)
+    }
+
+    pub fn gender(&self) -> Option<&'a [u8]> {
+        self.text_field(GENDER_POSITION)
+    }
+
+    pub fn pincode(&self) -> Option<&'a [u8]> {
+        self.text_field(PINCODE_POSITION)
+    }
+
+    pub fn state(&self) -> Option<&'a [u8]> {
+        self.text_field(STATE_POSITION)
+    }
+
+    /// Returns the photo, i.e. the rest of the data after the last delimiter, which may contain the delimiter byte.
+    pub fn photo(&self) -> &'a [u8] {
+        self.field(self.num_delimiters()).unwrap_or_default()
+    }
+
+    /// Returns the field at `position` unless it is the photo.
+    fn text_field(&self, position: usize) -> Option<&'a [u8]> {
+        (position < self.num_delimiters())
+            .then(|| self.field(position))
+            .flatten()
+    }
+}
+
+/// Splits the signed data of a secure QR code at its delimiters, whose number varies between the versions of the QR code.
+///
+/// The delimiters are counted up to the photo, which starts with the JPEG 2000 markers `FF 4F FF 51`, so the delimiter bytes inside the photo are not counted.
+/// Without such a photo, every delimiter byte of the data is counted.
+///
+/// # Return values
+/// Returns the fields split at however many delimiters are found, see [`AadhaarQr`], or `None` if there is none.
+pub fn parse_aadhaar_qr(signed_data: &[u8]) -> Option<AadhaarQr> {
+    let mut delimiter_indices = vec![];
+    for (i, byte) in signed_data.iter().enumerate() {
+        if *byte != DELIMITER {
+            continue;
+        }
+        delimiter_indices.push(i);
+        if signed_data[i + 1..].starts_with(&PHOTO_MARKER) {
+            break;
+        }
+    }
+    (!delimiter_indices.is_empty()).then_some(AadhaarQr {
+        signed_data,
+        delimiter_indices,
+    })
+}
+
+/// Returns the bytes of a decompressed secure QR code covered by its signature.
+///
+/// The payload is `signedData || signature`, where the signature has as many bytes as the modulus of the signing key.
+/// The message hashed by [`crate::RSASignatureVerifier::verify_pkcs1v15_signature`] must be exactly this region: any other slice of the payload does not verify against the signature.
+///
+/// # Arguments
+/// * qr - the decompressed payload, e.g. as returned by [`decompress_qr`].
+/// * key_bits - the bit length of the modulus of the signing key, e.g. 2048.
+///
+/// # Return values
+/// Returns the payload without the trailing `key_bits / 8` signature bytes, or `None` if the payload is not longer than the signature.
+pub fn signed_region(qr: &[u8], key_bits: usize) -> Option<&[u8]> {
+    let signed_len = qr.len().checked_sub(key_bits / 8)?;
+    (signed_len > 0).then(|| &qr[..signed_len])
+}
+
+/// Helpers to generate synthetic secure QR codes for tests.
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use flate2::{write::ZlibEncoder, Compression};
+    use rand::thread_rng;
+    use rsa::{
+        pkcs1v15::SigningKey,
+        sha2::Sha256,
+        signature::{SignatureEncoding, Signer},
+        RsaPrivateKey, RsaPublicKey,
+    };
+    use std::io::Write;
+
+    use super::{qr_fields, QrFieldLayout};
+    use crate::delimiter::DELIMITER;
+
+    /// The position of each field in the signed data.
+    pub(crate) use crate::conditional_secrets::{
+        GENDER_POSITION, PINCODE_POSITION, STATE_POSITION,
+    };
+    pub(crate) use crate::field_extractor::{DOB_POSITION, PHOTO_POSITION, REFERENCE_ID_POSITION};
+    pub(crate) use crate::name::NAME_POSITION;
+
+    /// The revealable fields of a synthetic secure QR code, the others are fixed to those of the sample payload.
+    #[derive(Clone, Debug)]
+    pub(crate) struct TestQrFields {
+        /// The last 4 digits of the Aadhaar number followed by the signing timestamp `YYYYMMDDhhmmssSSS`.
+        pub reference_id: String,
+        pub name: String,
+        /// The date of birth `DD-MM-YYYY`.
+        pub dob: String,
+        pub gender: String,
+        pub pincode: String,
+        pub state: String,
+        pub photo: Vec<u8>,
+    }
+
+    impl Default for TestQrFields {
+        fn default() -> Self {
+            Self {
+                reference_id: "269720240718124557387".to_string(),
+                name: "Sumit Kumar".to_string(),
+                dob: "01-01-1984".to_string(),
+                gender: "M".to_string(),
+                pincode: "110051".to_string(),
+                state: "Delhi".to_string(),
+                photo: vec![255, 79, 255, 81, 0, 47, 0, 0],
+            }
+        }
+    }
+
+    /// Builds the delimited payload of `fields`, signs it with a fresh 2048-bit key, and compresses it as in a secure QR code.
+    ///
+    /// # Return values
+    /// Returns the compressed QR bytes, the private key and the public key.
+    pub(crate) fn generate_test_qr(fields: TestQrFields) -> (Vec<u8>, RsaPrivateKey, RsaPublicKey) {
+        let text_fields = [
+            "V2",
+            "3",
+            &fields.reference_id,
+            &fields.name,
+            &fields.dob,
+            &fields.gender,
+            "C/O Ishwar Chand",
+            "East Delhi",
+            "",
+            "B-31, 3rd Floor",
+            "",
+            &fields.pincode,
+            "Krishna Nagar",
+            &fields.state,
+            "Radhey Shyam Park Extension",
+            "Gandhi Nagar",
+            "Krishna Nagar",
+            "1234",
+        ];
+        let mut signed_data = vec![];
+        for field in text_fields {
+            signed_data.extend_from_slice(field.as_bytes());
+            signed_data.push(DELIMITER);
+        }
+        signed_data.extend_from_slice(&fields.photo);
+
+        let mut rng = thread_rng();
+        let private_key = RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+        let public_key = RsaPublicKey::from(&private_key);
+        let signing_key = SigningKey::<Sha256>::new(private_key.clone());
+        let signature = signing_key.sign(&signed_data).to_vec();
+
+        let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+        encoder.write_all(&signed_data).unwrap();
+        encoder.write_all(&signature).unwrap();
+        (encoder.finish().unwrap(), private_key, public_key)
+    }
+
+    /// Removes the fields absent from `layout` from `signed_data` of the full layout, as a masked QR code omits them.
+    ///
+    /// Without the photo, the data ends with the last text field rather than the photo delimiter.
+    pub(crate) fn mask_fields(signed_data: &[u8], layout: &QrFieldLayout) -> Vec<u8> {
+        qr_fields(signed_data)
+            .expect("the data has every field")
+            .into_iter()
+            .enumerate()
+            .filter(|(position, _)| layout.is_present(*position))
+            .map(|(_, field)| field.to_vec())
+            .collect::<Vec<Vec<u8>>>()
+            .join(&DELIMITER)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::*;
+    use super::*;
+    use crate::qr_data_extractor::SIGNATURE_LEN;
+    use rsa::{
+        pkcs1v15::{Signature, VerifyingKey},
+        sha2::Sha256,
+        signature::Verifier,
+    };
+
+    #[test]
+    fn test_decompress_qr() {
+        let compressed = [
+            120, 218, 11, 51, 250, 111, 100, 102, 105, 110, 100, 96, 104, 105, 96, 102, 104, 100,
+            104, 98, 100, 104, 106, 100, 100, 105, 250, 63, 56, 49, 183, 32, 39, 85, 193, 47, 49,
+            55, 245, 191, 161, 177, 174, 129, 169, 174, 161, 165, 133, 201, 127, 95, 0, 100, 136,
+            15, 10,
+        ];
+        let expected = [
+            86, 50, 255, 50, 54, 57, 55, 50, 48, 49, 57, 48, 54, 49, 50, 49, 52, 50, 49, 53, 50,
+            50, 57, 53, 255, 83, 97, 109, 112, 108, 101, 32, 78, 97, 109, 101, 255, 49, 51, 45,
+            48, 53, 45, 49, 57, 56, 52, 255, 77,
+        ];
+        assert_eq!(decompress_qr(&compressed).unwrap(), expected);
+
+        // The same payload encoded as the decimal string of the QR code.
+        let decimal = BigUint::from_bytes_be(&compressed).to_str_radix(10);
+        let scanned = qr_bytes_from_decimal(&decimal).unwrap();
+        assert_eq!(decompress_qr(&scanned).unwrap(), expected);
+
+        assert!(decompress_qr(&expected).is_err());
+    }
+
+    #[test]
+    fn test_generate_test_qr() {
+        let fields = TestQrFields {
+            name: "Asha Devi".to_string(),
+            dob: "15-08-1990".to_string(),
+            gender: "F".to_string(),
+            state: "Kerala".to_string(),
+            ..TestQrFields::default()
+        };
+        let (qr, _, public_key) = generate_test_qr(fields.clone());
+
+        let decimal = BigUint::from_bytes_be(&qr).to_str_radix(10);
+        let decompressed = decompress_qr(&qr_bytes_from_decimal(&decimal).unwrap()).unwrap();
+        let (signed_data, signature) = decompressed.split_at(decompressed.len() - SIGNATURE_LEN);
+        let verifying_key = VerifyingKey::<Sha256>::new(public_key);
+        verifying_key
+            .verify(signed_data, &Signature::try_from(signature).unwrap())
+            .unwrap();
+
+        let parsed = qr_fields(signed_data).unwrap();
+        assert_eq!(
+            parsed[REFERENCE_ID_POSITION],
+            fields.reference_id.as_bytes()
+        );
+        assert_eq!(parsed[NAME_POSITION], fields.name.as_bytes());
+        assert_eq!(parsed[DOB_POSITION], fields.dob.as_bytes());
+        assert_eq!(parsed[GENDER_POSITION], fields.gender.as_bytes());
+        assert_eq!(parsed[PINCODE_POSITION], fields.pincode.as_bytes());
+        assert_eq!(parsed[STATE_POSITION], fields.state.as_bytes());
+        // The photo keeps its delimiter bytes.
+        assert_eq!(parsed[PHOTO_POSITION], fields.photo.as_slice());
+
+        assert!(qr_fields(&signed_data[..10]).is_none());
+    }
+
+    #[test]
+    fn test_signed_region() {
+        let (qr, _, public_key) = generate_test_qr(TestQrFields::default());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let region = signed_region(&decompressed, 2048).unwrap();
+        assert_eq!(region.len(), decompressed.len() - SIGNATURE_LEN);
+
+        let signature = Signature::try_from(&decompressed[region.len()..]).unwrap();
+        let verifying_key = VerifyingKey::<Sha256>::new(public_key);
+        verifying_key.verify(region, &signature).unwrap();
+        // One byte less or more than the region is not what was signed.
+        assert!(verifying_key
+            .verify(&region[..region.len() - 1], &signature)
+            .is_err());
+        assert!(verifying_key
+            .verify(&decompressed[..region.len() + 1], &signature)
+            .is_err());
+
+        assert!(signed_region(&decompressed[..SIGNATURE_LEN], 2048).is_none());
+        assert!(signed_region(&decompressed[..10], 2048).is_none());
+    }
+
+    #[test]
+    fn test_qr_fields_without_photo() {
+        use crate::age::{AgeCircuit, DOB_LEN};
+        use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+
+        let fields = TestQrFields {
+            photo: vec![],
+            ..TestQrFields::default()
+        };
+        let (qr, _, _) = generate_test_qr(fields.clone());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = signed_region(&decompressed, 2048).unwrap();
+        // A masked QR code ends with the last text field, without the photo delimiter.
+        let signed_data = signed_data.strip_suffix(&[DELIMITER]).unwrap();
+        assert!(qr_fields(signed_data).is_none());
+
+        let layout = QrFieldLayout::without_photo();
+        let parsed = qr_fields_with_layout(signed_data, &layout).unwrap();
+        assert_eq!(parsed[NUM_DELIMITERS], None);
+        let dob: [u8; DOB_LEN] = parsed[DOB_POSITION].unwrap().try_into().unwrap();
+        let age = AgeCircuit::<Fr>::from_dob(dob, (2024, 7, 8), true);
+        assert_eq!(age.age(), 40);
+        assert_eq!(parsed[GENDER_POSITION], Some(fields.gender.as_bytes()));
+        assert_eq!(parsed[PINCODE_POSITION], Some(fields.pincode.as_bytes()));
+        assert_eq!(parsed[STATE_POSITION], Some(fields.state.as_bytes()));
+
+        // With the trailing delimiter too.
+        let with_delimiter = [signed_data, &[DELIMITER]].concat();
+        let parsed_with_delimiter = qr_fields_with_layout(&with_delimiter, &layout).unwrap();
+        assert_eq!(parsed_with_delimiter, parsed);
+
+        // A QR code with the photo does not match the layout without it, and the full layout agrees with `qr_fields`.
+        let (qr, _, _) = generate_test_qr(TestQrFields::default());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = signed_region(&decompressed, 2048).unwrap();
+        assert!(qr_fields_with_layout(signed_data, &layout).is_none());
+        let parsed = qr_fields_with_layout(signed_data, &QrFieldLayout::default())
+            .unwrap()
+            .into_iter()
+            .map(Option::unwrap)
+            .collect::<Vec<&[u8]>>();
+        assert_eq!(Some(parsed), qr_fields(signed_data));
+    }
+
+    #[test]
+    fn test_delimiter_number() {
+        let layout = QrFieldLayout::default();
+        assert_eq!(layout.delimiter_number(0), None);
+        assert_eq!(
+            layout.delimiter_number(STATE_POSITION),
+            Some(STATE_POSITION)
+        );
+        assert_eq!(
+            layout.delimiter_number(NUM_DELIMITERS),
+            Some(NUM_DELIMITERS)
+        );
+
+        // The fields after an absent one follow a delimiter numbered one less.
+        let layout = layout.without(STATE_POSITION);
+        assert_eq!(layout.delimiter_number(STATE_POSITION), None);
+        assert_eq!(
+            layout.delimiter_number(PINCODE_POSITION),
+            Some(PINCODE_POSITION)
+        );
+        assert_eq!(
+            layout.delimiter_number(PHOTO_POSITION),
+            Some(NUM_DELIMITERS - 1)
+        );
+    }
+
+    #[test]
+    fn test_parse_aadhaar_qr() {
+        let fields = TestQrFields::default();
+        let (qr, _, _) = generate_test_qr(fields.clone());
+        let decompressed = decompress_qr(&qr).unwrap();
+        let signed_data = signed_region(&decompressed, 2048).unwrap();
+
+        // The full layout has 18 delimiters, and the delimiter bytes of the photo are not counted.
+        let parsed = parse_aadhaar_qr(signed_data).unwrap();
+        assert_eq!(parsed.num_delimiters(), NUM_DELIMITERS);
+        let expected = qr_fields(signed_data).unwrap();
+        for (position, field) in expected.iter().enumerate() {
+            assert_eq!(parsed.field(position), Some(*field));
+        }
+        assert_eq!(parsed.field(NUM_DELIMITERS + 1), None);
+        assert_eq!(parsed.reference_id(), Some(fields.reference_id.as_bytes()));
+        assert_eq!(parsed.name(), Some(fields.name.as_bytes()));
+        assert_eq!(parsed.dob(), Some(fields.dob.as_bytes()));
+        assert_eq!(parsed.gender(), Some(fields.gender.as_bytes()));
+        assert_eq!(parsed.pincode(), Some(fields.pincode.as_bytes()));
+        assert_eq!(parsed.state(), Some(fields.state.as_bytes()));
+        assert_eq!(parsed.photo(), fields.photo.as_slice());
+
+        // A version without the last text field has 17 delimiters, and the photo follows the last of them.
+        let last_field_start = expected[..NUM_DELIMITERS - 1]
+            .iter()
+            .map(|field| field.len() + 1)
+            .sum::<usize>();
+        let short_data = [&signed_data[..last_field_start], expected[NUM_DELIMITERS]].concat();
+        let parsed = parse_aadhaar_qr(&short_data).unwrap();
+        assert_eq!(parsed.num_delimiters(), NUM_DELIMITERS - 1);
+        assert_eq!(
+            parsed.delimiter_indices(),
+            &parse_aadhaar_qr(signed_data).unwrap().delimiter_indices()[..NUM_DELIMITERS - 1]
+        );
+        assert_eq!(
+            parsed.field(NUM_DELIMITERS - 1),
+            Some(fields.photo.as_slice())
+        );
+        assert_eq!(parsed.field(NUM_DELIMITERS), None);
+        assert_eq!(parsed.name(), Some(fields.name.as_bytes()));
+        assert_eq!(parsed.dob(), Some(fields.dob.as_bytes()));
+        assert_eq!(parsed.pincode(), Some(fields.pincode.as_bytes()));
+        assert_eq!(parsed.state(), Some(fields.state.as_bytes()));
+        assert_eq!(parsed.photo(), fields.photo.as_slice());
+
+        assert!(parse_aadhaar_qr(b"V2").is_none());
+    }
+}
@@ -1,21 +1,65 @@
-/*use halo2_base::halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+use halo2_base::halo2_proofs::{
+    circuit::{Cell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    poly::Rotation,
 };
 
-use halo2_base::utils::PrimeField;
+use halo2_base::gates::{GateInstructions, RangeInstructions};
+use halo2_base::halo2_proofs::halo2curves::bn256::Fr;
+use halo2_base::utils::{biguint_to_fe, fe_to_biguint, modulus, PrimeField};
+use halo2_base::{QuantumCell, SKIP_FIRST_PASS};
+#[cfg(feature = "sha256")]
+use halo2_dynamic_sha256::Sha256DynamicConfig;
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
-#[derive(Clone)]
+use crate::age::{is_adult, AgeCircuit, ADULT_AGE, DOB_LEN};
+use crate::comparison::ComparisonConfig;
+use crate::conditional_secrets::{GENDER_POSITION, PINCODE_LEN, PINCODE_POSITION, STATE_POSITION};
+use crate::delimiter::{DelimiterCircuit, DelimiterConfig, DELIMITER, NUM_DELIMITERS};
+use crate::field_extractor::{
+    FieldExtractorChip, AADHAAR_DIGITS_LEN, DOB_POSITION, REFERENCE_ID_POSITION, TIME_LEN,
+};
+use crate::key_registry::{poseidon_pubkey_hash, KeyRegistryChip};
+use crate::nullifier::{assign_nullifier_hash, compute_nullifier};
+use crate::params::{validate_msg_len, Mainnet2048};
+use crate::photo::pack_bytes;
+use crate::qr::{qr_fields_with_layout, QrFieldLayout};
+use crate::timestamp::{unix_timestamp, TimestampCircuit, TimestampConfig, TIMESTAMP_NUM_BITS};
+use crate::utils::{assign_byte_array, zeroize_fes, zeroize_with};
+#[cfg(feature = "sha256")]
+use crate::{
+    configure_rsa_sha256, BigUintInstructions, HashAlg, RSAConfig, RSAInstructions, RSAPubE,
+    RSAPublicKey, RSASignature, RSASignatureVerifier,
+};
+
+/// The default maximum number of bytes of the padded QR data, see [`AadhaarQRVerifierCircuit`].
+pub const DEFAULT_MAX_DATA_LEN: usize = 1536;
+
+/// The byte length of the RSA-2048 signature at the end of the decompressed QR data.
+pub const SIGNATURE_LEN: usize = 256;
+
+/// The public exponent of the UIDAI keys.
+pub const UIDAI_PUBLIC_EXPONENT: u64 = 65537;
+
+#[cfg(feature = "sha256")]
+#[derive(Clone, Debug)]
 pub struct AadhaarQRVerifierConfig {
+    rsa_config: RSAConfig<Fr>,
+    sha256_config: Sha256DynamicConfig<Fr>,
+    delimiter_config: DelimiterConfig,
+    timestamp_config: TimestampConfig,
     qr_data_padded: Column<Advice>,
     qr_data_padded_length: Column<Advice>,
-    delimiter_indices: Column<Advice>,
-    signature: Column<Advice>,
-    pub_key: Column<Advice>,
-    reveal_age_above18: Column<Advice>,
-    reveal_gender: Column<Advice>,
-    reveal_pin_code: Column<Advice>,
-    reveal_state: Column<Advice>,
+    /// One if the row is within `qr_data_padded_length`, and zero otherwise.
+    in_data: Column<Advice>,
+    /// The running sum of `in_data`, which equals `qr_data_padded_length` in the last row.
+    in_data_sum: Column<Advice>,
+    q_data: Selector,
+    q_first: Selector,
+    q_step: Selector,
     nullifier_seed: Column<Instance>,
     signal_hash: Column<Instance>,
     pubkey_hash: Column<Instance>,
@@ -25,14 +69,236 @@ pub struct AadhaarQRVerifierConfig {
     gender: Column<Instance>,
     pin_code: Column<Instance>,
     state: Column<Instance>,
+    /// The bounds `not_before` and `not_after` of the validity window in rows 0 and 1.
+    validity_window: Column<Instance>,
+}
+
+/// Returns the validity window accepting any timestamp of [`TIMESTAMP_NUM_BITS`] bits, i.e. the window of a circuit or of inputs without [`AadhaarQRVerifierCircuit::with_validity_window`].
+fn full_validity_window<F: PrimeField>() -> (F, F) {
+    (F::zero(), F::from((1u64 << TIMESTAMP_NUM_BITS) - 1))
+}
+
+/// The public inputs of [`AadhaarQRVerifierCircuit`], as revealed by a proof.
+///
+/// The attributes are `None` if they are not revealed, which leaves their columns empty as in [`AadhaarQRVerifierCircuit::possession_only`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AadhaarInputs<F: PrimeField> {
+    pub nullifier_seed: F,
+    pub signal_hash: F,
+    /// The hash of the UIDAI key as [`AadhaarQRVerifierCircuit::pubkey_hash`].
+    pub pubkey_hash: F,
+    pub nullifier: F,
+    /// The UNIX timestamp of the signature.
+    pub timestamp: F,
+    pub age_above18: Option<F>,
+    pub gender: Option<F>,
+    pub pin_code: Option<F>,
+    pub state: Option<F>,
+    /// The inclusive bounds `[not_before, not_after]` the timestamp is checked against, the full range of [`TIMESTAMP_NUM_BITS`] bits if `None`.
+    pub validity_window: Option<(F, F)>,
+}
+
+/// Encodes `fe` as a decimal string, which is exact whatever its value, e.g. a byte out of range in failing inputs.
+fn fe_to_decimal<F: PrimeField>(fe: &F) -> String {
+    fe_to_biguint(fe).to_str_radix(10)
+}
+
+/// Decodes a field element encoded by [`fe_to_decimal`].
+///
+/// # Return values
+/// Returns the element, or an error if `decimal` is not a decimal number less than the order of `F`.
+fn fe_from_decimal<F: PrimeField>(decimal: &str) -> serde_json::Result<F> {
+    let invalid = || -> serde_json::Error {
+        serde::de::Error::custom(format!("{decimal} is not a field element"))
+    };
+    let value = BigUint::parse_bytes(decimal.as_bytes(), 10).ok_or_else(invalid)?;
+    if value >= modulus::<F>() {
+        return Err(invalid());
+    }
+    Ok(biguint_to_fe(&value))
+}
+
+/// Encodes optional field elements with [`fe_to_decimal`], the unknown ones as `null`.
+fn fes_to_decimal<F: PrimeField>(fes: &[Option<F>]) -> Vec<Option<String>> {
+    fes.iter()
+        .map(|fe| fe.as_ref().map(fe_to_decimal))
+        .collect()
+}
+
+/// Decodes optional field elements encoded by [`fes_to_decimal`].
+fn fes_from_decimal<F: PrimeField>(
+    decimals: &[Option<String>],
+) -> serde_json::Result<Vec<Option<F>>> {
+    decimals
+        .iter()
+        .map(|decimal| decimal.as_deref().map(fe_from_decimal).transpose())
+        .collect()
+}
+
+/// Encodes an optional validity window with [`fe_to_decimal`].
+fn window_to_decimal<F: PrimeField>(window: &Option<(F, F)>) -> Option<[String; 2]> {
+    window.map(|(not_before, not_after)| [fe_to_decimal(&not_before), fe_to_decimal(&not_after)])
+}
+
+/// Decodes an optional validity window encoded by [`window_to_decimal`].
+fn window_from_decimal<F: PrimeField>(
+    window: &Option<[String; 2]>,
+) -> serde_json::Result<Option<(F, F)>> {
+    window
+        .as_ref()
+        .map(|[not_before, not_after]| {
+            Ok((fe_from_decimal(not_before)?, fe_from_decimal(not_after)?))
+        })
+        .transpose()
+}
+
+/// Returns the byte of an element of the padded QR data, or its least significant byte if the element is not a byte, which the circuit rejects.
+fn fe_to_byte<F: PrimeField>(fe: &Option<F>) -> u8 {
+    fe.map(|fe| fe_to_biguint(&fe).to_bytes_le()[0])
+        .unwrap_or_default()
+}
+
+/// Returns the bytes of optional field elements as [`fe_to_byte`], e.g. the big-endian bytes of the signature.
+fn fes_to_bytes<F: PrimeField>(fes: &[Option<F>]) -> Vec<u8> {
+    fes.iter().map(fe_to_byte).collect()
 }
 
+/// Parses decimal digits out of the circuit, or returns `None` if a byte is not a digit, as the circuit rejects it.
+fn parse_decimal(digits: &[u8]) -> Option<u64> {
+    digits.iter().try_fold(0u64, |acc, byte| {
+        byte.is_ascii_digit()
+            .then(|| acc * 10 + (byte - b'0') as u64)
+    })
+}
+
+/// The JSON of [`AadhaarInputs`], with the field elements encoded by [`fe_to_decimal`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct AadhaarInputsJson {
+    nullifier_seed: String,
+    signal_hash: String,
+    pubkey_hash: String,
+    nullifier: String,
+    timestamp: String,
+    age_above18: Option<String>,
+    gender: Option<String>,
+    pin_code: Option<String>,
+    state: Option<String>,
+    validity_window: Option<[String; 2]>,
+}
+
+impl<F: PrimeField> AadhaarInputs<F> {
+    /// Serializes the public inputs into JSON, e.g. to attach the instances of a failing proof to a bug report together with [`AadhaarQRVerifierCircuit::to_json`].
+    pub fn to_json(&self) -> String {
+        let json = AadhaarInputsJson {
+            nullifier_seed: fe_to_decimal(&self.nullifier_seed),
+            signal_hash: fe_to_decimal(&self.signal_hash),
+            pubkey_hash: fe_to_decimal(&self.pubkey_hash),
+            nullifier: fe_to_decimal(&self.nullifier),
+            timestamp: fe_to_decimal(&self.timestamp),
+            age_above18: self.age_above18.as_ref().map(fe_to_decimal),
+            gender: self.gender.as_ref().map(fe_to_decimal),
+            pin_code: self.pin_code.as_ref().map(fe_to_decimal),
+            state: self.state.as_ref().map(fe_to_decimal),
+            validity_window: window_to_decimal(&self.validity_window),
+        };
+        serde_json::to_string(&json).expect("the public inputs are always serializable")
+    }
+
+    /// Reads back the public inputs serialized by [`AadhaarInputs::to_json`].
+    ///
+    /// # Return values
+    /// Returns the public inputs, or an error if the JSON is malformed or holds a value that is not an element of `F`.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let json: AadhaarInputsJson = serde_json::from_str(json)?;
+        let attribute = |value: &Option<String>| value.as_deref().map(fe_from_decimal).transpose();
+        Ok(Self {
+            nullifier_seed: fe_from_decimal(&json.nullifier_seed)?,
+            signal_hash: fe_from_decimal(&json.signal_hash)?,
+            pubkey_hash: fe_from_decimal(&json.pubkey_hash)?,
+            nullifier: fe_from_decimal(&json.nullifier)?,
+            timestamp: fe_from_decimal(&json.timestamp)?,
+            age_above18: attribute(&json.age_above18)?,
+            gender: attribute(&json.gender)?,
+            pin_code: attribute(&json.pin_code)?,
+            state: attribute(&json.state)?,
+            validity_window: window_from_decimal(&json.validity_window)?,
+        })
+    }
+
+    /// Assembles the public inputs into one vector per instance column, in the order of the columns of [`AadhaarQRVerifierConfig`].
+    pub fn instances(&self) -> Vec<Vec<F>> {
+        let attribute = |value: Option<F>| value.into_iter().collect::<Vec<F>>();
+        let (not_before, not_after) = self.validity_window.unwrap_or_else(full_validity_window);
+        vec![
+            vec![self.nullifier_seed],
+            vec![self.signal_hash],
+            vec![self.pubkey_hash],
+            vec![self.nullifier],
+            vec![self.timestamp],
+            attribute(self.age_above18),
+            attribute(self.gender),
+            attribute(self.pin_code),
+            attribute(self.state),
+            vec![not_before, not_after],
+        ]
+    }
+}
+
+impl<F: PrimeField> Zeroize for AadhaarInputs<F> {
+    /// Zeroizes the values derived from the identity, i.e. the nullifier, the timestamp and the revealed attributes.
+    fn zeroize(&mut self) {
+        zeroize_with(&mut self.nullifier, F::zero());
+        zeroize_with(&mut self.timestamp, F::zero());
+        for attribute in [
+            &mut self.age_above18,
+            &mut self.gender,
+            &mut self.pin_code,
+            &mut self.state,
+        ] {
+            zeroize_with(attribute, Some(F::zero()));
+            zeroize_with(attribute, None);
+        }
+    }
+}
+
+impl<F: PrimeField> Drop for AadhaarInputs<F> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<F: PrimeField> ZeroizeOnDrop for AadhaarInputs<F> {}
+
+/// The JSON of the inputs of [`AadhaarQRVerifierCircuit`], with the field elements encoded by [`fe_to_decimal`] and the unknown ones as `null`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct AadhaarCircuitJson {
+    qr_data_padded: Vec<Option<String>>,
+    qr_data_padded_length: Option<String>,
+    signature: Vec<Option<String>>,
+    pub_key: Vec<Option<String>>,
+    reveal_age_above18: Option<String>,
+    reveal_gender: Option<String>,
+    reveal_pin_code: Option<String>,
+    reveal_state: Option<String>,
+    nullifier_seed: Option<String>,
+    signal_hash: Option<String>,
+    validity_window: Option<[String; 2]>,
+}
+
+/// The Aadhaar QR verifier over `MAX_DATA_LEN` rows of padded QR data.
+///
+/// The circuit verifies the RSA-SHA256 signature of the signed data with the UIDAI key, scans its delimiters and extracts its fields, all over the bytes whose signature is verified.
+/// It exposes the nullifier of the photo, the [`AadhaarQRVerifierCircuit::pubkey_hash`] of the key, the signing timestamp, checked to be within the validity window, and the revealed attributes.
+///
+/// `MAX_DATA_LEN` sets the number of rows of the data columns and the maximum size of the SHA256 chip, so it must be a multiple of 64, QR codes of different sizes only need a different instantiation, and `k` must be large enough to fit `MAX_DATA_LEN` rows.
 #[derive(Clone)]
-pub struct AadhaarQRVerifierCircuit<F: PrimeField> {
+pub struct AadhaarQRVerifierCircuit<F: PrimeField, const MAX_DATA_LEN: usize = DEFAULT_MAX_DATA_LEN>
+{
     qr_data_padded: Vec<Option<F>>,
     qr_data_padded_length: Option<F>,
-    delimiter_indices: Vec<Option<F>>,
+    /// The big-endian bytes of the signature.
     signature: Vec<Option<F>>,
+    /// The big-endian bytes of the modulus of the UIDAI key, whose public exponent is [`UIDAI_PUBLIC_EXPONENT`].
     pub_key: Vec<Option<F>>,
     reveal_age_above18: Option<F>,
     reveal_gender: Option<F>,
@@ -40,9 +306,292 @@ pub struct AadhaarQRVerifierCircuit<F: PrimeField> {
     reveal_state: Option<F>,
     nullifier_seed: Option<F>,
     signal_hash: Option<F>,
+    /// The validity window of [`AadhaarQRVerifierCircuit::with_validity_window`], the full range of [`TIMESTAMP_NUM_BITS`] bits if `None`.
+    validity_window: Option<(F, F)>,
+}
+
+impl<F: PrimeField, const MAX_DATA_LEN: usize> AadhaarQRVerifierCircuit<F, MAX_DATA_LEN> {
+    /// The number of instance columns allocated by [`AadhaarQRVerifierCircuit::configure`].
+    pub const NUM_INSTANCE_COLUMNS: usize = 10;
+
+    /// The index of the instance column of the public key hash.
+    pub const PUBKEY_HASH_COLUMN: usize = 2;
+
+    /// The index of the instance column of the nullifier.
+    pub const NULLIFIER_COLUMN: usize = 3;
+
+    /// The index of the instance column of the signing timestamp.
+    pub const TIMESTAMP_COLUMN: usize = 4;
+
+    /// The indices of the instance columns of the revealable attributes: age above 18, gender, pin code and state.
+    pub const ATTRIBUTE_COLUMNS: [usize; 4] = [5, 6, 7, 8];
+
+    /// The index of the instance column of the validity window, whose rows 0 and 1 hold `not_before` and `not_after`.
+    pub const VALIDITY_WINDOW_COLUMN: usize = 9;
+
+    /// Creates a circuit proving only the possession of a valid Aadhaar QR, without revealing any attribute.
+    ///
+    /// This is the minimal path of the circuit: all reveal flags are off, so only the nullifier, the public key hash, the timestamp and the signal are exposed, and the attribute columns of [`AadhaarQRVerifierCircuit::ATTRIBUTE_COLUMNS`] are left empty.
+    /// The signature is still verified against `modulus`, so the proof does not hold for QR data signed with another key.
+    ///
+    /// # Arguments
+    /// * qr - the decompressed QR data, i.e. the signed data followed by the [`SIGNATURE_LEN`]-byte signature.
+    /// * modulus - the modulus of the UIDAI key the QR data is signed with.
+    /// * nullifier_seed - the seed of the nullifier chosen by the application.
+    /// * signal_hash - the hash of the signal committed to by the proof.
+    pub fn possession_only(
+        qr: &[u8],
+        modulus: &BigUint,
+        nullifier_seed: F,
+        signal_hash: F,
+    ) -> Self {
+        let (data, signature) = qr.split_at(qr.len().saturating_sub(SIGNATURE_LEN));
+        let to_fes = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|byte| Some(F::from(*byte as u64)))
+                .collect::<Vec<Option<F>>>()
+        };
+        Self {
+            qr_data_padded: to_fes(data),
+            qr_data_padded_length: Some(F::from(data.len() as u64)),
+            signature: to_fes(signature),
+            pub_key: to_fes(&modulus.to_bytes_be()),
+            reveal_age_above18: Some(F::zero()),
+            reveal_gender: Some(F::zero()),
+            reveal_pin_code: Some(F::zero()),
+            reveal_state: Some(F::zero()),
+            nullifier_seed: Some(nullifier_seed),
+            signal_hash: Some(signal_hash),
+            validity_window: None,
+        }
+    }
+
+    /// Sets which attributes are revealed in the columns of [`AadhaarQRVerifierCircuit::ATTRIBUTE_COLUMNS`], in their order.
+    pub fn with_revealed(
+        mut self,
+        age_above18: bool,
+        gender: bool,
+        pin_code: bool,
+        state: bool,
+    ) -> Self {
+        let flag = |reveal: bool| Some(F::from(reveal as u64));
+        self.reveal_age_above18 = flag(age_above18);
+        self.reveal_gender = flag(gender);
+        self.reveal_pin_code = flag(pin_code);
+        self.reveal_state = flag(state);
+        self
+    }
+
+    /// Sets the public window `[not_before, not_after]` the signing timestamp must be within, both inclusive.
+    pub fn with_validity_window(mut self, not_before: F, not_after: F) -> Self {
+        self.validity_window = Some((not_before, not_after));
+        self
+    }
+
+    /// Returns true if no attribute is revealed, as in [`AadhaarQRVerifierCircuit::possession_only`].
+    pub fn is_possession_only(&self) -> bool {
+        [
+            self.reveal_age_above18,
+            self.reveal_gender,
+            self.reveal_pin_code,
+            self.reveal_state,
+        ]
+        .iter()
+        .all(|reveal| reveal.unwrap_or(F::zero()) == F::zero())
+    }
+
+    /// Creates a circuit over placeholder QR data with every field of [`QrFieldLayout::default`], e.g. to generate the keys of the circuit.
+    ///
+    /// The layout of the circuit does not depend on the QR data, so the keys also hold for proofs over real QR data, while the placeholder signature does not verify.
+    pub fn placeholder() -> Self {
+        let mut qr = vec![];
+        for position in 0..NUM_DELIMITERS {
+            let field: &[u8] = match position {
+                REFERENCE_ID_POSITION => b"000019700101000000000",
+                DOB_POSITION => b"01-01-1970",
+                GENDER_POSITION => b"M",
+                PINCODE_POSITION => b"000000",
+                _ => b"",
+            };
+            qr.extend_from_slice(field);
+            qr.push(DELIMITER);
+        }
+        // A one-byte photo, and the signature.
+        qr.resize(qr.len() + 1 + SIGNATURE_LEN, 0);
+        // An odd modulus of 2048 bits.
+        let modulus = (BigUint::from(1u64) << (SIGNATURE_LEN * 8 - 1)) + BigUint::from(1u64);
+        Self::possession_only(&qr, &modulus, F::zero(), F::zero())
+    }
+
+    /// Returns the signed data, i.e. the first `qr_data_padded_length` bytes of the padded QR data.
+    fn signed_data(&self) -> Vec<u8> {
+        let length = self
+            .qr_data_padded_length
+            .map(|length| fe_to_biguint(&length))
+            .unwrap_or_default();
+        self.qr_data_padded
+            .iter()
+            .enumerate()
+            .take_while(|(i, _)| BigUint::from(*i) < length)
+            .map(|(_, byte)| fe_to_byte(byte))
+            .collect()
+    }
+
+    /// Serializes all the inputs of the circuit into JSON: the QR data and its length, the signature, the key, the reveal flags, the nullifier seed, the signal hash and the validity window.
+    ///
+    /// The values are kept as they are, even if they are not bytes, so that a maintainer can reproduce a failing proof from a bug report with [`AadhaarQRVerifierCircuit::from_json`].
+    pub fn to_json(&self) -> String {
+        let fe = |value: &Option<F>| value.as_ref().map(fe_to_decimal);
+        let json = AadhaarCircuitJson {
+            qr_data_padded: fes_to_decimal(&self.qr_data_padded),
+            qr_data_padded_length: fe(&self.qr_data_padded_length),
+            signature: fes_to_decimal(&self.signature),
+            pub_key: fes_to_decimal(&self.pub_key),
+            reveal_age_above18: fe(&self.reveal_age_above18),
+            reveal_gender: fe(&self.reveal_gender),
+            reveal_pin_code: fe(&self.reveal_pin_code),
+            reveal_state: fe(&self.reveal_state),
+            nullifier_seed: fe(&self.nullifier_seed),
+            signal_hash: fe(&self.signal_hash),
+            validity_window: window_to_decimal(&self.validity_window),
+        };
+        serde_json::to_string(&json).expect("the circuit inputs are always serializable")
+    }
+
+    /// Reads back a circuit serialized by [`AadhaarQRVerifierCircuit::to_json`].
+    ///
+    /// # Return values
+    /// Returns the circuit, or an error if the JSON is malformed or holds a value that is not an element of `F`.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let json: AadhaarCircuitJson = serde_json::from_str(json)?;
+        let fe = |value: &Option<String>| value.as_deref().map(fe_from_decimal).transpose();
+        Ok(Self {
+            qr_data_padded: fes_from_decimal(&json.qr_data_padded)?,
+            qr_data_padded_length: fe(&json.qr_data_padded_length)?,
+            signature: fes_from_decimal(&json.signature)?,
+            pub_key: fes_from_decimal(&json.pub_key)?,
+            reveal_age_above18: fe(&json.reveal_age_above18)?,
+            reveal_gender: fe(&json.reveal_gender)?,
+            reveal_pin_code: fe(&json.reveal_pin_code)?,
+            reveal_state: fe(&json.reveal_state)?,
+            nullifier_seed: fe(&json.nullifier_seed)?,
+            signal_hash: fe(&json.signal_hash)?,
+            validity_window: window_from_decimal(&json.validity_window)?,
+        })
+    }
+}
+
+impl<F: PrimeField, const MAX_DATA_LEN: usize> Zeroize
+    for AadhaarQRVerifierCircuit<F, MAX_DATA_LEN>
+{
+    /// Zeroizes and empties the QR data and the signature, the public key being public.
+    fn zeroize(&mut self) {
+        zeroize_fes(&mut self.qr_data_padded);
+        zeroize_with(&mut self.qr_data_padded_length, None);
+        zeroize_fes(&mut self.signature);
+    }
+}
+
+impl<F: PrimeField, const MAX_DATA_LEN: usize> Drop for AadhaarQRVerifierCircuit<F, MAX_DATA_LEN> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
 }
 
-impl <F: PrimeField> Circuit<F> for  AadhaarQRVerifierCircuit<F> {
+impl<F: PrimeField, const MAX_DATA_LEN: usize> ZeroizeOnDrop
+    for AadhaarQRVerifierCircuit<F, MAX_DATA_LEN>
+{
+}
+
+impl<const MAX_DATA_LEN: usize> AadhaarQRVerifierCircuit<Fr, MAX_DATA_LEN> {
+    /// Computes the public key hash exposed by the circuit, i.e. [`poseidon_pubkey_hash`] of the [`SIGNATURE_LEN`]-byte modulus.
+    ///
+    /// This is the leaf of the key in a [`crate::key_registry::KeyRegistry`], so a verifier can check the exposed hash against a registry as well as against a single key.
+    pub fn pubkey_hash(modulus: &BigUint) -> Fr {
+        poseidon_pubkey_hash(modulus, SIGNATURE_LEN * 8)
+    }
+
+    /// Computes the public inputs of the circuit out of it, as the circuit constrains its instance columns.
+    ///
+    /// # Return values
+    /// Returns the public inputs, or `None` if the signed data does not have the fields of [`QrFieldLayout::default`], a date or a number of them is malformed, or the nullifier seed does not fit in a `u64`.
+    /// The circuit is not satisfiable with such data either, while a data of well-formed fields still needs a valid signature.
+    pub fn inputs(&self) -> Option<AadhaarInputs<Fr>> {
+        let signed_data = self.signed_data();
+        let fields = qr_fields_with_layout(&signed_data, &QrFieldLayout::default())?;
+
+        let time = fields[REFERENCE_ID_POSITION]?
+            .get(AADHAAR_DIGITS_LEN..AADHAAR_DIGITS_LEN + TIME_LEN)?;
+        let year = parse_decimal(&time[0..4])?;
+        let month = parse_decimal(&time[4..6])?;
+        let day = parse_decimal(&time[6..8])?;
+        let hour = parse_decimal(&time[8..10])?;
+        let timestamp = unix_timestamp(year, month, day, hour, 0, 0)?;
+        let dob: [u8; DOB_LEN] = fields[DOB_POSITION]?.try_into().ok()?;
+        let age = AgeCircuit::<Fr>::from_dob(dob, (year, month, day), false).age();
+        let gender = match fields[GENDER_POSITION]? {
+            [gender] => *gender,
+            _ => return None,
+        };
+        let pin_code = fields[PINCODE_POSITION]?;
+        if pin_code.len() != PINCODE_LEN {
+            return None;
+        }
+        let state = pack_bytes::<Fr>(fields[STATE_POSITION]?, 1).ok()?[0];
+
+        let nullifier_seed = self.nullifier_seed.unwrap_or(Fr::zero());
+        let nullifier = compute_nullifier(
+            fe_to_biguint(&nullifier_seed).to_u64()?,
+            &signed_data,
+            &QrFieldLayout::default(),
+        )?;
+        let modulus = BigUint::from_bytes_be(&fes_to_bytes(&self.pub_key));
+        let reveal = |flag: Option<Fr>, value: Fr| {
+            (flag.unwrap_or(Fr::zero()) != Fr::zero()).then_some(value)
+        };
+        Some(AadhaarInputs {
+            nullifier_seed,
+            signal_hash: self.signal_hash.unwrap_or(Fr::zero()),
+            pubkey_hash: Self::pubkey_hash(&modulus),
+            nullifier,
+            timestamp: Fr::from(timestamp),
+            age_above18: reveal(self.reveal_age_above18, Fr::from(is_adult(age) as u64)),
+            gender: reveal(self.reveal_gender, Fr::from(gender as u64)),
+            pin_code: reveal(self.reveal_pin_code, Fr::from(parse_decimal(pin_code)?)),
+            state: reveal(self.reveal_state, state),
+            validity_window: self.validity_window,
+        })
+    }
+}
+
+/// The cells of the verification context of [`AadhaarQRVerifierCircuit`], which are constrained equal to the cells of the other regions and to the instances.
+#[cfg(feature = "sha256")]
+struct VerifiedCells {
+    /// The bytes input to SHA256, masked to zero from `qr_data_padded_length` on.
+    masked_data: Vec<Cell>,
+    in_data: Vec<Cell>,
+    /// The length of the message hashed by SHA256.
+    data_len: Cell,
+    n_delimited_data: Vec<Cell>,
+    delimiter_indices: Vec<Cell>,
+    /// The year, month, day and hour of the signing time extracted from the reference ID, and their values.
+    signed_time: [Cell; 4],
+    signed_time_values: [Fr; 4],
+    /// A constant zero for the minute and the second of the signing time.
+    zero: Cell,
+    /// The timestamp checked against the validity window.
+    signed_at: Cell,
+    nullifier_seed: Cell,
+    signal_hash: Cell,
+    pubkey_hash: Cell,
+    nullifier: Cell,
+    attributes: [Cell; 4],
+    validity_window: [Cell; 2],
+}
+
+#[cfg(feature = "sha256")]
+impl<const MAX_DATA_LEN: usize> Circuit<Fr> for AadhaarQRVerifierCircuit<Fr, MAX_DATA_LEN> {
     type Config = AadhaarQRVerifierConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
@@ -50,16 +599,53 @@ impl <F: PrimeField> Circuit<F> for  AadhaarQRVerifierCircuit<F> {
         unimplemented!()
     }
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let (rsa_config, sha256_config) =
+            configure_rsa_sha256::<Fr, Mainnet2048>(meta, vec![MAX_DATA_LEN]);
+        let delimiter_config = DelimiterCircuit::configure(meta);
+        let timestamp_config = TimestampCircuit::configure(meta);
         let qr_data_padded = meta.advice_column();
         let qr_data_padded_length = meta.advice_column();
-        let delimiter_indices = meta.advice_column();
-        let signature = meta.advice_column();
-        let pub_key = meta.advice_column();
-        let reveal_age_above18 = meta.advice_column();
-        let reveal_gender = meta.advice_column();
-        let reveal_pin_code = meta.advice_column();
-        let reveal_state = meta.advice_column();
+        let in_data = meta.advice_column();
+        let in_data_sum = meta.advice_column();
+        let q_data = meta.selector();
+        let q_first = meta.selector();
+        let q_step = meta.selector();
+        meta.enable_equality(qr_data_padded);
+        meta.enable_equality(qr_data_padded_length);
+        meta.enable_equality(in_data);
+        meta.enable_equality(in_data_sum);
+
+        // Since `in_data` is a non-increasing sequence of bits over `MAX_DATA_LEN` rows, its sum `qr_data_padded_length` is at most `MAX_DATA_LEN`.
+        meta.create_gate("qr data padding", |meta| {
+            let q_data = meta.query_selector(q_data);
+            let byte = meta.query_advice(qr_data_padded, Rotation::cur());
+            let in_data = meta.query_advice(in_data, Rotation::cur());
+            let not_in_data = Expression::Constant(Fr::one()) - in_data.clone();
+            vec![
+                q_data.clone() * in_data * not_in_data.clone(),
+                q_data * byte * not_in_data,
+            ]
+        });
+
+        meta.create_gate("qr data length first row", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let in_data = meta.query_advice(in_data, Rotation::cur());
+            let in_data_sum = meta.query_advice(in_data_sum, Rotation::cur());
+            vec![q_first * (in_data_sum - in_data)]
+        });
+
+        meta.create_gate("qr data length step", |meta| {
+            let q_step = meta.query_selector(q_step);
+            let in_data_prev = meta.query_advice(in_data, Rotation::prev());
+            let in_data = meta.query_advice(in_data, Rotation::cur());
+            let sum_prev = meta.query_advice(in_data_sum, Rotation::prev());
+            let sum = meta.query_advice(in_data_sum, Rotation::cur());
+            vec![
+                q_step.clone() * in_data.clone() * (Expression::Constant(Fr::one()) - in_data_prev),
+                q_step * (sum - sum_prev - in_data),
+            ]
+        });
 
         let nullifier_seed = meta.instance_column();
         let signal_hash = meta.instance_column();
@@ -70,17 +656,34 @@ impl <F: PrimeField> Circuit<F> for  AadhaarQRVerifierCircuit<F> {
         let gender = meta.instance_column();
         let pin_code = meta.instance_column();
         let state = meta.instance_column();
+        let validity_window = meta.instance_column();
+        for column in [
+            nullifier_seed,
+            signal_hash,
+            pubkey_hash,
+            nullifier,
+            timestamp,
+            age_above18,
+            gender,
+            pin_code,
+            state,
+            validity_window,
+        ] {
+            meta.enable_equality(column);
+        }
 
         AadhaarQRVerifierConfig {
+            rsa_config,
+            sha256_config,
+            delimiter_config,
+            timestamp_config,
             qr_data_padded,
             qr_data_padded_length,
-            delimiter_indices,
-            signature,
-            pub_key,
-            reveal_age_above18,
-            reveal_gender,
-            reveal_pin_code,
-            reveal_state,
+            in_data,
+            in_data_sum,
+            q_data,
+            q_first,
+            q_step,
             nullifier_seed,
             signal_hash,
             pubkey_hash,
@@ -90,128 +693,647 @@ impl <F: PrimeField> Circuit<F> for  AadhaarQRVerifierCircuit<F> {
             gender,
             pin_code,
             state,
+            validity_window,
         }
     }
 
     fn synthesize(
         &self,
         config: Self::Config,
-        mut layouter: impl Layouter<F>,
+        mut layouter: impl Layouter<Fr>,
     ) -> Result<(), Error> {
-        layouter.assign_region(
+        if self.qr_data_padded.len() > MAX_DATA_LEN {
+            return Err(Error::Synthesis);
+        }
+        let signed_data = self.signed_data();
+        validate_msg_len(signed_data.len(), MAX_DATA_LEN).map_err(|_| Error::Synthesis)?;
+        let mut qr_data_padded = self.qr_data_padded.clone();
+        qr_data_padded.resize(MAX_DATA_LEN, None);
+        let length = self
+            .qr_data_padded_length
+            .map(|length| fe_to_biguint(&length))
+            .unwrap_or_default();
+        let in_data_bits = (0..MAX_DATA_LEN)
+            .map(|i| BigUint::from(i) < length)
+            .collect::<Vec<bool>>();
+
+        let (data_cells, length_cell, in_data_cells) = layouter.assign_region(
             || "assign inputs",
             |mut region| {
-                for (i, &value) in self.qr_data_padded.iter().enumerate() {
-                    region.assign_advice(
-                        || format!("qr_data_padded {}", i),
-                        config.qr_data_padded,
-                        i,
-                        || Value::known(value.unwrap_or(F::from(1)))
-                    )?;
-                }
-
-                region.assign_advice(
-                    || "qr_data_padded_length",
+                let data = assign_byte_array(&mut region, config.qr_data_padded, &qr_data_padded)?;
+                let length_cell = assign_byte_array(
+                    &mut region,
                     config.qr_data_padded_length,
-                    0,
-                    || Value::known(self.qr_data_padded_length.unwrap_or(F::from(1)))
+                    &[self.qr_data_padded_length],
                 )?;
 
-                for (i, &value) in self.delimiter_indices.iter().enumerate() {
-                    region.assign_advice(
-                        || format!("delimiter_indices {}", i),
-                        config.delimiter_indices,
+                let mut sum = 0u64;
+                let mut sum_cell = None;
+                let mut in_data_cells = Vec::with_capacity(MAX_DATA_LEN);
+                for (i, in_data) in in_data_bits.iter().enumerate() {
+                    config.q_data.enable(&mut region, i)?;
+                    if i == 0 {
+                        config.q_first.enable(&mut region, i)?;
+                    } else {
+                        config.q_step.enable(&mut region, i)?;
+                    }
+                    sum += *in_data as u64;
+                    in_data_cells.push(region.assign_advice(
+                        || format!("in_data {}", i),
+                        config.in_data,
                         i,
-                        || Value::known(value.unwrap_or(F::from(1)))
-                    )?;
-                }
-
-                for (i, &value) in self.signature.iter().enumerate() {
-                    region.assign_advice(
-                        || format!("signature {}", i),
-                        config.signature,
+                        || Value::known(Fr::from(*in_data as u64)),
+                    )?);
+                    sum_cell = Some(region.assign_advice(
+                        || format!("in_data_sum {}", i),
+                        config.in_data_sum,
                         i,
-                        || Value::known(value.unwrap_or(F::from(1)))
-                    )?;
+                        || Value::known(Fr::from(sum)),
+                    )?);
                 }
+                region.constrain_equal(length_cell[0].cell(), sum_cell.unwrap().cell())?;
+                Ok((data, length_cell[0].clone(), in_data_cells))
+            },
+        )?;
 
-                for (i, &value) in self.pub_key.iter().enumerate() {
-                    region.assign_advice(
-                        || format!("pub_key {}", i),
-                        config.pub_key,
-                        i,
-                        || Value::known(value.unwrap_or(F::from(1)))
-                    )?;
+        // The scan runs over the same rows as the padded data, whose bytes it is constrained equal to below.
+        let delimiter =
+            DelimiterCircuit::<Fr>::new(signed_data.clone()).with_max_data_len(MAX_DATA_LEN);
+        let scanned = delimiter.assign_fields(
+            &config.delimiter_config,
+            layouter.namespace(|| "delimiter scan"),
+        )?;
+        let mut n_delimited_data = delimiter.n_delimited_data();
+        n_delimited_data.resize(MAX_DATA_LEN, 0);
+        let mut delimiter_indices = delimiter.delimiter_indices();
+        delimiter_indices.resize(NUM_DELIMITERS, 0);
+
+        let biguint_config = config.rsa_config.biguint_config();
+        config.sha256_config.load(&mut layouter)?;
+        biguint_config.range().load_lookup_table(&mut layouter)?;
+        config.timestamp_config.load_calendar_table(&mut layouter)?;
+        let mut first_pass = SKIP_FIRST_PASS;
+        let cells = layouter.assign_region(
+            || "verify qr data",
+            |region| {
+                if first_pass {
+                    first_pass = false;
+                    return Ok(None);
                 }
+                let mut aux = biguint_config.new_context(region);
+                let ctx = &mut aux;
+                let gate = biguint_config.gate();
+                let range = biguint_config.range();
 
-                region.assign_advice(
-                    || "reveal_age_above18",
-                    config.reveal_age_above18,
-                    0,
-                    || Value::known(self.reveal_age_above18.unwrap_or(F::from(1)))
+                // The signature is verified over the signed data with the UIDAI key, whose limbs are hashed into the exposed public key hash.
+                let sign_big = BigUint::from_bytes_be(&fes_to_bytes(&self.signature));
+                let signature = config
+                    .rsa_config
+                    .assign_signature(ctx, RSASignature::new(Value::known(sign_big)))?;
+                let public_key = RSAPublicKey::new(
+                    Value::known(BigUint::from_bytes_be(&fes_to_bytes(&self.pub_key))),
+                    RSAPubE::Fix(BigUint::from(UIDAI_PUBLIC_EXPONENT)),
+                );
+                let public_key = config.rsa_config.assign_public_key(ctx, public_key)?;
+                let mut verifier = RSASignatureVerifier::new(
+                    config.rsa_config.clone(),
+                    config.sha256_config.clone(),
+                );
+                let result = verifier.verify_pkcs1v15_signature(
+                    ctx,
+                    &public_key,
+                    &signed_data,
+                    &signature,
+                    HashAlg::Sha256,
                 )?;
+                gate.assert_is_const(ctx, &result.is_valid, Fr::one());
+                let pubkey_hash = KeyRegistryChip::construct(gate.clone())
+                    .assign_pubkey_hash(ctx, &result.modulus_limbs);
 
-                region.assign_advice(
-                    || "reveal_gender",
-                    config.reveal_gender,
-                    0,
-                    || Value::known(self.reveal_gender.unwrap_or(F::from(1)))
-                )?;
+                // The padded data is the hashed bytes up to the hashed length, and zero after them.
+                if result.padded_msg.len() < MAX_DATA_LEN {
+                    return Err(Error::Synthesis);
+                }
+                let mut in_data = Vec::with_capacity(MAX_DATA_LEN);
+                let mut masked_data = Vec::with_capacity(MAX_DATA_LEN);
+                for (byte, bit) in result.padded_msg.iter().zip(in_data_bits.iter()) {
+                    let bit = gate.load_witness(ctx, Value::known(Fr::from(*bit as u64)));
+                    let masked = gate.mul(
+                        ctx,
+                        QuantumCell::Existing(byte),
+                        QuantumCell::Existing(&bit),
+                    );
+                    masked_data.push(masked.cell());
+                    in_data.push(bit.cell());
+                }
 
-                region.assign_advice(
-                    || "reveal_pin_code",
-                    config.reveal_pin_code,
-                    0,
-                    || Value::known(self.reveal_pin_code.unwrap_or(F::from(1)))
+                // The fields are extracted from the scanned `nDelimitedData`, whose bytes are those of the signed data.
+                let n_delimited_data = n_delimited_data
+                    .iter()
+                    .map(|byte| gate.load_witness(ctx, Value::known(Fr::from(*byte))))
+                    .collect::<Vec<_>>();
+                let delimiter_indices = delimiter_indices
+                    .iter()
+                    .map(|index| gate.load_witness(ctx, Value::known(Fr::from(*index as u64))))
+                    .collect::<Vec<_>>();
+                let fields = FieldExtractorChip::construct(range.clone()).extract_all(
+                    ctx,
+                    &n_delimited_data,
+                    &delimiter_indices,
+                    &result.msg_len,
+                    &QrFieldLayout::default(),
                 )?;
+                let photo = fields.photo.as_ref().ok_or(Error::Synthesis)?;
+                let nullifier_seed =
+                    gate.load_witness(ctx, Value::known(self.nullifier_seed.unwrap_or(Fr::zero())));
+                let nullifier = assign_nullifier_hash(gate, ctx, &nullifier_seed, photo);
 
-                region.assign_advice(
-                    || "reveal_state",
-                    config.reveal_state,
-                    0,
-                    || Value::known(self.reveal_state.unwrap_or(F::from(1)))
+                // Each attribute is exposed as its value if its reveal flag is one, and as zero otherwise.
+                let is_minor = range.is_less_than(
+                    ctx,
+                    QuantumCell::Existing(&fields.age),
+                    QuantumCell::Constant(Fr::from(ADULT_AGE)),
+                    8,
+                );
+                let age_above18 = gate.not(ctx, QuantumCell::Existing(&is_minor));
+                let attributes = [
+                    (&age_above18, self.reveal_age_above18),
+                    (&fields.gender, self.reveal_gender),
+                    (&fields.pincode, self.reveal_pin_code),
+                    (&fields.state, self.reveal_state),
+                ]
+                .map(|(value, reveal)| {
+                    let reveal = gate.load_witness(ctx, Value::known(reveal.unwrap_or(Fr::zero())));
+                    gate.num_to_bits(ctx, &reveal, 1);
+                    gate.mul(
+                        ctx,
+                        QuantumCell::Existing(&reveal),
+                        QuantumCell::Existing(value),
+                    )
+                    .cell()
+                });
+
+                // The signing time is the hour of the reference ID, whose timestamp is computed in the timestamp region and checked against the window here.
+                let mut signed_time_values = [Fr::zero(); 4];
+                for (value, cell) in signed_time_values.iter_mut().zip(fields.timestamp.iter()) {
+                    cell.value().map(|v| *value = *v);
+                }
+                let [year, month, day, hour] = signed_time_values
+                    .map(|value| fe_to_biguint(&value).to_u64().unwrap_or_default());
+                let timestamp = unix_timestamp(year, month, day, hour, 0, 0).unwrap_or_default();
+                let signed_at = gate.load_witness(ctx, Value::known(Fr::from(timestamp)));
+                let (not_before, not_after) =
+                    self.validity_window.unwrap_or_else(full_validity_window);
+                let not_before = gate.load_witness(ctx, Value::known(not_before));
+                let not_after = gate.load_witness(ctx, Value::known(not_after));
+                let is_within = ComparisonConfig::construct(range.clone()).is_within_window(
+                    ctx,
+                    &signed_at,
+                    &not_before,
+                    &not_after,
+                    TIMESTAMP_NUM_BITS,
+                );
+                gate.assert_is_const(ctx, &is_within, Fr::one());
+                let zero = gate.load_zero(ctx);
+
+                let signal_hash =
+                    gate.load_witness(ctx, Value::known(self.signal_hash.unwrap_or(Fr::zero())));
+                range.finalize(ctx);
+                Ok(Some(VerifiedCells {
+                    masked_data,
+                    in_data,
+                    data_len: result.msg_len.cell(),
+                    n_delimited_data: n_delimited_data.iter().map(|byte| byte.cell()).collect(),
+                    delimiter_indices: delimiter_indices.iter().map(|index| index.cell()).collect(),
+                    signed_time: fields.timestamp.clone().map(|value| value.cell()),
+                    signed_time_values,
+                    zero: zero.cell(),
+                    signed_at: signed_at.cell(),
+                    nullifier_seed: nullifier_seed.cell(),
+                    signal_hash: signal_hash.cell(),
+                    pubkey_hash: pubkey_hash.cell(),
+                    nullifier: nullifier.cell(),
+                    attributes,
+                    validity_window: [not_before.cell(), not_after.cell()],
+                }))
+            },
+        )?;
+        let cells = cells.unwrap();
+
+        let [year, month, day, hour] = cells.signed_time_values.map(Some);
+        let (time_inputs, timestamp) =
+            TimestampCircuit::new(year, month, day, hour, Some(Fr::zero()), Some(Fr::zero()))
+                .assign_timestamp_with_inputs(
+                    &config.timestamp_config,
+                    layouter.namespace(|| "timestamp"),
                 )?;
 
-                Ok(())
+        layouter.assign_region(
+            || "bind qr data",
+            |mut region| {
+                // The padded data, the scanned data and the hashed bytes are the same bytes.
+                for ((data, masked), scanned) in data_cells
+                    .iter()
+                    .zip(cells.masked_data.iter())
+                    .zip(scanned.data.iter())
+                {
+                    region.constrain_equal(data.cell(), *masked)?;
+                    region.constrain_equal(scanned.cell(), data.cell())?;
+                }
+                for (in_data, bit) in in_data_cells.iter().zip(cells.in_data.iter()) {
+                    region.constrain_equal(in_data.cell(), *bit)?;
+                }
+                region.constrain_equal(length_cell.cell(), cells.data_len)?;
+                for (scanned, loaded) in scanned
+                    .n_delimited_data
+                    .iter()
+                    .zip(cells.n_delimited_data.iter())
+                {
+                    region.constrain_equal(scanned.cell(), *loaded)?;
+                }
+                for (scanned, loaded) in scanned
+                    .delimiter_indices
+                    .iter()
+                    .zip(cells.delimiter_indices.iter())
+                {
+                    region.constrain_equal(scanned.cell(), *loaded)?;
+                }
+                // The timestamp is computed from the signing time at minute and second zero.
+                let signed_time = cells.signed_time.iter().chain([&cells.zero, &cells.zero]);
+                for (input, time) in time_inputs.iter().zip(signed_time) {
+                    region.constrain_equal(input.cell(), *time)?;
+                }
+                region.constrain_equal(timestamp.cell(), cells.signed_at)
             },
         )?;
+
+        layouter.constrain_instance(cells.nullifier_seed, config.nullifier_seed, 0)?;
+        layouter.constrain_instance(cells.signal_hash, config.signal_hash, 0)?;
+        layouter.constrain_instance(cells.pubkey_hash, config.pubkey_hash, 0)?;
+        layouter.constrain_instance(cells.nullifier, config.nullifier, 0)?;
+        layouter.constrain_instance(timestamp.cell(), config.timestamp, 0)?;
+        let attribute_columns = [
+            config.age_above18,
+            config.gender,
+            config.pin_code,
+            config.state,
+        ];
+        for (cell, column) in cells.attributes.iter().zip(attribute_columns) {
+            layouter.constrain_instance(*cell, column, 0)?;
+        }
+        for (row, cell) in cells.validity_window.iter().enumerate() {
+            layouter.constrain_instance(*cell, config.validity_window, row)?;
+        }
         Ok(())
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "sha256"))]
 mod tests {
     use super::*;
-    use halo2_base::halo2_proofs::dev::MockProver;
-    use halo2_base::halo2_proofs::halo2curves::bn256::Fr as Fp;
-    use std::vec;
+    use crate::params::Preset;
+    use crate::qr::decompress_qr;
+    use crate::qr::test_utils::{generate_test_qr, TestQrFields};
+    use halo2_base::halo2_proofs::dev::{MockProver, VerifyFailure};
+    use rsa::traits::PublicKeyParts;
+
+    const K: u32 = Mainnet2048::K;
+
+    /// The verifier over 256 bytes, which fit the signed data of the test QR codes.
+    type TestCircuit = AadhaarQRVerifierCircuit<Fr, 256>;
+
+    /// The UNIX timestamp of 2024-07-18 12:00:00, the signing hour of the reference ID of [`TestQrFields::default`].
+    const SIGNED_AT: u64 = 1721304000;
+
+    /// Returns a possession-only circuit over a test QR code signed with a fresh key, and the modulus of the key.
+    fn test_circuit(fields: TestQrFields) -> (TestCircuit, BigUint) {
+        let (qr, _, public_key) = generate_test_qr(fields);
+        let qr = decompress_qr(&qr).unwrap();
+        let modulus = BigUint::from_bytes_be(&public_key.n().to_bytes_be());
+        let circuit = TestCircuit::possession_only(&qr, &modulus, Fr::from(12345678), Fr::from(42));
+        (circuit, modulus)
+    }
+
+    fn run(circuit: &TestCircuit, instances: Vec<Vec<Fr>>) -> Result<(), Vec<VerifyFailure>> {
+        MockProver::run(K, circuit, instances).unwrap().verify()
+    }
 
     #[test]
     fn test_aadhaar_qr_verifier() {
-        let k = 5;
-        let circuit = AadhaarQRVerifierCircuit::<Fp> {
-            qr_data_padded: vec![Some(Fp::from(1)), Some(Fp::from(2)), Some(Fp::from(3))],
-            qr_data_padded_length: Some(Fp::from(3)),
-            delimiter_indices: vec![Some(Fp::from(1)), Some(Fp::from(2)), Some(Fp::from(3)), Some(Fp::from(4))],
-            signature: vec![Some(Fp::from(1)), Some(Fp::from(2))],
-            pub_key: vec![Some(Fp::from(1)), Some(Fp::from(2))],
-            reveal_age_above18: Some(Fp::from(1)),
-            reveal_gender: Some(Fp::from(1)),
-            reveal_pin_code: Some(Fp::from(1)),
-            reveal_state: Some(Fp::from(1)),
-            nullifier_seed: Some(Fp::from(1)),
-            signal_hash: Some(Fp::from(1)),
+        let (circuit, modulus) = test_circuit(TestQrFields::default());
+        let circuit = circuit.with_revealed(true, true, true, true);
+        let inputs = circuit.inputs().unwrap();
+        assert_eq!(inputs.nullifier_seed, Fr::from(12345678));
+        assert_eq!(inputs.signal_hash, Fr::from(42));
+        assert_eq!(inputs.pubkey_hash, TestCircuit::pubkey_hash(&modulus));
+        assert_eq!(
+            Some(inputs.nullifier),
+            compute_nullifier(12345678, &circuit.signed_data(), &QrFieldLayout::default())
+        );
+        assert_eq!(inputs.timestamp, Fr::from(SIGNED_AT));
+        assert_eq!(inputs.age_above18, Some(Fr::one()));
+        assert_eq!(inputs.gender, Some(Fr::from(b'M' as u64)));
+        assert_eq!(inputs.pin_code, Some(Fr::from(110051)));
+        // "Delhi" packed little-endian.
+        assert_eq!(inputs.state, Some(Fr::from(452723500356)));
+        assert_eq!(run(&circuit, inputs.instances()), Ok(()));
+
+        // A revealed attribute must be the one extracted from the signed data.
+        let forged = AadhaarInputs {
+            gender: Some(Fr::from(b'F' as u64)),
+            ..inputs.clone()
         };
+        assert!(run(&circuit, forged.instances()).is_err());
 
-        // Public inputs
-        let public_inputs = vec![
-            vec![
-                Fp::from(1), // nullifier_seed
-                Fp::from(1), // signal_hash
-            ]
-        ];
+        // A minor is not revealed as above 18.
+        let (circuit, _) = test_circuit(TestQrFields {
+            dob: "01-01-2010".to_string(),
+            ..TestQrFields::default()
+        });
+        let circuit = circuit.with_revealed(true, false, false, false);
+        let inputs = circuit.inputs().unwrap();
+        assert_eq!(inputs.age_above18, Some(Fr::zero()));
+        assert_eq!(run(&circuit, inputs.instances()), Ok(()));
+        let forged = AadhaarInputs {
+            age_above18: Some(Fr::one()),
+            ..inputs
+        };
+        assert!(run(&circuit, forged.instances()).is_err());
+    }
+
+    #[test]
+    fn test_tampered_qr_byte() {
+        let (circuit, _) = test_circuit(TestQrFields::default());
+        let instances = circuit.inputs().unwrap().instances();
+        assert_eq!(run(&circuit, instances.clone()), Ok(()));
 
-        let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
-        prover.assert_satisfied();
+        // A flipped byte of a field or of the photo hashed into the nullifier fails the signature, whatever the public inputs.
+        let photo_start = circuit.signed_data().len() - TestQrFields::default().photo.len();
+        for i in [3, photo_start + 1] {
+            let mut tampered = circuit.clone();
+            let byte = fe_to_byte(&tampered.qr_data_padded[i]);
+            tampered.qr_data_padded[i] = Some(Fr::from((byte ^ 1) as u64));
+            assert!(run(&tampered, instances.clone()).is_err());
+            let tampered_instances = tampered.inputs().unwrap().instances();
+            assert!(run(&tampered, tampered_instances).is_err());
+        }
+    }
+
+    #[test]
+    fn test_timestamp_within_window() {
+        let (circuit, _) = test_circuit(TestQrFields::default());
+
+        // The window is inclusive at both ends.
+        for (not_before, not_after) in [
+            (SIGNED_AT - 3600, SIGNED_AT + 3600),
+            (SIGNED_AT, SIGNED_AT),
+            (0, (1 << TIMESTAMP_NUM_BITS) - 1),
+        ] {
+            let circuit = circuit
+                .clone()
+                .with_validity_window(Fr::from(not_before), Fr::from(not_after));
+            let inputs = circuit.inputs().unwrap();
+            assert_eq!(
+                inputs.instances()[TestCircuit::VALIDITY_WINDOW_COLUMN],
+                vec![Fr::from(not_before), Fr::from(not_after)]
+            );
+            assert_eq!(run(&circuit, inputs.instances()), Ok(()));
+        }
+
+        // A timestamp before or after the window is rejected.
+        for (not_before, not_after) in [
+            (SIGNED_AT + 1, SIGNED_AT + 3600),
+            (SIGNED_AT - 3600, SIGNED_AT - 1),
+        ] {
+            let circuit = circuit
+                .clone()
+                .with_validity_window(Fr::from(not_before), Fr::from(not_after));
+            assert!(run(&circuit, circuit.inputs().unwrap().instances()).is_err());
+        }
+
+        // The public window must be the one checked in the circuit.
+        let inputs = AadhaarInputs {
+            validity_window: Some((Fr::from(SIGNED_AT + 1), Fr::from(SIGNED_AT + 3600))),
+            ..circuit.inputs().unwrap()
+        };
+        assert!(run(&circuit, inputs.instances()).is_err());
+    }
+
+    #[test]
+    fn test_possession_only() {
+        let (circuit, modulus) = test_circuit(TestQrFields::default());
+        assert!(circuit.is_possession_only());
+        assert_eq!(circuit.signature.len(), SIGNATURE_LEN);
+        assert_eq!(circuit.pub_key.len(), SIGNATURE_LEN);
+
+        let instances = circuit.inputs().unwrap().instances();
+        assert_eq!(instances[0], vec![Fr::from(12345678)]);
+        assert_eq!(instances[1], vec![Fr::from(42)]);
+        for column in TestCircuit::ATTRIBUTE_COLUMNS {
+            assert!(instances[column].is_empty());
+        }
+        assert_eq!(run(&circuit, instances), Ok(()));
+
+        // The signature is verified against the given key, so a proof does not hold for another one.
+        let other_modulus = &modulus + BigUint::from(2u64);
+        let mut qr = circuit.signed_data();
+        qr.extend(fes_to_bytes(&circuit.signature));
+        let circuit =
+            TestCircuit::possession_only(&qr, &other_modulus, Fr::from(12345678), Fr::from(42));
+        assert!(run(&circuit, circuit.inputs().unwrap().instances()).is_err());
+    }
+
+    #[test]
+    fn test_placeholder() {
+        let circuit = TestCircuit::placeholder();
+        assert!(circuit.is_possession_only());
+        let inputs = circuit.inputs().unwrap();
+        assert_eq!(inputs.timestamp, Fr::zero());
+
+        // The placeholder has the fields of a QR code, but not a valid signature.
+        assert!(run(&circuit, inputs.instances()).is_err());
+    }
+
+    #[test]
+    fn test_public_inputs_bound() {
+        let (circuit, _) = test_circuit(TestQrFields::default());
+        let inputs = circuit.inputs().unwrap();
+        assert_eq!(run(&circuit, inputs.instances()), Ok(()));
+
+        // Every public input other than the witnessed one is rejected.
+        for column in [
+            0,
+            1,
+            TestCircuit::PUBKEY_HASH_COLUMN,
+            TestCircuit::NULLIFIER_COLUMN,
+            TestCircuit::TIMESTAMP_COLUMN,
+        ] {
+            let mut forged = inputs.instances();
+            forged[column][0] += Fr::one();
+            assert!(run(&circuit, forged).is_err());
+        }
+        let mut forged = inputs.instances();
+        forged[TestCircuit::ATTRIBUTE_COLUMNS[1]] = vec![Fr::from(b'M' as u64)];
+        assert!(run(&circuit, forged).is_err());
+    }
+
+    #[test]
+    fn test_qr_data_padded_length() {
+        let (circuit, _) = test_circuit(TestQrFields::default());
+        let instances = circuit.inputs().unwrap().instances();
+        let length = circuit.signed_data().len();
+
+        // Zero padding after the length is not signed.
+        let mut padded = circuit.clone();
+        padded.qr_data_padded.resize(length + 5, Some(Fr::zero()));
+        assert_eq!(run(&padded, instances.clone()), Ok(()));
+
+        // A nonzero byte beyond the length is not padding.
+        padded.qr_data_padded[length + 1] = Some(Fr::one());
+        assert!(run(&padded, instances.clone()).is_err());
+
+        // A length other than the hashed one is rejected, even over the zero padding.
+        let mut longer = circuit.clone();
+        longer.qr_data_padded.resize(length + 5, Some(Fr::zero()));
+        longer.qr_data_padded_length = Some(Fr::from(length as u64 + 1));
+        assert!(run(&longer, instances.clone()).is_err());
+        let mut beyond = circuit;
+        beyond.qr_data_padded_length = Some(Fr::from(length as u64 + 1));
+        assert!(run(&beyond, instances).is_err());
+    }
+
+    fn check_max_data_len<const MAX_DATA_LEN: usize>() {
+        let (qr, _, public_key) = generate_test_qr(TestQrFields::default());
+        let qr = decompress_qr(&qr).unwrap();
+        let modulus = BigUint::from_bytes_be(&public_key.n().to_bytes_be());
+        let circuit = AadhaarQRVerifierCircuit::<Fr, MAX_DATA_LEN>::possession_only(
+            &qr,
+            &modulus,
+            Fr::from(12345678),
+            Fr::from(42),
+        );
+        let instances = circuit.inputs().unwrap().instances();
+        let prover = MockProver::run(K, &circuit, instances.clone()).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Data longer than `MAX_DATA_LEN` does not fit.
+        let mut circuit = circuit;
+        circuit
+            .qr_data_padded
+            .resize(MAX_DATA_LEN + 1, Some(Fr::zero()));
+        assert!(MockProver::run(K, &circuit, instances).is_err());
+    }
+
+    #[test]
+    fn test_max_data_len() {
+        check_max_data_len::<256>();
+        check_max_data_len::<1024>();
+    }
+
+    #[test]
+    fn test_aadhaar_inputs() {
+        let (circuit, _) = test_circuit(TestQrFields::default());
+        let circuit = circuit.with_revealed(false, true, false, true);
+        let inputs = circuit.inputs().unwrap();
+        let instances = inputs.instances();
+        assert_eq!(instances.len(), TestCircuit::NUM_INSTANCE_COLUMNS);
+        assert_eq!(
+            instances[TestCircuit::PUBKEY_HASH_COLUMN],
+            vec![inputs.pubkey_hash]
+        );
+        assert_eq!(
+            instances[TestCircuit::NULLIFIER_COLUMN],
+            vec![inputs.nullifier]
+        );
+        assert_eq!(
+            instances[TestCircuit::TIMESTAMP_COLUMN],
+            vec![Fr::from(SIGNED_AT)]
+        );
+        let [age_above18, gender, pin_code, state] = TestCircuit::ATTRIBUTE_COLUMNS;
+        assert!(instances[age_above18].is_empty());
+        assert_eq!(instances[gender], vec![Fr::from(b'M' as u64)]);
+        assert!(instances[pin_code].is_empty());
+        assert_eq!(instances[state], vec![Fr::from(452723500356)]);
+        // Without a window, the full range of timestamps is accepted.
+        assert_eq!(
+            instances[TestCircuit::VALIDITY_WINDOW_COLUMN],
+            vec![Fr::zero(), Fr::from((1 << TIMESTAMP_NUM_BITS) - 1)]
+        );
+        assert_eq!(run(&circuit, instances.clone()), Ok(()));
+
+        // A vector missing a column does not match the circuit.
+        let mut instances = instances;
+        instances.pop();
+        assert!(MockProver::run(K, &circuit, instances).is_err());
     }
-}*/
+
+    #[test]
+    fn test_zeroize() {
+        fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+        assert_zeroize_on_drop::<TestCircuit>();
+        assert_zeroize_on_drop::<AadhaarInputs<Fr>>();
+
+        let (circuit, _) = test_circuit(TestQrFields::default());
+        let mut circuit = circuit.with_revealed(true, true, true, true);
+        let mut inputs = circuit.inputs().unwrap();
+        let pub_key = circuit.pub_key.clone();
+        circuit.zeroize();
+        assert!(circuit.qr_data_padded.is_empty());
+        assert_eq!(circuit.qr_data_padded_length, None);
+        assert!(circuit.signature.is_empty());
+        // The key is public, so it is kept.
+        assert_eq!(circuit.pub_key, pub_key);
+
+        let pubkey_hash = inputs.pubkey_hash;
+        inputs.zeroize();
+        assert_eq!(inputs.nullifier, Fr::zero());
+        assert_eq!(inputs.timestamp, Fr::zero());
+        assert_eq!(inputs.age_above18, None);
+        assert_eq!(inputs.gender, None);
+        assert_eq!(inputs.pin_code, None);
+        assert_eq!(inputs.state, None);
+        assert_eq!(inputs.pubkey_hash, pubkey_hash);
+    }
+
+    #[test]
+    fn test_inputs_json() {
+        let (circuit, _) = test_circuit(TestQrFields::default());
+        let circuit = circuit
+            .with_revealed(false, true, false, false)
+            .with_validity_window(Fr::from(SIGNED_AT - 3600), Fr::from(SIGNED_AT + 3600));
+        let inputs = circuit.inputs().unwrap();
+
+        // The inputs read back serialize to the same JSON.
+        let json = circuit.to_json();
+        let reconstructed = TestCircuit::from_json(&json).unwrap();
+        assert_eq!(reconstructed.to_json(), json);
+        assert_eq!(reconstructed.pub_key, circuit.pub_key);
+        assert_eq!(reconstructed.inputs(), Some(inputs.clone()));
+        let inputs_json = inputs.to_json();
+        assert_eq!(
+            AadhaarInputs::<Fr>::from_json(&inputs_json).unwrap(),
+            inputs
+        );
+
+        // The reconstructed circuit has the same MockProver result, whether it is satisfied or not.
+        let reconstructed_inputs = AadhaarInputs::from_json(&inputs_json).unwrap();
+        assert_eq!(
+            run(&reconstructed, reconstructed_inputs.instances()),
+            Ok(())
+        );
+        let failing = AadhaarInputs {
+            signal_hash: Fr::from(43),
+            ..inputs.clone()
+        };
+        let reconstructed_failing = AadhaarInputs::from_json(&failing.to_json()).unwrap();
+        assert!(run(&reconstructed, reconstructed_failing.instances()).is_err());
+
+        // Malformed JSON and values out of the field are rejected.
+        assert!(TestCircuit::from_json("{}").is_err());
+        let out_of_field = inputs_json.replace(
+            &fe_to_decimal(&inputs.pubkey_hash),
+            &modulus::<Fr>().to_str_radix(10),
+        );
+        assert!(AadhaarInputs::<Fr>::from_json(&out_of_field).is_err());
+    }
+}
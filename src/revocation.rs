@@ -0,0 +1,286 @@
+//! Proving that the nullifier is not in a published revocation list without revealing it.
+use halo2_base::halo2_proofs::{circuit::Value, halo2curves::bn256::Fr};
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, GateInstructions},
+    utils::fe_to_biguint,
+    AssignedValue, Context, QuantumCell,
+};
+
+use crate::comparison::bits_less_than;
+use crate::pincode::{
+    assign_merkle_root, merkle_levels, merkle_path, MerklePath, MERKLE_RATE, MERKLE_T,
+};
+use crate::poseidon_chip::PoseidonChip;
+
+/// The bit length of the modulus of [`Fr`], which bounds the integers of the leaves and the nullifier.
+const FR_NUM_BITS: usize = 254;
+
+/// A Merkle tree of revoked nullifiers sorted in ascending order, whose root is published by the service.
+///
+/// The leaves are the revoked nullifiers between the sentinels zero and `-1`, i.e. the largest element of [`Fr`], padded with `-1` to a power of two.
+/// A nullifier is not revoked if and only if it lies strictly between two adjacent leaves, so neither sentinel can be proven not revoked.
+#[derive(Clone, Debug)]
+pub struct RevocationList {
+    /// The nodes of each level, from the leaves to the root.
+    levels: Vec<Vec<Fr>>,
+}
+
+/// The witness that a nullifier is not in a [`RevocationList`], i.e. the two adjacent leaves around it.
+#[derive(Clone, Debug)]
+pub struct NonMembershipWitness {
+    /// The largest leaf less than the nullifier.
+    pub low: Fr,
+    /// The smallest leaf greater than the nullifier, which follows `low` in the tree.
+    pub high: Fr,
+    /// The Merkle path of `low`.
+    pub low_path: MerklePath,
+    /// The siblings along the path of `high`, whose index is the next one of `low`.
+    pub high_siblings: Vec<Fr>,
+}
+
+impl RevocationList {
+    /// Creates new [`RevocationList`] from the revoked nullifiers, in any order.
+    pub fn new(revoked: &[Fr]) -> Self {
+        let mut leaves = [&[Fr::zero()][..], revoked, &[-Fr::one()]].concat();
+        leaves.sort_by_key(fe_to_biguint);
+        leaves.dedup();
+        leaves.resize(leaves.len().next_power_of_two(), -Fr::one());
+        Self {
+            levels: merkle_levels(leaves),
+        }
+    }
+
+    /// Returns the number of levels below the root.
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// Returns the root to publish.
+    pub fn root(&self) -> Fr {
+        self.levels[self.depth()][0]
+    }
+
+    /// Returns the witness that `nullifier` is not revoked, or `None` if it is a leaf, i.e. revoked or a sentinel.
+    pub fn non_membership_witness(&self, nullifier: Fr) -> Option<NonMembershipWitness> {
+        let leaves = &self.levels[0];
+        let key = fe_to_biguint(&nullifier);
+        let index = leaves.iter().position(|leaf| fe_to_biguint(leaf) > key)?;
+        if leaves[index - 1] == nullifier {
+            return None;
+        }
+        Some(NonMembershipWitness {
+            low: leaves[index - 1],
+            high: leaves[index],
+            low_path: merkle_path(&self.levels, index - 1),
+            high_siblings: merkle_path(&self.levels, index).siblings,
+        })
+    }
+}
+
+/// A chip constraining the nullifier not to be a leaf of a [`RevocationList`].
+///
+/// The witness is private, so the proof only reveals the root, i.e. the revocation list, and not the position of the nullifier in it.
+/// The nodes are hashed as in [`crate::pincode::PincodeAllowlistChip`].
+#[derive(Clone, Debug)]
+pub struct RevocationChip {
+    poseidon: PoseidonChip<MERKLE_T, MERKLE_RATE>,
+}
+
+impl RevocationChip {
+    /// Creates new [`RevocationChip`] from [`FlexGateConfig`].
+    pub fn construct(gate: FlexGateConfig<Fr>) -> Self {
+        Self {
+            poseidon: PoseidonChip::construct(gate),
+        }
+    }
+
+    /// Getter for [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<Fr> {
+        self.poseidon.gate()
+    }
+
+    /// Constrains the assigned nullifier to lie strictly between two adjacent leaves, and computes the root of the revocation tree from them.
+    ///
+    /// The index of the high leaf is constrained to be the next one of the low leaf, so no leaf can be skipped between them.
+    /// The leaves and the nullifier are compared as integers of their bits, which are constrained to be canonical.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * nullifier - the assigned nullifier, e.g. computed by [`PoseidonChip`] over the photo.
+    /// * witness - the adjacent leaves as computed by [`RevocationList::non_membership_witness`].
+    ///
+    /// # Return values
+    /// Returns the assigned root, which the caller must constrain equal to the published root, e.g. an instance, for the non-membership to hold.
+    pub fn assign_root<'v>(
+        &self,
+        ctx: &mut Context<'v, Fr>,
+        nullifier: &AssignedValue<'v, Fr>,
+        witness: &NonMembershipWitness,
+    ) -> AssignedValue<'v, Fr> {
+        assert_eq!(witness.low_path.siblings.len(), witness.high_siblings.len());
+        let gate = self.gate();
+        let low = gate.load_witness(ctx, Value::known(witness.low));
+        let high = gate.load_witness(ctx, Value::known(witness.high));
+        let low_index = gate.load_witness(ctx, Value::known(Fr::from(witness.low_path.index)));
+        let high_index = gate.add(
+            ctx,
+            QuantumCell::Existing(&low_index),
+            QuantumCell::Constant(Fr::one()),
+        );
+        let low_root = assign_merkle_root(
+            &self.poseidon,
+            ctx,
+            &low,
+            &low_index,
+            &witness.low_path.siblings,
+        );
+        let high_root = assign_merkle_root(
+            &self.poseidon,
+            ctx,
+            &high,
+            &high_index,
+            &witness.high_siblings,
+        );
+        gate.assert_equal(
+            ctx,
+            QuantumCell::Existing(&low_root),
+            QuantumCell::Existing(&high_root),
+        );
+
+        let low_bits = gate.num_to_bits(ctx, &low, FR_NUM_BITS);
+        let nullifier_bits = gate.num_to_bits(ctx, nullifier, FR_NUM_BITS);
+        let high_bits = gate.num_to_bits(ctx, &high, FR_NUM_BITS);
+        // The bits of a small element may also encode it plus the modulus, so the bits of `high` are bounded by those of `-1`.
+        // The bits of the others are then canonical too, as they are less than those of `high`.
+        let max = fe_to_biguint(&-Fr::one());
+        let max_bits = (0..FR_NUM_BITS)
+            .map(|i| gate.load_constant(ctx, Fr::from(max.bit(i as u64) as u64)))
+            .collect::<Vec<_>>();
+        let is_high_over = bits_less_than(gate, ctx, &max_bits, &high_bits);
+        gate.assert_is_const(ctx, &is_high_over, Fr::zero());
+        let is_above_low = bits_less_than(gate, ctx, &low_bits, &nullifier_bits);
+        gate.assert_is_const(ctx, &is_above_low, Fr::one());
+        let is_below_high = bits_less_than(gate, ctx, &nullifier_bits, &high_bits);
+        gate.assert_is_const(ctx, &is_below_high, Fr::one());
+        low_root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nullifier::{RATE, R_F, R_P, T};
+    use crate::photo::pack_photo;
+    use crate::poseidon_chip::test_utils::TestGateCircuit;
+    use crate::poseidon_chip::{to_circuit_fr, to_native_fr};
+    use halo2_base::halo2_proofs::dev::MockProver;
+    use halo2curves::bn256::Fr as FR;
+    use poseidon::Poseidon;
+    use std::rc::Rc;
+
+    const NULLIFIER_SEED: u64 = 12345678;
+
+    /// Returns the Poseidon hash of the seed followed by the packed photo, as [`PoseidonChip`] computes it.
+    fn nullifier(photo: &[u8]) -> Fr {
+        let mut inputs = vec![FR::from(NULLIFIER_SEED)];
+        inputs.extend(pack_photo::<Fr>(photo).unwrap().iter().map(to_native_fr));
+        let mut poseidon = Poseidon::<FR, T, RATE>::new(R_F, R_P);
+        poseidon.update(&inputs);
+        to_circuit_fr(&poseidon.squeeze())
+    }
+
+    fn non_membership_circuit(
+        photo: Vec<u8>,
+        witness: NonMembershipWitness,
+        root: Fr,
+    ) -> TestGateCircuit {
+        TestGateCircuit::new(Rc::new(
+            move |gate: &FlexGateConfig<Fr>, ctx: &mut Context<Fr>| {
+                let inputs = [Fr::from(NULLIFIER_SEED)]
+                    .into_iter()
+                    .chain(pack_photo::<Fr>(&photo).unwrap())
+                    .map(|input| gate.load_witness(ctx, Value::known(input)))
+                    .collect::<Vec<_>>();
+                let nullifier = PoseidonChip::<T, RATE>::construct(gate.clone()).hash(ctx, &inputs);
+                let chip = RevocationChip::construct(gate.clone());
+                let computed_root = chip.assign_root(ctx, &nullifier, &witness);
+                gate.assert_is_const(ctx, &computed_root, root);
+                Ok(())
+            },
+        ))
+    }
+
+    #[test]
+    fn test_revocation_list() {
+        let revoked = [Fr::from(7), -Fr::from(3), Fr::from(5), Fr::from(7)];
+        let list = RevocationList::new(&revoked);
+        // Zero, 5, 7, -3 and -1, padded to 8 leaves.
+        assert_eq!(list.depth(), 3);
+        for leaf in [
+            Fr::zero(),
+            Fr::from(5),
+            Fr::from(7),
+            -Fr::from(3),
+            -Fr::one(),
+        ] {
+            assert!(list.non_membership_witness(leaf).is_none());
+        }
+
+        let witness = list.non_membership_witness(Fr::from(6)).unwrap();
+        assert_eq!((witness.low, witness.high), (Fr::from(5), Fr::from(7)));
+        let witness = list.non_membership_witness(-Fr::from(4)).unwrap();
+        assert_eq!((witness.low, witness.high), (Fr::from(7), -Fr::from(3)));
+        assert_eq!(witness.low_path.index, 2);
+    }
+
+    #[test]
+    fn test_revocation_chip() {
+        let k = TestGateCircuit::K;
+        let photo = (0..100).map(|i| i as u8).collect::<Vec<u8>>();
+        let revoked = [Fr::from(110051), -Fr::from(560001), nullifier(b"revoked")];
+
+        // A nullifier out of the list is proven not revoked.
+        let list = RevocationList::new(&revoked);
+        let witness = list.non_membership_witness(nullifier(&photo)).unwrap();
+        let circuit = non_membership_circuit(photo.clone(), witness.clone(), list.root());
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The witness does not lead to the root of another list.
+        let other = RevocationList::new(&revoked[..2]);
+        let circuit = non_membership_circuit(photo.clone(), witness, other.root());
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Once revoked, the nullifier is a leaf, which has no witness.
+        let list = RevocationList::new(&[&revoked[..], &[nullifier(&photo)]].concat());
+        assert!(list.non_membership_witness(nullifier(&photo)).is_none());
+        let leaves = &list.levels[0];
+        let index = leaves
+            .iter()
+            .position(|leaf| *leaf == nullifier(&photo))
+            .unwrap();
+
+        // Neither the nullifier itself as the low leaf,
+        let witness = NonMembershipWitness {
+            low: leaves[index],
+            high: leaves[index + 1],
+            low_path: merkle_path(&list.levels, index),
+            high_siblings: merkle_path(&list.levels, index + 1).siblings,
+        };
+        let circuit = non_membership_circuit(photo.clone(), witness, list.root());
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // nor leaves skipping the nullifier are accepted.
+        let witness = NonMembershipWitness {
+            low: leaves[index - 1],
+            high: leaves[index + 1],
+            low_path: merkle_path(&list.levels, index - 1),
+            high_siblings: merkle_path(&list.levels, index + 1).siblings,
+        };
+        let circuit = non_membership_circuit(photo, witness, list.root());
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,296 @@
+//! A chip computing SHA-1 digests, only to verify legacy Aadhaar QR codes signed with RSA-SHA1.
+//!
+//! SHA-1 is broken against collisions, so this chip is insecure for new use: it is gated behind the `legacy-sha1` feature and exists only for archived documents.
+use halo2_base::halo2_proofs::circuit::Value;
+use halo2_base::{
+    gates::{flex_gate::FlexGateConfig, range::RangeConfig, GateInstructions, RangeInstructions},
+    utils::PrimeField,
+    AssignedValue, Context, QuantumCell,
+};
+
+/// The byte length of SHA-1 digests.
+pub const SHA1_HASH_LEN: usize = 20;
+
+/// The byte length of a SHA-1 block.
+const BLOCK_LEN: usize = 64;
+
+/// The initial hash value `H(0)`.
+const INIT_STATE: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+/// The constant `K` of each group of 20 rounds.
+const ROUND_CONSTANTS: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+/// A 32-bit word as its assigned bits, the least significant first.
+type Word<'v, F> = Vec<AssignedValue<'v, F>>;
+
+/// The assigned input and output of [`Sha1Config::digest`].
+#[derive(Clone, Debug)]
+pub struct AssignedSha1Result<'v, F: PrimeField> {
+    /// The assigned bytes of the message, without the padding.
+    pub input_bytes: Vec<AssignedValue<'v, F>>,
+    /// The assigned bytes of the digest in big-endian order.
+    pub output_bytes: Vec<AssignedValue<'v, F>>,
+}
+
+/// Configuration for the SHA-1 chip of [FIPS 180-4](https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.180-4.pdf).
+///
+/// The words are decomposed into bits, so the chip only needs the gates of [`RangeConfig`] and no lookup table of its own.
+/// The padding is fixed by the message length, so the number of constraints depends on it.
+#[derive(Clone, Debug)]
+pub struct Sha1Config<F: PrimeField> {
+    range: RangeConfig<F>,
+}
+
+impl<F: PrimeField> Sha1Config<F> {
+    /// Creates new [`Sha1Config`] from [`RangeConfig`].
+    ///
+    /// # Arguments
+    /// * range - a configuration for [`RangeConfig`], e.g. the one of [`crate::RSAConfig`].
+    ///
+    /// # Return values
+    /// Returns new [`Sha1Config`].
+    pub fn construct(range: RangeConfig<F>) -> Self {
+        Self { range }
+    }
+
+    /// Getter for [`RangeConfig`].
+    pub fn range(&self) -> &RangeConfig<F> {
+        &self.range
+    }
+
+    /// Getter for [`FlexGateConfig`].
+    pub fn gate(&self) -> &FlexGateConfig<F> {
+        self.range.gate()
+    }
+
+    /// Computes the SHA-1 digest of `msg`.
+    ///
+    /// # Arguments
+    /// * ctx - a region context.
+    /// * msg - the message bytes.
+    ///
+    /// # Return values
+    /// Returns the assigned bytes of `msg`, each range-checked to 8 bits, and of its digest.
+    pub fn digest<'v>(&self, ctx: &mut Context<'v, F>, msg: &[u8]) -> AssignedSha1Result<'v, F> {
+        let gate = self.gate();
+        let input_bytes = msg
+            .iter()
+            .map(|byte| gate.load_witness(ctx, Value::known(F::from(*byte as u64))))
+            .collect::<Vec<AssignedValue<F>>>();
+        let mut byte_bits = input_bytes
+            .iter()
+            .map(|byte| gate.num_to_bits(ctx, byte, 8))
+            .collect::<Vec<Vec<AssignedValue<F>>>>();
+
+        // The message is followed by 0x80, zeros and its bit length as a 64-bit big-endian integer.
+        let num_blocks = (msg.len() + 9 + BLOCK_LEN - 1) / BLOCK_LEN;
+        let mut padding = vec![0x80u8];
+        padding.resize(num_blocks * BLOCK_LEN - msg.len() - 8, 0);
+        padding.extend((msg.len() as u64 * 8).to_be_bytes());
+        for byte in padding {
+            let bits = (0..8)
+                .map(|j| gate.load_constant(ctx, F::from(((byte >> j) & 1) as u64)))
+                .collect();
+            byte_bits.push(bits);
+        }
+
+        let mut state = INIT_STATE
+            .iter()
+            .map(|value| self.constant_word(ctx, *value))
+            .collect::<Vec<Word<F>>>();
+        for block in byte_bits.chunks(BLOCK_LEN) {
+            state = self.compress(ctx, &state, block);
+        }
+
+        let output_bytes = state
+            .iter()
+            .flat_map(|word| word.chunks(8).rev())
+            .map(|bits| self.pack_bits(ctx, bits))
+            .collect();
+        AssignedSha1Result {
+            input_bytes,
+            output_bytes,
+        }
+    }
+
+    /// Applies the compression function to `state` with a block of 64 bytes, each given as its bits.
+    fn compress<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        state: &[Word<'v, F>],
+        block: &[Vec<AssignedValue<'v, F>>],
+    ) -> Vec<Word<'v, F>> {
+        // The message schedule, whose words are read in big-endian order.
+        let mut w = block
+            .chunks(4)
+            .map(|bytes| bytes.iter().rev().flatten().cloned().collect())
+            .collect::<Vec<Word<F>>>();
+        for t in 16..80 {
+            let x = self.xor_word(ctx, &w[t - 3], &w[t - 8]);
+            let x = self.xor_word(ctx, &x, &w[t - 14]);
+            let x = self.xor_word(ctx, &x, &w[t - 16]);
+            w.push(Self::rotate_left(&x, 1));
+        }
+
+        let mut a = state[0].clone();
+        let mut b = state[1].clone();
+        let mut c = state[2].clone();
+        let mut d = state[3].clone();
+        let mut e = state[4].clone();
+        for (t, w_t) in w.iter().enumerate() {
+            let f = match t / 20 {
+                0 => self.ch(ctx, &b, &c, &d),
+                2 => self.maj(ctx, &b, &c, &d),
+                _ => {
+                    let x = self.xor_word(ctx, &b, &c);
+                    self.xor_word(ctx, &x, &d)
+                }
+            };
+            let temp = self.add_words(
+                ctx,
+                &[&Self::rotate_left(&a, 5), &f, &e, w_t],
+                ROUND_CONSTANTS[t / 20],
+            );
+            e = d;
+            d = c;
+            c = Self::rotate_left(&b, 30);
+            b = a;
+            a = temp;
+        }
+
+        [a, b, c, d, e]
+            .iter()
+            .zip(state.iter())
+            .map(|(x, h)| self.add_words(ctx, &[h, x], 0))
+            .collect()
+    }
+
+    /// Returns `(b and c) xor (not b and d)`, i.e. `c` if `b` is set and `d` otherwise.
+    fn ch<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        b: &Word<'v, F>,
+        c: &Word<'v, F>,
+        d: &Word<'v, F>,
+    ) -> Word<'v, F> {
+        let gate = self.gate();
+        (0..32)
+            .map(|j| {
+                gate.select(
+                    ctx,
+                    QuantumCell::Existing(&c[j]),
+                    QuantumCell::Existing(&d[j]),
+                    QuantumCell::Existing(&b[j]),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the bitwise majority of `b`, `c` and `d`, i.e. `c` if `c` and `d` agree and `b` otherwise.
+    fn maj<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        b: &Word<'v, F>,
+        c: &Word<'v, F>,
+        d: &Word<'v, F>,
+    ) -> Word<'v, F> {
+        let gate = self.gate();
+        let c_xor_d = self.xor_word(ctx, c, d);
+        (0..32)
+            .map(|j| {
+                gate.select(
+                    ctx,
+                    QuantumCell::Existing(&b[j]),
+                    QuantumCell::Existing(&c[j]),
+                    QuantumCell::Existing(&c_xor_d[j]),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns `a xor b` bit by bit as `a + b - 2ab`.
+    fn xor_word<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        a: &Word<'v, F>,
+        b: &Word<'v, F>,
+    ) -> Word<'v, F> {
+        let gate = self.gate();
+        a.iter()
+            .zip(b.iter())
+            .map(|(a, b)| {
+                let coeff = gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(a),
+                    QuantumCell::Constant(-F::from(2)),
+                    QuantumCell::Constant(F::one()),
+                );
+                gate.mul_add(
+                    ctx,
+                    QuantumCell::Existing(&coeff),
+                    QuantumCell::Existing(b),
+                    QuantumCell::Existing(a),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the sum of `words` and `constant` modulo `2^32`.
+    ///
+    /// At most 7 words are added, so that the sum fits in 35 bits and its decomposition drops exactly the carry.
+    fn add_words<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        words: &[&Word<'v, F>],
+        constant: u32,
+    ) -> Word<'v, F> {
+        assert!(words.len() < 8);
+        let gate = self.gate();
+        let packed = words
+            .iter()
+            .map(|word| self.pack_bits(ctx, word))
+            .collect::<Vec<AssignedValue<F>>>();
+        let mut terms = packed
+            .iter()
+            .map(QuantumCell::Existing)
+            .collect::<Vec<QuantumCell<F>>>();
+        terms.push(QuantumCell::Constant(F::from(constant as u64)));
+        let sum = gate.sum(ctx, terms);
+        let mut bits = gate.num_to_bits(ctx, &sum, 35);
+        bits.truncate(32);
+        bits
+    }
+
+    /// Packs bits, the least significant first, into an assigned integer.
+    fn pack_bits<'v>(
+        &self,
+        ctx: &mut Context<'v, F>,
+        bits: &[AssignedValue<'v, F>],
+    ) -> AssignedValue<'v, F> {
+        let bases = (0..bits.len())
+            .map(|j| QuantumCell::Constant(F::from(1u64 << j)))
+            .collect::<Vec<QuantumCell<F>>>();
+        let bits = bits
+            .iter()
+            .map(QuantumCell::Existing)
+            .collect::<Vec<QuantumCell<F>>>();
+        self.gate().inner_product(ctx, bits, bases)
+    }
+
+    /// Assigns the bits of a constant word.
+    fn constant_word<'v>(&self, ctx: &mut Context<'v, F>, value: u32) -> Word<'v, F> {
+        (0..32)
+            .map(|j| {
+                self.gate()
+                    .load_constant(ctx, F::from(((value >> j) & 1) as u64))
+            })
+            .collect()
+    }
+
+    /// Rotates `word` left by `n` bits, which only permutes the assigned bits.
+    fn rotate_left<'v>(word: &Word<'v, F>, n: usize) -> Word<'v, F> {
+        let mut bits = word.clone();
+        bits.rotate_right(n);
+        bits
+    }
+}
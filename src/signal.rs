@@ -1,19 +1,28 @@
 use halo2_base::halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
     poly::Rotation,
 };
 use halo2_base::utils::PrimeField;
 
+use crate::utils::convert_field;
+
 #[derive(Clone, Debug)]
 pub struct SquareConfig {
     advice: [Column<Advice>; 2],
+    /// The square of the signal hash in the row 0, so that the signal cannot be changed without changing the public input.
+    instance: Column<Instance>,
     selector: Selector,
+    signal_hash_inv: Column<Advice>,
+    is_zero: Column<Advice>,
+    nonzero_selector: Selector,
 }
 
 #[derive(Default, Clone)]
 pub struct SquareCircuit<F: PrimeField> {
     signal_hash: Value<F>,
+    /// If true, a zero signal hash is rejected.
+    require_nonzero: bool,
 }
 
 impl<F: PrimeField> Circuit<F> for SquareCircuit<F> {
@@ -28,6 +37,9 @@ impl<F: PrimeField> Circuit<F> for SquareCircuit<F> {
         let advice = [cs.advice_column(), cs.advice_column()];
         let instance = cs.instance_column();
         let selector = cs.selector();
+        let signal_hash_inv = cs.advice_column();
+        let is_zero = cs.advice_column();
+        let nonzero_selector = cs.selector();
 
         cs.enable_equality(advice[0]);
         cs.enable_equality(advice[1]);
@@ -41,10 +53,35 @@ impl<F: PrimeField> Circuit<F> for SquareCircuit<F> {
             vec![s * (signal_hash_square - signal_hash.clone() * signal_hash)]
         });
 
+        // `is_zero` is one if the signal hash is zero, and zero otherwise.
+        cs.create_gate("signal hash is zero", |meta| {
+            let s = meta.query_selector(selector);
+            let signal_hash = meta.query_advice(advice[0], Rotation::cur());
+            let signal_hash_inv = meta.query_advice(signal_hash_inv, Rotation::cur());
+            let is_zero = meta.query_advice(is_zero, Rotation::cur());
+
+            vec![
+                s.clone()
+                    * (is_zero.clone()
+                        - (Expression::Constant(F::one()) - signal_hash.clone() * signal_hash_inv)),
+                s * signal_hash * is_zero,
+            ]
+        });
+
+        cs.create_gate("signal hash is nonzero", |meta| {
+            let s = meta.query_selector(nonzero_selector);
+            let is_zero = meta.query_advice(is_zero, Rotation::cur());
+
+            vec![s * is_zero]
+        });
+
         SquareConfig {
             advice,
-            //instance,
+            instance,
             selector,
+            signal_hash_inv,
+            is_zero,
+            nonzero_selector,
         }
     }
     fn synthesize(
@@ -53,26 +90,44 @@ impl<F: PrimeField> Circuit<F> for SquareCircuit<F> {
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
         let signal_hash = self.signal_hash.clone(); // Clone to avoid multiple borrows
-        layouter.assign_region(
+        let signal_hash_square_cell = layouter.assign_region(
             || "square region",
             |mut region| {
                 config.selector.enable(&mut region, 0)?;
+                if self.require_nonzero {
+                    config.nonzero_selector.enable(&mut region, 0)?;
+                }
 
                 let _signal_hash_cell =
                     region.assign_advice(|| "signal hash", config.advice[0], 0, || signal_hash)?;
 
-                let _signal_hash_square_cell = region.assign_advice(
+                let signal_hash_square_cell = region.assign_advice(
                     || "signal hash square",
                     config.advice[1],
                     0,
                     || signal_hash.map(|v| v * v),
                 )?;
 
-                // Correctly constrain the public input
-                //layouter.constrain_instance(signal_hash_square_cell.cell(), config.instance, 0)?;
-                Ok(())
+                region.assign_advice(
+                    || "signal hash inverse",
+                    config.signal_hash_inv,
+                    0,
+                    || signal_hash.map(|v| v.invert().unwrap_or(F::zero())),
+                )?;
+
+                region.assign_advice(
+                    || "signal hash is zero",
+                    config.is_zero,
+                    0,
+                    || signal_hash.map(|v| F::from(bool::from(v.is_zero()) as u64)),
+                )?;
+
+                Ok(signal_hash_square_cell)
             },
-        )
+        )?;
+
+        // Correctly constrain the public input
+        layouter.constrain_instance(signal_hash_square_cell.cell(), config.instance, 0)
     }
 }
 
@@ -80,14 +135,43 @@ impl<F: PrimeField> SquareCircuit<F> {
     pub fn new(signal_hash: F) -> Self {
         Self {
             signal_hash: Value::known(signal_hash),
+            require_nonzero: false,
         }
     }
+
+    /// Returns the public input of the circuit, i.e. the square of `signal_hash`.
+    pub fn instance(signal_hash: F) -> F {
+        signal_hash * signal_hash
+    }
+
+    /// Sets whether the circuit rejects a zero signal hash, which usually means the front-end forgot to set the signal.
+    pub fn require_nonzero(mut self, require_nonzero: bool) -> Self {
+        self.require_nonzero = require_nonzero;
+        self
+    }
+
+    /// Converts the signal hash to the field `G` by [`convert_field`], e.g. to prove the circuit over `bn256::Fr` with the other subcircuits for the aggregation.
+    ///
+    /// # Return values
+    /// Returns `None` if the signal hash does not fit in `G`.
+    pub fn convert_field<G: PrimeField>(&self) -> Option<SquareCircuit<G>> {
+        let mut fits = true;
+        let signal_hash = self.signal_hash.map(|signal_hash| {
+            let converted = convert_field(signal_hash);
+            fits = converted.is_some();
+            converted.unwrap_or(G::zero())
+        });
+        fits.then(|| SquareCircuit {
+            signal_hash,
+            require_nonzero: self.require_nonzero,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use halo2_base::halo2_proofs::dev::MockProver;
-    use halo2_base::halo2_proofs::halo2curves::pasta::Fp;
+    use halo2_base::halo2_proofs::halo2curves::{bn256::Fr, pasta::Fp};
     #[test]
     fn test_square_circuit() {
         use super::*;
@@ -96,11 +180,66 @@ mod tests {
 
         let circuit = SquareCircuit {
             signal_hash: Value::known(Fp::from(signal_hash)),
+            require_nonzero: false,
         };
 
         let public_inputs = vec![Fp::from(signal_hash * signal_hash)];
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
         prover.assert_satisfied();
+
+        // The same signal hash is proven over `bn256::Fr`, the field of the aggregated SNARK.
+        let circuit = circuit.require_nonzero(true).convert_field::<Fr>().unwrap();
+        let public_inputs = vec![SquareCircuit::instance(Fr::from(signal_hash))];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        prover.assert_satisfied();
+
+        // A signal hash out of `bn256::Fr` cannot be converted.
+        assert!(SquareCircuit::new(-Fp::one())
+            .convert_field::<Fr>()
+            .is_none());
+    }
+    #[test]
+    fn test_nonzero_signal() {
+        use super::*;
+        let k = 4;
+
+        let circuit = SquareCircuit::new(Fp::zero()).require_nonzero(true);
+        let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        let circuit = SquareCircuit::new(Fp::zero()).require_nonzero(false);
+        let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let circuit = SquareCircuit::new(Fp::from(5)).require_nonzero(true);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(25)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn test_signal_malleability() {
+        use super::*;
+        let k = 4;
+        let signal_hash = Fp::from(5);
+        let square = SquareCircuit::instance(signal_hash);
+        assert_eq!(square, Fp::from(25));
+
+        let circuit = SquareCircuit::new(signal_hash);
+        let prover = MockProver::run(k, &circuit, vec![vec![square]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Another signal with the old square is unsatisfiable, as is the old signal with another square.
+        let circuit = SquareCircuit::new(Fp::from(6));
+        let prover = MockProver::run(k, &circuit, vec![vec![square]]).unwrap();
+        assert!(prover.verify().is_err());
+        let circuit = SquareCircuit::new(signal_hash);
+        let prover = MockProver::run(k, &circuit, vec![vec![Fp::from(36)]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // Only the negated signal shares the square, as squaring cannot tell the two apart.
+        let circuit = SquareCircuit::new(-signal_hash);
+        let prover = MockProver::run(k, &circuit, vec![vec![square]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
     }
 }
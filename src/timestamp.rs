@@ -1,10 +1,205 @@
 use halo2_base::halo2_proofs::{
-    circuit::{Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{
+        Advice, Circuit, Column, ConstraintSystem, Error, Expression, Selector, TableColumn,
+        VirtualCells,
+    },
+    poly::Rotation,
 };
 
 use halo2_base::utils::PrimeField;
 
+use crate::utils::convert_field;
+
+/// The bit widths the inputs are range-checked to, in the order year, month, day, hour, minute and second.
+///
+/// The timestamp is computed from the lower 32 bits of each input, so without the range checks an input `x + 2^32` would alias `x`.
+pub const TIMESTAMP_BITS: [usize; 6] = [13, 4, 5, 5, 6, 6];
+
+/// The bit width of the timestamps up to the year 2106, e.g. to compare them by [`crate::comparison::ComparisonConfig::is_within_window`].
+pub const TIMESTAMP_NUM_BITS: usize = 32;
+
+/// The number of days in each month of a common year.
+const DAYS_IN_MONTH: [u64; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+/// The first year of the UNIX epoch, and the least year [`TimestampCircuit`] accepts.
+pub const MIN_YEAR: u64 = 1970;
+
+/// The greatest year [`TimestampCircuit`] accepts, i.e. the last one whose leap years are those divisible by 4.
+pub const MAX_YEAR: u64 = 2099;
+
+/// Returns the number of days from the UNIX epoch to January 1st of `year`, or `None` if `year` is not in `MIN_YEAR..=MAX_YEAR`.
+pub fn days_till_year(year: u64) -> Option<u64> {
+    if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+        return None;
+    }
+    // The years divisible by 4 from 1972 up to `year - 1`.
+    Some((year - MIN_YEAR) * 365 + (year - 1969) / 4)
+}
+
+/// Returns the number of days from January 1st to the first day of `month`, or `None` if `month` is not in `1..=12`.
+///
+/// The days of February are 29 if `is_leap` is true.
+pub fn days_till_month(month: u64, is_leap: bool) -> Option<u64> {
+    days_in_month(month, is_leap)?;
+    Some(
+        (1..month)
+            .map(|month| days_in_month(month, is_leap).unwrap())
+            .sum(),
+    )
+}
+
+/// Returns the UNIX timestamp of the date and time, or `None` if the year is not in `MIN_YEAR..=MAX_YEAR` or the month is not in `1..=12`.
+///
+/// This is the value [`TimestampCircuit::assign_timestamp`] constrains in the circuit.
+pub fn unix_timestamp(
+    year: u64,
+    month: u64,
+    day: u64,
+    hour: u64,
+    minute: u64,
+    second: u64,
+) -> Option<u64> {
+    let days_till_month = days_till_month(month, year % 4 == 0)?;
+    let days = days_till_year(year)? + days_till_month + day.checked_sub(1)?;
+    Some(days * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+/// Loads the rows `(year, days_till_year(year))` for the years in `MIN_YEAR..=MAX_YEAR` into `year_table` and `days_table`, after the row `(0, 0)` looked up where no year is checked.
+fn load_year_table<F: PrimeField>(
+    layouter: &mut impl Layouter<F>,
+    year_table: TableColumn,
+    days_table: TableColumn,
+) -> Result<(), Error> {
+    layouter.assign_table(
+        || "days till year",
+        |mut table_region| {
+            let rows = std::iter::once((0, 0))
+                .chain((MIN_YEAR..=MAX_YEAR).map(|year| (year, days_till_year(year).unwrap())));
+            for (offset, (year, days)) in rows.enumerate() {
+                table_region.assign_cell(
+                    || format!("year {}", year),
+                    year_table,
+                    offset,
+                    || Value::known(F::from(year)),
+                )?;
+                table_region.assign_cell(
+                    || format!("days till year {}", year),
+                    days_table,
+                    offset,
+                    || Value::known(F::from(days)),
+                )?;
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Loads the rows `(is_leap * 128 + month, days_till_month(month, is_leap))` into `month_table` and `days_table`, after the row `(0, 0)` looked up where no month is checked.
+fn load_month_table<F: PrimeField>(
+    layouter: &mut impl Layouter<F>,
+    month_table: TableColumn,
+    days_table: TableColumn,
+) -> Result<(), Error> {
+    layouter.assign_table(
+        || "days till month",
+        |mut table_region| {
+            let mut rows = vec![(0, 0)];
+            for is_leap in [false, true] {
+                for month in 1..=12 {
+                    let days = days_till_month(month, is_leap).unwrap();
+                    rows.push((is_leap as u64 * 128 + month, days));
+                }
+            }
+            for (offset, (key, days)) in rows.into_iter().enumerate() {
+                table_region.assign_cell(
+                    || format!("month {}", key),
+                    month_table,
+                    offset,
+                    || Value::known(F::from(key)),
+                )?;
+                table_region.assign_cell(
+                    || format!("days till month {}", key),
+                    days_table,
+                    offset,
+                    || Value::known(F::from(days)),
+                )?;
+            }
+            Ok(())
+        },
+    )
+}
+
+/// The key looked up in the calendar table at the rows where no date is checked, i.e. that of January 1st of a common year.
+const CALENDAR_DUMMY_KEY: u64 = calendar_key(false, 1, 1);
+
+/// Returns the number of days in `month`, or `None` if `month` is not in `1..=12`.
+///
+/// February has 29 days if `is_leap` is true.
+pub fn days_in_month(month: u64, is_leap: bool) -> Option<u64> {
+    let days = *DAYS_IN_MONTH.get((month as usize).checked_sub(1)?)?;
+    Some(days + (month == 2 && is_leap) as u64)
+}
+
+/// Returns the key of the date `(month, day)` in a leap year if `is_leap` is true, and in a common year otherwise.
+///
+/// The key is unique as long as both `month` and `day` are less than 128.
+pub const fn calendar_key(is_leap: bool, month: u64, day: u64) -> u64 {
+    (is_leap as u64 * 128 + month) * 128 + day
+}
+
+/// Loads the keys of all valid dates of common and leap years into `table`.
+///
+/// The table takes 731 rows, so a circuit looking dates up in it needs at least `2^10` rows.
+pub fn load_calendar_table<F: PrimeField>(
+    layouter: &mut impl Layouter<F>,
+    table: TableColumn,
+) -> Result<(), Error> {
+    layouter.assign_table(
+        || "calendar",
+        |mut table_region| {
+            let mut offset = 0;
+            for is_leap in [false, true] {
+                for month in 1..=12 {
+                    for day in 1..=days_in_month(month, is_leap).unwrap() {
+                        table_region.assign_cell(
+                            || format!("date {}-{}", month, day),
+                            table,
+                            offset,
+                            || Value::known(F::from(calendar_key(is_leap, month, day))),
+                        )?;
+                        offset += 1;
+                    }
+                }
+            }
+            Ok(())
+        },
+    )
+}
+
+/// Returns the expression to be looked up in the table of [`load_calendar_table`].
+///
+/// It is the key of the date if `q` is one, and that of a valid date otherwise, so the lookup only constrains the rows of `q`.
+pub(crate) fn calendar_lookup_input<F: PrimeField>(
+    q: Expression<F>,
+    is_leap: Expression<F>,
+    month: Expression<F>,
+    day: Expression<F>,
+) -> Expression<F> {
+    let base = Expression::Constant(F::from(128));
+    let key = (is_leap * base.clone() + month) * base + day;
+    q.clone() * key
+        + (Expression::Constant(F::one()) - q) * Expression::Constant(F::from(CALENDAR_DUMMY_KEY))
+}
+
+/// Returns one if the year decomposed into the `bit` column from the current row is divisible by 4, and zero otherwise.
+fn is_leap<F: PrimeField>(meta: &mut VirtualCells<'_, F>, bit: Column<Advice>) -> Expression<F> {
+    let bit0 = meta.query_advice(bit, Rotation::cur());
+    let bit1 = meta.query_advice(bit, Rotation::next());
+    let one = Expression::Constant(F::one());
+    (one.clone() - bit0) * (one - bit1)
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct TimestampCircuit<F: PrimeField> {
     year: Option<F>,
@@ -25,6 +220,39 @@ pub struct TimestampConfig {
     minute: Column<Advice>,
     second: Column<Advice>,
     timestamp: Column<Advice>,
+    /// Enabled at the rows of `bit` whose bit is not the most significant one of an input.
+    q_bit_step: Selector,
+    /// Enabled at the rows of `bit` whose bit is the most significant one of an input.
+    q_bit_last: Selector,
+    /// The bits of each input, the least significant first, in consecutive rows.
+    bit: Column<Advice>,
+    /// The integer of the bits of an input from the row up to the most significant one, which equals the input in the first row.
+    bit_acc: Column<Advice>,
+    /// Enabled at the row of the inputs, whose month and day are looked up in `calendar_table`.
+    q_date: Selector,
+    calendar_table: TableColumn,
+    /// The number of days from the UNIX epoch to the date of the inputs.
+    days: Column<Advice>,
+    /// The number of days from the UNIX epoch to January 1st of the year, looked up in `year_table` with the year.
+    days_till_year: Column<Advice>,
+    /// The number of days from January 1st to the first day of the month, looked up in `month_table` with the month.
+    days_till_month: Column<Advice>,
+    year_table: TableColumn,
+    year_days_table: TableColumn,
+    month_table: TableColumn,
+    month_days_table: TableColumn,
+}
+
+impl TimestampConfig {
+    /// Loads the table of [`load_calendar_table`], which the month and the day are looked up in, and the day-count tables of the years and the months.
+    pub fn load_calendar_table<F: PrimeField>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Error> {
+        load_calendar_table(layouter, self.calendar_table)?;
+        load_year_table(layouter, self.year_table, self.year_days_table)?;
+        load_month_table(layouter, self.month_table, self.month_days_table)
+    }
 }
 
 impl<F: PrimeField> TimestampCircuit<F> {
@@ -45,6 +273,22 @@ impl<F: PrimeField> TimestampCircuit<F> {
             second,
         }
     }
+
+    /// Converts the inputs to the field `G` by [`convert_field`], e.g. to prove the circuit over `bn256::Fr` with the other subcircuits for the aggregation.
+    ///
+    /// # Return values
+    /// Returns `None` if an input does not fit in `G`.
+    pub fn convert_field<G: PrimeField>(&self) -> Option<TimestampCircuit<G>> {
+        let convert = |input: Option<F>| input.map(convert_field).transpose();
+        Some(TimestampCircuit {
+            year: convert(self.year)?,
+            month: convert(self.month)?,
+            day: convert(self.day)?,
+            hour: convert(self.hour)?,
+            minute: convert(self.minute)?,
+            second: convert(self.second)?,
+        })
+    }
 }
 
 impl<F: PrimeField> Circuit<F> for TimestampCircuit<F> {
@@ -65,6 +309,100 @@ impl<F: PrimeField> Circuit<F> for TimestampCircuit<F> {
         let minute = meta.advice_column();
         let second = meta.advice_column();
         let timestamp = meta.advice_column();
+        meta.enable_equality(timestamp);
+        let q_bit_step = meta.selector();
+        let q_bit_last = meta.selector();
+        let bit = meta.advice_column();
+        let bit_acc = meta.advice_column();
+        for column in [year, month, day, hour, minute, second, bit_acc] {
+            meta.enable_equality(column);
+        }
+        let q_date = meta.complex_selector();
+        let calendar_table = meta.lookup_table_column();
+        let days = meta.advice_column();
+        let days_till_year = meta.advice_column();
+        let days_till_month = meta.advice_column();
+        let year_table = meta.lookup_table_column();
+        let year_days_table = meta.lookup_table_column();
+        let month_table = meta.lookup_table_column();
+        let month_days_table = meta.lookup_table_column();
+
+        // The year is decomposed first, so its two least significant bits are in the row of the inputs and the next one.
+        // A year is a leap one if it is divisible by 4, which agrees with the Gregorian calendar from 1901 to 2099.
+        meta.lookup("timestamp date", |meta| {
+            let q_date = meta.query_selector(q_date);
+            let is_leap = is_leap(meta, bit);
+            let month = meta.query_advice(month, Rotation::cur());
+            let day = meta.query_advice(day, Rotation::cur());
+            vec![(
+                calendar_lookup_input(q_date, is_leap, month, day),
+                calendar_table,
+            )]
+        });
+
+        // The year table only holds the years in `MIN_YEAR..=MAX_YEAR`, so it also range-checks the year.
+        meta.lookup("timestamp days till year", |meta| {
+            let q_date = meta.query_selector(q_date);
+            let year = meta.query_advice(year, Rotation::cur());
+            let days_till_year = meta.query_advice(days_till_year, Rotation::cur());
+            vec![
+                (q_date.clone() * year, year_table),
+                (q_date * days_till_year, year_days_table),
+            ]
+        });
+
+        meta.lookup("timestamp days till month", |meta| {
+            let q_date = meta.query_selector(q_date);
+            let is_leap = is_leap(meta, bit);
+            let month = meta.query_advice(month, Rotation::cur());
+            let days_till_month = meta.query_advice(days_till_month, Rotation::cur());
+            vec![
+                (
+                    q_date.clone() * (is_leap * Expression::Constant(F::from(128)) + month),
+                    month_table,
+                ),
+                (q_date * days_till_month, month_days_table),
+            ]
+        });
+
+        meta.create_gate("timestamp", |meta| {
+            let sel = meta.query_selector(sel);
+            let day = meta.query_advice(day, Rotation::cur());
+            let hour = meta.query_advice(hour, Rotation::cur());
+            let minute = meta.query_advice(minute, Rotation::cur());
+            let second = meta.query_advice(second, Rotation::cur());
+            let timestamp = meta.query_advice(timestamp, Rotation::cur());
+            let days = meta.query_advice(days, Rotation::cur());
+            let days_till_year = meta.query_advice(days_till_year, Rotation::cur());
+            let days_till_month = meta.query_advice(days_till_month, Rotation::cur());
+            let constant = |value: u64| Expression::Constant(F::from(value));
+            vec![
+                sel.clone() * (days.clone() - days_till_year - days_till_month - day + constant(1)),
+                sel * (timestamp
+                    - days * constant(86400)
+                    - hour * constant(3600)
+                    - minute * constant(60)
+                    - second),
+            ]
+        });
+
+        meta.create_gate("timestamp input bits", |meta| {
+            let q_bit_step = meta.query_selector(q_bit_step);
+            let q_bit_last = meta.query_selector(q_bit_last);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let bit_acc = meta.query_advice(bit_acc, Rotation::cur());
+            let bit_acc_next = meta.query_advice(bit_acc, Rotation::next());
+            vec![
+                (q_bit_step.clone() + q_bit_last.clone())
+                    * bit.clone()
+                    * (bit.clone() - Expression::Constant(F::one())),
+                q_bit_step
+                    * (bit_acc.clone()
+                        - bit.clone()
+                        - bit_acc_next * Expression::Constant(F::from(2))),
+                q_bit_last * (bit_acc - bit),
+            ]
+        });
 
         // Constraints to ensure the inputs are within valid ranges
         /*meta.create_gate("year range", |meta| {
@@ -134,6 +472,19 @@ impl<F: PrimeField> Circuit<F> for TimestampCircuit<F> {
             minute,
             second,
             timestamp,
+            q_bit_step,
+            q_bit_last,
+            bit,
+            bit_acc,
+            q_date,
+            calendar_table,
+            days,
+            days_till_year,
+            days_till_month,
+            year_table,
+            year_days_table,
+            month_table,
+            month_days_table,
         }
     }
 
@@ -142,110 +493,154 @@ impl<F: PrimeField> Circuit<F> for TimestampCircuit<F> {
         config: Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
+        config.load_calendar_table(&mut layouter)?;
+        self.assign_timestamp(&config, layouter)?;
+        Ok(())
+    }
+}
+
+impl<F: PrimeField> TimestampCircuit<F> {
+    /// Assigns the date inputs and the UNIX timestamp computed from them.
+    ///
+    /// Each input is decomposed into its [`TIMESTAMP_BITS`] bits, so an input out of range, e.g. one aliasing a valid input in its lower 32 bits, makes the circuit unsatisfied.
+    /// So does an invalid date, e.g. month 13 or February 30th, whose month and day are not found in the table of [`TimestampConfig::load_calendar_table`].
+    /// The timestamp is constrained to [`unix_timestamp`] of the inputs by a gate over the days looked up in the day-count tables, which reject a year out of `MIN_YEAR..=MAX_YEAR`.
+    ///
+    /// The table of [`TimestampConfig::load_calendar_table`] must be loaded once in the circuit.
+    ///
+    /// # Return values
+    /// Returns the assigned timestamp cell, so that a parent circuit can constrain it equal to cells in other regions.
+    pub fn assign_timestamp(
+        &self,
+        config: &TimestampConfig,
+        layouter: impl Layouter<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.assign_timestamp_with_inputs(config, layouter)
+            .map(|(_, timestamp)| timestamp)
+    }
+
+    /// Assigns the same cells as [`TimestampCircuit::assign_timestamp`].
+    ///
+    /// # Return values
+    /// Returns the assigned year, month, day, hour, minute and second, so that a parent circuit can constrain them equal to the date it parsed, e.g. out of the signed data, and the assigned timestamp cell.
+    #[allow(clippy::type_complexity)]
+    pub fn assign_timestamp_with_inputs(
+        &self,
+        config: &TimestampConfig,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<([AssignedCell<F, F>; 6], AssignedCell<F, F>), Error> {
         layouter.assign_region(
             || "timestamp calculation",
             |mut region| {
                 config.sel.enable(&mut region, 0)?;
+                config.q_date.enable(&mut region, 0)?;
 
-                region.assign_advice(
+                let year = region.assign_advice(
                     || "year",
                     config.year,
                     0,
                     || Value::known(self.year.ok_or(Error::Synthesis).unwrap()),
                 )?;
-                region.assign_advice(
+                let month = region.assign_advice(
                     || "month",
                     config.month,
                     0,
                     || Value::known(self.month.ok_or(Error::Synthesis).unwrap()),
                 )?;
-                region.assign_advice(
+                let day = region.assign_advice(
                     || "day",
                     config.day,
                     0,
                     || Value::known(self.day.ok_or(Error::Synthesis).unwrap()),
                 )?;
-                region.assign_advice(
+                let hour = region.assign_advice(
                     || "hour",
                     config.hour,
                     0,
                     || Value::known(self.hour.ok_or(Error::Synthesis).unwrap()),
                 )?;
-                region.assign_advice(
+                let minute = region.assign_advice(
                     || "minute",
                     config.minute,
                     0,
                     || Value::known(self.minute.ok_or(Error::Synthesis).unwrap()),
                 )?;
-                region.assign_advice(
+                let second = region.assign_advice(
                     || "second",
                     config.second,
                     0,
                     || Value::known(self.second.ok_or(Error::Synthesis).unwrap()),
                 )?;
 
-                // Days in each month
-                let days_till_previous_month: [F; 12] = [
-                    F::from(0u64),
-                    F::from(31u64),
-                    F::from(59u64),
-                    F::from(90u64),
-                    F::from(120u64),
-                    F::from(151u64),
-                    F::from(181u64),
-                    F::from(212u64),
-                    F::from(243u64),
-                    F::from(273u64),
-                    F::from(304u64),
-                    F::from(334u64),
-                ];
-
-                // Calculate leap years
-                let leap_years_before = |year: u64| -> u64 {
-                    (year - 1969) / 4 - (year - 1901) / 100 + (year - 1601) / 400
-                };
-
-                let year_val = self
-                    .year
-                    .map(|year| year.get_lower_32() as u64)
-                    .unwrap_or(0);
-                let month_val = self
-                    .month
-                    .map(|month| month.get_lower_32() as u64)
-                    .unwrap_or(0);
-                let day_val = self.day.map(|day| day.get_lower_32() as u64).unwrap_or(0);
-                let hour_val = self
-                    .hour
-                    .map(|hour| hour.get_lower_32() as u64)
-                    .unwrap_or(0);
-                let minute_val = self
-                    .minute
-                    .map(|minute| minute.get_lower_32() as u64)
-                    .unwrap_or(0);
-                let second_val = self
-                    .second
-                    .map(|second| second.get_lower_32() as u64)
-                    .unwrap_or(0);
-
-                let days_passed = Value::known(F::from(
-                    (year_val - 1970) * 365 + leap_years_before(year_val),
-                ))
-                .and_then(|days| {
-                    Value::known(days + F::from(days_till_previous_month[(month_val - 1) as usize]))
-                })
-                .and_then(|days| Value::known(days + F::from(day_val - 1)));
+                let inputs = [&year, &month, &day, &hour, &minute, &second];
+                let mut offset = 0;
+                for (input, num_bits) in inputs.iter().zip(TIMESTAMP_BITS) {
+                    let bits = input.value().map(|value| {
+                        let repr = value.to_repr();
+                        (0..num_bits)
+                            .map(|i| F::from(((repr.as_ref()[i / 8] >> (i % 8)) & 1) as u64))
+                            .collect::<Vec<F>>()
+                    });
+                    for i in 0..num_bits {
+                        if i + 1 < num_bits {
+                            config.q_bit_step.enable(&mut region, offset + i)?;
+                        } else {
+                            config.q_bit_last.enable(&mut region, offset + i)?;
+                        }
+                        let bit = bits.as_ref().map(|bits| bits[i]);
+                        region.assign_advice(|| "bit", config.bit, offset + i, || bit)?;
+                        // The bits above the i-th one form an integer whose double is added to the i-th bit.
+                        let bit_acc = bits.as_ref().map(|bits| {
+                            bits[i..]
+                                .iter()
+                                .rev()
+                                .fold(F::zero(), |acc, bit| acc + acc + bit)
+                        });
+                        let bit_acc = region.assign_advice(
+                            || "bit_acc",
+                            config.bit_acc,
+                            offset + i,
+                            || bit_acc,
+                        )?;
+                        if i == 0 {
+                            region.constrain_equal(input.cell(), bit_acc.cell())?;
+                        }
+                    }
+                    offset += num_bits;
+                }
+
+                let value = |input: Option<F>| input.map(|input| input.get_lower_32() as u64);
+                let year_val = value(self.year).unwrap_or(0);
+                let month_val = value(self.month).unwrap_or(0);
+                let day_val = value(self.day).unwrap_or(0);
+                // A year or a month out of the day-count tables fails their lookups, so zero is assigned for its days.
+                let days_till_year = days_till_year(year_val).unwrap_or(0);
+                let days_till_month = days_till_month(month_val, year_val % 4 == 0).unwrap_or(0);
+                let days = F::from(days_till_year + days_till_month) + F::from(day_val) - F::one();
+                region.assign_advice(
+                    || "days_till_year",
+                    config.days_till_year,
+                    0,
+                    || Value::known(F::from(days_till_year)),
+                )?;
+                region.assign_advice(
+                    || "days_till_month",
+                    config.days_till_month,
+                    0,
+                    || Value::known(F::from(days_till_month)),
+                )?;
+                region.assign_advice(|| "days", config.days, 0, || Value::known(days))?;
 
                 // Convert days to seconds and add hours, minutes, and seconds
-                let total_seconds = days_passed
-                    .map(|d| d * F::from(86400u64))
-                    .and_then(|t| Value::known(t) + Value::known(F::from(hour_val * 3600)))
-                    .and_then(|t| Value::known(t) + Value::known(F::from(minute_val * 60)))
-                    .and_then(|t| Value::known(t) + Value::known(F::from(second_val)));
+                let total_seconds = hour.value().copied() * Value::known(F::from(3600u64))
+                    + minute.value().copied() * Value::known(F::from(60u64))
+                    + second.value().copied()
+                    + Value::known(days * F::from(86400u64));
 
                 // Expose the total seconds as a public output
-                region.assign_advice(|| "timestamp", config.timestamp, 0, || total_seconds)?;
-
-                Ok(())
+                let timestamp =
+                    region.assign_advice(|| "timestamp", config.timestamp, 0, || total_seconds)?;
+                Ok(([year, month, day, hour, minute, second], timestamp))
             },
         )
     }
@@ -254,11 +649,89 @@ impl<F: PrimeField> Circuit<F> for TimestampCircuit<F> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use halo2_base::halo2_proofs::{dev::MockProver, halo2curves::pasta::Fp};
+    use halo2_base::halo2_proofs::{
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::Fp},
+        plonk::Instance,
+        poly::Rotation,
+    };
+
+    #[derive(Debug, Clone)]
+    struct TimestampWiringConfig {
+        timestamp: TimestampConfig,
+        doubled: [Column<Advice>; 2],
+        sel: Selector,
+        instance: Column<Instance>,
+    }
+
+    /// Feeds the timestamp output into a separate doubling gate through the returned cell.
+    #[derive(Debug, Clone, Default)]
+    struct TimestampWiringCircuit<F: PrimeField> {
+        timestamp: TimestampCircuit<F>,
+    }
+
+    impl<F: PrimeField> Circuit<F> for TimestampWiringCircuit<F> {
+        type Config = TimestampWiringConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let timestamp = TimestampCircuit::<F>::configure(meta);
+            let doubled = [meta.advice_column(), meta.advice_column()];
+            let sel = meta.selector();
+            let instance = meta.instance_column();
+            meta.enable_equality(doubled[0]);
+            meta.enable_equality(doubled[1]);
+            meta.enable_equality(instance);
+
+            meta.create_gate("double timestamp", |meta| {
+                let sel = meta.query_selector(sel);
+                let timestamp = meta.query_advice(doubled[0], Rotation::cur());
+                let out = meta.query_advice(doubled[1], Rotation::cur());
+                vec![sel * (out - timestamp.clone() - timestamp)]
+            });
+
+            TimestampWiringConfig {
+                timestamp,
+                doubled,
+                sel,
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.timestamp.load_calendar_table(&mut layouter)?;
+            let timestamp = self
+                .timestamp
+                .assign_timestamp(&config.timestamp, layouter.namespace(|| "timestamp"))?;
+            let doubled = layouter.assign_region(
+                || "double timestamp",
+                |mut region| {
+                    config.sel.enable(&mut region, 0)?;
+                    let timestamp =
+                        timestamp.copy_advice(|| "timestamp", &mut region, config.doubled[0], 0)?;
+                    region.assign_advice(
+                        || "doubled timestamp",
+                        config.doubled[1],
+                        0,
+                        || timestamp.value().map(|v| *v + *v),
+                    )
+                },
+            )?;
+            layouter.constrain_instance(doubled.cell(), config.instance, 0)
+        }
+    }
 
     #[test]
     fn test_timestamp_circuit() {
-        let k = 6;
+        let k = 10;
         let circuit = TimestampCircuit {
             year: Some(Fp::from(2023u64)),
             month: Some(Fp::from(7u64)),
@@ -272,5 +745,163 @@ mod tests {
 
         let prover = MockProver::run(k, &circuit, public_inputs).unwrap();
         assert_eq!(prover.verify(), Ok(()));
+
+        // The same inputs are proven over `bn256::Fr`, the field of the aggregated SNARK.
+        let circuit = circuit.convert_field::<Fr>().unwrap();
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // An input out of `bn256::Fr` cannot be converted.
+        let circuit = TimestampCircuit {
+            year: Some(-Fp::one()),
+            ..TimestampCircuit::default()
+        };
+        assert!(circuit.convert_field::<Fr>().is_none());
+    }
+
+    #[test]
+    fn test_timestamp_cell_wiring() {
+        let k = 10;
+        let circuit = TimestampWiringCircuit {
+            timestamp: TimestampCircuit::new(
+                Some(Fp::from(2023u64)),
+                Some(Fp::from(7u64)),
+                Some(Fp::from(8u64)),
+                Some(Fp::from(12u64)),
+                Some(Fp::from(34u64)),
+                Some(Fp::from(56u64)),
+            ),
+        };
+
+        // 2023-07-08T12:34:56Z
+        let timestamp = Fp::from(1688819696u64);
+        let prover = MockProver::run(k, &circuit, vec![vec![timestamp + timestamp]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(k, &circuit, vec![vec![timestamp]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The day of February 29th counts after February in a leap year: 2024-03-01T00:00:00Z.
+        let circuit = TimestampWiringCircuit {
+            timestamp: TimestampCircuit::new(
+                Some(Fp::from(2024u64)),
+                Some(Fp::from(3u64)),
+                Some(Fp::from(1u64)),
+                Some(Fp::zero()),
+                Some(Fp::zero()),
+                Some(Fp::zero()),
+            ),
+        };
+        let timestamp = Fp::from(1709251200u64);
+        let prover = MockProver::run(k, &circuit, vec![vec![timestamp + timestamp]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+        let day_before = timestamp - Fp::from(86400u64);
+        let prover = MockProver::run(k, &circuit, vec![vec![day_before + day_before]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_unix_timestamp() {
+        assert_eq!(unix_timestamp(1970, 1, 1, 0, 0, 0), Some(0));
+        assert_eq!(unix_timestamp(2023, 7, 8, 12, 34, 56), Some(1688819696));
+        assert_eq!(unix_timestamp(2024, 3, 1, 0, 0, 0), Some(1709251200));
+        assert_eq!(unix_timestamp(2099, 12, 31, 23, 59, 59), Some(4102444799));
+        // The years before the epoch have no timestamp rather than underflowing.
+        assert_eq!(unix_timestamp(1969, 12, 31, 23, 59, 59), None);
+        assert_eq!(unix_timestamp(2100, 1, 1, 0, 0, 0), None);
+        assert_eq!(unix_timestamp(2023, 13, 1, 0, 0, 0), None);
+        assert_eq!(unix_timestamp(2023, 1, 0, 0, 0, 0), None);
+        assert_eq!(days_till_month(3, true), Some(60));
+        assert_eq!(days_till_month(3, false), Some(59));
+        assert_eq!(days_till_year(2000), Some(10957));
+    }
+
+    #[test]
+    fn test_timestamp_range_check() {
+        let k = 10;
+        let circuit = |year: Fp, hour: Fp| {
+            TimestampCircuit::new(
+                Some(year),
+                Some(Fp::from(7u64)),
+                Some(Fp::from(8u64)),
+                Some(hour),
+                Some(Fp::from(34u64)),
+                Some(Fp::from(56u64)),
+            )
+        };
+        let overflow = Fp::from(1u64 << 32);
+
+        // The year aliases 2023 under the truncation to 32 bits, and so would the timestamp.
+        let prover =
+            MockProver::run(k, &circuit(Fp::from(2023u64) + overflow, Fp::from(12u64)), vec![])
+                .unwrap();
+        assert!(prover.verify().is_err());
+        let prover =
+            MockProver::run(k, &circuit(Fp::from(2023u64), Fp::from(12u64) + overflow), vec![])
+                .unwrap();
+        assert!(prover.verify().is_err());
+
+        // A year of 14 bits is rejected even if it is not truncated.
+        let prover =
+            MockProver::run(k, &circuit(Fp::from(1u64 << 13), Fp::from(12u64)), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // A year of 13 bits out of `MIN_YEAR..=MAX_YEAR` is rejected by the day-count table.
+        for year in [8191, MIN_YEAR - 1, MAX_YEAR + 1] {
+            let prover =
+                MockProver::run(k, &circuit(Fp::from(year), Fp::from(23u64)), vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+        for year in [MIN_YEAR, MAX_YEAR] {
+            let prover =
+                MockProver::run(k, &circuit(Fp::from(year), Fp::from(23u64)), vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_timestamp_calendar() {
+        let k = 10;
+        let circuit = |year: u64, month: u64, day: u64| {
+            TimestampCircuit::new(
+                Some(Fp::from(year)),
+                Some(Fp::from(month)),
+                Some(Fp::from(day)),
+                Some(Fp::from(12u64)),
+                Some(Fp::from(34u64)),
+                Some(Fp::from(56u64)),
+            )
+        };
+        let is_satisfied = |year: u64, month: u64, day: u64| {
+            let prover = MockProver::run(k, &circuit(year, month, day), vec![]).unwrap();
+            prover.verify().is_ok()
+        };
+
+        assert!(is_satisfied(2023, 12, 31));
+        assert!(is_satisfied(2023, 1, 1));
+        // Month 13 fits in the 4 bits of the month but is not in the calendar.
+        assert!(!is_satisfied(2023, 13, 1));
+        assert!(!is_satisfied(2023, 0, 1));
+        assert!(!is_satisfied(2023, 7, 0));
+        assert!(!is_satisfied(2023, 4, 31));
+
+        // February has 29 days only in leap years.
+        assert!(!is_satisfied(2024, 2, 30));
+        assert!(is_satisfied(2024, 2, 29));
+        assert!(!is_satisfied(2023, 2, 29));
+        assert!(is_satisfied(2023, 2, 28));
+    }
+
+    #[test]
+    fn test_days_in_month() {
+        assert_eq!(days_in_month(1, false), Some(31));
+        assert_eq!(days_in_month(2, false), Some(28));
+        assert_eq!(days_in_month(2, true), Some(29));
+        assert_eq!(days_in_month(4, true), Some(30));
+        assert_eq!(days_in_month(0, false), None);
+        assert_eq!(days_in_month(13, false), None);
+        // The keys of distinct dates are distinct.
+        assert_ne!(calendar_key(true, 2, 28), calendar_key(false, 2, 28));
+        assert_ne!(calendar_key(false, 1, 31), calendar_key(false, 2, 3));
     }
 }
@@ -0,0 +1,479 @@
+use halo2_base::halo2_proofs::{
+    circuit::{AssignedCell, Region, Value},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Fixed, FloorPlanner, Instance, Selector,
+    },
+};
+use halo2_base::utils::{biguint_to_fe, fe_to_biguint, modulus, PrimeField};
+use num_bigint::BigUint;
+use std::collections::{HashMap, HashSet};
+use std::ptr;
+use std::sync::atomic::{compiler_fence, Ordering};
+
+/// Assigns `bytes` to consecutive rows of `column`, starting from the first row of `region`.
+///
+/// Unknown witnesses are assigned as zero, the padding byte of the QR data.
+///
+/// # Return values
+/// Returns the assigned cells in the order of `bytes`.
+pub fn assign_byte_array<F: PrimeField>(
+    region: &mut Region<'_, F>,
+    column: Column<Advice>,
+    bytes: &[Option<F>],
+) -> Result<Vec<AssignedCell<F, F>>, Error> {
+    bytes
+        .iter()
+        .enumerate()
+        .map(|(i, byte)| {
+            region.assign_advice(
+                || format!("byte {}", i),
+                column,
+                i,
+                || Value::known(byte.unwrap_or(F::zero())),
+            )
+        })
+        .collect()
+}
+
+/// Maps a hash to a field element, e.g. a SHA-256 digest that does not fit in the BN254 scalar field.
+///
+/// `bytes` are read as a big-endian integer and reduced modulo the order of `F`, not truncated, which matches `uint256(hash) % SNARK_SCALAR_FIELD` in the verifier contract.
+/// The nullifier is mapped with this function.
+pub fn hash_to_field<F: PrimeField>(bytes: &[u8]) -> F {
+    biguint_to_fe(&(BigUint::from_bytes_be(bytes) % modulus::<F>()))
+}
+
+/// Converts `x` to the field `G` by its integer, e.g. a witness of a subcircuit tested over `pasta::Fp` to `bn256::Fr`, the field of the aggregated SNARK.
+///
+/// # Return values
+/// Returns `None` if the integer of `x` is not less than the order of `G`, as it would be reduced to another value.
+pub fn convert_field<F: PrimeField, G: PrimeField>(x: F) -> Option<G> {
+    let x = fe_to_biguint(&x);
+    (x < modulus::<G>()).then(|| biguint_to_fe(&x))
+}
+
+/// Overwrites `value` with `zero` by a volatile write, which the compiler does not elide even if `value` is not read afterwards.
+///
+/// Field elements do not implement [`zeroize::Zeroize`], so the witnesses holding them are zeroized with this function instead.
+pub fn zeroize_with<T: Copy>(value: &mut T, zero: T) {
+    // SAFETY: `value` is a valid and aligned mutable reference, and `T` is `Copy`, so no destructor is skipped by the write.
+    unsafe { ptr::write_volatile(value, zero) };
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Overwrites the elements of `values` with zero by [`zeroize_with`] and empties it, e.g. the QR bytes of a witness.
+pub fn zeroize_fes<F: PrimeField>(values: &mut Vec<Option<F>>) {
+    for value in values.iter_mut() {
+        zeroize_with(value, Some(F::zero()));
+        zeroize_with(value, None);
+    }
+    values.clear();
+}
+
+/// The shape of the [`ConstraintSystem`] of a circuit, which a refactoring must keep for the verifying key to stay the same.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitShape {
+    pub num_advice_columns: usize,
+    pub num_fixed_columns: usize,
+    pub num_instance_columns: usize,
+    pub num_selectors: usize,
+    pub num_gates: usize,
+    pub num_lookups: usize,
+    pub degree: usize,
+}
+
+impl CircuitShape {
+    /// Configures the circuit `C` in a new [`ConstraintSystem`] and reads its shape.
+    pub fn of<F: PrimeField, C: Circuit<F>>() -> Self {
+        let mut meta = ConstraintSystem::<F>::default();
+        C::configure(&mut meta);
+        Self::from(&meta)
+    }
+
+    /// Returns one `name: expected -> actual` line per count differing from `expected`, so that no line means the same shape.
+    pub fn diff(&self, expected: &Self) -> Vec<String> {
+        [
+            (
+                "advice columns",
+                expected.num_advice_columns,
+                self.num_advice_columns,
+            ),
+            (
+                "fixed columns",
+                expected.num_fixed_columns,
+                self.num_fixed_columns,
+            ),
+            (
+                "instance columns",
+                expected.num_instance_columns,
+                self.num_instance_columns,
+            ),
+            ("selectors", expected.num_selectors, self.num_selectors),
+            ("gates", expected.num_gates, self.num_gates),
+            ("lookup arguments", expected.num_lookups, self.num_lookups),
+            ("degree", expected.degree, self.degree),
+        ]
+        .into_iter()
+        .filter(|(_, expected, actual)| expected != actual)
+        .map(|(name, expected, actual)| format!("{}: {} -> {}", name, expected, actual))
+        .collect()
+    }
+}
+
+impl<F: PrimeField> From<&ConstraintSystem<F>> for CircuitShape {
+    fn from(meta: &ConstraintSystem<F>) -> Self {
+        Self {
+            num_advice_columns: meta.num_advice_columns(),
+            num_fixed_columns: meta.num_fixed_columns(),
+            num_instance_columns: meta.num_instance_columns(),
+            num_selectors: meta.num_selectors(),
+            num_gates: meta.gates().len(),
+            num_lookups: meta.lookups().len(),
+            degree: meta.degree(),
+        }
+    }
+}
+
+/// Panics with the differing counts of [`CircuitShape::diff`] if the circuit `C` does not have the `expected` shape.
+///
+/// Tests call it with a stored shape, so that a change of the column layout is caught before it breaks the verifying keys in use.
+#[track_caller]
+pub fn assert_circuit_shape<F: PrimeField, C: Circuit<F>>(expected: &CircuitShape) {
+    let diff = CircuitShape::of::<F, C>().diff(expected);
+    assert!(
+        diff.is_empty(),
+        "the circuit shape changed:\n{}",
+        diff.join("\n")
+    );
+}
+
+/// An [`Assignment`] recording the cells assigned by a circuit without checking its constraints, as the key generation does, and without bounding its rows.
+///
+/// The witnesses are computed as in [`halo2_base::halo2_proofs::dev::MockProver`], so the chips lay out the same cells, but a circuit too large for a degree is still measured.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DryRun {
+    /// The assigned advice cells, as pairs of column index and row.
+    pub advice_cells: HashSet<(usize, usize)>,
+    /// The number of rows used by each fixed column, by column index.
+    pub fixed_rows: HashMap<usize, usize>,
+    /// The number of rows used by any column, selector or copy.
+    pub rows: usize,
+}
+
+impl DryRun {
+    /// Configures the circuit `C` and assigns `circuit` by its floor planner.
+    ///
+    /// # Return values
+    /// Returns the recorded cells and the [`ConstraintSystem`] of `C`, or the error of the synthesis.
+    pub fn of<F: PrimeField, C: Circuit<F>>(
+        circuit: &C,
+    ) -> Result<(Self, ConstraintSystem<F>), Error> {
+        let mut meta = ConstraintSystem::<F>::default();
+        let config = C::configure(&mut meta);
+        let mut run = Self::default();
+        C::FloorPlanner::synthesize(&mut run, circuit, config, meta.constants().clone())?;
+        Ok((run, meta))
+    }
+
+    /// Returns the number of assigned cells in the advice columns queried by the lookup arguments of `meta`.
+    pub fn lookup_cells<F: PrimeField>(&self, meta: &ConstraintSystem<F>) -> usize {
+        let columns = meta
+            .lookups()
+            .iter()
+            .flat_map(|lookup| lookup.input_expressions())
+            .flat_map(|expression| {
+                expression.evaluate(
+                    &|_| vec![],
+                    &|_| vec![],
+                    &|_| vec![],
+                    &|query| vec![query.column_index()],
+                    &|_| vec![],
+                    &|_| vec![],
+                    &|a| a,
+                    &|mut a, mut b| {
+                        a.append(&mut b);
+                        a
+                    },
+                    &|mut a, mut b| {
+                        a.append(&mut b);
+                        a
+                    },
+                    &|a, _| a,
+                )
+            })
+            .collect::<HashSet<usize>>();
+        self.advice_cells
+            .iter()
+            .filter(|(column, _)| columns.contains(column))
+            .count()
+    }
+
+    /// Returns the smallest degree `k` whose usable rows, those not taken by the blinding factors, hold the rows of this run.
+    pub fn min_k<F: PrimeField>(&self, meta: &ConstraintSystem<F>) -> u32 {
+        let mut k = 1;
+        while (1 << k) < meta.minimum_rows() || self.rows + meta.blinding_factors() + 1 > 1 << k {
+            k += 1;
+        }
+        k
+    }
+
+    fn use_row(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl<F: PrimeField> Assignment<F> for DryRun {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        // The region reads the assigned value back from `to`, so the chips compute their witnesses as in a proof.
+        let _ = to();
+        self.advice_cells.insert((column.index(), row));
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let _ = to();
+        let rows = self.fixed_rows.entry(column.index()).or_default();
+        *rows = (*rows).max(row + 1);
+        self.use_row(row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _: Column<Any>,
+        left_row: usize,
+        _: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.use_row(left_row);
+        self.use_row(right_row);
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_base::halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner},
+        dev::MockProver,
+        halo2curves::{bn256::Fr, pasta::Fp},
+        plonk::{Circuit, ConstraintSystem, Instance},
+    };
+    use sha2::{Digest, Sha256};
+
+    #[derive(Clone, Debug)]
+    struct ByteArrayConfig {
+        bytes: Column<Advice>,
+        instance: Column<Instance>,
+    }
+
+    #[derive(Clone, Default)]
+    struct ByteArrayCircuit {
+        bytes: Vec<Option<Fr>>,
+    }
+
+    impl Circuit<Fr> for ByteArrayCircuit {
+        type Config = ByteArrayConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+            let bytes = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(bytes);
+            meta.enable_equality(instance);
+            ByteArrayConfig { bytes, instance }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fr>,
+        ) -> Result<(), Error> {
+            let cells = layouter.assign_region(
+                || "byte array",
+                |mut region| assign_byte_array(&mut region, config.bytes, &self.bytes),
+            )?;
+            for (i, cell) in cells.iter().enumerate() {
+                layouter.constrain_instance(cell.cell(), config.instance, i)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_assign_byte_array_unknown_is_zero() {
+        let k = 4;
+        let circuit = ByteArrayCircuit {
+            bytes: vec![Some(Fr::from(86u64)), None, Some(Fr::from(255u64))],
+        };
+
+        let public_inputs = vec![Fr::from(86u64), Fr::zero(), Fr::from(255u64)];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let public_inputs = vec![Fr::from(86u64), Fr::one(), Fr::from(255u64)];
+        let prover = MockProver::run(k, &circuit, vec![public_inputs]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn test_zeroize_fes() {
+        let mut value = Fr::from(86u64);
+        zeroize_with(&mut value, Fr::zero());
+        assert_eq!(value, Fr::zero());
+
+        let mut values = vec![Some(Fr::from(86u64)), None, Some(Fr::from(255u64))];
+        zeroize_fes(&mut values);
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn test_circuit_shape() {
+        let expected = CircuitShape {
+            num_advice_columns: 1,
+            num_fixed_columns: 0,
+            num_instance_columns: 1,
+            num_selectors: 0,
+            num_gates: 0,
+            num_lookups: 0,
+            // The degree required by the permutation argument.
+            degree: 3,
+        };
+        assert_circuit_shape::<Fr, ByteArrayCircuit>(&expected);
+        assert!(CircuitShape::of::<Fr, ByteArrayCircuit>()
+            .diff(&expected)
+            .is_empty());
+
+        // A layout with another column is reported with the differing count only.
+        let widened = CircuitShape {
+            num_advice_columns: 2,
+            ..expected
+        };
+        assert_eq!(
+            CircuitShape::of::<Fr, ByteArrayCircuit>().diff(&widened),
+            vec!["advice columns: 2 -> 1".to_string()]
+        );
+        let result = std::panic::catch_unwind(|| {
+            assert_circuit_shape::<Fr, ByteArrayCircuit>(&widened);
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_hash_to_field() {
+        let from_decimal =
+            |v: &str| biguint_to_fe::<Fr>(&BigUint::parse_bytes(v.as_bytes(), 10).unwrap());
+
+        // `uint256(sha256("")) % SNARK_SCALAR_FIELD` as computed by the verifier contract.
+        let expected = from_decimal(
+            "15434364762196996140549589341552222435606443046533897618586580254812431104081",
+        );
+        assert_eq!(hash_to_field::<Fr>(&Sha256::digest(b"")), expected);
+
+        // `uint256(sha256("abc")) % SNARK_SCALAR_FIELD`.
+        let expected = from_decimal(
+            "18677639871572974699784617692370438394459790493768411346368373269989391603114",
+        );
+        assert_eq!(hash_to_field::<Fr>(&Sha256::digest(b"abc")), expected);
+
+        // Values below the field order are kept as they are.
+        assert_eq!(hash_to_field::<Fr>(&[1, 0]), Fr::from(256u64));
+        assert_eq!(hash_to_field::<Fr>(&[]), Fr::zero());
+    }
+
+    #[test]
+    fn test_convert_field() {
+        // The witnesses of the subcircuits, e.g. dates and signal hashes, keep their values.
+        let timestamp = 1688819696u64;
+        assert_eq!(
+            convert_field::<Fp, Fr>(Fp::from(timestamp)),
+            Some(Fr::from(timestamp))
+        );
+        assert_eq!(
+            convert_field::<Fr, Fp>(Fr::from(timestamp)),
+            Some(Fp::from(timestamp))
+        );
+
+        // The order of `bn256::Fr` is below that of `pasta::Fp`, so the largest values of the latter do not fit.
+        let fr_max = -Fr::one();
+        let fp_max = convert_field::<Fr, Fp>(fr_max).unwrap();
+        assert_eq!(convert_field::<Fp, Fr>(fp_max), Some(fr_max));
+        assert_eq!(convert_field::<Fp, Fr>(fp_max + Fp::one()), None);
+        assert_eq!(convert_field::<Fp, Fr>(-Fp::one()), None);
+    }
+}